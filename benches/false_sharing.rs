@@ -0,0 +1,86 @@
+// Compares tightly packed and cache-line padded events.
+//
+// Each of two threads signals and consumes its own event. The events are neighbours in the same
+// array, so with the packed layout both threads write to the same cache line. On Linux the threads
+// are pinned to different cores, so that the cache line has to bounce between them.
+//
+// Run with `cargo bench --bench false_sharing`.
+
+use std::hint::black_box;
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, PaddedAutoResetEvent};
+
+const ITERATIONS: usize = 10_000_000;
+const RUNS: usize = 5;
+
+fn main() {
+    println!(
+        "size_of::<AutoResetEvent>() = {}, size_of::<PaddedAutoResetEvent>() = {}",
+        size_of::<AutoResetEvent>(),
+        size_of::<PaddedAutoResetEvent>()
+    );
+
+    let packed: Vec<AutoResetEvent> = (0..2).map(|_| AutoResetEvent::new().unwrap()).collect();
+    let padded: Vec<PaddedAutoResetEvent> = (0..2)
+        .map(|_| PaddedAutoResetEvent::new().unwrap())
+        .collect();
+
+    report("packed", &packed, |event| event);
+    report("padded", &padded, |event| event);
+}
+
+fn report<E: Sync>(name: &str, events: &[E], get: fn(&E) -> &AutoResetEvent) {
+    let best = (0..RUNS).map(|_| run(events, get)).min().unwrap();
+    println!(
+        "{name}: {:.1} ns per signal/try_wait pair",
+        best.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn run<E: Sync>(events: &[E], get: fn(&E) -> &AutoResetEvent) -> Duration {
+    let barrier = Barrier::new(events.len());
+
+    thread::scope(|scope| {
+        let threads: Vec<_> = events
+            .iter()
+            .enumerate()
+            .map(|(core, event)| {
+                let barrier = &barrier;
+                scope.spawn(move || {
+                    pin_to_core(core);
+                    barrier.wait();
+
+                    let start = Instant::now();
+                    let event = get(event);
+                    for _ in 0..ITERATIONS {
+                        event.signal();
+                        black_box(event.try_wait());
+                    }
+                    start.elapsed()
+                })
+            })
+            .collect();
+
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .max()
+            .unwrap()
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn pin_to_core(core: usize) {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        // Pinning is best effort, e.g. the core might not be part of the affinity mask
+        libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_core(_core: usize) {}
@@ -0,0 +1,303 @@
+// A soak test: producers hand work items to consumers through a single event.
+//
+// Every producer adds an item to a shared counter and signals the event; consumers wait for the
+// event and take all pending items. Signals are coalesced, so a consumer may take several items
+// per wakeup, but an item must never be left behind and no wait may succeed without a signal:
+//
+// * lost wakeups: items that are still pending after the producers have finished, or phases in
+//   which items are pending but no consumer makes progress for a second
+// * duplicate wakeups: more successful waits than signals
+//
+// The signal→wake latency percentiles are reported at the end. The process exits with status 1 if
+// a lost or duplicate wakeup was detected.
+//
+//     cargo run --release --example stress -- --producers 4 --consumers 4 --duration 60
+//
+// Run with `--help` for all options.
+
+use std::env;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, Backend};
+
+// How long consumers may make no progress while items are pending before it is reported
+const STALL_THRESHOLD: Duration = Duration::from_secs(1);
+
+const USAGE: &str = "\
+usage: stress [options]
+
+  --producers <n>      producer threads (default 2)
+  --consumers <n>      consumer threads (default 2)
+  --rate <n>           signals per second per producer, 0 for as fast as possible (default 0)
+  --timeout-mix <p>    fraction of waits that use try_wait_for instead of wait (default 0.5)
+  --timeout-us <n>     timeout of try_wait_for in microseconds, 0 for try_wait (default 1000)
+  --duration <s>       seconds to run (default 10)
+  --backend <name>     backend of the event (default: the configured one)";
+
+#[derive(Debug)]
+struct Config {
+    producers: usize,
+    consumers: usize,
+    rate: u64,
+    timeout_mix: f64,
+    timeout: Duration,
+    duration: Duration,
+    backend: Option<Backend>,
+}
+
+impl Config {
+    fn parse() -> Result<Self, String> {
+        let mut config = Config {
+            producers: 2,
+            consumers: 2,
+            rate: 0,
+            timeout_mix: 0.5,
+            timeout: Duration::from_millis(1),
+            duration: Duration::from_secs(10),
+            backend: None,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for {}", arg))?;
+            let invalid = || format!("invalid value for {}: {}", arg, value);
+            match arg.as_str() {
+                "--producers" => config.producers = value.parse().map_err(|_| invalid())?,
+                "--consumers" => config.consumers = value.parse().map_err(|_| invalid())?,
+                "--rate" => config.rate = value.parse().map_err(|_| invalid())?,
+                "--timeout-mix" => {
+                    config.timeout_mix = value.parse().map_err(|_| invalid())?;
+                    if !(0.0..=1.0).contains(&config.timeout_mix) {
+                        return Err(invalid());
+                    }
+                }
+                "--timeout-us" => {
+                    config.timeout = Duration::from_micros(value.parse().map_err(|_| invalid())?)
+                }
+                "--duration" => {
+                    config.duration = Duration::from_secs_f64(value.parse().map_err(|_| invalid())?)
+                }
+                "--backend" => {
+                    let backend = Backend::available()
+                        .iter()
+                        .find(|backend| backend.name() == value)
+                        .ok_or_else(|| format!("backend {} is not available", value))?;
+                    config.backend = Some(*backend);
+                }
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+
+        if config.producers == 0 || config.consumers == 0 {
+            return Err("at least one producer and one consumer are required".into());
+        }
+        Ok(config)
+    }
+}
+
+// The state shared by all threads
+struct Shared {
+    event: AutoResetEvent,
+    // Items that have been produced but not consumed yet
+    pending: AtomicU64,
+    produced: AtomicU64,
+    consumed: AtomicU64,
+    signals: AtomicU64,
+    // Waits that consumed a signal, and waits that timed out
+    wakeups: AtomicU64,
+    timeouts: AtomicU64,
+    stalls: AtomicU64,
+    producing: AtomicBool,
+    consuming: AtomicBool,
+}
+
+fn main() {
+    let config = Config::parse().unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+
+    let mut builder = AutoResetEvent::builder().latency_stats(true);
+    if let Some(backend) = config.backend {
+        builder = builder.backend(backend);
+    }
+    let shared = Shared {
+        event: builder.build().expect("failed to create event"),
+        pending: AtomicU64::new(0),
+        produced: AtomicU64::new(0),
+        consumed: AtomicU64::new(0),
+        signals: AtomicU64::new(0),
+        wakeups: AtomicU64::new(0),
+        timeouts: AtomicU64::new(0),
+        stalls: AtomicU64::new(0),
+        producing: AtomicBool::new(true),
+        consuming: AtomicBool::new(true),
+    };
+    println!("{:?}, backend {}", config, shared.event.backend());
+
+    let start = Instant::now();
+    let (shared, config) = (&shared, &config);
+    let lost = thread::scope(|scope| {
+        let producers: Vec<_> = (0..config.producers)
+            .map(|_| scope.spawn(|| produce(shared, config)))
+            .collect();
+        let consumers: Vec<_> = (0..config.consumers)
+            .map(|seed| scope.spawn(move || consume(shared, config, seed as u64 + 1)))
+            .collect();
+        let watchdog = scope.spawn(|| watch(shared));
+
+        thread::sleep(config.duration);
+        shared.producing.store(false, Ordering::SeqCst);
+        producers
+            .into_iter()
+            .for_each(|producer| producer.join().unwrap());
+
+        // All items have to be consumed without further signals
+        let deadline = Instant::now() + STALL_THRESHOLD;
+        while shared.pending.load(Ordering::SeqCst) != 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let lost = shared.pending.load(Ordering::SeqCst);
+
+        // Wakes the consumers until all of them have noticed the shutdown
+        shared.consuming.store(false, Ordering::SeqCst);
+        for consumer in consumers {
+            while !consumer.is_finished() {
+                shared.event.signal();
+                thread::sleep(Duration::from_millis(1));
+            }
+            consumer.join().unwrap();
+        }
+        watchdog.join().unwrap();
+        lost
+    });
+
+    report(shared, start.elapsed(), lost);
+}
+
+fn produce(shared: &Shared, config: &Config) {
+    let start = Instant::now();
+    let mut sent = 0u64;
+    while shared.producing.load(Ordering::Relaxed) {
+        if config.rate != 0 {
+            let next = start + Duration::from_secs_f64(sent as f64 / config.rate as f64);
+            let now = Instant::now();
+            if next > now {
+                thread::sleep(next - now);
+            }
+        }
+
+        // The item has to be visible before the signal
+        shared.pending.fetch_add(1, Ordering::SeqCst);
+        shared.produced.fetch_add(1, Ordering::Relaxed);
+        shared.signals.fetch_add(1, Ordering::SeqCst);
+        shared.event.signal();
+        sent += 1;
+    }
+}
+
+fn consume(shared: &Shared, config: &Config, seed: u64) {
+    let mut random = seed;
+    while shared.consuming.load(Ordering::SeqCst) {
+        // xorshift64
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+        let timed = (random as f64 / u64::MAX as f64) < config.timeout_mix;
+
+        let woken = if !timed {
+            shared.event.wait();
+            true
+        } else if config.timeout.is_zero() {
+            shared.event.try_wait()
+        } else {
+            shared.event.try_wait_for(config.timeout)
+        };
+
+        if woken {
+            shared.wakeups.fetch_add(1, Ordering::SeqCst);
+            let items = shared.pending.swap(0, Ordering::SeqCst);
+            shared.consumed.fetch_add(items, Ordering::Relaxed);
+        } else {
+            shared.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// Counts the phases in which items are pending but none is consumed
+fn watch(shared: &Shared) {
+    let mut last_consumed = 0;
+    let mut progress = Instant::now();
+    let mut stalled = false;
+    while shared.consuming.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(10));
+
+        let consumed = shared.consumed.load(Ordering::SeqCst);
+        if consumed != last_consumed || shared.pending.load(Ordering::SeqCst) == 0 {
+            last_consumed = consumed;
+            progress = Instant::now();
+            stalled = false;
+        } else if !stalled && progress.elapsed() > STALL_THRESHOLD {
+            stalled = true;
+            shared.stalls.fetch_add(1, Ordering::SeqCst);
+            eprintln!(
+                "stall: items are pending, but none has been consumed for {:?}",
+                STALL_THRESHOLD
+            );
+        }
+    }
+}
+
+fn report(shared: &Shared, elapsed: Duration, lost: u64) {
+    let signals = shared.signals.load(Ordering::SeqCst);
+    let wakeups = shared.wakeups.load(Ordering::SeqCst);
+    // Including the shutdown signals, which may have been consumed as well
+    let duplicates = wakeups.saturating_sub(shared.event.snapshot().total_signals);
+    let stalls = shared.stalls.load(Ordering::SeqCst);
+
+    println!("elapsed:    {:?}", elapsed);
+    println!(
+        "signals:    {} ({:.0}/s)",
+        signals,
+        signals as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "items:      {} produced, {} consumed",
+        shared.produced.load(Ordering::SeqCst),
+        shared.consumed.load(Ordering::SeqCst)
+    );
+    println!(
+        "waits:      {} woken, {} timed out",
+        wakeups,
+        shared.timeouts.load(Ordering::SeqCst)
+    );
+
+    let latency = shared.event.latency_stats();
+    if !latency.is_empty() {
+        println!(
+            "latency:    p50 {:?}, p90 {:?}, p99 {:?}, p99.9 {:?}, max {:?} ({} samples)",
+            latency.value_at_quantile(0.5),
+            latency.value_at_quantile(0.9),
+            latency.value_at_quantile(0.99),
+            latency.value_at_quantile(0.999),
+            latency.max(),
+            latency.count()
+        );
+    }
+
+    println!("lost:       {} items left behind, {} stalls", lost, stalls);
+    println!("duplicates: {} wakeups without a signal", duplicates);
+
+    if lost != 0 || stalls != 0 || duplicates != 0 {
+        process::exit(1);
+    }
+}
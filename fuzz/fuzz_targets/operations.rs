@@ -0,0 +1,201 @@
+// Runs sequences of operations on a small set of events across a few threads and checks the
+// invariants of the events afterwards.
+//
+//     cargo +nightly fuzz run operations
+//
+// Every wait has a timeout, so that sequences that consume more signals than they send finish.
+
+#![no_main]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use nova_autoreset_event::{AutoResetEvent, Backend, Fault, FaultInjector};
+
+const EVENTS: usize = 3;
+const MAX_THREADS: usize = 4;
+const MAX_OPERATIONS: usize = 64;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    backend: u8,
+    threads: Vec<Vec<Operation>>,
+}
+
+// The `u8`s select a handle of the thread, modulo the number of handles it holds
+#[derive(Arbitrary, Debug)]
+enum Operation {
+    Signal(u8),
+    SignalMany(u8, u8),
+    TryWait(u8),
+    // With a timeout of up to 3ms
+    WaitFor(u8, u8),
+    WaitAnyFor(u8, u8, u8),
+    // Adds a clone of a handle to the handles of the thread
+    Clone(u8),
+    Drop(u8),
+    Inject(FaultKind),
+}
+
+#[derive(Arbitrary, Debug)]
+enum FaultKind {
+    Interrupt,
+    SpuriousWakeup,
+    ReadWouldBlock,
+    ShortWrite,
+    Timeout,
+}
+
+// An event and what the threads did with it
+struct Tracked {
+    event: AutoResetEvent,
+    signals: AtomicU64,
+    consumed: AtomicU64,
+}
+
+// A handle of a thread
+type Handle = Arc<Tracked>;
+
+fn pick(handles: &[Handle], index: u8) -> Option<&Handle> {
+    if handles.is_empty() {
+        None
+    } else {
+        Some(&handles[index as usize % handles.len()])
+    }
+}
+
+fn run(operations: &[Operation], mut handles: Vec<Handle>, faults: &FaultInjector) {
+    for operation in operations.iter().take(MAX_OPERATIONS) {
+        match *operation {
+            Operation::Signal(a) => {
+                if let Some(handle) = pick(&handles, a) {
+                    handle.signals.fetch_add(1, Ordering::SeqCst);
+                    handle.event.signal();
+                }
+            }
+            Operation::SignalMany(a, b) => {
+                if let (Some(a), Some(b)) = (pick(&handles, a), pick(&handles, b)) {
+                    a.signals.fetch_add(1, Ordering::SeqCst);
+                    b.signals.fetch_add(1, Ordering::SeqCst);
+                    AutoResetEvent::signal_many(&[&a.event, &b.event]);
+                }
+            }
+            Operation::TryWait(a) => {
+                if let Some(handle) = pick(&handles, a)
+                    && handle.event.try_wait()
+                {
+                    handle.consumed.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            Operation::WaitFor(a, timeout) => {
+                let timeout = Duration::from_millis(timeout as u64 % 4);
+                if let Some(handle) = pick(&handles, a)
+                    && handle.event.try_wait_for(timeout)
+                {
+                    handle.consumed.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            Operation::WaitAnyFor(a, b, timeout) => {
+                let timeout = Duration::from_millis(timeout as u64 % 4);
+                if let (Some(a), Some(b)) = (pick(&handles, a), pick(&handles, b)) {
+                    // The same event may be passed twice
+                    let events = [a, b];
+                    if let Some(index) = AutoResetEvent::try_wait_any_for(
+                        &[&events[0].event, &events[1].event],
+                        timeout,
+                    ) {
+                        events[index].consumed.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+            Operation::Clone(a) => {
+                if let Some(handle) = pick(&handles, a) {
+                    handles.push(handle.clone());
+                }
+            }
+            Operation::Drop(a) => {
+                if !handles.is_empty() {
+                    handles.swap_remove(a as usize % handles.len());
+                }
+            }
+            Operation::Inject(ref kind) => {
+                let fault = match kind {
+                    FaultKind::Interrupt => Fault::Interrupt,
+                    FaultKind::SpuriousWakeup => Fault::SpuriousWakeup,
+                    FaultKind::ReadWouldBlock => Fault::ReadWouldBlock,
+                    FaultKind::ShortWrite => Fault::ShortWrite,
+                    FaultKind::Timeout => Fault::Timeout,
+                };
+                faults.inject(fault, 1);
+            }
+        }
+    }
+}
+
+fn check(tracked: &Tracked) {
+    let signals = tracked.signals.load(Ordering::SeqCst);
+    let consumed = tracked.consumed.load(Ordering::SeqCst);
+    let snapshot = tracked.event.snapshot();
+
+    assert_eq!(snapshot.waiters, 0, "{:?}", snapshot);
+    assert_eq!(snapshot.total_signals, signals, "{:?}", snapshot);
+    assert_eq!(snapshot.total_waits, consumed, "{:?}", snapshot);
+    // Every consumption takes a signal, and signals are only coalesced while the event is
+    // signalled
+    assert!(consumed <= signals, "{:?}", snapshot);
+    assert!(!snapshot.signalled || consumed < signals, "{:?}", snapshot);
+    assert!(
+        signals == 0 || consumed > 0 || snapshot.signalled,
+        "{:?}",
+        snapshot
+    );
+
+    assert_eq!(
+        tracked.event.try_wait(),
+        snapshot.signalled,
+        "{:?}",
+        snapshot
+    );
+    assert!(!tracked.event.try_wait());
+}
+
+fuzz_target!(|input: Input| {
+    let backends = Backend::available();
+    let backend = backends[input.backend as usize % backends.len()];
+    let faults = FaultInjector::new();
+
+    let events: Vec<Handle> = (0..EVENTS)
+        .map(|_| {
+            Arc::new(Tracked {
+                event: AutoResetEvent::builder()
+                    .backend(backend)
+                    .faults(faults.clone())
+                    .build()
+                    .unwrap(),
+                signals: AtomicU64::new(0),
+                consumed: AtomicU64::new(0),
+            })
+        })
+        .collect();
+
+    // Events whose handles have all been dropped by the threads are gone at the end
+    let tracked: Vec<Weak<Tracked>> = events.iter().map(Arc::downgrade).collect();
+    thread::scope(|scope| {
+        for operations in input.threads.iter().take(MAX_THREADS) {
+            let handles = events.clone();
+            let faults = &faults;
+            scope.spawn(move || run(operations, handles, faults));
+        }
+        drop(events);
+    });
+
+    faults.clear();
+    tracked
+        .iter()
+        .filter_map(Weak::upgrade)
+        .for_each(|tracked| check(&tracked));
+});
@@ -0,0 +1,159 @@
+#ifndef NOVA_AUTORESET_EVENT_H
+#define NOVA_AUTORESET_EVENT_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/**
+ * An autoreset event, see [`AutoResetEvent`].
+ */
+typedef struct NovaAutoResetEvent NovaAutoResetEvent;
+
+/**
+ * An autoreset event with a stable layout, named `NovaRawAutoResetEvent` in C.
+ *
+ * The layout is part of the stable interface and is the same on all platforms:
+ *
+ * - `state`: bit 0 is set while the event is signalled, the remaining bits count the threads
+ *   that are blocked on it. It is only accessed atomically.
+ * - `reserved`: always 0.
+ * - `handles`: on Unix, the read and write end of a nonblocking pipe. On Windows, a Win32
+ *   autoreset event in the first slot, the second slot is 0.
+ *
+ * The event does not have the features of [`AutoResetEvent`], e.g. it cannot be closed, and its
+ * kernel object is created by [`nova_raw_are_init`] rather than on demand. Apart from
+ * [`nova_raw_are_init`] and [`nova_raw_are_destroy`], the functions may be called concurrently
+ * from any thread. An initialized event must not be moved.
+ */
+typedef struct {
+  uint32_t state;
+  uint32_t reserved;
+  intptr_t handles[2];
+} NovaRawAutoResetEvent;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an autoreset event. Returns null if it cannot be created.
+ *
+ * The event has to be destroyed with [`nova_are_destroy`].
+ */
+NovaAutoResetEvent *nova_are_create(void);
+
+/**
+ * Destroys an event. Does nothing if `event` is null.
+ *
+ * # Safety
+ *
+ * `event` must be null or have been returned by [`nova_are_create`], and must not be used after
+ * the call. No thread may be blocked on the event.
+ */
+void nova_are_destroy(NovaAutoResetEvent *event);
+
+/**
+ * Signals the event, see [`AutoResetEvent::signal`].
+ *
+ * # Safety
+ *
+ * `event` must be a live event returned by [`nova_are_create`].
+ */
+void nova_are_signal(const NovaAutoResetEvent *event);
+
+/**
+ * Waits for the event to be signalled, see [`AutoResetEvent::wait`].
+ *
+ * # Safety
+ *
+ * `event` must be a live event returned by [`nova_are_create`].
+ */
+void nova_are_wait(const NovaAutoResetEvent *event);
+
+/**
+ * Consumes the signal if the event is signalled, see [`AutoResetEvent::try_wait`]. Returns
+ * `true` if a signal has been consumed.
+ *
+ * # Safety
+ *
+ * `event` must be a live event returned by [`nova_are_create`].
+ */
+bool nova_are_try_wait(const NovaAutoResetEvent *event);
+
+/**
+ * Waits for the event to be signalled for at most `timeout_ns` nanoseconds, see
+ * [`AutoResetEvent::try_wait_for`]. Returns `true` if a signal has been consumed.
+ *
+ * # Safety
+ *
+ * `event` must be a live event returned by [`nova_are_create`].
+ */
+bool nova_are_try_wait_for(const NovaAutoResetEvent *event, uint64_t timeout_ns);
+
+/**
+ * Initializes an event in place. Returns `false` if its kernel object cannot be created, in
+ * which case the event is left untouched.
+ *
+ * The event has to be destroyed with [`nova_raw_are_destroy`].
+ *
+ * # Safety
+ *
+ * `event` must be valid for writes. It must not point to an initialized event.
+ */
+bool nova_raw_are_init(NovaRawAutoResetEvent *event);
+
+/**
+ * Releases the kernel object of an event. The memory of the event is not freed.
+ *
+ * # Safety
+ *
+ * `event` must have been initialized with [`nova_raw_are_init`], and must not be used after the
+ * call, unless it is initialized again. No thread may be blocked on the event.
+ */
+void nova_raw_are_destroy(NovaRawAutoResetEvent *event);
+
+/**
+ * Signals the event, see [`AutoResetEvent::signal`].
+ *
+ * # Safety
+ *
+ * `event` must have been initialized with [`nova_raw_are_init`].
+ */
+void nova_raw_are_signal(NovaRawAutoResetEvent *event);
+
+/**
+ * Waits for the event to be signalled, see [`AutoResetEvent::wait`].
+ *
+ * # Safety
+ *
+ * `event` must have been initialized with [`nova_raw_are_init`].
+ */
+void nova_raw_are_wait(NovaRawAutoResetEvent *event);
+
+/**
+ * Consumes the signal if the event is signalled, see [`AutoResetEvent::try_wait`]. Returns
+ * `true` if a signal has been consumed.
+ *
+ * # Safety
+ *
+ * `event` must have been initialized with [`nova_raw_are_init`].
+ */
+bool nova_raw_are_try_wait(NovaRawAutoResetEvent *event);
+
+/**
+ * Waits for the event to be signalled for at most `timeout_ns` nanoseconds, see
+ * [`AutoResetEvent::try_wait_for`]. Returns `true` if a signal has been consumed.
+ *
+ * # Safety
+ *
+ * `event` must have been initialized with [`nova_raw_are_init`].
+ */
+bool nova_raw_are_try_wait_for(NovaRawAutoResetEvent *event, uint64_t timeout_ns);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NOVA_AUTORESET_EVENT_H */
@@ -0,0 +1,165 @@
+use std::io;
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::{Duration, Instant};
+
+use tokio::io::unix::AsyncFd;
+
+use crate::{AutoResetEvent, WaitResult};
+
+/// An autoreset event that Tokio tasks can wait for without blocking the runtime.
+///
+/// The event registers its file descriptor with the reactor of the runtime via [`AsyncFd`], and
+/// [`wait`](Self::wait) consumes the signal once the reactor reports the fd as readable. The
+/// wrapped [`AutoResetEvent`] is available via [`event`](Self::event), so threads outside the
+/// runtime can signal it, or wait for it with the blocking functions.
+///
+/// ```
+/// use std::sync::Arc;
+/// use nova_autoreset_event::AsyncAutoResetEvent;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let event = Arc::new(AsyncAutoResetEvent::new().unwrap());
+/// let signaller = {
+///     let event = event.clone();
+///     std::thread::spawn(move || event.signal())
+/// };
+///
+/// event.wait().await;
+/// signaller.join().unwrap();
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "tokio"))))]
+#[derive(Debug)]
+pub struct AsyncAutoResetEvent {
+    // Declared first, so that the fd is deregistered before the event closes it
+    fd: AsyncFd<RawFd>,
+    event: AutoResetEvent,
+    // The span that represents the event in tokio-console
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    resource: tracing::Span,
+}
+
+impl AsyncAutoResetEvent {
+    /// Creates an unsignalled event and registers it with the reactor of the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, like [`AsyncFd::new`].
+    pub fn new() -> io::Result<Self> {
+        Self::from_event(AutoResetEvent::new()?)
+    }
+
+    /// Registers an existing event with the reactor of the current runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, like [`AsyncFd::new`].
+    pub fn from_event(event: AutoResetEvent) -> io::Result<Self> {
+        Ok(Self {
+            fd: AsyncFd::new(event.as_raw_fd())?,
+            #[cfg(all(feature = "tracing", tokio_unstable))]
+            resource: crate::console::resource("AsyncAutoResetEvent", event.label()),
+            event,
+        })
+    }
+
+    /// Waits until the event is signalled and consumes the signal, or until it is
+    /// [closed](AutoResetEvent::close).
+    ///
+    /// # Cancel safety
+    ///
+    /// The signal is only consumed when the future completes, so dropping it before, e.g. when it
+    /// loses a `select!`, leaves the signal to the next wait. The reactor wakes all tasks that
+    /// wait for the fd, so a dropped future does not swallow the wakeup of another one.
+    pub async fn wait(&self) {
+        self.wait_result().await;
+    }
+
+    /// Like [`wait`](Self::wait), but reports whether the wait consumed a signal or ended because
+    /// the event has been closed. Never returns [`WaitResult::TimedOut`]. Cancel-safe like
+    /// [`wait`](Self::wait).
+    pub async fn wait_result(&self) -> WaitResult {
+        #[cfg(all(feature = "tracing", tokio_unstable))]
+        return crate::console::traced(
+            crate::console::AsyncOp::new(
+                &self.resource,
+                "AsyncAutoResetEvent::wait",
+                "poll_readable",
+            ),
+            wait_readable(&self.fd, &self.event),
+        )
+        .await;
+
+        #[cfg(not(all(feature = "tracing", tokio_unstable)))]
+        wait_readable(&self.fd, &self.event).await
+    }
+
+    /// Waits for the event to be signalled for at most `timeout`, on the timer of the runtime.
+    /// Returns `true` if a signal has been consumed, and `false` on timeout or once the event has
+    /// been closed.
+    ///
+    /// Cancel-safe like [`wait`](Self::wait). A timeout that cannot be represented as an
+    /// [`Instant`] never expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime has no time driver enabled.
+    pub async fn wait_timeout(&self, timeout: Duration) -> bool {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.wait_deadline(deadline).await,
+            None => self.wait_result().await == WaitResult::Signalled,
+        }
+    }
+
+    /// Like [`wait_timeout`](Self::wait_timeout), but waits until `deadline`.
+    pub async fn wait_deadline(&self, deadline: Instant) -> bool {
+        let deadline = tokio::time::Instant::from_std(deadline);
+        matches!(
+            tokio::time::timeout_at(deadline, self.wait_result()).await,
+            Ok(WaitResult::Signalled)
+        )
+    }
+
+    /// Consumes the signal if the event is signalled, see [`AutoResetEvent::try_wait`].
+    pub fn try_wait(&self) -> bool {
+        self.event.try_wait()
+    }
+
+    /// Signals the event, see [`AutoResetEvent::signal`].
+    pub fn signal(&self) {
+        self.event.signal();
+    }
+
+    /// Returns the wrapped event.
+    pub fn event(&self) -> &AutoResetEvent {
+        &self.event
+    }
+
+    /// Deregisters the event from the reactor and returns it.
+    pub fn into_event(self) -> AutoResetEvent {
+        let Self { fd, event, .. } = self;
+        drop(fd);
+        event
+    }
+}
+
+// Waits for `event`, whose fd is registered as `fd`
+pub(crate) async fn wait_readable(fd: &AsyncFd<RawFd>, event: &AutoResetEvent) -> WaitResult {
+    loop {
+        if event.try_wait() {
+            return WaitResult::Signalled;
+        }
+        if event.is_closed() {
+            return WaitResult::Closed;
+        }
+
+        let mut guard = fd
+            .readable()
+            .await
+            .expect("the Tokio reactor of the event has shut down");
+        // The readiness is stale if another waiter consumed the signal. A signal after the check
+        // above is seen by the check at the top of the loop.
+        guard.clear_ready();
+    }
+}
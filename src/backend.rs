@@ -0,0 +1,177 @@
+use std::env;
+use std::fmt;
+use std::io;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::state::KernelObject;
+
+/// The environment variable that selects the backend of events that are created without an
+/// explicit [`Builder::backend`](crate::Builder::backend).
+///
+/// It accepts the [name](Backend::name) of a backend, or `fastest` to use [`Backend::fastest`].
+/// Unknown or unavailable backends are ignored. The variable is read once per process.
+pub const BACKEND_ENV_VAR: &str = "NOVA_AUTORESET_EVENT_BACKEND";
+
+/// The kernel primitive that blocked threads are parked on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// `eventfd` (Linux, Android)
+    EventFd,
+    /// A futex (Linux, Android). Multi-waits over futex events use `futex_waitv` (Linux 5.16+).
+    /// An `eventfd` is only created if the fd of the event is requested, or if the event is
+    /// waited on together with events of other backends.
+    Futex,
+    /// A kqueue with an `EVFILT_USER` event (macOS, iOS, BSD)
+    Kqueue,
+    /// A pipe (all Unix platforms)
+    Pipe,
+    /// A Win32 event object (Windows)
+    Win32Event,
+}
+
+impl Backend {
+    /// Returns the backends that are available on this platform, starting with the native one.
+    pub fn available() -> &'static [Backend] {
+        crate::sys::BACKENDS
+    }
+
+    /// Returns the native backend of this platform.
+    pub fn native() -> Backend {
+        Self::available()[0]
+    }
+
+    /// Returns `true` if the backend is available on this platform.
+    pub fn is_available(self) -> bool {
+        Self::available().contains(&self)
+    }
+
+    /// Returns the backend with the lowest signal→wake round-trip latency on this machine.
+    ///
+    /// The first call measures the latency of every available backend, which takes a few
+    /// milliseconds. The result is cached for the lifetime of the process. If [`BACKEND_ENV_VAR`]
+    /// names an available backend, it is returned without measuring.
+    pub fn fastest() -> Backend {
+        static FASTEST: OnceLock<Backend> = OnceLock::new();
+
+        *FASTEST.get_or_init(|| {
+            env_backend()
+                .and_then(Self::from_name)
+                .filter(|backend| backend.is_available())
+                .unwrap_or_else(measure_fastest)
+        })
+    }
+
+    /// Returns the name of the backend, as accepted by [`BACKEND_ENV_VAR`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::EventFd => "eventfd",
+            Backend::Futex => "futex",
+            Backend::Kqueue => "kqueue",
+            Backend::Pipe => "pipe",
+            Backend::Win32Event => "win32-event",
+        }
+    }
+
+    // The backend of events that do not request a specific one
+    pub(crate) fn configured() -> Backend {
+        static CONFIGURED: OnceLock<Backend> = OnceLock::new();
+
+        *CONFIGURED.get_or_init(|| {
+            match env_backend() {
+                Some("fastest") => Self::fastest(),
+                Some(name) => {
+                    Self::from_name(name)
+                        .filter(|backend| backend.is_available())
+                        .unwrap_or_else(Self::native)
+                }
+                None => Self::native(),
+            }
+        })
+    }
+
+    fn from_name(name: &str) -> Option<Backend> {
+        [
+            Backend::EventFd,
+            Backend::Futex,
+            Backend::Kqueue,
+            Backend::Pipe,
+            Backend::Win32Event,
+        ]
+        .into_iter()
+        .find(|backend| backend.name() == name)
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn env_backend() -> Option<&'static str> {
+    static VALUE: OnceLock<Option<String>> = OnceLock::new();
+
+    VALUE
+        .get_or_init(|| env::var(BACKEND_ENV_VAR).ok())
+        .as_deref()
+}
+
+fn measure_fastest() -> Backend {
+    let available = Backend::available();
+    // The measurement needs a second thread
+    if available.len() == 1 || cfg!(madsim) {
+        return available[0];
+    }
+
+    available
+        .iter()
+        .filter_map(|&backend| {
+            crate::sys::round_trip(backend)
+                .ok()
+                .map(|latency| (latency, backend))
+        })
+        .min_by_key(|&(latency, _)| latency)
+        .map_or_else(Backend::native, |(_, backend)| backend)
+}
+
+/// Measures the median latency of a ping-pong between two threads, blocking on raw kernel objects
+/// without the userspace fast path.
+pub(crate) fn round_trip<K: KernelObject + Sync>(config: K::Config) -> io::Result<Duration> {
+    const WARMUP: usize = 20;
+    const ROUNDS: usize = 200;
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(1));
+
+    let ping = K::create(config)?;
+    let pong = K::create(config)?;
+    let mut samples = Vec::with_capacity(ROUNDS);
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for _ in 0..WARMUP + ROUNDS {
+                if matches!(ping.block(TIMEOUT), Ok(false)) {
+                    return;
+                }
+                pong.wake();
+            }
+        });
+
+        for round in 0..WARMUP + ROUNDS {
+            let start = Instant::now();
+            ping.wake();
+            if !pong.block(TIMEOUT)? {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
+
+            if round >= WARMUP {
+                samples.push(start.elapsed());
+            }
+        }
+        Ok(())
+    })?;
+
+    samples.sort_unstable();
+    Ok(samples[ROUNDS / 2])
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{AutoResetEvent, EventError};
+
+/// Limits the number of items in flight between producers and consumers.
+///
+/// Producers call [`enter`](Self::enter) before handing an item over, which blocks while
+/// `capacity` items are in flight, and consumers call [`leave`](Self::leave) once they are done
+/// with an item. The gate only does the counting, so it bounds any handoff, e.g. an unbounded
+/// channel or a shared queue.
+///
+/// The blocked producers wait on a single event. As signals are not counted, a producer that
+/// enters after a wait signals the event again while there is room left, so that several
+/// `leave` calls in a row wake as many producers.
+///
+/// ```
+/// use nova_autoreset_event::BackpressureGate;
+///
+/// let gate = BackpressureGate::new(2).unwrap();
+/// gate.enter();
+/// gate.enter();
+/// assert!(!gate.try_enter());
+///
+/// gate.leave();
+/// assert!(gate.try_enter());
+/// ```
+pub struct BackpressureGate {
+    capacity: usize,
+    in_flight: AtomicUsize,
+    // The producers that are blocked in `enter`, or about to be
+    waiters: AtomicUsize,
+    room: AutoResetEvent,
+}
+
+impl BackpressureGate {
+    /// Creates a gate that lets `capacity` items be in flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Result<Self, EventError> {
+        assert!(capacity > 0, "the capacity of a gate must not be zero");
+        Ok(Self {
+            capacity,
+            in_flight: AtomicUsize::new(0),
+            waiters: AtomicUsize::new(0),
+            room: AutoResetEvent::new()?,
+        })
+    }
+
+    /// Returns the number of items that may be in flight.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of items in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Adds an item, blocking while the gate is at capacity.
+    pub fn enter(&self) {
+        if !self.try_enter() {
+            self.enter_slow(None);
+        }
+    }
+
+    /// Adds an item if the gate is not at capacity. Returns `false` otherwise.
+    pub fn try_enter(&self) -> bool {
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |in_flight| {
+                (in_flight < self.capacity).then_some(in_flight + 1)
+            })
+            .is_ok()
+    }
+
+    /// Like [`enter`](Self::enter), but returns `false` if the gate is still at capacity after
+    /// `timeout`.
+    pub fn try_enter_for(&self, timeout: Duration) -> bool {
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        self.try_enter() || self.enter_slow(Some(Instant::now().checked_add(timeout)))
+    }
+
+    /// Removes an item, and wakes a producer that is blocked in [`enter`](Self::enter).
+    ///
+    /// # Panics
+    ///
+    /// Panics if no item is in flight.
+    pub fn leave(&self) {
+        self.in_flight
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |in_flight| {
+                in_flight.checked_sub(1)
+            })
+            .expect("left a gate without a matching enter");
+        // Either a producer that registers itself sees the decremented count, or this sees the
+        // producer
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            self.room.signal();
+        }
+    }
+
+    // Registers as a waiter before checking the count again, see `leave`. The outer `None`
+    // blocks without a deadline.
+    fn enter_slow(&self, deadline: Option<Option<Instant>>) -> bool {
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let entered = loop {
+            if self.try_enter() {
+                break true;
+            }
+            match deadline {
+                None => self.room.wait(),
+                Some(deadline) => {
+                    let remaining = match deadline {
+                        Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                        None => Duration::MAX,
+                    };
+                    if remaining.is_zero() {
+                        break false;
+                    }
+                    self.room.try_wait_for(remaining);
+                }
+            }
+        };
+        // Pass the signal on if there is room for another blocked producer. Acquires the count
+        // that `leave` has released, unlike the relaxed `in_flight`.
+        if self.waiters.fetch_sub(1, Ordering::SeqCst) > 1
+            && self.in_flight.load(Ordering::Acquire) < self.capacity
+        {
+            self.room.signal();
+        }
+        entered
+    }
+}
+
+impl fmt::Debug for BackpressureGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackpressureGate")
+            .field("capacity", &self.capacity)
+            .field("in_flight", &self.in_flight())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use crate::AutoResetEvent;
+
+/// Signals the events of all its subscribers at once, e.g. to tell every worker that the
+/// configuration has changed.
+///
+/// Subscribers register their own event, so they can wait for it together with other events.
+/// [`broadcast`](Self::broadcast) signals the events via [`AutoResetEvent::signal_many`], which
+/// batches the wakeups. The list of subscribers is copied on subscription changes, so a broadcast
+/// does not block them, and subscribers that are added during a broadcast may or may not be
+/// signalled.
+///
+/// ```
+/// use std::sync::Arc;
+/// use nova_autoreset_event::{AutoResetEvent, Broadcaster};
+///
+/// let broadcaster = Broadcaster::new();
+/// let a = Arc::new(AutoResetEvent::new().unwrap());
+/// let b = Arc::new(AutoResetEvent::new().unwrap());
+/// let _a = broadcaster.subscribe(a.clone());
+/// let _b = broadcaster.subscribe(b.clone());
+///
+/// broadcaster.broadcast();
+/// assert!(a.try_wait() && b.try_wait());
+/// ```
+#[derive(Default)]
+pub struct Broadcaster {
+    shared: Arc<Shared>,
+}
+
+/// The registration of an event with a [`Broadcaster`], see [`Broadcaster::subscribe`].
+///
+/// Dropping the subscription unsubscribes the event.
+#[must_use = "dropping a subscription unsubscribes the event"]
+pub struct Subscription {
+    id: u64,
+    shared: Weak<Shared>,
+}
+
+// Replaced as a whole on every change, so that broadcasts can signal a snapshot
+type Subscribers = Arc<Vec<(u64, Arc<AutoResetEvent>)>>;
+
+#[derive(Default)]
+struct Shared {
+    subscribers: Mutex<Subscribers>,
+    next_id: AtomicU64,
+}
+
+impl Broadcaster {
+    /// Creates a broadcaster without subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes an event, which is signalled by every broadcast until the subscription is
+    /// dropped. An event can be subscribed more than once.
+    pub fn subscribe(&self, event: Arc<AutoResetEvent>) -> Subscription {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut subscribers = self.shared.lock();
+        let mut updated = Vec::clone(&subscribers);
+        updated.push((id, event));
+        *subscribers = Arc::new(updated);
+
+        Subscription {
+            id,
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    /// Signals the events of all subscribers.
+    pub fn broadcast(&self) {
+        let subscribers = self.shared.lock().clone();
+        let events: Vec<&AutoResetEvent> = subscribers.iter().map(|(_, event)| &**event).collect();
+        AutoResetEvent::signal_many(&events);
+    }
+
+    /// Returns the number of subscriptions.
+    pub fn len(&self) -> usize {
+        self.shared.lock().len()
+    }
+
+    /// Returns `true` if there are no subscriptions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Subscription {
+    /// Unsubscribes the event, like dropping the subscription.
+    pub fn unsubscribe(self) {}
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Subscribers> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared.upgrade() {
+            let mut subscribers = shared.lock();
+            let updated = subscribers
+                .iter()
+                .filter(|(id, _)| *id != self.id)
+                .cloned()
+                .collect();
+            *subscribers = Arc::new(updated);
+        }
+    }
+}
+
+impl fmt::Debug for Broadcaster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Broadcaster")
+            .field("subscribers", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
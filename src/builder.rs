@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+use std::io;
+#[cfg(any(feature = "log", feature = "tracing"))]
+use std::time::Duration;
+
+#[cfg(feature = "fault-injection")]
+use crate::FaultInjector;
+#[cfg(feature = "virtual-time")]
+use crate::MockClock;
+use crate::{AutoResetEvent, Backend, EventError, EventOperation};
+
+/// Configures and creates an [`AutoResetEvent`].
+///
+/// ```
+/// use nova_autoreset_event::AutoResetEvent;
+///
+/// let event = AutoResetEvent::builder().build().unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    pub(crate) backend: Option<Backend>,
+    pub(crate) label: Option<Cow<'static, str>>,
+    pub(crate) latency_stats: bool,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: MetricsMode,
+    #[cfg(feature = "virtual-time")]
+    pub(crate) clock: Option<MockClock>,
+    #[cfg(feature = "fault-injection")]
+    pub(crate) faults: Option<FaultInjector>,
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    pub(crate) warn_after: Option<Duration>,
+    #[cfg(windows)]
+    pub(crate) priority_boost: Option<bool>,
+    #[cfg(unix)]
+    pub(crate) interrupts: InterruptPolicy,
+    #[cfg(unix)]
+    pub(crate) lazy: bool,
+}
+
+impl Builder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the backend of the event.
+    ///
+    /// By default, the backend selected via [`BACKEND_ENV_VAR`](crate::BACKEND_ENV_VAR) is used,
+    /// or the [native](Backend::native) one. Pass [`Backend::fastest()`] to use the backend with
+    /// the lowest wake latency on this machine. Building fails with
+    /// [`EventErrorKind::Unsupported`](crate::EventErrorKind::Unsupported) if the backend is not
+    /// available on this platform.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Sets a label that identifies the event in diagnostics: in its `Debug` output, and in the
+    /// spans, events and metrics of the `tracing` and `metrics` features.
+    pub fn label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Records a histogram of the signal→wake latencies of the event, see
+    /// [`AutoResetEvent::latency_stats`].
+    ///
+    /// Disabled by default, as it requires a timestamp for every signal and about 5 KiB of memory
+    /// per event.
+    pub fn latency_stats(mut self, enable: bool) -> Self {
+        self.latency_stats = enable;
+        self
+    }
+
+    /// Sets how the event reports to the `metrics` facade.
+    ///
+    /// Defaults to [`MetricsMode::PerEvent`].
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn metrics(mut self, mode: MetricsMode) -> Self {
+        self.metrics = mode;
+        self
+    }
+
+    /// Measures the timeouts of [`AutoResetEvent::try_wait_for`] on a virtual clock.
+    ///
+    /// A wait then times out once the clock has been [advanced](MockClock::advance) past its
+    /// deadline, no matter how much real time has passed, which makes timeout tests
+    /// deterministic. Signals still wake waiters immediately. Waits on several events via
+    /// [`AutoResetEvent::try_wait_any_for`] and the watchdog use the real clock.
+    #[cfg(feature = "virtual-time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "virtual-time")))]
+    pub fn clock(mut self, clock: MockClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Injects the faults queued on `faults` into the kernel operations of the event, to test
+    /// that they are recovered from. See [`FaultInjector`].
+    #[cfg(feature = "fault-injection")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+    pub fn faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Logs a warning when a thread has been blocked on the event for longer than `threshold`.
+    ///
+    /// The warning includes the label of the event and the name of the blocked thread. It is
+    /// emitted once per wait, via `tracing` if the `tracing` feature is enabled, otherwise via
+    /// `log`. Waits on several events via [`AutoResetEvent::wait_any`] are not watched.
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "log", feature = "tracing"))))]
+    pub fn warn_if_waiting_longer_than(mut self, threshold: Duration) -> Self {
+        self.warn_after = Some(threshold);
+        self
+    }
+
+    /// Controls the priority boost of threads woken by the event.
+    ///
+    /// Windows temporarily boosts the priority of a thread whose wait has been satisfied. With
+    /// `false`, the boost is disabled while a thread is blocked on this event; with `true`, it is
+    /// enabled even if it has been disabled via `SetThreadPriorityBoost` or
+    /// `SetProcessPriorityBoost`. The previous setting of the thread is restored after the wait.
+    /// By default, the setting of the waiting thread is used.
+    #[cfg(windows)]
+    #[cfg_attr(docsrs, doc(cfg(windows)))]
+    pub fn priority_boost(mut self, enable: bool) -> Self {
+        self.priority_boost = Some(enable);
+        self
+    }
+
+    /// Sets what the waits of the event do when a Unix signal handler interrupts a blocking call
+    /// (`EINTR`).
+    ///
+    /// Defaults to [`InterruptPolicy::Retry`].
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn on_interrupt(mut self, policy: InterruptPolicy) -> Self {
+        self.interrupts = policy;
+        self
+    }
+
+    /// Defers the creation of the kernel object of the event until it is first needed: when a
+    /// thread blocks on the event, or when its fd is requested.
+    ///
+    /// Events that never block then do not consume any file descriptors, e.g. events that are
+    /// created "just in case". The price is that a failure to create the kernel object, e.g. once
+    /// the process has run out of file descriptors, is no longer reported by
+    /// [`build`](Self::build): blocking waits and [`AsFd::as_fd`](std::os::fd::AsFd::as_fd) panic
+    /// with the message of the [`EventError`] instead.
+    /// [`wait_checked`](AutoResetEvent::wait_checked),
+    /// [`try_wait_for_checked`](AutoResetEvent::try_wait_for_checked) and
+    /// [`AutoResetEvent::try_as_fd`] report it as an error for [`EventOperation::Create`].
+    ///
+    /// Disabled by default: with lazy creation, running out of file descriptors turns from an error
+    /// of `build` into a panic in the middle of an unrelated wait, and the infallible waits and
+    /// `as_fd` give no way to handle it.
+    #[cfg(unix)]
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    pub fn lazy(mut self, enable: bool) -> Self {
+        self.lazy = enable;
+        self
+    }
+
+    /// Creates the event.
+    ///
+    /// Fails with [`EventErrorKind::ResourceExhausted`](crate::EventErrorKind::ResourceExhausted)
+    /// if the kernel object cannot be created because the process has run out of file
+    /// descriptors or handles, unless its creation is deferred via [`lazy`](Self::lazy).
+    pub fn build(self) -> Result<AutoResetEvent, EventError> {
+        let backend = self.resolve_backend()?;
+        AutoResetEvent::from_builder(self, backend)
+    }
+
+    /// Creates `n` events with the same configuration.
+    ///
+    /// The backend is resolved once for all events. With [`lazy`](Self::lazy), this does not
+    /// make any syscalls.
+    pub fn build_many(self, n: usize) -> Result<Vec<AutoResetEvent>, EventError> {
+        let backend = self.resolve_backend()?;
+
+        let mut events = Vec::with_capacity(n);
+        for _ in 0..n {
+            events.push(AutoResetEvent::from_builder(self.clone(), backend)?);
+        }
+        Ok(events)
+    }
+
+    fn resolve_backend(&self) -> Result<Backend, EventError> {
+        match self.backend {
+            Some(backend) if !backend.is_available() => {
+                Err(EventError::new(
+                    EventOperation::Create,
+                    backend,
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "the backend is not available on this platform",
+                    ),
+                ))
+            }
+            Some(backend) => Ok(backend),
+            None => Ok(Backend::configured()),
+        }
+    }
+}
+
+/// What the waits of an event do when a Unix signal handler interrupts a blocking call (`EINTR`),
+/// see [`Builder::on_interrupt`].
+///
+/// The policy applies to the blocking `poll`, `kevent` and `futex` calls of every backend. Reads
+/// and writes of the kernel objects do not block, and are always retried. Waits on several
+/// events, like [`AutoResetEvent::wait_any`], and the waits that count signals, like
+/// [`AutoResetEvent::wait_count`], always retry.
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum InterruptPolicy {
+    /// The blocking call is restarted, for the remaining time of a timed wait. A signal handler
+    /// therefore cannot end a wait unless it signals the event.
+    #[default]
+    Retry,
+    /// The wait ends: [`AutoResetEvent::wait_result`] and
+    /// [`AutoResetEvent::try_wait_for_result`] return
+    /// [`WaitResult::Interrupted`](crate::WaitResult::Interrupted), [`AutoResetEvent::wait`]
+    /// returns and [`AutoResetEvent::try_wait_for`] returns `false`.
+    Report,
+}
+
+/// How an event reports to the `metrics` facade, see [`Builder::metrics`].
+///
+/// All metrics are labelled with `backend = <backend name>`, and the blocking wait counter with
+/// `outcome = signalled | timeout`. Exporters that keep a series per label set, like Prometheus,
+/// therefore keep a few series per distinct event label, not per event.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum MetricsMode {
+    /// The metrics are additionally labelled with `event = <label>`, or an empty string for
+    /// events without a label.
+    #[default]
+    PerEvent,
+    /// The metrics are not labelled with the label of the event, so that they are aggregated with
+    /// those of all other events of the same backend. Use this for labels with a high
+    /// cardinality, e.g. labels that contain an index.
+    Aggregate,
+    /// The event does not record any metrics.
+    Disabled,
+}
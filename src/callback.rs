@@ -0,0 +1,139 @@
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::iter;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::AutoResetEvent;
+use crate::pool::{Pool, Worker};
+
+// The dispatcher threads, which never exit. Their control events are signalled when a callback
+// has been added or detached.
+static DISPATCHERS: Pool<Arc<Callback>> = Pool::new("nova-autoreset-event-callback", dispatch);
+
+thread_local! {
+    // The callback that the dispatcher thread is running
+    static RUNNING: Cell<*const Callback> = const { Cell::new(ptr::null()) };
+}
+
+/// A callback that runs whenever an event is signalled, see [`AutoResetEvent::on_signal`].
+///
+/// Dropping the registration detaches the callback. If the callback is running on another thread
+/// at that point, the drop waits for it to return, so that it does not run anymore afterwards. A
+/// callback can drop its own registration.
+#[must_use = "dropping a registration detaches the callback"]
+pub struct Registration {
+    callback: Arc<Callback>,
+    dispatcher: Arc<Worker<Arc<Callback>>>,
+}
+
+struct Callback {
+    event: Arc<AutoResetEvent>,
+    run: Mutex<Box<dyn FnMut() + Send>>,
+    detached: AtomicBool,
+}
+
+pub(crate) fn on_signal(
+    event: Arc<AutoResetEvent>,
+    run: Box<dyn FnMut() + Send>,
+) -> io::Result<Registration> {
+    let callback = Arc::new(Callback {
+        event,
+        run: Mutex::new(run),
+        detached: AtomicBool::new(false),
+    });
+
+    let dispatcher = DISPATCHERS.add(callback.clone())?;
+    Ok(Registration {
+        callback,
+        dispatcher,
+    })
+}
+
+impl Registration {
+    /// Returns the event that triggers the callback.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.callback.event
+    }
+
+    /// Returns `true` until the callback has been detached because the event has been
+    /// [closed](AutoResetEvent::close) or the callback has panicked.
+    pub fn is_attached(&self) -> bool {
+        !self.callback.detached.load(Ordering::SeqCst) && !self.callback.event.is_closed()
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.callback.detached.store(true, Ordering::SeqCst);
+        // Waits for a running call to return, unless the callback drops its own registration
+        if RUNNING.get() != Arc::as_ptr(&self.callback) {
+            drop(lock(&self.callback.run));
+        }
+        self.dispatcher.control.signal();
+    }
+}
+
+impl fmt::Debug for Registration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registration")
+            .field("event", &self.callback.event)
+            .field("attached", &self.is_attached())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Callback {
+    fn call(self: &Arc<Self>) {
+        let mut run = lock(&self.run);
+        if self.detached.load(Ordering::SeqCst) {
+            return;
+        }
+
+        RUNNING.set(Arc::as_ptr(self));
+        let result = panic::catch_unwind(AssertUnwindSafe(&mut *run));
+        RUNNING.set(ptr::null());
+        if result.is_err() {
+            // The panic has been reported by the panic hook. The other callbacks of the thread
+            // keep running.
+            self.detached.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+// The loop of a dispatcher thread
+fn dispatch(dispatcher: Arc<Worker<Arc<Callback>>>) {
+    let mut callbacks = Vec::new();
+    loop {
+        dispatcher.take_added(&mut callbacks);
+
+        // Closed events would end every wait right away
+        let len = callbacks.len();
+        callbacks.retain(|callback: &Arc<Callback>| {
+            !callback.detached.load(Ordering::SeqCst) && !callback.event.is_closed()
+        });
+        dispatcher.removed(len - callbacks.len());
+
+        let events: Vec<&AutoResetEvent> = iter::once(&dispatcher.control)
+            .chain(callbacks.iter().map(|callback| &*callback.event))
+            .collect();
+        match AutoResetEvent::wait_any(&events) {
+            0 => continue,
+            index => {
+                let callback = &callbacks[index - 1];
+                // A closed event ends the wait without a signal
+                if !callback.event.is_closed() {
+                    callback.call();
+                }
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panicking callback is caught, so a poisoned lock does not leave anything inconsistent
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
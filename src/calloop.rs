@@ -0,0 +1,113 @@
+use std::io;
+use std::sync::Arc;
+
+use calloop::generic::Generic;
+use calloop::{EventSource, Interest, Mode, Poll, PostAction, Readiness, Token, TokenFactory};
+
+use crate::AutoResetEvent;
+
+/// A calloop [`EventSource`] that dispatches the signals of an [`AutoResetEvent`].
+///
+/// The source registers the fd of the event with the event loop, so that worker threads wake a
+/// calloop-based event loop, e.g. of a Wayland compositor, by signalling the event. Every dispatch
+/// consumes the signal and calls the callback once, so signals that arrive before the event loop
+/// gets to the source are coalesced into a single call. Other threads may wait for the same event;
+/// a signal that they consume is not dispatched.
+///
+/// Once the event has been [closed](AutoResetEvent::close), the source removes itself from the
+/// event loop.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use calloop::EventLoop;
+/// use nova_autoreset_event::{AutoResetEvent, CalloopSource};
+///
+/// let mut event_loop = EventLoop::<usize>::try_new().unwrap();
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// event_loop
+///     .handle()
+///     .insert_source(CalloopSource::new(event.clone()), |(), &mut (), wakeups| {
+///         *wakeups += 1;
+///     })
+///     .unwrap();
+///
+/// std::thread::spawn(move || event.signal());
+///
+/// let mut wakeups = 0;
+/// while wakeups == 0 {
+///     event_loop.dispatch(Duration::from_secs(1), &mut wakeups).unwrap();
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "calloop"))))]
+#[derive(Debug)]
+pub struct CalloopSource {
+    event: Arc<AutoResetEvent>,
+    source: Generic<Arc<AutoResetEvent>>,
+}
+
+impl CalloopSource {
+    /// Creates a source that dispatches the signals of `event`.
+    pub fn new(event: Arc<AutoResetEvent>) -> Self {
+        Self {
+            source: Generic::new(event.clone(), Interest::READ, Mode::Level),
+            event,
+        }
+    }
+
+    /// Returns the event.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+}
+
+impl EventSource for CalloopSource {
+    type Event = ();
+    type Metadata = ();
+    type Ret = ();
+    type Error = io::Error;
+
+    fn process_events<F>(
+        &mut self,
+        readiness: Readiness,
+        token: Token,
+        mut callback: F,
+    ) -> Result<PostAction, Self::Error>
+    where
+        F: FnMut(Self::Event, &mut Self::Metadata) -> Self::Ret,
+    {
+        let event = &self.event;
+        self.source.process_events(readiness, token, |_, _| {
+            // Consuming the signal drains the fd, so the level-triggered readiness ends with it
+            if event.try_wait() {
+                callback((), &mut ());
+                Ok(PostAction::Continue)
+            } else if event.is_closed() {
+                Ok(PostAction::Remove)
+            } else {
+                // Another thread has consumed the signal
+                Ok(PostAction::Continue)
+            }
+        })
+    }
+
+    fn register(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.source.register(poll, token_factory)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &mut Poll,
+        token_factory: &mut TokenFactory,
+    ) -> calloop::Result<()> {
+        self.source.reregister(poll, token_factory)
+    }
+
+    fn unregister(&mut self, poll: &mut Poll) -> calloop::Result<()> {
+        self.source.unregister(poll)
+    }
+}
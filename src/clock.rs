@@ -0,0 +1,125 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::state::KernelWaker;
+
+/// A virtual clock for deterministic timeout tests, see [`Builder::clock`](crate::Builder::clock).
+///
+/// The clock starts at zero and only advances via [`advance`](Self::advance). Clones share the
+/// same time.
+///
+/// ```
+/// use std::time::Duration;
+/// use nova_autoreset_event::{AutoResetEvent, MockClock};
+///
+/// let clock = MockClock::new();
+/// let event = AutoResetEvent::builder().clock(clock.clone()).build().unwrap();
+///
+/// std::thread::scope(|scope| {
+///     let waiter = scope.spawn(|| event.try_wait_for(Duration::from_secs(60)));
+///     // The deadline is taken when the wait starts, so the clock keeps advancing until then
+///     while !waiter.is_finished() {
+///         clock.advance(Duration::from_secs(1));
+///         std::thread::yield_now();
+///     }
+///     assert!(!waiter.join().unwrap());
+///     assert!(clock.now() >= Duration::from_secs(60));
+/// });
+/// ```
+#[derive(Clone, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "virtual-time")))]
+pub struct MockClock {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    // Nanoseconds since the creation of the clock
+    now: AtomicU64,
+    next_id: AtomicU64,
+    waiters: Mutex<Vec<Waiter>>,
+}
+
+// A thread that waits for a virtual deadline, see `MockClock::register`
+struct Waiter {
+    id: u64,
+    waker: *const (),
+    wake: unsafe fn(*const ()),
+}
+
+// Wakers are `Sync`, and they outlive their registration
+unsafe impl Send for Waiter {}
+
+impl MockClock {
+    /// Creates a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the virtual time since the clock has been created.
+    pub fn now(&self) -> Duration {
+        Duration::from_nanos(self.inner.now.load(Ordering::SeqCst))
+    }
+
+    /// Advances the clock by `duration`. Waits whose deadline has passed time out.
+    pub fn advance(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+        let _ = self
+            .inner
+            .now
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |now| {
+                Some(now.saturating_add(nanos))
+            });
+
+        // The waiters re-check their deadline
+        for waiter in self.waiters().iter() {
+            unsafe { (waiter.wake)(waiter.waker) };
+        }
+    }
+
+    // Registers a waker that is woken whenever the clock advances, until the registration is
+    // dropped
+    pub(crate) fn register<'a, W: KernelWaker>(&'a self, waker: &'a W) -> Registration<'a> {
+        unsafe fn wake<W: KernelWaker>(waker: *const ()) {
+            unsafe { (*(waker as *const W)).wake() }
+        }
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        self.waiters().push(Waiter {
+            id,
+            waker: waker as *const W as *const (),
+            wake: wake::<W>,
+        });
+        Registration { clock: self, id }
+    }
+
+    fn waiters(&self) -> MutexGuard<'_, Vec<Waiter>> {
+        self.inner
+            .waiters
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for MockClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockClock")
+            .field("now", &self.now())
+            .finish_non_exhaustive()
+    }
+}
+
+// Unregisters a waker from the clock. As `advance` wakes the wakers while it holds the lock, the
+// waker is not used after the registration has been dropped.
+pub(crate) struct Registration<'a> {
+    clock: &'a MockClock,
+    id: u64,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.clock.waiters().retain(|waiter| waiter.id != self.id);
+    }
+}
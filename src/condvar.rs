@@ -0,0 +1,130 @@
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::Duration;
+
+use crate::waiter::WaiterQueue;
+
+/// A condition variable whose waiters block on autoreset events.
+///
+/// It has the semantics of [`std::sync::Condvar`], but without spurious wakeups: a wait only
+/// returns after a notification or a timeout. Every waiting thread blocks on an event of its own,
+/// and notifications wake the waiters in the order in which they started waiting. Timed waits
+/// use the deadline handling of
+/// [`AutoResetEvent::try_wait_for`](crate::AutoResetEvent::try_wait_for).
+///
+/// As a `MutexGuard` does not give access to its mutex, the waits take the mutex as well.
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use std::thread;
+/// use nova_autoreset_event::RawCondvar;
+///
+/// let pair = Arc::new((Mutex::new(false), RawCondvar::new()));
+/// let notifier = {
+///     let pair = pair.clone();
+///     thread::spawn(move || {
+///         *pair.0.lock().unwrap() = true;
+///         pair.1.notify_one();
+///     })
+/// };
+///
+/// let (mutex, condvar) = &*pair;
+/// let mut ready = mutex.lock().unwrap();
+/// while !*ready {
+///     ready = condvar.wait(ready, mutex);
+/// }
+/// notifier.join().unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "condvar")))]
+#[derive(Default)]
+pub struct RawCondvar {
+    waiters: WaiterQueue,
+}
+
+impl RawCondvar {
+    /// Creates a condition variable without waiters.
+    pub const fn new() -> Self {
+        Self {
+            waiters: WaiterQueue::new(()),
+        }
+    }
+
+    /// Unlocks the mutex, blocks until the condition variable is notified, and locks the mutex
+    /// again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` is not a guard of `mutex`, and, like [`Mutex::lock`], if the mutex is
+    /// poisoned.
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>, mutex: &'a Mutex<T>) -> MutexGuard<'a, T> {
+        let data = check_guard(&guard, mutex);
+        let waiter = self.waiters.lock().push();
+
+        drop(guard);
+        waiter.wait();
+        lock_again(mutex, data)
+    }
+
+    /// Like [`wait`](Self::wait), but returns after `timeout` if the condition variable has not
+    /// been notified. The `bool` is `true` if the wait timed out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` is not a guard of `mutex`, and, like [`Mutex::lock`], if the mutex is
+    /// poisoned.
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        mutex: &'a Mutex<T>,
+        timeout: Duration,
+    ) -> (MutexGuard<'a, T>, bool) {
+        let data = check_guard(&guard, mutex);
+        let waiter = self.waiters.lock().push();
+
+        drop(guard);
+        let timed_out = !self.waiters.wait_timeout(&waiter, timeout);
+        (lock_again(mutex, data), timed_out)
+    }
+
+    /// Wakes the thread that has been waiting the longest. Returns `false` if no thread is
+    /// waiting.
+    pub fn notify_one(&self) -> bool {
+        self.waiters.lock().wake_one()
+    }
+
+    /// Wakes all waiting threads. Returns the number of threads that have been woken.
+    pub fn notify_all(&self) -> usize {
+        self.waiters.lock().wake_all()
+    }
+}
+
+// Returns the address of the data the guard protects. A mutex that can be locked although the
+// guard is held is not the one of the guard; the address is compared once the mutex is locked
+// again, see `lock_again`.
+fn check_guard<T>(guard: &MutexGuard<'_, T>, mutex: &Mutex<T>) -> *const T {
+    if !matches!(mutex.try_lock(), Err(TryLockError::WouldBlock)) {
+        mismatched_guard();
+    }
+    &**guard
+}
+
+fn lock_again<'a, T>(mutex: &'a Mutex<T>, data: *const T) -> MutexGuard<'a, T> {
+    let guard = mutex.lock().unwrap();
+    if !std::ptr::eq(&*guard, data) {
+        mismatched_guard();
+    }
+    guard
+}
+
+#[cold]
+fn mismatched_guard() -> ! {
+    panic!("the guard passed to RawCondvar does not belong to the mutex")
+}
+
+impl fmt::Debug for RawCondvar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawCondvar")
+            .field("waiters", &self.waiters.lock().len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,81 @@
+// Instrumentation for tokio-console, which shows events as resources and async waits as their
+// async operations, like the primitives of `tokio::sync`.
+//
+// tokio-console only picks up this instrumentation if the application is built with
+// `--cfg tokio_unstable`, just like the instrumentation of tokio itself.
+
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::task::Poll;
+
+use tracing::Span;
+
+// Creates the resource span of an event. `concrete_type` is the type that tokio-console shows.
+pub(crate) fn resource(concrete_type: &'static str, label: Option<&str>) -> Span {
+    tracing::trace_span!(
+        parent: None,
+        "runtime.resource",
+        concrete_type,
+        kind = "Sync",
+        label,
+    )
+}
+
+// Reports that the signalled flag of an event has been set or cleared
+pub(crate) fn state_update(resource: &Span, signalled: bool) {
+    resource.in_scope(|| {
+        tracing::trace!(target: "runtime::resource::state_update", signalled);
+    });
+}
+
+// An async operation on a resource, e.g. a future that waits for an event
+#[derive(Debug)]
+pub(crate) struct AsyncOp {
+    resource: Span,
+    op: Span,
+    poll: Span,
+    // The name of the poll operation that tokio-console shows, e.g. `poll_wait`
+    poll_op: &'static str,
+}
+
+impl AsyncOp {
+    // `source` is the function that started the operation
+    pub(crate) fn new(resource: &Span, source: &'static str, poll_op: &'static str) -> Self {
+        let (op, poll) = resource.in_scope(|| {
+            let op = tracing::trace_span!(
+                "runtime.resource.async_op",
+                source,
+                inherits_child_attrs = false,
+            );
+            let poll = op.in_scope(|| tracing::trace_span!("runtime.resource.async_op.poll"));
+            (op, poll)
+        });
+        Self {
+            resource: resource.clone(),
+            op,
+            poll,
+            poll_op,
+        }
+    }
+
+    // Polls the operation within its spans, and reports whether it is ready
+    pub(crate) fn poll<T>(&self, poll: impl FnOnce() -> Poll<T>) -> Poll<T> {
+        let _resource = self.resource.enter();
+        let _op = self.op.enter();
+        let _poll = self.poll.enter();
+        let result = poll();
+        tracing::trace!(
+            target: "runtime::resource::poll_op",
+            op_name = self.poll_op,
+            is_ready = result.is_ready(),
+        );
+        result
+    }
+}
+
+// Runs `future` as the async operation `op`
+#[cfg(all(unix, feature = "tokio"))]
+pub(crate) async fn traced<F: Future>(op: AsyncOp, future: F) -> F::Output {
+    let mut future = pin!(future);
+    poll_fn(|cx| op.poll(|| future.as_mut().poll(cx))).await
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+use winapi::um::consoleapi::SetConsoleCtrlHandler;
+
+use crate::AutoResetEvent;
+
+// The live events. The handler is installed while the list is not empty.
+static EVENTS: Mutex<Vec<Arc<Shared>>> = Mutex::new(Vec::new());
+
+/// An [`AutoResetEvent`] that is signalled when the console sends a control event to the process,
+/// e.g. on Ctrl+C, Ctrl+Break or when the console window is closed.
+///
+/// This is the Windows counterpart of `OsSignalEvent` on Unix: the handler installed via
+/// `SetConsoleCtrlHandler` signals the event, so that shutdown code waits for it like for any
+/// other event, e.g. via [`AutoResetEvent::wait_any`]. [`take_received`](Self::take_received)
+/// tells which control events have arrived. While an event exists, the control events are
+/// handled, i.e. Ctrl+C and Ctrl+Break no longer terminate the process.
+///
+/// On `CTRL_CLOSE_EVENT`, `CTRL_LOGOFF_EVENT` and `CTRL_SHUTDOWN_EVENT`, Windows terminates the
+/// process shortly after the handler has returned, so the remaining cleanup has to be quick.
+///
+/// ```no_run
+/// use nova_autoreset_event::ConsoleCtrlEvent;
+///
+/// let shutdown = ConsoleCtrlEvent::new().unwrap();
+/// shutdown.wait();
+/// println!("received {:?}", shutdown.take_received());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+pub struct ConsoleCtrlEvent {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    event: AutoResetEvent,
+    // A bit per control type, e.g. `1 << CTRL_C_EVENT`
+    received: AtomicU32,
+}
+
+impl ConsoleCtrlEvent {
+    /// Creates an event that is signalled by console control events.
+    ///
+    /// Fails if the event cannot be created or the handler cannot be installed.
+    pub fn new() -> io::Result<Self> {
+        let shared = Arc::new(Shared {
+            event: AutoResetEvent::new()?,
+            received: AtomicU32::new(0),
+        });
+
+        let mut events = EVENTS.lock().unwrap();
+        if events.is_empty() && unsafe { SetConsoleCtrlHandler(Some(handler), TRUE) } == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+        events.push(shared.clone());
+        Ok(Self { shared })
+    }
+
+    /// Returns the control types that have been received since the last call, in ascending order,
+    /// e.g. `CTRL_C_EVENT`.
+    ///
+    /// Control events are coalesced like the signals of the event: a control event that arrives
+    /// several times between two calls is returned once.
+    pub fn take_received(&self) -> Vec<u32> {
+        let mut bits = self.shared.received.swap(0, Ordering::AcqRel);
+        let mut received = Vec::new();
+        while bits != 0 {
+            received.push(bits.trailing_zeros());
+            bits &= bits - 1;
+        }
+        received
+    }
+}
+
+unsafe extern "system" fn handler(ctrl_type: DWORD) -> BOOL {
+    let events = EVENTS.lock().unwrap_or_else(|err| err.into_inner());
+    for shared in events.iter() {
+        if ctrl_type < 32 {
+            shared.received.fetch_or(1 << ctrl_type, Ordering::AcqRel);
+        }
+        shared.event.signal();
+    }
+    if events.is_empty() { FALSE } else { TRUE }
+}
+
+impl Deref for ConsoleCtrlEvent {
+    type Target = AutoResetEvent;
+
+    fn deref(&self) -> &AutoResetEvent {
+        &self.shared.event
+    }
+}
+
+impl Drop for ConsoleCtrlEvent {
+    fn drop(&mut self) {
+        let mut events = EVENTS.lock().unwrap_or_else(|err| err.into_inner());
+        events.retain(|shared| !Arc::ptr_eq(shared, &self.shared));
+        if events.is_empty() {
+            unsafe { SetConsoleCtrlHandler(Some(handler), FALSE) };
+        }
+    }
+}
+
+impl fmt::Debug for ConsoleCtrlEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsoleCtrlEvent")
+            .field("event", &self.shared.event)
+            .finish_non_exhaustive()
+    }
+}
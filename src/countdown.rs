@@ -0,0 +1,136 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{AutoResetEvent, EventError};
+
+/// An event that is set while a count of pending operations is zero.
+///
+/// The count is raised via [`add`](Self::add) or [`guard`](Self::guard), and lowered via
+/// [`signal`](Self::signal) or by dropping the [`SignalGuard`]. [`wait`](Self::wait) blocks until
+/// it has dropped to zero. Unlike a latch, the count can be raised again afterwards, so the event
+/// can be reused.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use nova_autoreset_event::CountdownEvent;
+///
+/// let countdown = Arc::new(CountdownEvent::new(0).unwrap());
+/// for _ in 0..4 {
+///     let guard = countdown.guard();
+///     thread::spawn(move || drop(guard));
+/// }
+/// countdown.wait();
+/// assert_eq!(countdown.count(), 0);
+/// ```
+pub struct CountdownEvent {
+    count: AtomicUsize,
+    // Signalled when the count drops to zero. Every waiter that returns passes the signal on, so
+    // that all of them are released.
+    zero: AutoResetEvent,
+}
+
+/// Signals a [`CountdownEvent`] once when it is dropped, also if the thread unwinds, see
+/// [`CountdownEvent::guard`].
+#[must_use = "the countdown is signalled when the guard is dropped"]
+pub struct SignalGuard {
+    countdown: Arc<CountdownEvent>,
+}
+
+impl CountdownEvent {
+    /// Creates a countdown event with an initial count.
+    pub fn new(count: usize) -> Result<Self, EventError> {
+        Ok(Self {
+            count: AtomicUsize::new(count),
+            zero: AutoResetEvent::new()?,
+        })
+    }
+
+    /// Raises the count by `n`.
+    pub fn add(&self, n: usize) {
+        self.count.fetch_add(n, Ordering::AcqRel);
+    }
+
+    /// Lowers the count by one. Returns `true` if it has dropped to zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the count is already zero.
+    pub fn signal(&self) -> bool {
+        let previous = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            })
+            .expect("a countdown event was signalled more often than its count");
+        if previous == 1 {
+            self.zero.signal();
+        }
+        previous == 1
+    }
+
+    /// Raises the count by one and returns a guard that lowers it again when it is dropped.
+    pub fn guard(self: &Arc<Self>) -> SignalGuard {
+        self.add(1);
+        SignalGuard {
+            countdown: self.clone(),
+        }
+    }
+
+    /// Returns the current count.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Blocks until the count is zero.
+    pub fn wait(&self) {
+        self.wait_until(None);
+    }
+
+    /// Blocks until the count is zero, for at most `timeout`. Returns `false` on timeout.
+    pub fn try_wait_for(&self, timeout: Duration) -> bool {
+        self.wait_until(Instant::now().checked_add(timeout))
+    }
+
+    // Waits without a deadline if it is `None`. Returns `false` if the deadline has passed first.
+    pub(crate) fn wait_until(&self, deadline: Option<Instant>) -> bool {
+        // A signal may be left over from an earlier time the count dropped to zero
+        while self.count() != 0 {
+            match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if !self.zero.try_wait_for(timeout) && self.count() != 0 {
+                        return false;
+                    }
+                }
+                None => self.zero.wait(),
+            }
+        }
+        self.zero.signal();
+        true
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        self.countdown.signal();
+    }
+}
+
+impl fmt::Debug for CountdownEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountdownEvent")
+            .field("count", &self.count())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for SignalGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignalGuard")
+            .field("countdown", &self.countdown)
+            .finish()
+    }
+}
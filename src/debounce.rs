@@ -0,0 +1,110 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{AutoResetEvent, EventError, TimerWheel};
+
+/// Signals an event at most once per interval, however often it is signalled.
+///
+/// The first signal after a quiet period is delivered right away. Signals within the interval
+/// after that are coalesced into one trailing signal at the end of the interval, so the last
+/// change is never lost. The trailing signals are delivered by a [`TimerWheel`] that is shared by
+/// all signalers and started on first use.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use nova_autoreset_event::{AutoResetEvent, DebouncedSignaler};
+///
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// let signaler = DebouncedSignaler::new(&event, Duration::from_millis(20)).unwrap();
+///
+/// for _ in 0..1000 {
+///     signaler.signal();
+/// }
+/// assert!(event.try_wait());
+/// // The trailing signal
+/// event.wait();
+/// ```
+pub struct DebouncedSignaler {
+    event: Arc<AutoResetEvent>,
+    min_interval: Duration,
+    wheel: &'static TimerWheel,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // When the event was last signalled, or will be by the pending timer
+    last: Option<Instant>,
+    // Whether a trailing signal is scheduled at `last`
+    scheduled: bool,
+}
+
+impl DebouncedSignaler {
+    /// Creates a signaler for `event` that signals it at most once per `min_interval`.
+    ///
+    /// Fails if the shared timer wheel cannot be created.
+    pub fn new(event: &Arc<AutoResetEvent>, min_interval: Duration) -> Result<Self, EventError> {
+        Ok(Self {
+            event: event.clone(),
+            min_interval,
+            wheel: TimerWheel::shared()?,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// Signals the event, or schedules a trailing signal if it has been signalled within the
+    /// interval.
+    pub fn signal(&self) {
+        let now = Instant::now();
+        let mut state = self.lock();
+        if let Some(last) = state.last {
+            if state.scheduled && last > now {
+                // Coalesced into the pending trailing signal
+                return;
+            }
+            match last.checked_add(self.min_interval) {
+                // The interval does not end in any practical time
+                None => return,
+                Some(next) if next > now => {
+                    state.last = Some(next);
+                    state.scheduled = true;
+                    drop(state);
+                    self.wheel.signal_at(&self.event, next);
+                    return;
+                }
+                Some(_) => {}
+            }
+        }
+
+        state.last = Some(now);
+        state.scheduled = false;
+        drop(state);
+        self.event.signal();
+    }
+
+    /// Returns the event.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+
+    /// Returns the minimal interval between two signals of the event.
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for DebouncedSignaler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebouncedSignaler")
+            .field("event", &self.event)
+            .field("min_interval", &self.min_interval)
+            .field("state", &*self.lock())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,150 @@
+// The registry of live events behind the `diagnostics` feature.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::Backend;
+use crate::state::{self, State};
+
+static REGISTRY: Mutex<BTreeMap<u64, Weak<Record>>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// The diagnostic state of a live event. It is owned by the event and removes itself from the
+// registry when the event is dropped.
+#[derive(Debug)]
+pub(crate) struct Record {
+    id: u64,
+    label: Option<String>,
+    backend: Backend,
+    pub(crate) state: State,
+    pub(crate) signals: AtomicU64,
+    pub(crate) blocking_waits: AtomicU64,
+    pub(crate) timeouts: AtomicU64,
+    // The threads that are blocked on the event, by the id of their wait
+    #[cfg(feature = "debug-backtraces")]
+    parked: Mutex<BTreeMap<u64, ParkedWaiter>>,
+}
+
+// A thread that is blocked on an event
+#[cfg(feature = "debug-backtraces")]
+#[derive(Debug)]
+struct ParkedWaiter {
+    thread: String,
+    backtrace: std::backtrace::Backtrace,
+}
+
+impl Record {
+    pub(crate) fn register(label: Option<&str>, backend: Backend) -> Arc<Self> {
+        let record = Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            label: label.map(str::to_owned),
+            backend,
+            state: Arc::new(AtomicU32::new(0)),
+            signals: AtomicU64::new(0),
+            blocking_waits: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            #[cfg(feature = "debug-backtraces")]
+            parked: Mutex::new(BTreeMap::new()),
+        });
+
+        registry().insert(record.id, Arc::downgrade(&record));
+        record
+    }
+
+    // Captures the backtrace of the calling thread, which is about to block on the event. Returns
+    // the id to pass to `unpark()` once the wait is over.
+    #[cfg(feature = "debug-backtraces")]
+    pub(crate) fn park(&self) -> u64 {
+        let thread = std::thread::current();
+        let waiter = ParkedWaiter {
+            thread: match thread.name() {
+                Some(name) => name.to_owned(),
+                None => format!("{:?}", thread.id()),
+            },
+            backtrace: std::backtrace::Backtrace::force_capture(),
+        };
+
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        lock(&self.parked).insert(id, waiter);
+        id
+    }
+
+    #[cfg(feature = "debug-backtraces")]
+    pub(crate) fn unpark(&self, id: u64) {
+        lock(&self.parked).remove(&id);
+    }
+}
+
+impl Drop for Record {
+    fn drop(&mut self) {
+        registry().remove(&self.id);
+    }
+}
+
+fn registry() -> std::sync::MutexGuard<'static, BTreeMap<u64, Weak<Record>>> {
+    lock(&REGISTRY)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // The maps are never left inconsistent, so a panic while they were locked does not matter
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Returns a human-readable dump of all live events.
+///
+/// The dump lists the label, backend, state and waiter count of every event, along with the
+/// number of signals, blocking waits and timeouts since its creation, in the order the events were
+/// created. The state of each event is read atomically, but the events are not read at the same
+/// instant.
+///
+/// With the `debug-backtraces` feature, the dump also contains the name and backtrace of every
+/// thread that is blocked on an event (waits on several events via `wait_any` are not included).
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+pub fn dump_state() -> String {
+    let records: Vec<Arc<Record>> = registry().values().filter_map(Weak::upgrade).collect();
+
+    let states: Vec<u32> = records
+        .iter()
+        .map(|record| record.state.load(Ordering::SeqCst))
+        .collect();
+    let waiters: u32 = states.iter().map(|&state| state::waiters(state)).sum();
+    let signalled = states
+        .iter()
+        .filter(|&&state| state::is_signalled(state))
+        .count();
+
+    let mut dump = format!(
+        "{} live events, {} signalled, {} waiters\n",
+        records.len(),
+        signalled,
+        waiters
+    );
+    for (record, state) in records.iter().zip(states) {
+        let _ = writeln!(
+            dump,
+            "  {} ({}): {}, {} waiters, {} signals, {} blocking waits, {} timeouts",
+            record.label.as_deref().unwrap_or("<unlabelled>"),
+            record.backend,
+            if state::is_signalled(state) {
+                "signalled"
+            } else {
+                "unsignalled"
+            },
+            state::waiters(state),
+            record.signals.load(Ordering::Relaxed),
+            record.blocking_waits.load(Ordering::Relaxed),
+            record.timeouts.load(Ordering::Relaxed),
+        );
+
+        #[cfg(feature = "debug-backtraces")]
+        for waiter in lock(&record.parked).values() {
+            let _ = writeln!(dump, "    blocked thread {}:", waiter.thread);
+            for line in waiter.backtrace.to_string().lines() {
+                let _ = writeln!(dump, "      {}", line);
+            }
+        }
+    }
+    dump
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::iter;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::state::GenericEvent;
+use crate::{AutoResetEvent, EventError};
+
+/// Runs per-event callbacks for many events on a single thread.
+///
+/// Instead of a blocked thread per event, the thread that calls [`run`](Self::run) or
+/// [`dispatch`](Self::dispatch) waits for all events of the dispatcher at once, like
+/// [`AutoResetEvent::wait_any`], i.e. via `epoll`/`poll`, `kqueue` or `WaitForMultipleObjects`,
+/// and runs the callbacks of the signalled events. The dispatcher consumes the signals, so its
+/// events should not be waited on otherwise.
+///
+/// A [closed](AutoResetEvent::close) event is removed together with its callback. `run` returns
+/// once the [stop event](Self::stop_event) has been signalled, e.g. by another thread.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use nova_autoreset_event::{AutoResetEvent, EventDispatcher};
+///
+/// let a = Arc::new(AutoResetEvent::new().unwrap());
+/// let b = Arc::new(AutoResetEvent::new().unwrap());
+/// let calls = Arc::new(AtomicUsize::new(0));
+///
+/// let mut dispatcher = EventDispatcher::new().unwrap();
+/// for event in [&a, &b] {
+///     let calls = calls.clone();
+///     dispatcher.add(event.clone(), move || {
+///         calls.fetch_add(1, Ordering::Relaxed);
+///     });
+/// }
+///
+/// a.signal();
+/// b.signal();
+/// assert_eq!(dispatcher.dispatch(), 2);
+/// assert_eq!(calls.load(Ordering::Relaxed), 2);
+/// ```
+pub struct EventDispatcher {
+    stop: Arc<AutoResetEvent>,
+    entries: Vec<Entry>,
+    next_key: u64,
+}
+
+/// Identifies an event and its callback in an [`EventDispatcher`], see [`EventDispatcher::add`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DispatchKey(u64);
+
+struct Entry {
+    key: DispatchKey,
+    event: Arc<AutoResetEvent>,
+    callback: Box<dyn FnMut() + Send>,
+}
+
+// How a wait of the dispatcher has ended
+enum Dispatched {
+    Callbacks(usize),
+    Stopped,
+    TimedOut,
+}
+
+impl EventDispatcher {
+    /// Creates a dispatcher without events.
+    pub fn new() -> Result<Self, EventError> {
+        Ok(Self {
+            stop: Arc::new(AutoResetEvent::new()?),
+            entries: Vec::new(),
+            next_key: 0,
+        })
+    }
+
+    /// Adds an event, and runs `callback` whenever it is signalled. An event can be added more
+    /// than once, in which case each signal runs one of its callbacks.
+    pub fn add(
+        &mut self,
+        event: Arc<AutoResetEvent>,
+        callback: impl FnMut() + Send + 'static,
+    ) -> DispatchKey {
+        let key = DispatchKey(self.next_key);
+        self.next_key += 1;
+        self.entries.push(Entry {
+            key,
+            event,
+            callback: Box::new(callback),
+        });
+        key
+    }
+
+    /// Removes an event and its callback. Returns `false` if the key is unknown, e.g. because the
+    /// event has been closed.
+    pub fn remove(&mut self, key: DispatchKey) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.key != key);
+        self.entries.len() < len
+    }
+
+    /// Returns the number of events.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the dispatcher has no events.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the event that stops [`run`](Self::run) when it is signalled. A signal while the
+    /// dispatcher does not wait stops the next wait.
+    pub fn stop_event(&self) -> &Arc<AutoResetEvent> {
+        &self.stop
+    }
+
+    /// Dispatches the signals of the events until the [stop event](Self::stop_event) is
+    /// signalled.
+    ///
+    /// # Panics
+    ///
+    /// Panics, on Windows, if the dispatcher has more than 63 events. Panics of the callbacks are
+    /// propagated.
+    pub fn run(&mut self) {
+        while !matches!(self.dispatch_for(None), Dispatched::Stopped) {}
+    }
+
+    /// Waits until one of the events is signalled, and runs the callbacks of all signalled events,
+    /// in the order in which they have been added. Returns the number of callbacks that have run,
+    /// which is zero if the wait has been ended by the [stop event](Self::stop_event) or by
+    /// closing an event.
+    ///
+    /// # Panics
+    ///
+    /// Panics, on Windows, if the dispatcher has more than 63 events. Panics of the callbacks are
+    /// propagated.
+    pub fn dispatch(&mut self) -> usize {
+        match self.dispatch_for(None) {
+            Dispatched::Callbacks(calls) => calls,
+            Dispatched::Stopped | Dispatched::TimedOut => 0,
+        }
+    }
+
+    /// Like [`dispatch`](Self::dispatch), but returns zero if none of the events is signalled
+    /// within the timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics, on Windows, if the dispatcher has more than 63 events. Panics of the callbacks are
+    /// propagated.
+    pub fn try_dispatch_for(&mut self, timeout: Duration) -> usize {
+        match self.dispatch_for(Some(timeout)) {
+            Dispatched::Callbacks(calls) => calls,
+            Dispatched::Stopped | Dispatched::TimedOut => 0,
+        }
+    }
+
+    fn dispatch_for(&mut self, timeout: Option<Duration>) -> Dispatched {
+        let events =
+            iter::once(&self.stop.inner).chain(self.entries.iter().map(|entry| &entry.event.inner));
+        let woken = match GenericEvent::wait_any(events, timeout) {
+            None => return Dispatched::TimedOut,
+            Some(0) => return Dispatched::Stopped,
+            Some(index) => index - 1,
+        };
+
+        // The woken event has been consumed already. The others are checked as well, so that an
+        // event that is signalled all the time does not starve the ones after it.
+        let mut calls = 0;
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            if (index == woken && !entry.event.is_closed()) || entry.event.try_wait() {
+                (entry.callback)();
+                calls += 1;
+            }
+        }
+
+        self.entries.retain(|entry| !entry.event.is_closed());
+        Dispatched::Callbacks(calls)
+    }
+}
+
+impl fmt::Debug for EventDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventDispatcher")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
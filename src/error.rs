@@ -0,0 +1,183 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::Backend;
+
+/// An error of the kernel object of an event.
+///
+/// Wraps the [`io::Error`] of the failed call, together with the failed operation, the backend and
+/// a classification that tells whether to retry, raise a limit or fall back to another backend.
+/// Creating an event reports failures this way, as do the checked variants of the waits and
+/// [`signal`](crate::AutoResetEvent::signal_checked). Converts into an
+/// [`io::Error`] of the same kind, so `?` keeps working in functions that return
+/// [`io::Result`].
+///
+/// ```
+/// use nova_autoreset_event::{AutoResetEvent, Backend, EventErrorKind};
+///
+/// let event = match AutoResetEvent::new() {
+///     Err(err) if err.kind() == EventErrorKind::Unsupported => {
+///         AutoResetEvent::builder().backend(Backend::native()).build()
+///     }
+///     result => result,
+/// };
+/// ```
+#[derive(Debug)]
+pub struct EventError {
+    operation: EventOperation,
+    kind: EventErrorKind,
+    backend: Backend,
+    source: io::Error,
+}
+
+/// The operation that failed with an [`EventError`].
+///
+/// New variants may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventOperation {
+    /// Creating the kernel object, e.g. via [`AutoResetEvent::new`](crate::AutoResetEvent::new).
+    Create,
+    /// Blocking on the kernel object, e.g. in
+    /// [`wait_checked`](crate::AutoResetEvent::wait_checked).
+    Wait,
+    /// Waking the kernel object in
+    /// [`signal_checked`](crate::AutoResetEvent::signal_checked).
+    Signal,
+}
+
+/// The cause of an [`EventError`].
+///
+/// New variants may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventErrorKind {
+    /// The process or the system ran out of file descriptors, handles or kernel memory, e.g.
+    /// `EMFILE` or `ENFILE`. Raising the limit (`ulimit -n`) or closing unused events helps.
+    ResourceExhausted,
+    /// A sandbox or security policy denied creating the kernel object, e.g. `EPERM` from a seccomp
+    /// filter. Another backend may be permitted.
+    PermissionDenied,
+    /// The backend is not available on this platform, or the kernel does not support it, e.g.
+    /// `ENOSYS` on an old kernel. Another backend has to be used.
+    Unsupported,
+    /// Any other error.
+    Other,
+}
+
+impl EventError {
+    pub(crate) fn new(operation: EventOperation, backend: Backend, source: io::Error) -> Self {
+        Self {
+            operation,
+            kind: classify(&source),
+            backend,
+            source,
+        }
+    }
+
+    // Like `new`, but returns the `EventError` that `source` carries if there is one, e.g. that of
+    // a kernel object that failed to be created on demand during a wait
+    pub(crate) fn from_io(operation: EventOperation, backend: Backend, source: io::Error) -> Self {
+        source
+            .downcast::<EventError>()
+            .unwrap_or_else(|source| Self::new(operation, backend, source))
+    }
+
+    /// Returns the operation that failed.
+    pub fn operation(&self) -> EventOperation {
+        self.operation
+    }
+
+    /// Returns the cause of the error.
+    pub fn kind(&self) -> EventErrorKind {
+        self.kind
+    }
+
+    /// Returns the backend of the kernel object.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Returns the error of the failed call.
+    pub fn io_error(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Returns the error of the failed call, discarding the diagnostics.
+    pub fn into_io_error(self) -> io::Error {
+        self.source
+    }
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self.operation {
+            EventOperation::Create => "create",
+            EventOperation::Wait => "wait for",
+            EventOperation::Signal => "signal",
+        };
+        write!(
+            f,
+            "failed to {} {} event: {}",
+            operation, self.backend, self.source
+        )?;
+        match self.kind {
+            EventErrorKind::ResourceExhausted => {
+                f.write_str(
+                    " (too many open files or handles: raise the limit or close unused events)",
+                )
+            }
+            EventErrorKind::PermissionDenied => {
+                f.write_str(" (denied by a sandbox or security policy: try another backend)")
+            }
+            EventErrorKind::Unsupported => f.write_str(" (try another backend)"),
+            EventErrorKind::Other => Ok(()),
+        }
+    }
+}
+
+impl Error for EventError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<EventError> for io::Error {
+    fn from(err: EventError) -> Self {
+        io::Error::new(err.source.kind(), err)
+    }
+}
+
+fn classify(err: &io::Error) -> EventErrorKind {
+    if is_resource_exhausted(err) {
+        return EventErrorKind::ResourceExhausted;
+    }
+    match err.kind() {
+        io::ErrorKind::OutOfMemory => EventErrorKind::ResourceExhausted,
+        io::ErrorKind::PermissionDenied => EventErrorKind::PermissionDenied,
+        io::ErrorKind::Unsupported => EventErrorKind::Unsupported,
+        _ => EventErrorKind::Other,
+    }
+}
+
+// The fd and handle limits do not have an `io::ErrorKind` of their own
+#[cfg(unix)]
+fn is_resource_exhausted(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EMFILE | libc::ENFILE))
+}
+
+#[cfg(windows)]
+fn is_resource_exhausted(err: &io::Error) -> bool {
+    use winapi::shared::winerror::{ERROR_NO_SYSTEM_RESOURCES, ERROR_TOO_MANY_OPEN_FILES};
+
+    matches!(
+        err.raw_os_error().map(|code| code as u32),
+        Some(ERROR_TOO_MANY_OPEN_FILES | ERROR_NO_SYSTEM_RESOURCES)
+    )
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_resource_exhausted(_err: &io::Error) -> bool {
+    false
+}
@@ -0,0 +1,965 @@
+use std::fmt;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+use std::io;
+#[cfg(unix)]
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsHandle, AsRawHandle, BorrowedHandle, RawHandle};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use crate::InterruptPolicy;
+use crate::instrument::Instruments;
+use crate::latency::HistogramSnapshot;
+use crate::state::GenericEvent;
+#[cfg(unix)]
+use crate::state::TryAsFd;
+use crate::{
+    Backend, Builder, EventError, EventOperation, EventSnapshot, Registration, WaitFuture,
+    WaitResult, WaitTimeoutFuture, WaitToken, sys,
+};
+
+/// An autoreset event.
+///
+/// See the [module-level documentation](crate) for more information.
+///
+/// The `Debug` output shows the label, the backend, whether the event is signalled or closed and
+/// the number of blocked waiters.
+///
+/// # Dropping
+///
+/// An event cannot be dropped while a thread is blocked on it: waits borrow the event, so the
+/// event is kept alive by the references or the `Arc` that the waiting threads hold. The kernel
+/// object is released when the last owner drops the event, after all waits have returned. To end
+/// the waits of other owners, e.g. on shutdown, [`close`](Self::close) the event; they return
+/// [`WaitResult::Closed`].
+///
+/// The fd or handle that has been handed out via `AsFd`/`AsHandle` is closed together with the
+/// event, so event loops have to deregister it before.
+pub struct AutoResetEvent {
+    pub(crate) inner: GenericEvent<sys::Waker>,
+}
+
+impl AutoResetEvent {
+    /// Creates a new autoreset event.
+    ///
+    /// The kernel object of the event is created right away, and a failure to create it is
+    /// returned as an [`EventError`]. On Unix, [`Builder::lazy`] defers its creation until it is
+    /// first needed.
+    pub fn new() -> Result<Self, EventError> {
+        Self::builder().build()
+    }
+
+    /// Creates `n` autoreset events with the default configuration.
+    ///
+    /// See [`Builder::build_many`].
+    pub fn create_many(n: usize) -> Result<Vec<Self>, EventError> {
+        Self::builder().build_many(n)
+    }
+
+    /// Returns a builder to configure the event.
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    pub(crate) fn from_builder(builder: Builder, backend: Backend) -> Result<Self, EventError> {
+        let waker = sys::waker(&builder, backend)
+            .map_err(|err| EventError::from_io(EventOperation::Create, backend, err))?;
+        let inner = GenericEvent::new(waker, Instruments::new(&builder, backend));
+        #[cfg(feature = "virtual-time")]
+        let inner = inner.with_clock(builder.clock);
+        #[cfg(unix)]
+        let inner = inner.with_interruptible(builder.interrupts == InterruptPolicy::Report);
+
+        Ok(Self { inner })
+    }
+
+    /// Waits for the event to be signalled.
+    ///
+    /// If the event is already in the signalled state, this function will return immediately and
+    /// reset the event to the unsignalled state. Otherwise, it will spin for a short, adaptively
+    /// tuned time and then block until another thread signals the event.
+    ///
+    /// Unix signals that are delivered to the thread while it is blocked do not end the wait: the
+    /// interrupted system call is restarted, unless the event has been built with
+    /// [`InterruptPolicy::Report`](crate::InterruptPolicy::Report).
+    ///
+    /// There are no spurious wakeups: a wakeup of the kernel object whose signal has already been
+    /// consumed by another thread, or readiness without a wakeup (e.g. `POLLERR` or an unrelated
+    /// kqueue filter), is absorbed and the thread blocks again.
+    ///
+    /// Also returns once the event has been [closed](Self::close). Use
+    /// [`wait_result`](Self::wait_result) to tell both cases apart.
+    pub fn wait(&self) {
+        self.inner.wait();
+    }
+
+    /// Waits for the event to be signalled or closed.
+    ///
+    /// Like [`wait`](Self::wait), but returns [`WaitResult::Closed`] instead of consuming a signal
+    /// once the event has been closed. Never returns [`WaitResult::TimedOut`].
+    pub fn wait_result(&self) -> WaitResult {
+        self.inner.wait()
+    }
+
+    /// Waits for the event to be signalled or closed, and returns OS errors instead of panicking.
+    ///
+    /// Like [`wait`](Self::wait), but if the kernel object fails, e.g. with `EBADF`, the wait ends
+    /// with an [`EventError`] that wraps the error, also with the `no-panic` feature. A signal that
+    /// the wait has consumed is reported as success, even if an error occurred before. If the
+    /// kernel object of a [lazy](Builder::lazy) event cannot be created, the error is reported for
+    /// [`EventOperation::Create`], e.g. with
+    /// [`EventErrorKind::ResourceExhausted`](crate::EventErrorKind::ResourceExhausted).
+    pub fn wait_checked(&self) -> Result<(), EventError> {
+        match crate::state::checked(|| self.inner.wait()) {
+            (WaitResult::Signalled, _) | (_, None) => Ok(()),
+            (_, Some(err)) => {
+                Err(EventError::from_io(
+                    EventOperation::Wait,
+                    self.backend(),
+                    err,
+                ))
+            }
+        }
+    }
+
+    /// Waits for the event to be signalled and returns the number of signals it received.
+    ///
+    /// Signals that arrive while the event is already signalled are coalesced into a single
+    /// wakeup. This returns how many signals have been coalesced since the previous call, so that
+    /// a consumer can take exactly as many work items as have been signalled. The result is always
+    /// at least 1, unless the event has been [closed](Self::close).
+    ///
+    /// The count is only reset by `wait_count`: signals consumed by [`wait`](Self::wait) and the
+    /// other wait functions are included in the next result.
+    pub fn wait_count(&self) -> u64 {
+        self.inner.wait_count()
+    }
+
+    /// Consumes the pending signal without blocking and returns the number of signals that were
+    /// pending and are discarded by this call, e.g. to learn how much work has accumulated in a
+    /// task queue.
+    ///
+    /// Signals coalesce into the pending signal until a wait, [`reset`](Self::reset) or `drain`
+    /// consumes it, and each of them discards the signals that it stood for. So the result only
+    /// includes the signals since the pending signal was last consumed. Returns 0 if the event is
+    /// not signalled or has been [closed](Self::close). A signal that races with a concurrent wait
+    /// may leave the event signalled although the wait has already discarded it, and then a
+    /// following `drain` returns 0. The discarded signals are not counted by a later
+    /// [`wait_count`](Self::wait_count).
+    ///
+    /// ```
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// let event = AutoResetEvent::new().unwrap();
+    /// for _ in 0..3 {
+    ///     event.signal();
+    /// }
+    /// assert_eq!(event.drain(), 3);
+    /// assert_eq!(event.drain(), 0);
+    /// ```
+    pub fn drain(&self) -> usize {
+        self.inner.drain()
+    }
+
+    /// Waits until at least `n` signals have arrived and consumes exactly `n` of them.
+    ///
+    /// The signals are counted like those of [`wait_count`](Self::wait_count), including coalesced
+    /// ones, and both take from the same count: signals beyond `n` are left for the next call.
+    /// This lets a consumer that processes work in chunks of `n` items wake up once per chunk
+    /// instead of once per item. A signal that is consumed by [`wait`](Self::wait) or the other
+    /// wait functions is still counted. `wait_many(0)` returns right away.
+    ///
+    /// Returns [`WaitResult::Closed`] without consuming signals once the event has been
+    /// [closed](Self::close). Never returns [`WaitResult::TimedOut`].
+    ///
+    /// ```
+    /// use nova_autoreset_event::{AutoResetEvent, WaitResult};
+    ///
+    /// let event = AutoResetEvent::new().unwrap();
+    /// for _ in 0..5 {
+    ///     event.signal();
+    /// }
+    /// assert_eq!(event.wait_many(3), WaitResult::Signalled);
+    /// assert_eq!(event.wait_count(), 2);
+    /// ```
+    pub fn wait_many(&self, n: u64) -> WaitResult {
+        self.inner.wait_many(n)
+    }
+
+    /// Polls the event from an async executor: consumes the signal and returns `Ready`, or
+    /// registers the waker of `cx` and returns `Pending`.
+    ///
+    /// This is the building block for futures and reactors that do not depend on a specific
+    /// runtime. A registered task is woken by the next signal or once the event has been
+    /// [closed](Self::close), which also makes the poll return `Ready`. All registered tasks are
+    /// woken by a signal, and the first one to poll again consumes it; the others register again.
+    /// Signals via [`signal_from_handler`](Self::signal_from_handler) do not wake tasks, unless
+    /// the `reactor` feature is enabled.
+    ///
+    /// Tasks and threads that block in [`wait`](Self::wait) or the other blocking waits can wait
+    /// on the same event: a signal wakes a blocked thread as well as the polling tasks, and
+    /// whichever consumes it first returns, while the others wait again. So every signal is
+    /// consumed by exactly one of them.
+    ///
+    /// # Cancel safety
+    ///
+    /// A poll only consumes the signal when it returns `Ready`. A task that stops polling after
+    /// `Pending` does not consume a signal, and does not swallow the wakeup of other tasks.
+    pub fn poll_wait(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.poll_wait_result(cx).map(|_| ())
+    }
+
+    // Like `poll_wait`, but tells a signal from closing the event
+    pub(crate) fn poll_wait_result(&self, cx: &mut Context<'_>) -> Poll<WaitResult> {
+        let result = self.inner.poll_wait(cx);
+        #[cfg(feature = "reactor")]
+        if result.is_pending()
+            && let Some(tasks) = self.inner.watch()
+        {
+            crate::reactor::watch(self, tasks);
+        }
+        result
+    }
+
+    /// Returns a future that waits for the event to be signalled or closed, like
+    /// [`wait_result`](Self::wait_result), for any executor.
+    ///
+    /// The future is cancel-safe: dropping it before it completes neither consumes nor loses a
+    /// signal, see [`WaitFuture`].
+    ///
+    /// ```
+    /// use nova_autoreset_event::{AutoResetEvent, WaitResult};
+    ///
+    /// # futures_executor::block_on(async {
+    /// let event = AutoResetEvent::new().unwrap();
+    /// event.signal();
+    /// assert_eq!(event.wait_async().await, WaitResult::Signalled);
+    /// # });
+    /// ```
+    pub fn wait_async(&self) -> WaitFuture<'_> {
+        WaitFuture {
+            event: self,
+            #[cfg(all(feature = "tracing", tokio_unstable))]
+            op: self.async_op("AutoResetEvent::wait_async"),
+        }
+    }
+
+    /// Returns a future that waits for the event to be signalled for at most `timeout`, like
+    /// [`try_wait_for`](Self::try_wait_for), for any executor.
+    ///
+    /// The future resolves to `true` if it consumed a signal, and to `false` on timeout or once
+    /// the event has been closed. It is cancel-safe, see [`WaitTimeoutFuture`]. A timeout that
+    /// cannot be represented as an [`Instant`] never expires.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// # futures_executor::block_on(async {
+    /// let event = AutoResetEvent::new().unwrap();
+    /// assert!(!event.wait_async_timeout(Duration::from_millis(10)).await);
+    /// event.signal();
+    /// assert!(event.wait_async_timeout(Duration::from_millis(10)).await);
+    /// # });
+    /// ```
+    pub fn wait_async_timeout(&self, timeout: Duration) -> WaitTimeoutFuture<'_> {
+        WaitTimeoutFuture {
+            event: self,
+            deadline: Instant::now().checked_add(timeout),
+            timer: None,
+            #[cfg(all(feature = "tracing", tokio_unstable))]
+            op: self.async_op("AutoResetEvent::wait_async_timeout"),
+        }
+    }
+
+    /// Like [`wait_async_timeout`](Self::wait_async_timeout), but waits until `deadline`.
+    pub fn wait_async_deadline(&self, deadline: Instant) -> WaitTimeoutFuture<'_> {
+        WaitTimeoutFuture {
+            event: self,
+            deadline: Some(deadline),
+            timer: None,
+            #[cfg(all(feature = "tracing", tokio_unstable))]
+            op: self.async_op("AutoResetEvent::wait_async_deadline"),
+        }
+    }
+
+    // Starts an async wait that tokio-console shows as an operation on the event
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    fn async_op(&self, source: &'static str) -> crate::console::AsyncOp {
+        crate::console::AsyncOp::new(self.inner.instruments().resource(), source, "poll_wait")
+    }
+
+    /// Tries to wait for the event to be signalled.
+    ///
+    /// If the event is already in the signalled state, this function will return `true` immediately
+    /// and reset the event to the unsignalled state. Otherwise, or if the event has been
+    /// [closed](Self::close), it will return `false` immediately.
+    pub fn try_wait(&self) -> bool {
+        self.inner.try_wait()
+    }
+
+    /// Tries to wait for the event to be signalled for a specified duration.
+    ///
+    /// If the event is already in the signalled state, this function will return `true` immediately
+    /// and reset the event to the unsignalled state. If the event is signalled within the timeout,
+    /// it will return `true`. Otherwise, it will return `false`. As with [`wait`](Self::wait),
+    /// there are no spurious wakeups: `false` is only returned once the timeout has elapsed.
+    ///
+    /// Like [`wait`](Self::wait), the wait is restarted after an interrupted system call, for the
+    /// remaining time, unless the event has been built with
+    /// [`InterruptPolicy::Report`](crate::InterruptPolicy::Report), which ends it with `false`.
+    /// Timeouts beyond the limits of the kernel APIs, e.g. about 24.8 days for `poll`, are waited
+    /// for in chunks, so that the wait never ends early. Closing the event ends the wait with
+    /// `false`.
+    pub fn try_wait_for(&self, timeout: Duration) -> bool {
+        self.try_wait_for_result(timeout) == WaitResult::Signalled
+    }
+
+    /// Tries to wait for the event to be signalled for a specified duration, and reports why the
+    /// wait ended.
+    ///
+    /// Like [`try_wait_for`](Self::try_wait_for), but tells a timeout apart from a
+    /// [closed](Self::close) event.
+    pub fn try_wait_for_result(&self, timeout: Duration) -> WaitResult {
+        #[cfg(feature = "virtual-time")]
+        if let Some(clock) = self.inner.clock() {
+            return self.inner.try_wait_for_virtual(clock, timeout);
+        }
+
+        self.inner.try_wait_for(timeout)
+    }
+
+    /// Tries to wait for the event to be signalled for a specified duration, and returns early
+    /// when the thread receives a Unix signal.
+    ///
+    /// Like [`try_wait_for_result`](Self::try_wait_for_result), but a blocking call that is
+    /// interrupted by a signal handler (`EINTR`) ends the wait with [`WaitResult::Interrupted`]
+    /// instead of being restarted, so that the caller can e.g. check a flag that the handler has
+    /// set. This is the [`InterruptPolicy::Report`](crate::InterruptPolicy::Report) of a single
+    /// wait. The wait is only interrupted by handlers installed without `SA_RESTART`, or for the
+    /// kernel APIs that are never restarted, like `poll`. On Windows, waits are not interrupted.
+    pub fn try_wait_for_interruptible(&self, timeout: Duration) -> WaitResult {
+        #[cfg(feature = "virtual-time")]
+        if let Some(clock) = self.inner.clock() {
+            return self.inner.try_wait_for_virtual(clock, timeout);
+        }
+
+        self.inner.try_wait_for_interruptible(timeout)
+    }
+
+    /// Tries to wait for the event to be signalled for a specified duration, and returns OS
+    /// errors instead of panicking.
+    ///
+    /// Like [`try_wait_for`](Self::try_wait_for), but if the kernel object fails, the wait ends
+    /// with the error, see [`wait_checked`](Self::wait_checked).
+    pub fn try_wait_for_checked(&self, timeout: Duration) -> Result<bool, EventError> {
+        match crate::state::checked(|| self.try_wait_for_result(timeout)) {
+            (WaitResult::Signalled, _) => Ok(true),
+            (_, None) => Ok(false),
+            (_, Some(err)) => {
+                Err(EventError::from_io(
+                    EventOperation::Wait,
+                    self.backend(),
+                    err,
+                ))
+            }
+        }
+    }
+
+    /// Prepares a wait in an external event loop, e.g. an epoll loop or mio.
+    ///
+    /// The calling thread is registered as a waiter until the token is passed to
+    /// [`commit_wait`](Self::commit_wait) or [`cancel_wait`](Self::cancel_wait). Signals from now
+    /// on wake the kernel object, so the event loop can block on the fd or handle without missing
+    /// a signal that arrives between its last check and the block. If
+    /// [`WaitToken::is_ready`] returns `true`, the event was already signalled or closed and the
+    /// event loop should not block.
+    ///
+    /// ```
+    /// use nova_autoreset_event::{AutoResetEvent, WaitResult};
+    ///
+    /// let event = AutoResetEvent::new().unwrap();
+    /// let token = event.prepare_wait();
+    /// if !token.is_ready() {
+    ///     // Block on the fd or handle of the event, e.g. in epoll_wait
+    /// #   event.signal();
+    /// }
+    /// assert_eq!(event.commit_wait(token), WaitResult::Signalled);
+    /// ```
+    pub fn prepare_wait(&self) -> WaitToken<'_> {
+        WaitToken {
+            event: self,
+            ready: self.inner.prepare_wait(),
+        }
+    }
+
+    /// Completes a wait prepared via [`prepare_wait`](Self::prepare_wait), once the event loop
+    /// reports the event as ready.
+    ///
+    /// Consumes the signal without blocking. Returns [`WaitResult::TimedOut`] if there is none,
+    /// e.g. because another thread consumed it first; the event loop prepares another wait then.
+    /// A signal is consumed at most once, no matter how many threads prepared waits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token was prepared on another event.
+    pub fn commit_wait(&self, token: WaitToken<'_>) -> WaitResult {
+        self.check_token(&token);
+        std::mem::forget(token);
+        self.inner.commit_wait()
+    }
+
+    /// Cancels a wait prepared via [`prepare_wait`](Self::prepare_wait) without consuming a
+    /// signal. A pending signal is left to other waiters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token was prepared on another event.
+    pub fn cancel_wait(&self, token: WaitToken<'_>) {
+        self.check_token(&token);
+        drop(token);
+    }
+
+    fn check_token(&self, token: &WaitToken<'_>) {
+        assert!(
+            std::ptr::eq(self, token.event),
+            "the wait token was prepared on another event"
+        );
+    }
+
+    /// Signals the event.
+    ///
+    /// If there is a thread waiting on the event, it will be woken up and the event will be reset
+    /// to the unsignalled state. If there are no threads waiting, the event will remain in the
+    /// signalled state until a thread waits on it.
+    ///
+    /// A signal is consumed by exactly one wait, no matter how many threads are waiting and on
+    /// every backend. If the kernel object wakes several threads, e.g. a kqueue that is waited on
+    /// by several threads, all but the one that consumes the signal block again.
+    ///
+    /// Signals are not counted: signalling an event that is already signalled has no effect, so
+    /// any number of signals before a wait is consumed by that single wait. This holds on every
+    /// backend, although an `eventfd` or a Win32 event would behave differently on their own. Use
+    /// [`wait_count`](Self::wait_count) to learn how many signals have been coalesced.
+    pub fn signal(&self) {
+        self.inner.signal()
+    }
+
+    /// Signals the event, and returns OS errors instead of panicking.
+    ///
+    /// Like [`signal`](Self::signal), but returns an [`EventError`] if the kernel object cannot be
+    /// woken, also with the `no-panic` feature. The event is signalled in userspace anyway, so that
+    /// [`try_wait`](Self::try_wait) sees the signal, but a blocked waiter may not be woken.
+    pub fn signal_checked(&self) -> Result<(), EventError> {
+        match crate::state::checked(|| self.inner.signal()) {
+            ((), None) => Ok(()),
+            ((), Some(err)) => Err(EventError::new(EventOperation::Signal, self.backend(), err)),
+        }
+    }
+
+    /// Signals the event only if a thread is blocked on it, and otherwise does nothing. Returns
+    /// `true` if the event was signalled.
+    ///
+    /// Unlike [`signal`](Self::signal), this never latches a wakeup for a later wait. It suits
+    /// producers whose consumers re-check the shared state before they block, e.g. via
+    /// [`prepare_wait`](Self::prepare_wait): a consumer that has not registered yet sees the
+    /// update anyway. Threads that are about to block are already counted, see
+    /// [`waiter_count`](Self::waiter_count). A waiter that gives up concurrently, e.g. because its
+    /// wait times out, may still leave the signal pending. Polling tasks are not waiters, so they
+    /// are not woken unless a thread is blocked as well.
+    pub fn signal_if_waiting(&self) -> bool {
+        self.inner.signal_if_waiting()
+    }
+
+    /// Wakes all threads that are blocked on the event right now, without leaving it signalled,
+    /// e.g. to broadcast "re-evaluate your state" to workers.
+    ///
+    /// This is like `PulseEvent` on Windows for a manual-reset event. The woken waits return as if
+    /// the event had been signalled: [`wait_result`](Self::wait_result) returns
+    /// [`WaitResult::Signalled`] and [`try_wait_for`](Self::try_wait_for) returns `true`. No signal
+    /// is set, consumed or counted, so a later wait blocks as if the pulse had not happened, also
+    /// one by a woken thread, and the counting waits like [`wait_count`](Self::wait_count) block
+    /// again. Threads that are about to block are already counted as blocked, see
+    /// [`waiter_count`](Self::waiter_count). Only the waits on this event alone are woken: waits
+    /// via [`wait_any`](Self::wait_any) or [`prepare_wait`](Self::prepare_wait), polling tasks and
+    /// waits on a [closed](Self::close) event are not.
+    ///
+    /// The waiters are woken one after another with a single kernel wakeup each. Threads that
+    /// start to wait in the meantime yield instead of blocking until all of them have returned.
+    pub fn pulse(&self) {
+        self.inner.pulse()
+    }
+
+    /// Signals the event for up to `n` waiters, e.g. to publish a batch of work items with one
+    /// call.
+    ///
+    /// Wakes as many of the threads that are blocked on the event right now as there are, up to
+    /// `n`, and signals the event once if none is blocked, like [`signal`](Self::signal). The
+    /// woken waits consume one of the signals each, and the remaining ones are lost. A woken
+    /// thread that waits again blocks like any other waiter, so a looping worker cannot take the
+    /// signals of the others. Threads that are about to block are already counted as blocked, see
+    /// [`waiter_count`](Self::waiter_count). Waits via [`wait_any`](Self::wait_any) or
+    /// [`prepare_wait`](Self::prepare_wait) and polling tasks are only woken by the signal that is
+    /// set if no other thread is blocked. [`wait_count`](Self::wait_count) counts `n` signals.
+    /// `signal_n(0)` does nothing.
+    ///
+    /// The waiters are woken one after another with a single kernel wakeup each, see
+    /// [`pulse`](Self::pulse).
+    pub fn signal_n(&self, n: u32) {
+        self.inner.signal_n(n)
+    }
+
+    /// Signals the event from a Unix signal handler.
+    ///
+    /// This has the same effect as [`signal`](Self::signal), but is async-signal-safe: it only
+    /// updates atomics and makes at most a few plain syscalls (`write`, `futex` or `kevent`) on
+    /// kernel objects that already exist. It does not allocate, lock, log or panic, and failed
+    /// syscalls are ignored. The signal is not seen by the instrumentation of the event, e.g. the
+    /// `log`, `metrics` or `testing` features, faults are not injected into it, and it does not
+    /// wake the tasks that poll the event via [`poll_wait`](Self::poll_wait). With the `reactor`
+    /// feature, the tasks are woken by the background thread that watches the kernel object.
+    ///
+    /// The syscalls may overwrite `errno`, which the handler should save and restore.
+    ///
+    /// ```no_run
+    /// use nova_autoreset_event::LazyAutoResetEvent;
+    ///
+    /// // Forced via `LazyAutoResetEvent::force` before the handler is installed
+    /// static SHUTDOWN: LazyAutoResetEvent = LazyAutoResetEvent::new();
+    ///
+    /// extern "C" fn on_sigterm(_: libc::c_int) {
+    ///     if let Some(event) = LazyAutoResetEvent::get(&SHUTDOWN) {
+    ///         event.signal_from_handler();
+    ///     }
+    /// }
+    /// ```
+    #[cfg(unix)]
+    pub fn signal_from_handler(&self) {
+        self.inner.signal_from_handler()
+    }
+
+    /// Closes the event and wakes all threads that are blocked on it.
+    ///
+    /// Blocked and future waits return [`WaitResult::Closed`] (or `false` for the `bool`
+    /// variants) without consuming a signal, and signals are ignored from now on. A pending
+    /// signal is discarded. On Unix, the fd of a closed event stays readable, so that event loops
+    /// that poll it notice the shutdown as well.
+    ///
+    /// Closing cannot be undone. Returns `false` if the event had already been closed.
+    ///
+    /// This shuts down worker threads without a sentinel signal:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use nova_autoreset_event::{AutoResetEvent, WaitResult};
+    ///
+    /// let event = Arc::new(AutoResetEvent::new().unwrap());
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let event = event.clone();
+    ///         thread::spawn(move || {
+    ///             while event.wait_result() == WaitResult::Signalled {
+    ///                 // Process the work
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    ///
+    /// event.close();
+    /// for worker in workers {
+    ///     worker.join().unwrap();
+    /// }
+    /// ```
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    /// Returns `true` if the event has been [closed](Self::close).
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Discards a pending signal without blocking, e.g. to re-initialize a worker between batches
+    /// of jobs. Returns `true` if the event was signalled.
+    ///
+    /// Unlike [`try_wait`](Self::try_wait), the discarded signal is not counted as consumed by a
+    /// wait, e.g. in the [snapshot](Self::snapshot) of the event. The signals that have been
+    /// counted for [`wait_count`](Self::wait_count) are kept. If the fd or handle has been handed
+    /// out, the pending wakeup of the kernel object is drained as well, so that event loops do not
+    /// see a stale readiness. A signal that arrives concurrently may or may not be discarded.
+    pub fn reset(&self) -> bool {
+        self.inner.reset()
+    }
+
+    /// Returns `true` if the event is signalled, without consuming the signal.
+    ///
+    /// The signalled flag lives in userspace on every backend, so this neither reads nor waits on
+    /// the kernel object. The result is a snapshot: another thread may consume the signal right
+    /// afterwards, so use [`try_wait`](Self::try_wait) to take it. A [closed](Self::close) event is
+    /// never signalled.
+    pub fn is_signalled(&self) -> bool {
+        self.inner.is_signalled()
+    }
+
+    /// Returns the number of threads that are blocked on the event, e.g. for a diagnostics
+    /// dashboard.
+    ///
+    /// Every blocking wait counts, including the timed ones, waits on several events via
+    /// [`wait_any`](Self::wait_any) and threads that hold a [`WaitToken`]. A thread is counted
+    /// from shortly before it blocks in the kernel until it has consumed the signal or given up,
+    /// and polling tasks are not counted. The count is maintained in userspace for every backend,
+    /// so this is a single atomic load. It is the same as [`EventSnapshot::waiters`].
+    pub fn waiter_count(&self) -> u32 {
+        self.inner.waiters()
+    }
+
+    /// Signals multiple events.
+    ///
+    /// This is equivalent to calling [`signal`](Self::signal) on each event, but with fewer
+    /// syscalls: events without a blocked waiter are signalled in userspace. With the `io-uring`
+    /// feature, the remaining `eventfd` writes are submitted in batches.
+    pub fn signal_many(events: &[&AutoResetEvent]) {
+        GenericEvent::signal_many(events.iter().map(|event| &event.inner))
+    }
+
+    /// Waits for one of the events to be signalled.
+    ///
+    /// Consumes the signal of one event and returns its index. If several events are signalled,
+    /// the first one in the list is chosen; the others remain signalled. For up to 32 events (on
+    /// Unix) the call does not allocate.
+    ///
+    /// A [closed](Self::close) event ends the wait as well, without consuming a signal: check
+    /// [`is_closed`](Self::is_closed) on the returned event to tell it apart from a signal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` is empty, or, on Windows, if it contains more than 64 events.
+    pub fn wait_any(events: &[&AutoResetEvent]) -> usize {
+        assert!(
+            !events.is_empty(),
+            "cannot wait for an empty list of events"
+        );
+        GenericEvent::wait_any(events.iter().map(|event| &event.inner), None)
+            .expect("untimed waits cannot time out")
+    }
+
+    /// Waits for one of the events to be signalled for a specified duration.
+    ///
+    /// Like [`wait_any`](Self::wait_any), but returns `None` if none of the events is signalled
+    /// within the timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` is empty, or, on Windows, if it contains more than 64 events.
+    pub fn try_wait_any_for(events: &[&AutoResetEvent], timeout: Duration) -> Option<usize> {
+        assert!(
+            !events.is_empty(),
+            "cannot wait for an empty list of events"
+        );
+        GenericEvent::wait_any(events.iter().map(|event| &event.inner), Some(timeout))
+    }
+
+    /// Returns a channel that receives a message whenever the event is signalled, so that the event
+    /// can be used in `crossbeam_channel::select!` together with channels.
+    ///
+    /// The receiver is a plain `crossbeam_channel::Receiver`, so it can also be added to a
+    /// `crossbeam_channel::Select` whose set of operations is only known at runtime.
+    ///
+    /// The signals are forwarded by a thread that is shared by up to 63 events; further threads are
+    /// spawned as needed. The forwarding thread consumes the signals, so the event should not be
+    /// waited on otherwise. The channel holds at most one message: signals that arrive while a
+    /// message is pending are coalesced with it, like the signals of the event itself.
+    ///
+    /// Closing the event disconnects the channel. Once the receiver has been dropped, the
+    /// forwarding thread releases the event when it is signalled or closed the next time.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use crossbeam_channel::select;
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// let event = Arc::new(AutoResetEvent::new().unwrap());
+    /// let ticks = event.as_tick_receiver().unwrap();
+    /// let (_sender, messages) = crossbeam_channel::unbounded::<String>();
+    ///
+    /// event.signal();
+    /// select! {
+    ///     recv(ticks) -> _ => println!("signalled"),
+    ///     recv(messages) -> message => println!("{:?}", message),
+    /// }
+    /// ```
+    #[cfg(feature = "crossbeam-channel")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "crossbeam-channel")))]
+    pub fn as_tick_receiver(
+        self: &std::sync::Arc<Self>,
+    ) -> std::io::Result<crossbeam_channel::Receiver<()>> {
+        crate::tick::tick_receiver(self.clone())
+    }
+
+    /// Returns an io_uring submission entry that waits for the event, for thread-per-core runtimes
+    /// that drive an io_uring, like glommio or monoio.
+    ///
+    /// The entry is a multishot `IORING_OP_POLL_ADD` for `POLLIN` on the fd of the event, so it
+    /// produces a completion per signal until it is cancelled: pass the result of every completion
+    /// to [`complete_uring_poll`](Self::complete_uring_poll), which consumes the signal. Signals
+    /// that arrive before the signal of a completion has been consumed are coalesced with it. The
+    /// kernel may end a multishot poll, e.g. on overflow of the completion queue, in which case the
+    /// completion lacks `IORING_CQE_F_MORE` (see `io_uring::cqueue::more`) and a new entry has to
+    /// be submitted.
+    ///
+    /// Runtimes that depend on another version of `io-uring` can build the same entry from
+    /// [`as_raw_fd`](AsRawFd::as_raw_fd) and `libc::POLLIN`. The fd is closed together with the
+    /// event, so the poll has to be cancelled, e.g. via `AsyncCancel` on its user data, before the
+    /// event is dropped.
+    ///
+    /// ```
+    /// use io_uring::{IoUring, cqueue};
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// # let Ok(mut ring) = IoUring::new(8) else { return };
+    /// let event = AutoResetEvent::new().unwrap();
+    /// let entry = event.uring_poll_entry().user_data(42);
+    /// unsafe { ring.submission().push(&entry).unwrap() };
+    ///
+    /// event.signal();
+    /// ring.submit_and_wait(1).unwrap();
+    /// let completion = ring.completion().next().unwrap();
+    /// assert_eq!(completion.user_data(), 42);
+    /// assert!(event.complete_uring_poll(completion.result()).unwrap());
+    /// assert!(cqueue::more(completion.flags()));
+    /// ```
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "io-uring", target_os = "linux"))))]
+    pub fn uring_poll_entry(&self) -> io_uring::squeue::Entry {
+        crate::uring::poll_entry(self.as_raw_fd())
+    }
+
+    /// Handles the completion of an entry from [`uring_poll_entry`](Self::uring_poll_entry),
+    /// given its result. Returns `true` if a signal has been consumed.
+    ///
+    /// Returns `false` if the signal has been consumed by another waiter, and once the event has
+    /// been [closed](Self::close): check [`is_closed`](Self::is_closed) to tell them apart, and
+    /// cancel the poll of a closed event, as its fd stays readable. Fails with the error of the
+    /// poll if the result is negative, e.g. `ECANCELED` once it has been cancelled.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "io-uring", target_os = "linux"))))]
+    pub fn complete_uring_poll(&self, result: i32) -> std::io::Result<bool> {
+        if result < 0 {
+            return Err(std::io::Error::from_raw_os_error(-result));
+        }
+        Ok(self.try_wait())
+    }
+
+    /// Runs `callback` whenever the event is signalled, until the returned [`Registration`] is
+    /// dropped.
+    ///
+    /// The callbacks run on a thread that is shared by up to 63 events; further threads are
+    /// spawned as needed. The thread consumes the signal before it runs the callback, so the event
+    /// should not be waited on otherwise, and signals that arrive while the callback runs are
+    /// coalesced into one more call. A long-running callback delays the other callbacks of its
+    /// thread.
+    ///
+    /// The callback is detached once the event has been [closed](Self::close), or if it panics.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc;
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// let event = Arc::new(AutoResetEvent::new().unwrap());
+    /// let (sender, receiver) = mpsc::channel();
+    /// let registration = event.on_signal(move || sender.send(()).unwrap()).unwrap();
+    ///
+    /// event.signal();
+    /// receiver.recv().unwrap();
+    /// drop(registration);
+    /// ```
+    pub fn on_signal(
+        self: &std::sync::Arc<Self>,
+        callback: impl FnMut() + Send + 'static,
+    ) -> std::io::Result<Registration> {
+        crate::callback::on_signal(self.clone(), Box::new(callback))
+    }
+
+    /// Returns the number of signals that found the event already signalled since it was created.
+    ///
+    /// Such a signal is merged into the pending one and does not cause a wakeup of its own. A
+    /// producer can watch this counter to detect that the consumer falls behind. Signals to a
+    /// [closed](Self::close) event are not counted.
+    pub fn coalesced_signals(&self) -> u64 {
+        self.inner.coalesced_signals()
+    }
+
+    /// Returns the label of the event, if it has been set via [`Builder::label`].
+    pub fn label(&self) -> Option<&str> {
+        self.inner.instruments().label()
+    }
+
+    /// Declares the thread that is going to signal the event.
+    ///
+    /// An untimed [`wait`](Self::wait) on the declared thread panics instead of blocking forever.
+    /// Without a declaration, a thread that has been blocked in an untimed wait for a second
+    /// panics if it is the only thread that has ever signalled the event; declaring another
+    /// thread as the signaller disables this heuristic.
+    #[cfg(feature = "deadlock-detection")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "deadlock-detection")))]
+    pub fn declare_signaller(&self, thread: std::thread::ThreadId) {
+        self.inner.instruments().declare_signaller(thread)
+    }
+
+    /// Returns the signals and consumptions of the event in the order they were recorded.
+    #[cfg(feature = "testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+    pub fn operations(&self) -> Vec<crate::Operation> {
+        self.inner.instruments().operations().operations()
+    }
+
+    /// Asserts that every signal of the event has been consumed or coalesced.
+    ///
+    /// Call this at the end of a test, after all threads that use the event have finished. Every
+    /// signal that found the event unsignalled must have been consumed by exactly one wait; a
+    /// signal that is still pending, or a wait that returned without a signal, makes the call
+    /// panic with the recorded [`operations`](Self::operations).
+    #[cfg(feature = "testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+    pub fn assert_no_lost_signals(&self) {
+        self.inner
+            .instruments()
+            .operations()
+            .assert_no_lost_signals(self.label())
+    }
+
+    /// Returns when the event was last signalled, or `None` if it has never been signalled.
+    ///
+    /// The timestamp is stored with relaxed ordering and is taken after the signal: it is meant
+    /// for monitoring, e.g. to detect a heartbeat that has gone stale, not for synchronization.
+    #[cfg(feature = "timestamps")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "timestamps")))]
+    pub fn last_signal_instant(&self) -> Option<Instant> {
+        self.inner.instruments().last_signal_instant()
+    }
+
+    /// Returns when a wait last consumed a signal of the event, or `None` if no wait has returned
+    /// successfully yet.
+    ///
+    /// Like [`last_signal_instant`](Self::last_signal_instant), this is meant for monitoring.
+    #[cfg(feature = "timestamps")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "timestamps")))]
+    pub fn last_wake_instant(&self) -> Option<Instant> {
+        self.inner.instruments().last_wake_instant()
+    }
+
+    /// Returns the signal→wake latencies of threads that blocked on the event.
+    ///
+    /// The latency is measured from the most recent [`signal`](Self::signal) to the moment a
+    /// thread that blocked in the kernel consumes the signal. Waits that are satisfied in
+    /// userspace are not recorded. The snapshot is empty unless recording has been enabled via
+    /// [`Builder::latency_stats`].
+    pub fn latency_stats(&self) -> HistogramSnapshot {
+        self.inner.instruments().latency_stats()
+    }
+
+    /// Returns a snapshot of the state and the counters of the event.
+    ///
+    /// Unlike the `Debug` output, the snapshot is meant to be consumed programmatically, e.g. by
+    /// monitoring agents. The state is read atomically, the counters are read separately from it.
+    pub fn snapshot(&self) -> EventSnapshot {
+        self.inner.snapshot(self.backend())
+    }
+
+    /// Returns the backend of the event.
+    pub fn backend(&self) -> Backend {
+        sys::backend(self.inner.waker())
+    }
+}
+
+#[cfg(unix)]
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+impl AutoResetEvent {
+    /// Returns the fd of the event, like [`AsFd::as_fd`], but fails instead of panicking if it
+    /// cannot be created: the kernel object of a [lazy](Builder::lazy) event, or the eventfd that
+    /// the [futex](Backend::Futex) backend creates when its fd is first requested.
+    ///
+    /// ```
+    /// use nova_autoreset_event::AutoResetEvent;
+    ///
+    /// let event = AutoResetEvent::builder().lazy(true).build().unwrap();
+    /// let fd = event.try_as_fd().unwrap();
+    /// # drop(fd);
+    /// ```
+    pub fn try_as_fd(&self) -> Result<BorrowedFd<'_>, EventError> {
+        self.inner
+            .waker()
+            .try_as_fd()
+            .map_err(|err| EventError::from_io(EventOperation::Create, self.backend(), err))?;
+        Ok(self.as_fd())
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg_attr(docsrs, doc(cfg(any(target_os = "macos", target_os = "ios"))))]
+impl AutoResetEvent {
+    /// Signals the event whenever a message arrives on a Mach port.
+    ///
+    /// The port is registered with `EVFILT_MACHPORT` on the kqueue of the event, so that
+    /// notifications delivered via Mach messages (e.g. by CoreAudio or IOKit) wake the same waiters
+    /// as [`signal`](Self::signal). The message is not received: the owner of the receive right
+    /// remains responsible for dequeuing it. Every message that arrives signals the event once.
+    /// Messages are picked up by blocking waits; [`try_wait`](Self::try_wait) does not observe
+    /// them.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the event does not use the
+    /// [kqueue](Backend::Kqueue) backend.
+    pub fn attach_mach_port(&self, port: libc::mach_port_t) -> io::Result<()> {
+        sys::attach_mach_port(self.inner.waker(), port)
+    }
+
+    /// Stops signalling the event for messages on a Mach port.
+    pub fn detach_mach_port(&self, port: libc::mach_port_t) -> io::Result<()> {
+        sys::detach_mach_port(self.inner.waker(), port)
+    }
+}
+
+impl fmt::Debug for AutoResetEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoResetEvent")
+            .field("label", &self.label())
+            .field("backend", &self.backend())
+            .field("signalled", &self.inner.is_signalled())
+            .field("closed", &self.inner.is_closed())
+            .field("waiters", &self.inner.waiters())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for AutoResetEvent {
+    fn as_raw_fd(&self) -> RawFd {
+        self.as_fd().as_raw_fd()
+    }
+}
+
+/// Panics if the fd cannot be created, see [`AutoResetEvent::try_as_fd`].
+#[cfg(unix)]
+impl AsFd for AutoResetEvent {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.export().as_fd()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawHandle for AutoResetEvent {
+    fn as_raw_handle(&self) -> RawHandle {
+        self.as_handle().as_raw_handle()
+    }
+}
+
+#[cfg(windows)]
+impl AsHandle for AutoResetEvent {
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.inner.export().as_handle()
+    }
+}
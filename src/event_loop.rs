@@ -0,0 +1,115 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::AutoResetEvent;
+
+type WakeFn = dyn Fn() + Send + Sync;
+
+/// Signals an event and wakes a UI event loop in one call.
+///
+/// Background threads often need to wake worker threads that block on events as well as the
+/// event loop of the GUI, which cannot block on an event. An `EventLoopWaker` bundles the event
+/// with a callback that wakes the event loop, e.g. by posting a message to it, so that there is
+/// one wake path: [`signal`](Self::signal) signals the event and then calls the callback. With
+/// the `winit` feature, [`EventLoopWaker::winit`] creates a waker that sends a user event to a
+/// winit event loop.
+///
+/// The waker can be cloned cheaply and handed to every thread that signals. The UI thread
+/// consumes the signal like any other thread, e.g. with [`AutoResetEvent::try_wait`] when it
+/// handles the wakeup.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use nova_autoreset_event::{AutoResetEvent, EventLoopWaker};
+///
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// let wakeups = Arc::new(AtomicUsize::new(0));
+/// let waker = {
+///     let wakeups = wakeups.clone();
+///     EventLoopWaker::new(event.clone(), move || {
+///         wakeups.fetch_add(1, Ordering::Relaxed);
+///     })
+/// };
+///
+/// waker.signal();
+/// assert!(event.try_wait());
+/// assert_eq!(wakeups.load(Ordering::Relaxed), 1);
+/// ```
+#[derive(Clone)]
+pub struct EventLoopWaker {
+    event: Arc<AutoResetEvent>,
+    wake: Arc<WakeFn>,
+}
+
+impl EventLoopWaker {
+    /// Creates a waker that signals `event` and calls `wake`.
+    ///
+    /// `wake` is called on the signalling thread, after the event has been signalled, so the
+    /// event loop sees the signal when it handles the wakeup. It should not block.
+    pub fn new(event: Arc<AutoResetEvent>, wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            event,
+            wake: Arc::new(wake),
+        }
+    }
+
+    /// Creates a waker that signals `event` and sends a clone of `user_event` to a winit event
+    /// loop via `proxy`.
+    ///
+    /// Once the event loop has exited, only the event is signalled.
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use nova_autoreset_event::{AutoResetEvent, EventLoopWaker};
+    /// use winit::event_loop::EventLoop;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Wake;
+    ///
+    /// let event_loop = EventLoop::<Wake>::with_user_event().build().unwrap();
+    /// let event = Arc::new(AutoResetEvent::new().unwrap());
+    /// let waker = EventLoopWaker::winit(event.clone(), event_loop.create_proxy(), Wake);
+    ///
+    /// std::thread::spawn(move || waker.signal());
+    /// // In `ApplicationHandler::user_event`, consume the signal via `event.try_wait()`
+    /// ```
+    #[cfg(feature = "winit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "winit")))]
+    pub fn winit<T>(
+        event: Arc<AutoResetEvent>,
+        proxy: winit::event_loop::EventLoopProxy<T>,
+        user_event: T,
+    ) -> Self
+    where
+        T: Clone + Send + 'static,
+    {
+        // Neither the proxy nor the user event needs to be `Sync`
+        let target = std::sync::Mutex::new((proxy, user_event));
+        Self::new(event, move || {
+            let target = target.lock().unwrap_or_else(|err| err.into_inner());
+            let (proxy, user_event) = &*target;
+            // Fails once the event loop has exited, when there is nothing left to wake
+            let _ = proxy.send_event(user_event.clone());
+        })
+    }
+
+    /// Signals the event, and then wakes the event loop.
+    pub fn signal(&self) {
+        self.event.signal();
+        (self.wake)();
+    }
+
+    /// Returns the event that is signalled.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+}
+
+impl fmt::Debug for EventLoopWaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventLoopWaker")
+            .field("event", &self.event)
+            .finish_non_exhaustive()
+    }
+}
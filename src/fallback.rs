@@ -0,0 +1,22 @@
+use std::io;
+use std::time::Duration;
+
+use crate::pipe::Pipe;
+use crate::state::LazyKernel;
+use crate::{Backend, Builder};
+
+pub(crate) type Waker = LazyKernel<Pipe>;
+
+pub(crate) const BACKENDS: &[Backend] = &[Backend::Pipe];
+
+pub(crate) fn waker(builder: &Builder, _backend: Backend) -> io::Result<Waker> {
+    LazyKernel::with_laziness((), builder.lazy)
+}
+
+pub(crate) fn backend(_waker: &Waker) -> Backend {
+    Backend::Pipe
+}
+
+pub(crate) fn round_trip(_backend: Backend) -> io::Result<Duration> {
+    crate::backend::round_trip::<Pipe>(())
+}
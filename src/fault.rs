@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+#[cfg(unix)]
+use crate::syscall::Ret;
+
+/// A fault that a [`FaultInjector`] can inject into an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+pub enum Fault {
+    /// A blocking wait fails with `EINTR` ([`io::ErrorKind::Interrupted`]) instead of blocking.
+    Interrupt,
+    /// A blocking wait returns as if it had been woken, without a signal.
+    SpuriousWakeup,
+    /// A `read` of the kernel object fails with `EAGAIN`, as if another thread had consumed the
+    /// wakeup first. Unix only.
+    ReadWouldBlock,
+    /// A `write` to the kernel object returns without having written anything. Unix only.
+    ShortWrite,
+    /// A signal that has to wake a blocked waiter sleeps for the
+    /// [signal delay](FaultInjector::set_signal_delay) between setting the signalled flag and
+    /// waking the kernel object.
+    DelayedSignal,
+    /// A blocking wait fails with an unexpected error. The event panics, unless the `no-panic`
+    /// feature is enabled.
+    WaitError,
+    /// A `write` to the kernel object fails with `EIO`. The event panics, unless the `no-panic`
+    /// feature is enabled. Unix only.
+    WriteError,
+    /// A blocking wait returns as if its timeout had elapsed, without blocking. Waits only time
+    /// out once their deadline has passed, so this behaves like a kernel timeout that is shorter
+    /// than requested, e.g. one chunk of an oversized timeout.
+    Timeout,
+}
+
+const FAULTS: [Fault; 8] = [
+    Fault::Interrupt,
+    Fault::SpuriousWakeup,
+    Fault::ReadWouldBlock,
+    Fault::ShortWrite,
+    Fault::DelayedSignal,
+    Fault::WaitError,
+    Fault::WriteError,
+    Fault::Timeout,
+];
+
+thread_local! {
+    // The injector of the event whose kernel object the thread is currently operating on
+    static ACTIVE: RefCell<Option<FaultInjector>> = const { RefCell::new(None) };
+}
+
+/// Injects faults into the kernel operations of events, see [`Builder::faults`](crate::Builder::faults).
+///
+/// Faults are injected on demand: [`inject`](Self::inject) queues a number of faults of a kind,
+/// which are then injected into the next operations that can fail this way. This makes the
+/// recovery paths of the backends reachable in tests: interrupted and spurious wakeups are
+/// retried, wakeups that are lost to a failed `read` are picked up by the next `poll`, and short
+/// writes are repeated. None of them may be observable by the user of the event.
+///
+/// Faults are injected into [`signal`](crate::AutoResetEvent::signal) and the waits of the event.
+/// Clones share the queued faults and the counters, so one injector can drive several events.
+///
+/// ```
+/// use std::time::Duration;
+/// use nova_autoreset_event::{AutoResetEvent, Fault, FaultInjector};
+///
+/// let faults = FaultInjector::new();
+/// let event = AutoResetEvent::builder().faults(faults.clone()).build().unwrap();
+///
+/// faults.inject(Fault::Interrupt, 1);
+/// faults.inject(Fault::SpuriousWakeup, 1);
+/// assert!(!event.try_wait_for(Duration::from_millis(10)));
+/// assert_eq!(faults.injected(Fault::Interrupt), 1);
+/// assert_eq!(faults.injected(Fault::SpuriousWakeup), 1);
+/// ```
+#[derive(Clone, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+pub struct FaultInjector {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    // The number of queued faults, indexed by kind
+    pending: [AtomicU32; FAULTS.len()],
+    injected: [AtomicU64; FAULTS.len()],
+    signal_delay_ns: AtomicU64,
+}
+
+impl FaultInjector {
+    /// Creates an injector without queued faults. The signal delay is 10ms.
+    pub fn new() -> Self {
+        let injector = Self::default();
+        injector.set_signal_delay(Duration::from_millis(10));
+        injector
+    }
+
+    /// Queues `count` faults of a kind. They are injected into the next operations that can fail
+    /// this way.
+    pub fn inject(&self, fault: Fault, count: u32) {
+        let _ = self.inner.pending[fault as usize].fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |pending| Some(pending.saturating_add(count)),
+        );
+    }
+
+    /// Discards the queued faults of all kinds.
+    pub fn clear(&self) {
+        for pending in &self.inner.pending {
+            pending.store(0, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns the number of faults of a kind that are queued but have not been injected yet.
+    pub fn pending(&self, fault: Fault) -> u32 {
+        self.inner.pending[fault as usize].load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of faults of a kind that have been injected.
+    pub fn injected(&self, fault: Fault) -> u64 {
+        self.inner.injected[fault as usize].load(Ordering::SeqCst)
+    }
+
+    /// Sets how long a [`Fault::DelayedSignal`] delays the wakeup.
+    pub fn set_signal_delay(&self, delay: Duration) {
+        let nanos = delay.as_nanos().min(u64::MAX as u128) as u64;
+        self.inner.signal_delay_ns.store(nanos, Ordering::SeqCst);
+    }
+
+    // Consumes a queued fault. Returns `false` if none is queued.
+    fn take(&self, fault: Fault) -> bool {
+        let taken = self.inner.pending[fault as usize]
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                pending.checked_sub(1)
+            })
+            .is_ok();
+        if taken {
+            self.inner.injected[fault as usize].fetch_add(1, Ordering::SeqCst);
+        }
+        taken
+    }
+
+    // Blocks via `block`, unless an interrupt, a spurious wakeup, an error or a timeout is due
+    pub(crate) fn block(&self, block: impl FnOnce() -> io::Result<bool>) -> io::Result<bool> {
+        if self.take(Fault::Interrupt) {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        if self.take(Fault::WaitError) {
+            return Err(io::Error::other("injected wait error"));
+        }
+        if self.take(Fault::SpuriousWakeup) {
+            return Ok(true);
+        }
+        if self.take(Fault::Timeout) {
+            return Ok(false);
+        }
+        self.activate(block)
+    }
+
+    // Wakes via `wake`, delayed if a delayed signal is due
+    pub(crate) fn wake(&self, wake: impl FnOnce()) {
+        if self.take(Fault::DelayedSignal) {
+            let delay = self.inner.signal_delay_ns.load(Ordering::SeqCst);
+            std::thread::sleep(Duration::from_nanos(delay));
+        }
+        self.activate(wake)
+    }
+
+    // Runs `f` with the injector active for the syscalls of the calling thread
+    fn activate<R>(&self, f: impl FnOnce() -> R) -> R {
+        // Restores the previously active injector, also if `f` panics
+        struct Restore(Option<FaultInjector>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                ACTIVE.set(self.0.take());
+            }
+        }
+
+        let _restore = Restore(ACTIVE.replace(Some(self.clone())));
+        f()
+    }
+}
+
+impl fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FaultInjector")
+            .field("pending", &FAULTS.map(|fault| (fault, self.pending(fault))))
+            .field(
+                "injected",
+                &FAULTS.map(|fault| (fault, self.injected(fault))),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+// Returns the result of an injected fault if one is due for the libc call `name`
+#[cfg(unix)]
+pub(crate) fn syscall<T: Ret>(name: &str) -> Option<io::Result<T>> {
+    ACTIVE.with_borrow(|active| {
+        let injector = active.as_ref()?;
+        match name {
+            "read" if injector.take(Fault::ReadWouldBlock) => {
+                Some(Err(io::Error::from_raw_os_error(libc::EAGAIN)))
+            }
+            "write" if injector.take(Fault::ShortWrite) => Some(Ok(T::ZERO)),
+            "write" if injector.take(Fault::WriteError) => {
+                Some(Err(io::Error::from_raw_os_error(libc::EIO)))
+            }
+            _ => None,
+        }
+    })
+}
@@ -0,0 +1,302 @@
+//! A C interface to [`AutoResetEvent`].
+//!
+//! The functions are declared in `include/nova_autoreset_event.h`, which is generated by
+//! [cbindgen](https://github.com/mozilla/cbindgen) with the configuration in `cbindgen.toml`.
+//! `tests/ffi.rs` checks that it is up to date and regenerates it if `UPDATE_HEADER` is set.
+//!
+//! To link the crate into a C or C++ program, build it as a static or dynamic library:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! An event is created with the default configuration and used via an opaque pointer. The
+//! functions must not be called with a null pointer, except for [`nova_are_destroy`]. A panic
+//! inside the event aborts the process.
+//!
+//! [`RawAutoResetEvent`] has a stable layout instead, so that it can be embedded in C structs,
+//! e.g. ones that are shared with plugins built against another version of the crate. It is used
+//! via the `nova_raw_are_*` functions.
+
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::AutoResetEvent;
+use crate::state::{KernelObject, KernelWaker, POLL_INTERVAL, unexpected};
+
+#[cfg(unix)]
+type RawKernel = crate::pipe::Pipe;
+#[cfg(windows)]
+type RawKernel = crate::sys::EventHandle;
+
+/// An autoreset event, see [`AutoResetEvent`].
+pub struct NovaAutoResetEvent {
+    event: AutoResetEvent,
+}
+
+/// Creates an autoreset event. Returns null if it cannot be created.
+///
+/// The event has to be destroyed with [`nova_are_destroy`].
+#[unsafe(no_mangle)]
+pub extern "C" fn nova_are_create() -> *mut NovaAutoResetEvent {
+    match AutoResetEvent::new() {
+        Ok(event) => Box::into_raw(Box::new(NovaAutoResetEvent { event })),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Destroys an event. Does nothing if `event` is null.
+///
+/// # Safety
+///
+/// `event` must be null or have been returned by [`nova_are_create`], and must not be used after
+/// the call. No thread may be blocked on the event.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_are_destroy(event: *mut NovaAutoResetEvent) {
+    if !event.is_null() {
+        drop(unsafe { Box::from_raw(event) });
+    }
+}
+
+/// Signals the event, see [`AutoResetEvent::signal`].
+///
+/// # Safety
+///
+/// `event` must be a live event returned by [`nova_are_create`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_are_signal(event: *const NovaAutoResetEvent) {
+    unsafe { &(*event).event }.signal()
+}
+
+/// Waits for the event to be signalled, see [`AutoResetEvent::wait`].
+///
+/// # Safety
+///
+/// `event` must be a live event returned by [`nova_are_create`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_are_wait(event: *const NovaAutoResetEvent) {
+    unsafe { &(*event).event }.wait()
+}
+
+/// Consumes the signal if the event is signalled, see [`AutoResetEvent::try_wait`]. Returns
+/// `true` if a signal has been consumed.
+///
+/// # Safety
+///
+/// `event` must be a live event returned by [`nova_are_create`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_are_try_wait(event: *const NovaAutoResetEvent) -> bool {
+    unsafe { &(*event).event }.try_wait()
+}
+
+/// Waits for the event to be signalled for at most `timeout_ns` nanoseconds, see
+/// [`AutoResetEvent::try_wait_for`]. Returns `true` if a signal has been consumed.
+///
+/// # Safety
+///
+/// `event` must be a live event returned by [`nova_are_create`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_are_try_wait_for(
+    event: *const NovaAutoResetEvent,
+    timeout_ns: u64,
+) -> bool {
+    unsafe { &(*event).event }.try_wait_for(Duration::from_nanos(timeout_ns))
+}
+
+/// An autoreset event with a stable layout, named `NovaRawAutoResetEvent` in C.
+///
+/// The layout is part of the stable interface and is the same on all platforms:
+///
+/// - `state`: bit 0 is set while the event is signalled, the remaining bits count the threads
+///   that are blocked on it. It is only accessed atomically.
+/// - `reserved`: always 0.
+/// - `handles`: on Unix, the read and write end of a nonblocking pipe. On Windows, a Win32
+///   autoreset event in the first slot, the second slot is 0.
+///
+/// The event does not have the features of [`AutoResetEvent`], e.g. it cannot be closed, and its
+/// kernel object is created by [`nova_raw_are_init`] rather than on demand. Apart from
+/// [`nova_raw_are_init`] and [`nova_raw_are_destroy`], the functions may be called concurrently
+/// from any thread. An initialized event must not be moved.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RawAutoResetEvent {
+    state: u32,
+    reserved: u32,
+    handles: [isize; 2],
+}
+
+// The event is signalled
+const RAW_SIGNALLED: u32 = 1;
+// One blocked waiter
+const RAW_WAITER: u32 = 2;
+
+impl RawAutoResetEvent {
+    // Borrows the state word and the kernel object of an initialized event
+    unsafe fn parts<'a>(event: *mut Self) -> (&'a AtomicU32, ManuallyDrop<RawKernel>) {
+        let state = unsafe { AtomicU32::from_ptr(&raw mut (*event).state) };
+        let handles = unsafe { (*event).handles };
+        #[cfg(unix)]
+        let kernel = unsafe { RawKernel::from_raw_fds(handles.map(|fd| fd as _)) };
+        #[cfg(windows)]
+        let kernel = unsafe { RawKernel::from_raw_handle(handles[0] as _) };
+        (state, ManuallyDrop::new(kernel))
+    }
+}
+
+/// Initializes an event in place. Returns `false` if its kernel object cannot be created, in
+/// which case the event is left untouched.
+///
+/// The event has to be destroyed with [`nova_raw_are_destroy`].
+///
+/// # Safety
+///
+/// `event` must be valid for writes. It must not point to an initialized event.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_init(event: *mut RawAutoResetEvent) -> bool {
+    #[cfg(unix)]
+    let kernel = RawKernel::create(());
+    #[cfg(windows)]
+    let kernel = RawKernel::create(None);
+    let Ok(kernel) = kernel else {
+        return false;
+    };
+    #[cfg(unix)]
+    let handles = kernel.into_raw_fds().map(|fd| fd as isize);
+    #[cfg(windows)]
+    let handles = [kernel.into_raw_handle() as isize, 0];
+
+    unsafe {
+        event.write(RawAutoResetEvent {
+            state: 0,
+            reserved: 0,
+            handles,
+        })
+    };
+    true
+}
+
+/// Releases the kernel object of an event. The memory of the event is not freed.
+///
+/// # Safety
+///
+/// `event` must have been initialized with [`nova_raw_are_init`], and must not be used after the
+/// call, unless it is initialized again. No thread may be blocked on the event.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_destroy(event: *mut RawAutoResetEvent) {
+    let (_, kernel) = unsafe { RawAutoResetEvent::parts(event) };
+    drop(ManuallyDrop::into_inner(kernel));
+}
+
+/// Signals the event, see [`AutoResetEvent::signal`].
+///
+/// # Safety
+///
+/// `event` must have been initialized with [`nova_raw_are_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_signal(event: *mut RawAutoResetEvent) {
+    let (state, kernel) = unsafe { RawAutoResetEvent::parts(event) };
+    let prev = state.fetch_or(RAW_SIGNALLED, Ordering::SeqCst);
+    if prev & RAW_SIGNALLED == 0 && prev >= RAW_WAITER {
+        kernel.wake();
+    }
+}
+
+/// Waits for the event to be signalled, see [`AutoResetEvent::wait`].
+///
+/// # Safety
+///
+/// `event` must have been initialized with [`nova_raw_are_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_wait(event: *mut RawAutoResetEvent) {
+    let (state, kernel) = unsafe { RawAutoResetEvent::parts(event) };
+    raw_wait(state, &kernel, None);
+}
+
+/// Consumes the signal if the event is signalled, see [`AutoResetEvent::try_wait`]. Returns
+/// `true` if a signal has been consumed.
+///
+/// # Safety
+///
+/// `event` must have been initialized with [`nova_raw_are_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_try_wait(event: *mut RawAutoResetEvent) -> bool {
+    let (state, _) = unsafe { RawAutoResetEvent::parts(event) };
+    raw_take(state, 0)
+}
+
+/// Waits for the event to be signalled for at most `timeout_ns` nanoseconds, see
+/// [`AutoResetEvent::try_wait_for`]. Returns `true` if a signal has been consumed.
+///
+/// # Safety
+///
+/// `event` must have been initialized with [`nova_raw_are_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn nova_raw_are_try_wait_for(
+    event: *mut RawAutoResetEvent,
+    timeout_ns: u64,
+) -> bool {
+    let (state, kernel) = unsafe { RawAutoResetEvent::parts(event) };
+    raw_wait(state, &kernel, Some(Duration::from_nanos(timeout_ns)))
+}
+
+// Consumes the signal. If `waiter` is set, the calling thread is removed from the waiter count on
+// success.
+fn raw_take(state: &AtomicU32, waiter: u32) -> bool {
+    state
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| {
+            (state & RAW_SIGNALLED != 0).then(|| (state & !RAW_SIGNALLED) - waiter)
+        })
+        .is_ok()
+}
+
+// The same protocol as `GenericEvent`, without closing and instrumentation: a waiter registers
+// before it blocks, so that a signal that finds it registered wakes the kernel object
+fn raw_wait(state: &AtomicU32, kernel: &RawKernel, timeout: Option<Duration>) -> bool {
+    if raw_take(state, 0) {
+        return true;
+    }
+    if timeout.is_some_and(|timeout| timeout.is_zero()) {
+        return false;
+    }
+    // `None` if the deadline is not representable, which we treat as an infinite timeout
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+
+    state.fetch_add(RAW_WAITER, Ordering::SeqCst);
+    loop {
+        if raw_take(state, RAW_WAITER) {
+            return true;
+        }
+
+        let remaining = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    // Leaves unless the event has been signalled in the meantime
+                    let left = state.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |state| {
+                        (state & RAW_SIGNALLED == 0).then(|| state - RAW_WAITER)
+                    });
+                    if left.is_ok() {
+                        return false;
+                    }
+                    continue;
+                }
+                Some(deadline - now)
+            }
+            None => None,
+        };
+
+        // Wakeups of signals that another thread consumed first are spurious, the state is
+        // re-checked at the top of the loop
+        match kernel.block(remaining) {
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => {
+                unexpected("waiting for the event", err);
+                // Only reached with the `no-panic` feature, see `check_block`
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::state::unexpected;
+use crate::{AutoResetEvent, sys};
+
+/// An [`AutoResetEvent`] that is signalled when a file or directory changes.
+///
+/// The event only tells that something has changed, not what: it is meant to trigger a rescan,
+/// e.g. the reload of a configuration file, and can be waited on together with other events via
+/// [`AutoResetEvent::wait_any`]. Changes are coalesced like signals, so a burst of changes may
+/// signal the event once.
+///
+/// A thread per watch forwards the notifications of inotify (Linux), `EVFILT_VNODE` (macOS and
+/// BSD) or `FindFirstChangeNotificationW` (Windows) to the event, and is stopped when the
+/// `FsWatchEvent` is dropped.
+///
+/// A watched directory reports changes of its entries. Editors often save a file by replacing
+/// it, which is only seen by a watch of its directory; on Windows, a file is always watched via
+/// its directory.
+///
+/// ```no_run
+/// use nova_autoreset_event::FsWatchEvent;
+///
+/// let config_dir = FsWatchEvent::new("/etc/my-service").unwrap();
+/// loop {
+///     config_dir.wait();
+///     // reload the configuration
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "fs-watch")))]
+pub struct FsWatchEvent {
+    event: Arc<AutoResetEvent>,
+    path: PathBuf,
+    watch: Arc<Watch>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FsWatchEvent {
+    /// Creates an event that is signalled when the file or directory at `path` changes.
+    ///
+    /// Fails if the path does not exist or cannot be watched, or if the event cannot be created.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let event = Arc::new(AutoResetEvent::new()?);
+        let watch = Arc::new(Watch::new(&path)?);
+
+        let thread = {
+            let event = event.clone();
+            let watch = watch.clone();
+            thread::Builder::new()
+                .name("nova-autoreset-event-fs-watch".into())
+                .spawn(move || {
+                    loop {
+                        match watch.wait() {
+                            Ok(true) => event.signal(),
+                            Ok(false) => break,
+                            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                            Err(err) => {
+                                unexpected("waiting for file system changes", err);
+                                break;
+                            }
+                        }
+                    }
+                })?
+        };
+
+        Ok(Self {
+            event,
+            path,
+            watch,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the watched path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for FsWatchEvent {
+    type Target = AutoResetEvent;
+
+    fn deref(&self) -> &AutoResetEvent {
+        &self.event
+    }
+}
+
+impl Drop for FsWatchEvent {
+    fn drop(&mut self) {
+        self.watch.stop();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for FsWatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FsWatchEvent")
+            .field("path", &self.path)
+            .field("event", &self.event)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(windows)]
+use sys::PathWatch as Watch;
+
+// The kernel watch, and a pipe whose write end is closed to stop the thread
+#[cfg(unix)]
+struct Watch {
+    watch: sys::PathWatch,
+    stop_reader: io::PipeReader,
+    stop_writer: std::sync::Mutex<Option<io::PipeWriter>>,
+}
+
+#[cfg(unix)]
+impl Watch {
+    fn new(path: &Path) -> io::Result<Self> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let (stop_reader, stop_writer) = io::pipe()?;
+        Ok(Self {
+            watch: sys::PathWatch::new(&path)?,
+            stop_reader,
+            stop_writer: std::sync::Mutex::new(Some(stop_writer)),
+        })
+    }
+
+    // Blocks until the path changes or the watch is stopped. Returns `false` once it has been
+    // stopped.
+    fn wait(&self) -> io::Result<bool> {
+        use std::os::fd::{AsFd, AsRawFd};
+
+        let watch_fd = self.watch.fd().as_raw_fd();
+        let stop_fd = self.stop_reader.as_fd().as_raw_fd();
+        let mut pollfds = [watch_fd, stop_fd].map(|fd| {
+            libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            }
+        });
+        crate::syscall::call(
+            "poll",
+            format_args!("[{}, {}], timeout -1 ms", watch_fd, stop_fd),
+            || unsafe { libc::poll(pollfds.as_mut_ptr(), 2, -1) },
+        )?;
+
+        if pollfds[1].revents != 0 {
+            return Ok(false);
+        }
+        self.watch.drain()?;
+        Ok(true)
+    }
+
+    fn stop(&self) {
+        self.stop_writer.lock().unwrap().take();
+    }
+}
@@ -0,0 +1,313 @@
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::ptr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use crate::linux::EventFd;
+use crate::pipe::poll_any;
+use crate::state::{KernelObject, KernelWaker, SignalSafeWaker, TryAsFd, unexpected};
+use crate::{Backend, EventError, EventOperation, syscall};
+
+// The maximum number of futexes `futex_waitv` can wait for
+const FUTEX_WAITV_MAX: usize = 128;
+
+// Flags of a `futex_waitv` entry: a private 32-bit futex
+const FUTEX_32: u32 = 2;
+const FUTEX_WAITV_FLAGS: u32 = FUTEX_32 | libc::FUTEX_PRIVATE_FLAG as u32;
+
+// Set once `futex_waitv` turned out to be unavailable (Linux < 5.16, or seccomp filters)
+static FUTEX_WAITV_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+// An entry of the `futex_waitv` array
+#[repr(C)]
+struct FutexWaitv {
+    val: u64,
+    uaddr: u64,
+    flags: u32,
+    reserved: u32,
+}
+
+// The kernel side of the event: a futex word that holds a pending wakeup.
+//
+// Futexes cannot be polled. An eventfd that mirrors the futex is created when the fd of the event
+// is requested, or when the event is part of a multi-wait that `futex_waitv` cannot handle. From
+// then on, every wakeup is posted to both.
+#[derive(Debug)]
+pub(crate) struct Futex {
+    token: AtomicU32,
+    fd: OnceLock<EventFd>,
+}
+
+impl KernelObject for Futex {
+    type Config = ();
+
+    fn create((): ()) -> io::Result<Self> {
+        Ok(Self {
+            token: AtomicU32::new(0),
+            fd: OnceLock::new(),
+        })
+    }
+
+    fn backend((): ()) -> Backend {
+        Backend::Futex
+    }
+}
+
+impl Futex {
+    // Consumes a pending wakeup. Returns `false` if there was none.
+    fn take(&self) -> bool {
+        let taken = self.token.swap(0, Ordering::SeqCst) != 0;
+        if taken && let Some(fd) = self.fd.get() {
+            fd.drain();
+        }
+        taken
+    }
+
+    // Returns the eventfd that mirrors the futex, creating it if necessary. Panics if it cannot be
+    // created.
+    pub(crate) fn fd(&self) -> &EventFd {
+        self.try_fd().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // Returns the eventfd that mirrors the futex, creating it if necessary. A failure carries the
+    // `EventError` of the creation, like the one of `LazyKernel::try_get`.
+    fn try_fd(&self) -> io::Result<&EventFd> {
+        if let Some(fd) = self.fd.get() {
+            return Ok(fd);
+        }
+        // Threads that race to create the eventfd create one each, all but the first one are
+        // dropped again
+        let mut created = false;
+        let new = EventFd::create(())
+            .map_err(|err| EventError::new(EventOperation::Create, Backend::Futex, err))?;
+        let fd = self.fd.get_or_init(|| {
+            created = true;
+            new
+        });
+
+        // Wakeups that were posted before the eventfd existed have to be mirrored
+        if created && self.token.load(Ordering::SeqCst) != 0 {
+            fd.wake();
+        }
+        Ok(fd)
+    }
+
+    // Waits via `futex_waitv`. Returns `None` if it is not available.
+    fn waitv<'a>(
+        futexes: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> Option<io::Result<bool>> {
+        if FUTEX_WAITV_UNSUPPORTED.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut waiters: [FutexWaitv; FUTEX_WAITV_MAX] = unsafe { std::mem::zeroed() };
+        let mut count = 0;
+        for futex in futexes.clone() {
+            if count == FUTEX_WAITV_MAX {
+                return None;
+            }
+            waiters[count] = FutexWaitv {
+                val: 0,
+                uaddr: futex.token.as_ptr() as u64,
+                flags: FUTEX_WAITV_FLAGS,
+                reserved: 0,
+            };
+            count += 1;
+        }
+
+        // The timeout of `futex_waitv` is absolute
+        let deadline = timeout.and_then(|timeout| {
+            let mut now: libc::timespec = unsafe { std::mem::zeroed() };
+            unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+
+            let nanos = now.tv_nsec as u64 + timeout.subsec_nanos() as u64;
+            let secs = (now.tv_sec as u64)
+                .checked_add(timeout.as_secs())?
+                .checked_add(nanos / 1_000_000_000)?;
+            Some(libc::timespec {
+                tv_sec: libc::time_t::try_from(secs).ok()?,
+                tv_nsec: (nanos % 1_000_000_000) as libc::c_long,
+            })
+        });
+
+        let res = syscall::call(
+            "futex_waitv",
+            format_args!("{} futexes, timeout {:?}", count, timeout),
+            || unsafe {
+                libc::syscall(
+                    libc::SYS_futex_waitv,
+                    waiters.as_ptr(),
+                    count as libc::c_uint,
+                    0 as libc::c_uint,
+                    deadline.as_ref().map_or(ptr::null(), |ts| ts as *const _),
+                    libc::CLOCK_MONOTONIC,
+                )
+            },
+        );
+
+        if let Err(err) = res {
+            return match err.raw_os_error() {
+                Some(libc::ENOSYS) | Some(libc::EPERM) => {
+                    FUTEX_WAITV_UNSUPPORTED.store(true, Ordering::Relaxed);
+                    None
+                }
+                // One of the futexes has been woken before the call
+                Some(libc::EAGAIN) => {
+                    Self::take_any(futexes);
+                    Some(Ok(true))
+                }
+                Some(libc::ETIMEDOUT) => Some(Ok(false)),
+                _ => Some(Err(err)),
+            };
+        }
+
+        Self::take_any(futexes);
+        Some(Ok(true))
+    }
+
+    // Consumes the pending wakeups of all futexes. Returns `true` if there was one.
+    fn take_any<'a>(futexes: impl Iterator<Item = &'a Self>) -> bool {
+        futexes.fold(false, |taken, futex| futex.take() | taken)
+    }
+
+    // Prepares futexes for a multi-wait via their eventfds. Returns `true` if one of them has a
+    // pending wakeup, in which case the caller must not block.
+    pub(crate) fn prepare_poll<'a>(futexes: impl Iterator<Item = &'a Self> + Clone) -> bool {
+        // All eventfds have to exist before the tokens are checked, so that later wakeups are
+        // visible to `poll`
+        for futex in futexes.clone() {
+            futex.fd();
+        }
+        Self::take_any(futexes)
+    }
+}
+
+impl KernelWaker for Futex {
+    fn wake(&self) {
+        self.token.store(1, Ordering::SeqCst);
+
+        let res = syscall::call(
+            "futex",
+            format_args!("{:p}, FUTEX_WAKE, 1", self.token.as_ptr()),
+            || unsafe {
+                libc::syscall(
+                    libc::SYS_futex,
+                    self.token.as_ptr(),
+                    libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                    1,
+                )
+            },
+        );
+
+        // This should not happen
+        if let Err(err) = res {
+            unexpected("futex", err);
+        }
+
+        if let Some(fd) = self.fd.get() {
+            fd.wake();
+        }
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        if self.take() {
+            return Ok(true);
+        }
+
+        let ts = timeout.map(|timeout| {
+            libc::timespec {
+                tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as libc::c_long,
+            }
+        });
+        let res = syscall::call(
+            "futex",
+            format_args!(
+                "{:p}, FUTEX_WAIT, 0, timeout {:?}",
+                self.token.as_ptr(),
+                timeout
+            ),
+            || unsafe {
+                libc::syscall(
+                    libc::SYS_futex,
+                    self.token.as_ptr(),
+                    libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+                    0,
+                    ts.as_ref().map_or(ptr::null(), |ts| ts as *const _),
+                )
+            },
+        );
+
+        if let Err(err) = res {
+            match err.raw_os_error() {
+                // The futex has been woken before the call
+                Some(libc::EAGAIN) => {}
+                Some(libc::ETIMEDOUT) => return Ok(false),
+                _ => return Err(err),
+            }
+        }
+
+        // If another thread stole the wakeup in the meantime, this is a spurious wakeup: the caller
+        // re-checks the state and blocks again for the remaining time
+        self.take();
+        Ok(true)
+    }
+
+    fn block_any<'a>(
+        futexes: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        if Self::take_any(futexes.clone()) {
+            return Ok(true);
+        }
+
+        if let Some(result) = Self::waitv(futexes.clone(), timeout) {
+            return result;
+        }
+
+        if Self::prepare_poll(futexes.clone()) {
+            return Ok(true);
+        }
+        poll_any(futexes, |futex| futex.fd().as_fd(), timeout)
+    }
+
+    fn drain(&self) {
+        self.token.store(0, Ordering::SeqCst);
+        if let Some(fd) = self.fd.get() {
+            fd.drain();
+        }
+    }
+}
+
+impl SignalSafeWaker for Futex {
+    fn wake_from_handler(&self) {
+        self.token.store(1, Ordering::SeqCst);
+        unsafe {
+            libc::syscall(
+                libc::SYS_futex,
+                self.token.as_ptr(),
+                libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+                1,
+            )
+        };
+
+        if let Some(fd) = self.fd.get() {
+            fd.wake_from_handler();
+        }
+    }
+}
+
+impl AsFd for Futex {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd().as_fd()
+    }
+}
+
+impl TryAsFd for Futex {
+    fn try_as_fd(&self) -> io::Result<BorrowedFd<'_>> {
+        Ok(self.try_fd()?.as_fd())
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+#[cfg(all(feature = "tracing", tokio_unstable))]
+use crate::console::AsyncOp;
+use crate::{AutoResetEvent, Timer, TimerWheel, WaitResult};
+
+/// A future that waits for an event to be signalled or closed, see
+/// [`AutoResetEvent::wait_async`].
+///
+/// # Cancel safety
+///
+/// The future only consumes the signal in the poll that completes it. Dropping it before, e.g.
+/// when it loses a `select!`, leaves a pending signal to the next wait. A signal that woke the
+/// task of a dropped future is not lost either, as a signal wakes all polling tasks.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitFuture<'a> {
+    pub(crate) event: &'a AutoResetEvent,
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    pub(crate) op: AsyncOp,
+}
+
+impl Future for WaitFuture<'_> {
+    type Output = WaitResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<WaitResult> {
+        #[cfg(all(feature = "tracing", tokio_unstable))]
+        return self.op.poll(|| self.event.poll_wait_result(cx));
+
+        #[cfg(not(all(feature = "tracing", tokio_unstable)))]
+        self.event.poll_wait_result(cx)
+    }
+}
+
+/// A future that waits for an event with a timeout, see
+/// [`AutoResetEvent::wait_async_timeout`] and [`AutoResetEvent::wait_async_deadline`].
+///
+/// It resolves to `true` if it consumed a signal, and to `false` once the deadline has passed or
+/// the event has been closed. The deadline is kept by the timer wheel that the crate shares
+/// between its helpers, so the future works with any executor. The timer is only scheduled once
+/// the future has returned `Pending`, and is cancelled when the future is dropped.
+///
+/// # Cancel safety
+///
+/// Like [`WaitFuture`], the future only consumes the signal in the poll that resolves it to
+/// `true`.
+///
+/// # Panics
+///
+/// Polling panics if the thread of the shared timer wheel cannot be started.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitTimeoutFuture<'a> {
+    pub(crate) event: &'a AutoResetEvent,
+    // `None` if the deadline is not representable, which we treat as an infinite timeout
+    pub(crate) deadline: Option<Instant>,
+    // The event that the wheel signals at the deadline, once scheduled
+    pub(crate) timer: Option<(Arc<AutoResetEvent>, Timer)>,
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    pub(crate) op: AsyncOp,
+}
+
+impl Future for WaitTimeoutFuture<'_> {
+    type Output = bool;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        let this = self.get_mut();
+        #[cfg(all(feature = "tracing", tokio_unstable))]
+        return this
+            .op
+            .poll(|| poll_deadline(this.event, this.deadline, &mut this.timer, cx));
+
+        #[cfg(not(all(feature = "tracing", tokio_unstable)))]
+        poll_deadline(this.event, this.deadline, &mut this.timer, cx)
+    }
+}
+
+// Polls a `WaitTimeoutFuture`, whose fields are passed separately, so that its async operation can
+// be borrowed at the same time
+fn poll_deadline(
+    event: &AutoResetEvent,
+    deadline: Option<Instant>,
+    timer: &mut Option<(Arc<AutoResetEvent>, Timer)>,
+    cx: &mut Context<'_>,
+) -> Poll<bool> {
+    if let Poll::Ready(result) = event.poll_wait_result(cx) {
+        return Poll::Ready(result == WaitResult::Signalled);
+    }
+    let Some(deadline) = deadline else {
+        return Poll::Pending;
+    };
+    if Instant::now() >= deadline {
+        return Poll::Ready(false);
+    }
+
+    let (expired, _) = timer.get_or_insert_with(|| {
+        let wheel = TimerWheel::shared()
+            .unwrap_or_else(|err| panic!("failed to start the timer thread: {}", err));
+        let expired = Arc::new(AutoResetEvent::new().unwrap_or_else(|err| panic!("{}", err)));
+        let timer = wheel.signal_at(&expired, deadline);
+        (expired, timer)
+    });
+    // The wheel signals at the deadline, not before
+    match expired.poll_wait_result(cx) {
+        Poll::Ready(_) => Poll::Ready(false),
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+impl Drop for WaitTimeoutFuture<'_> {
+    fn drop(&mut self) {
+        if let Some((_, timer)) = &self.timer {
+            timer.cancel();
+        }
+    }
+}
+
+impl fmt::Debug for WaitTimeoutFuture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitTimeoutFuture")
+            .field("event", &self.event.label())
+            .field("deadline", &self.deadline)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for WaitFuture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitFuture")
+            .field("event", &self.event.label())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::AutoResetEvent;
+use crate::waiter::WaiterQueue;
+
+/// The idle workers of a thread pool, which producers wake when they submit work.
+///
+/// Workers that find no work [`park`](Self::park) in the set, and producers call
+/// [`wake_one`](Self::wake_one) after submitting a job or [`wake_all`](Self::wake_all), e.g. on
+/// shutdown. A wake that finds no parked worker is kept as a permit, which the next `park`
+/// consumes without blocking, so a job that is submitted while a worker is between its last
+/// check for work and `park` is not missed. Like the signals of an event, permits are not
+/// counted. Each `wake_one` wakes at most one worker, and parked workers are woken in the order
+/// in which they parked; every worker blocks on an event of its own.
+///
+/// A return from `park` does not mean that there is work, so workers check for it again:
+///
+/// ```
+/// use std::collections::VecDeque;
+/// use std::sync::{Arc, Mutex};
+/// use std::thread;
+/// use nova_autoreset_event::IdleSet;
+///
+/// let idle = Arc::new(IdleSet::new());
+/// let jobs = Arc::new(Mutex::new(VecDeque::new()));
+///
+/// let worker = {
+///     let (idle, jobs) = (idle.clone(), jobs.clone());
+///     thread::spawn(move || loop {
+///         match jobs.lock().unwrap().pop_front() {
+///             Some(0) => return,
+///             Some(_) => continue,
+///             None => {}
+///         }
+///         idle.park();
+///     })
+/// };
+///
+/// for job in [1, 2, 0] {
+///     jobs.lock().unwrap().push_back(job);
+///     idle.wake_one();
+/// }
+/// worker.join().unwrap();
+/// ```
+#[derive(Default)]
+pub struct IdleSet {
+    // The state is the permit
+    parked: WaiterQueue<bool>,
+}
+
+impl IdleSet {
+    /// Creates a set without parked workers.
+    pub const fn new() -> Self {
+        Self {
+            parked: WaiterQueue::new(false),
+        }
+    }
+
+    /// Parks the calling thread until it is woken, or returns right away if a permit is
+    /// available.
+    pub fn park(&self) {
+        if let Some(parker) = self.enqueue() {
+            parker.wait();
+        }
+    }
+
+    /// Like [`park`](Self::park), but returns after `timeout` if the thread has not been woken.
+    /// Returns `false` if the wait timed out.
+    pub fn park_timeout(&self, timeout: Duration) -> bool {
+        match self.enqueue() {
+            Some(parker) => self.parked.wait_timeout(&parker, timeout),
+            None => true,
+        }
+    }
+
+    /// Wakes the worker that has been parked the longest. If no worker is parked, leaves a permit
+    /// for the next [`park`](Self::park) and returns `false`.
+    pub fn wake_one(&self) -> bool {
+        let mut parked = self.parked.lock();
+        let woken = parked.wake_one();
+        if !woken {
+            parked.state = true;
+        }
+        woken
+    }
+
+    /// Wakes all parked workers, and leaves a permit for the next [`park`](Self::park). Returns
+    /// the number of workers that have been woken.
+    pub fn wake_all(&self) -> usize {
+        let mut parked = self.parked.lock();
+        parked.state = true;
+        parked.wake_all()
+    }
+
+    /// Returns the number of parked workers.
+    pub fn parked(&self) -> usize {
+        self.parked.lock().len()
+    }
+
+    // Consumes the permit, or queues the event of the calling thread and returns it
+    fn enqueue(&self) -> Option<Arc<AutoResetEvent>> {
+        let mut parked = self.parked.lock();
+        if std::mem::take(&mut parked.state) {
+            return None;
+        }
+        Some(parked.push())
+    }
+}
+
+impl fmt::Debug for IdleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parked = self.parked.lock();
+        f.debug_struct("IdleSet")
+            .field("parked", &parked.len())
+            .field("permit", &parked.state)
+            .finish_non_exhaustive()
+    }
+}
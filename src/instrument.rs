@@ -0,0 +1,693 @@
+// Instrumentation hooks of the event core.
+//
+// Without the corresponding features, all hooks compile to nothing, so that the fast path does not
+// pay for instrumentation it does not use.
+
+use std::borrow::Cow;
+use std::io;
+#[cfg(any(feature = "diagnostics", feature = "leak-check"))]
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "fault-injection")]
+use crate::FaultInjector;
+#[cfg(feature = "metrics")]
+use crate::MetricsMode;
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::Record;
+use crate::latency::{HistogramSnapshot, LatencyHistogram};
+#[cfg(feature = "leak-check")]
+use crate::leak_check::Tracked;
+#[cfg(feature = "replay")]
+use crate::replay::{Recorded, TraceOp};
+use crate::state::State;
+#[cfg(feature = "testing")]
+use crate::testing::{OperationKind, OperationLog};
+use crate::{Backend, Builder};
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+const TARGET: &str = "nova_autoreset_event";
+
+// The per-event state of the instrumentation
+#[derive(Debug)]
+pub(crate) struct Instruments {
+    label: Option<Cow<'static, str>>,
+    // The time of the last signal, see `timestamp()`. Only maintained if the wake latency is
+    // recorded or the `timestamps` feature is enabled.
+    last_signal: AtomicU64,
+    // The time a wait last consumed a signal
+    #[cfg(feature = "timestamps")]
+    last_wake: AtomicU64,
+    latency: Option<Box<LatencyHistogram>>,
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    #[cfg(feature = "diagnostics")]
+    record: Arc<Record>,
+    // The threshold of the watchdog
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    warn_after: Option<Duration>,
+    #[cfg(feature = "deadlock-detection")]
+    signallers: Signallers,
+    #[cfg(feature = "testing")]
+    operations: OperationLog,
+    #[cfg(feature = "fault-injection")]
+    faults: Option<FaultInjector>,
+    // Only held to be dropped with the event
+    #[cfg(feature = "leak-check")]
+    _tracked: Arc<Tracked>,
+    // Set if the event has been created in a `record_trace` scope
+    #[cfg(feature = "replay")]
+    recorded: Option<Recorded>,
+    // The span that represents the event in tokio-console
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    resource: tracing::Span,
+}
+
+impl Instruments {
+    pub(crate) fn new(builder: &Builder, backend: Backend) -> Self {
+        let label = builder.label.clone();
+        #[cfg(not(any(feature = "diagnostics", feature = "metrics", feature = "leak-check")))]
+        let _ = backend;
+        Self {
+            last_signal: AtomicU64::new(0),
+            #[cfg(feature = "timestamps")]
+            last_wake: AtomicU64::new(0),
+            latency: builder
+                .latency_stats
+                .then(|| Box::new(LatencyHistogram::new())),
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::new(label.as_deref(), backend, builder.metrics),
+            #[cfg(feature = "diagnostics")]
+            record: Record::register(label.as_deref(), backend),
+            #[cfg(any(feature = "log", feature = "tracing"))]
+            warn_after: builder.warn_after,
+            #[cfg(feature = "deadlock-detection")]
+            signallers: Signallers::new(),
+            #[cfg(feature = "testing")]
+            operations: OperationLog::default(),
+            #[cfg(feature = "fault-injection")]
+            faults: builder.faults.clone(),
+            #[cfg(feature = "leak-check")]
+            _tracked: Tracked::track(label.as_deref(), backend),
+            #[cfg(feature = "replay")]
+            recorded: Recorded::attach(),
+            #[cfg(all(feature = "tracing", tokio_unstable))]
+            resource: crate::console::resource("AutoResetEvent", label.as_deref()),
+            label,
+        }
+    }
+
+    // The span that represents the event in tokio-console
+    #[cfg(all(feature = "tracing", tokio_unstable))]
+    pub(crate) fn resource(&self) -> &tracing::Span {
+        &self.resource
+    }
+
+    // Returns the state word of a new event
+    pub(crate) fn state(&self) -> State {
+        #[cfg(feature = "diagnostics")]
+        return self.record.state.clone();
+
+        #[cfg(not(feature = "diagnostics"))]
+        State::new(0)
+    }
+
+    pub(crate) fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    // Called when the event is signalled
+    #[inline(always)]
+    pub(crate) fn signal(&self) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(target: TARGET, label = self.label(), "signal");
+
+        #[cfg(feature = "metrics")]
+        self.metrics.signals.increment(1);
+
+        #[cfg(feature = "diagnostics")]
+        self.record.signals.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "deadlock-detection")]
+        self.signallers.signal();
+
+        if cfg!(any(feature = "metrics", feature = "timestamps")) || self.latency.is_some() {
+            self.last_signal.store(timestamp(), Ordering::Relaxed);
+        }
+    }
+
+    // Called after the signalled flag has been set, with its previous value
+    #[inline(always)]
+    pub(crate) fn signalled(&self, coalesced: bool) {
+        #[cfg(feature = "testing")]
+        self.operations.record(if coalesced {
+            OperationKind::CoalescedSignal
+        } else {
+            OperationKind::Signal
+        });
+
+        #[cfg(feature = "replay")]
+        self.record(TraceOp::Signal);
+
+        #[cfg(all(feature = "tracing", tokio_unstable))]
+        if !coalesced {
+            crate::console::state_update(&self.resource, true);
+        }
+
+        #[cfg(not(feature = "testing"))]
+        let _ = coalesced;
+    }
+
+    // Called when the event has been closed
+    #[inline(always)]
+    pub(crate) fn closed(&self) {
+        #[cfg(feature = "replay")]
+        self.record(TraceOp::Close);
+    }
+
+    // Wakes the kernel object of the event via `wake`
+    #[inline(always)]
+    pub(crate) fn wake(&self, wake: impl FnOnce()) {
+        #[cfg(feature = "fault-injection")]
+        if let Some(faults) = &self.faults {
+            return faults.wake(wake);
+        }
+
+        wake()
+    }
+
+    // Blocks on the kernel object of the event via `block`
+    #[inline(always)]
+    pub(crate) fn block(&self, block: impl FnOnce() -> io::Result<bool>) -> io::Result<bool> {
+        #[cfg(feature = "fault-injection")]
+        if let Some(faults) = &self.faults {
+            return faults.block(block);
+        }
+
+        block()
+    }
+
+    // Called after a wait consumed the signal
+    #[inline(always)]
+    pub(crate) fn consumed(&self) {
+        #[cfg(feature = "testing")]
+        self.operations.record(OperationKind::Consume);
+
+        #[cfg(feature = "replay")]
+        self.record(TraceOp::Wake);
+
+        #[cfg(all(feature = "tracing", tokio_unstable))]
+        crate::console::state_update(&self.resource, false);
+
+        #[cfg(feature = "timestamps")]
+        self.last_wake.store(timestamp(), Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "timestamps")]
+    pub(crate) fn last_signal_instant(&self) -> Option<Instant> {
+        instant(self.last_signal.load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "timestamps")]
+    pub(crate) fn last_wake_instant(&self) -> Option<Instant> {
+        instant(self.last_wake.load(Ordering::Relaxed))
+    }
+
+    #[cfg(feature = "testing")]
+    pub(crate) fn operations(&self) -> &OperationLog {
+        &self.operations
+    }
+
+    // Called before the calling thread blocks on the event without a timeout. Panics if it is the
+    // declared signaller of the event.
+    #[inline(always)]
+    pub(crate) fn check_deadlock(&self) {
+        #[cfg(feature = "deadlock-detection")]
+        if self.signallers.is_declared() {
+            self.report_deadlock("it is the declared signaller of the event");
+        }
+    }
+
+    #[cfg(feature = "deadlock-detection")]
+    #[cold]
+    pub(crate) fn report_deadlock(&self, reason: &str) -> ! {
+        let current = std::thread::current();
+        panic!(
+            "deadlock: thread {} ({:?}) waits for event {} without a timeout, but {}",
+            current.name().unwrap_or("<unnamed>"),
+            current.id(),
+            self.label().unwrap_or("<unlabelled>"),
+            reason
+        );
+    }
+
+    #[cfg(feature = "deadlock-detection")]
+    pub(crate) fn declare_signaller(&self, thread: std::thread::ThreadId) {
+        *self
+            .signallers
+            .declared
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(thread);
+    }
+
+    // Returns the recorded wake latencies, empty if they are not recorded
+    pub(crate) fn latency_stats(&self) -> HistogramSnapshot {
+        self.latency
+            .as_ref()
+            .map(|latency| latency.snapshot())
+            .unwrap_or_default()
+    }
+
+    // Records the outcome of a blocking wait on the event
+    fn finished(&self, signalled: bool) {
+        #[cfg(feature = "metrics")]
+        if signalled {
+            self.metrics.signalled_waits.increment(1);
+        } else {
+            self.metrics.timeouts.increment(1);
+        }
+
+        #[cfg(feature = "diagnostics")]
+        if !signalled {
+            self.record.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+
+        #[cfg(feature = "replay")]
+        if !signalled {
+            self.record(TraceOp::Timeout);
+        }
+
+        #[cfg(not(any(feature = "metrics", feature = "diagnostics", feature = "replay")))]
+        let _ = signalled;
+    }
+
+    #[cfg(feature = "replay")]
+    fn record(&self, op: TraceOp) {
+        if let Some(recorded) = &self.recorded {
+            recorded.record(op);
+        }
+    }
+
+    // Records the signal→wake latency of a blocked waiter that consumed a signal
+    fn woken(&self) {
+        if !cfg!(feature = "metrics") && self.latency.is_none() {
+            return;
+        }
+
+        let signalled = self.last_signal.load(Ordering::Relaxed);
+        if signalled == 0 {
+            return;
+        }
+        let latency = timestamp().saturating_sub(signalled);
+
+        #[cfg(feature = "metrics")]
+        self.metrics.wake_latency.record(latency as f64 * 1e-9);
+
+        if let Some(histogram) = &self.latency {
+            histogram.record(latency);
+        }
+    }
+
+    // Starts a wait on the event that has left the userspace fast path and may block in the kernel
+    #[inline(always)]
+    pub(crate) fn start_wait(&self, timeout: Option<Duration>) -> BlockingWait<'_> {
+        #[cfg(feature = "diagnostics")]
+        self.record.blocking_waits.fetch_add(1, Ordering::Relaxed);
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            target: TARGET,
+            "blocking_wait",
+            label = self.label(),
+            timeout_us = timeout.map(|timeout| timeout.as_micros() as u64),
+            duration_us = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+        );
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = timeout;
+
+        BlockingWait {
+            instruments: Some(self),
+            #[cfg(feature = "tracing")]
+            span: span.entered(),
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+            #[cfg(any(feature = "log", feature = "tracing"))]
+            warn_at: self
+                .warn_after
+                .and_then(|threshold| Instant::now().checked_add(threshold)),
+            #[cfg(feature = "deadlock-detection")]
+            deadlock_at: timeout
+                .is_none()
+                .then(|| Instant::now() + DEADLOCK_GRACE_PERIOD),
+            #[cfg(feature = "debug-backtraces")]
+            parked: Some(self.record.park()),
+        }
+    }
+}
+
+// A wait that has left the userspace fast path and may block in the kernel. Must be finished with
+// the outcome of the wait.
+pub(crate) struct BlockingWait<'a> {
+    // The event that is waited on, `None` for a wait on several events
+    instruments: Option<&'a Instruments>,
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    start: Instant,
+    // When the watchdog warns about the wait, `None` if it is disabled or has already warned
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    warn_at: Option<Instant>,
+    // When the heuristic of the deadlock detection is checked, `None` for timed waits
+    #[cfg(feature = "deadlock-detection")]
+    deadlock_at: Option<Instant>,
+    // The id of the parked waiter in the diagnostics record
+    #[cfg(feature = "debug-backtraces")]
+    parked: Option<u64>,
+}
+
+#[cfg(feature = "debug-backtraces")]
+impl Drop for BlockingWait<'_> {
+    fn drop(&mut self) {
+        if let (Some(instruments), Some(id)) = (self.instruments, self.parked) {
+            instruments.record.unpark(id);
+        }
+    }
+}
+
+impl<'a> BlockingWait<'a> {
+    // Starts a blocking wait on several events
+    #[inline(always)]
+    pub(crate) fn start_any(events: usize, timeout: Option<Duration>) -> Self {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            target: TARGET,
+            "blocking_wait_any",
+            events,
+            timeout_us = timeout.map(|timeout| timeout.as_micros() as u64),
+            duration_us = tracing::field::Empty,
+            outcome = tracing::field::Empty,
+        );
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = (events, timeout);
+
+        Self {
+            instruments: None,
+            #[cfg(feature = "tracing")]
+            span: span.entered(),
+            #[cfg(feature = "tracing")]
+            start: Instant::now(),
+            #[cfg(any(feature = "log", feature = "tracing"))]
+            warn_at: None,
+            #[cfg(feature = "deadlock-detection")]
+            deadlock_at: None,
+            #[cfg(feature = "debug-backtraces")]
+            parked: None,
+        }
+    }
+
+    // Limits the timeout of a kernel wait, so that the watchdog and the deadlock detection can
+    // check the wait in time, and so that waits re-check the state with the `no-panic` feature
+    #[inline(always)]
+    pub(crate) fn clip(&self, timeout: Option<Duration>) -> Option<Duration> {
+        #[allow(unused_mut)]
+        let mut next: Option<Instant> = None;
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        {
+            next = earliest(next, self.warn_at);
+        }
+        #[cfg(feature = "deadlock-detection")]
+        {
+            next = earliest(next, self.deadlock_at);
+        }
+        // A wakeup may have been lost to a failed syscall, see `state::unexpected`. Simulated waits
+        // do not block anyway.
+        #[cfg(all(feature = "no-panic", not(madsim)))]
+        {
+            next = earliest(
+                next,
+                Instant::now().checked_add(crate::state::POLL_INTERVAL),
+            );
+        }
+
+        match next {
+            Some(next) => {
+                let until_next = next.saturating_duration_since(Instant::now());
+                Some(timeout.map_or(until_next, |timeout| timeout.min(until_next)))
+            }
+            None => timeout,
+        }
+    }
+
+    // Called after each kernel wait of an untimed wait. Returns `true` if the waiting thread is
+    // still the only thread that has ever signalled the event after the grace period.
+    #[cfg(feature = "deadlock-detection")]
+    pub(crate) fn deadlocked(&mut self) -> bool {
+        match (self.deadlock_at, self.instruments) {
+            (Some(deadlock_at), Some(instruments)) if Instant::now() >= deadlock_at => {
+                self.deadlock_at = None;
+                instruments.signallers.is_sole()
+            }
+            _ => false,
+        }
+    }
+
+    // Called after each kernel wait. Warns once the threshold of the watchdog has passed.
+    #[inline(always)]
+    pub(crate) fn check(&mut self) {
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        if let Some(warn_at) = self.warn_at
+            && Instant::now() >= warn_at
+        {
+            self.warn_at = None;
+            self.warn();
+        }
+    }
+
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    #[cold]
+    fn warn(&self) {
+        let Some(instruments) = self.instruments else {
+            return;
+        };
+        let label = instruments.label().unwrap_or("<unlabelled>");
+        let threshold = instruments.warn_after.unwrap_or_default();
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            target: TARGET,
+            label,
+            thread,
+            threshold_ms = threshold.as_millis() as u64,
+            "thread has been blocked on event for longer than the threshold"
+        );
+
+        #[cfg(not(feature = "tracing"))]
+        log::warn!(
+            target: TARGET,
+            "thread {} has been blocked on event {} for more than {:?}",
+            thread,
+            label,
+            threshold
+        );
+    }
+
+    // Records the outcome of a wait on a single event: `true` if a signal has been consumed,
+    // `false` on timeout
+    #[inline(always)]
+    pub(crate) fn finish(self, signalled: bool) {
+        let woken = self.instruments.filter(|_| signalled);
+        self.complete(signalled, woken)
+    }
+
+    // Records the outcome of a wait on several events: the event whose signal has been consumed,
+    // or `None` on timeout
+    #[inline(always)]
+    pub(crate) fn finish_any(self, woken: Option<&'a Instruments>) {
+        self.complete(woken.is_some(), woken)
+    }
+
+    #[inline(always)]
+    fn complete(self, signalled: bool, woken: Option<&'a Instruments>) {
+        if let Some(instruments) = self.instruments {
+            instruments.finished(signalled);
+        }
+        if let Some(woken) = woken {
+            woken.woken();
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let duration_us = self.start.elapsed().as_micros() as u64;
+            let outcome = if signalled { "signalled" } else { "timeout" };
+            self.span.record("duration_us", duration_us);
+            self.span.record("outcome", outcome);
+            if !signalled {
+                tracing::debug!(target: TARGET, duration_us, "timeout");
+            }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = signalled;
+    }
+}
+
+#[cfg(any(
+    feature = "log",
+    feature = "tracing",
+    feature = "deadlock-detection",
+    all(feature = "no-panic", not(madsim))
+))]
+fn earliest(a: Option<Instant>, b: Option<Instant>) -> Option<Instant> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// Process-wide origin of the signal timestamps
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+// Nanoseconds since `epoch()`. Never 0, which marks a missing timestamp.
+fn timestamp() -> u64 {
+    (epoch().elapsed().as_nanos() as u64).max(1)
+}
+
+// Converts a timestamp back to an `Instant`
+#[cfg(feature = "timestamps")]
+fn instant(timestamp: u64) -> Option<Instant> {
+    (timestamp != 0).then(|| epoch() + Duration::from_nanos(timestamp))
+}
+
+// Handles of the metrics of an event. They are registered when the event is created, so that
+// recording a value does not need a lookup or an allocation.
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
+struct Metrics {
+    signals: metrics::Counter,
+    // The blocking waits, by outcome
+    signalled_waits: metrics::Counter,
+    timeouts: metrics::Counter,
+    wake_latency: metrics::Histogram,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    fn new(label: Option<&str>, backend: Backend, mode: MetricsMode) -> Self {
+        let mut labels = vec![metrics::Label::new("backend", backend.name())];
+        match mode {
+            MetricsMode::PerEvent => {
+                labels.push(metrics::Label::new("event", label.unwrap_or("").to_owned()));
+            }
+            MetricsMode::Aggregate => {}
+            MetricsMode::Disabled => {
+                return Self {
+                    signals: metrics::Counter::noop(),
+                    signalled_waits: metrics::Counter::noop(),
+                    timeouts: metrics::Counter::noop(),
+                    wake_latency: metrics::Histogram::noop(),
+                };
+            }
+        }
+
+        let with_outcome = |outcome: &'static str| {
+            let mut labels = labels.clone();
+            labels.push(metrics::Label::new("outcome", outcome));
+            labels
+        };
+        Self {
+            signals: metrics::counter!("nova_autoreset_event_signals_total", labels.clone()),
+            signalled_waits: metrics::counter!(
+                "nova_autoreset_event_blocking_waits_total",
+                with_outcome("signalled")
+            ),
+            timeouts: metrics::counter!(
+                "nova_autoreset_event_blocking_waits_total",
+                with_outcome("timeout")
+            ),
+            wake_latency: metrics::histogram!("nova_autoreset_event_wake_latency_seconds", labels),
+        }
+    }
+}
+
+// How long an untimed wait blocks before the heuristic of the deadlock detection is checked. A
+// thread may signal an event itself and then wait for another thread to signal it again.
+#[cfg(feature = "deadlock-detection")]
+const DEADLOCK_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+// A unique id of the calling thread that fits into an atomic. Never `NO_SIGNALLER` or
+// `MANY_SIGNALLERS`.
+#[cfg(feature = "deadlock-detection")]
+fn thread_token() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(2);
+    thread_local! {
+        static TOKEN: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    TOKEN.with(|token| *token)
+}
+
+#[cfg(feature = "deadlock-detection")]
+const NO_SIGNALLER: u64 = 0;
+#[cfg(feature = "deadlock-detection")]
+const MANY_SIGNALLERS: u64 = 1;
+
+// The threads that are expected to signal the event
+#[cfg(feature = "deadlock-detection")]
+#[derive(Debug)]
+struct Signallers {
+    // The token of the only thread that has signalled the event so far, or one of the markers
+    observed: AtomicU64,
+    declared: std::sync::Mutex<Option<std::thread::ThreadId>>,
+}
+
+#[cfg(feature = "deadlock-detection")]
+impl Signallers {
+    fn new() -> Self {
+        Self {
+            observed: AtomicU64::new(NO_SIGNALLER),
+            declared: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn signal(&self) {
+        let token = thread_token();
+        let observed = self.observed.load(Ordering::Relaxed);
+        if observed == NO_SIGNALLER {
+            if let Err(observed) = self.observed.compare_exchange(
+                NO_SIGNALLER,
+                token,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) && observed != token
+            {
+                self.observed.store(MANY_SIGNALLERS, Ordering::Relaxed);
+            }
+        } else if observed != token && observed != MANY_SIGNALLERS {
+            self.observed.store(MANY_SIGNALLERS, Ordering::Relaxed);
+        }
+    }
+
+    fn declared(&self) -> Option<std::thread::ThreadId> {
+        *self.declared.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Returns `true` if the calling thread is the declared signaller
+    fn is_declared(&self) -> bool {
+        self.declared() == Some(std::thread::current().id())
+    }
+
+    // Returns `true` if no signaller has been declared and the calling thread is the only thread
+    // that has signalled the event so far
+    fn is_sole(&self) -> bool {
+        self.declared().is_none() && self.observed.load(Ordering::Relaxed) == thread_token()
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Each power of two is split into 2^SUB_BUCKET_BITS linear sub-buckets, which bounds the relative
+// error of a recorded value to 1/16
+const SUB_BUCKET_BITS: u32 = 4;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+
+// Values at or above 2^MAX_EXPONENT ns (about 18 minutes) are counted in the last bucket
+const MAX_EXPONENT: u32 = 40;
+const BUCKETS: usize = SUB_BUCKETS * (MAX_EXPONENT - SUB_BUCKET_BITS + 1) as usize;
+
+// Returns the bucket of a value in nanoseconds
+fn bucket(nanos: u64) -> usize {
+    if nanos < SUB_BUCKETS as u64 {
+        return nanos as usize;
+    }
+
+    let exponent = (63 - nanos.leading_zeros()).min(MAX_EXPONENT);
+    if exponent == MAX_EXPONENT {
+        return BUCKETS - 1;
+    }
+    let sub_bucket = (nanos >> (exponent - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+    (exponent - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS + sub_bucket
+}
+
+// Returns the largest value in nanoseconds that falls into a bucket
+fn bucket_limit(bucket: usize) -> u64 {
+    if bucket < SUB_BUCKETS {
+        return bucket as u64;
+    }
+
+    let exponent = (bucket / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let sub_bucket = (bucket % SUB_BUCKETS) as u64;
+    let width = 1 << (exponent - SUB_BUCKET_BITS);
+    (1 << exponent) + (sub_bucket + 1) * width - 1
+}
+
+// A histogram of signal→wake latencies that can be recorded concurrently without locking
+pub(crate) struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    sum: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub(crate) fn new() -> Self {
+        Self {
+            buckets: [const { AtomicU64::new(0) }; BUCKETS],
+            sum: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn record(&self, nanos: u64) {
+        self.buckets[bucket(nanos)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(nanos, Ordering::Relaxed);
+        self.min.fetch_min(nanos, Ordering::Relaxed);
+        self.max.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    // The fields are read one by one, so a snapshot that is taken while values are recorded may
+    // not be consistent
+    pub(crate) fn snapshot(&self) -> HistogramSnapshot {
+        let buckets: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+
+        HistogramSnapshot {
+            count: buckets.iter().sum(),
+            buckets,
+            sum: self.sum.load(Ordering::Relaxed),
+            min: self.min.load(Ordering::Relaxed),
+            max: self.max.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl fmt::Debug for LatencyHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatencyHistogram").finish_non_exhaustive()
+    }
+}
+
+/// A snapshot of the signal→wake latencies of an event.
+///
+/// Latencies are recorded with a relative error of at most 1/16 (6.25%); [`min`](Self::min),
+/// [`max`](Self::max) and [`mean`](Self::mean) are exact.
+#[derive(Debug, Clone, Default)]
+pub struct HistogramSnapshot {
+    // Empty if no value has been recorded
+    buckets: Vec<u64>,
+    count: u64,
+    sum: u64,
+    min: u64,
+    max: u64,
+}
+
+impl HistogramSnapshot {
+    /// Returns the number of recorded latencies.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `true` if no latency has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the smallest recorded latency, or zero if the snapshot is empty.
+    pub fn min(&self) -> Duration {
+        if self.is_empty() {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.min)
+    }
+
+    /// Returns the largest recorded latency, or zero if the snapshot is empty.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// Returns the mean latency, or zero if the snapshot is empty.
+    pub fn mean(&self) -> Duration {
+        if self.is_empty() {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.sum / self.count)
+    }
+
+    /// Returns the latency below which the fraction `quantile` of the recorded latencies lies,
+    /// e.g. `0.99` for the 99th percentile. Returns zero if the snapshot is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantile` is not within `0.0..=1.0`.
+    pub fn value_at_quantile(&self, quantile: f64) -> Duration {
+        assert!(
+            (0.0..=1.0).contains(&quantile),
+            "quantile {} is not within 0.0..=1.0",
+            quantile
+        );
+        if self.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let rank = ((quantile * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let nanos = bucket_limit(bucket).clamp(self.min, self.max);
+                return Duration::from_nanos(nanos);
+            }
+        }
+        self.max()
+    }
+}
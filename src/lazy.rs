@@ -0,0 +1,75 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use crate::AutoResetEvent;
+
+/// An [`AutoResetEvent`] that can be created in a `static` item.
+///
+/// The event is created with the default configuration on first use, and the
+/// `AutoResetEvent` API is available via `Deref`. Like a lazily created kernel object, an event
+/// that cannot be created at that point panics.
+///
+/// The first use allocates, so [`signal_from_handler`](AutoResetEvent::signal_from_handler) must
+/// not be the first use: call [`force`](Self::force) before installing the signal handler.
+///
+/// ```
+/// use nova_autoreset_event::LazyAutoResetEvent;
+///
+/// static WAKE: LazyAutoResetEvent = LazyAutoResetEvent::new();
+///
+/// WAKE.signal();
+/// assert!(WAKE.try_wait());
+/// ```
+pub struct LazyAutoResetEvent {
+    event: OnceLock<AutoResetEvent>,
+}
+
+impl LazyAutoResetEvent {
+    /// Creates an event that is initialized on first use.
+    pub const fn new() -> Self {
+        Self {
+            event: OnceLock::new(),
+        }
+    }
+
+    /// Creates the event if it has not been created yet, and returns it.
+    pub fn force(this: &Self) -> &AutoResetEvent {
+        this.event
+            .get_or_init(|| AutoResetEvent::new().unwrap_or_else(|err| panic!("{}", err)))
+    }
+
+    /// Returns the event if it has already been created.
+    pub fn get(this: &Self) -> Option<&AutoResetEvent> {
+        this.event.get()
+    }
+}
+
+impl Default for LazyAutoResetEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for LazyAutoResetEvent {
+    type Target = AutoResetEvent;
+
+    fn deref(&self) -> &AutoResetEvent {
+        Self::force(self)
+    }
+}
+
+impl AsRef<AutoResetEvent> for LazyAutoResetEvent {
+    fn as_ref(&self) -> &AutoResetEvent {
+        Self::force(self)
+    }
+}
+
+impl fmt::Debug for LazyAutoResetEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Self::get(self) {
+            Some(event) => event.fmt(f),
+            None => f.write_str("LazyAutoResetEvent(<uninitialized>)"),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::sync::{Arc, Weak};
+
+use crate::Backend;
+
+thread_local! {
+    // The events created in each active `with_leak_check` scope of the thread, innermost last
+    static SCOPES: RefCell<Vec<Vec<Weak<Tracked>>>> = const { RefCell::new(Vec::new()) };
+}
+
+// Owned by an event while it is alive, so that the scopes can observe its drop
+#[derive(Debug)]
+pub(crate) struct Tracked {
+    label: Option<String>,
+    backend: Backend,
+}
+
+impl Tracked {
+    // Adds a new event to all active scopes of the calling thread
+    pub(crate) fn track(label: Option<&str>, backend: Backend) -> Arc<Self> {
+        let tracked = Arc::new(Self {
+            label: label.map(str::to_owned),
+            backend,
+        });
+        SCOPES.with_borrow_mut(|scopes| {
+            for scope in scopes {
+                scope.push(Arc::downgrade(&tracked));
+            }
+        });
+        tracked
+    }
+}
+
+// Pops the scope, also if the closure panics
+struct Scope;
+
+impl Scope {
+    fn enter() -> Self {
+        SCOPES.with_borrow_mut(|scopes| scopes.push(Vec::new()));
+        Self
+    }
+
+    fn leaked(&self) -> Vec<Arc<Tracked>> {
+        SCOPES.with_borrow(|scopes| {
+            scopes
+                .last()
+                .into_iter()
+                .flatten()
+                .filter_map(Weak::upgrade)
+                .collect()
+        })
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        SCOPES.with_borrow_mut(|scopes| scopes.pop());
+    }
+}
+
+/// Runs `f` and panics if an event that has been created within it is still alive afterwards.
+///
+/// Every live event owns its kernel object, so this also catches leaked file descriptors and
+/// handles, e.g. of events that are kept alive by an `Arc` cycle. Only events that are created on
+/// the calling thread are tracked, so that tests running in parallel do not interfere with each
+/// other. Scopes can be nested. If `f` panics, the panic is propagated without checking.
+///
+/// ```
+/// use nova_autoreset_event::{AutoResetEvent, with_leak_check};
+///
+/// with_leak_check(|| {
+///     let event = AutoResetEvent::new().unwrap();
+///     event.signal();
+///     assert!(event.try_wait());
+/// });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "leak-check")))]
+pub fn with_leak_check<R>(f: impl FnOnce() -> R) -> R {
+    let scope = Scope::enter();
+    let result = f();
+
+    let leaked = scope.leaked();
+    drop(scope);
+    if !leaked.is_empty() {
+        let mut message = format!(
+            "{} events created in with_leak_check are still alive",
+            leaked.len()
+        );
+        for tracked in &leaked {
+            let _ = write!(
+                message,
+                "\n  {} ({})",
+                tracked.label.as_deref().unwrap_or("<unlabelled>"),
+                tracked.backend
+            );
+        }
+        panic!("{}", message);
+    }
+    result
+}
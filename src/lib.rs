@@ -13,32 +13,172 @@
 //! using Win32 `CreateEvent` on Windows, `eventfd` on Linux, `kqueue` on macOS/BSD, and a pipe-based
 //! fallback on other platforms. The `eventfd`, `kqueue` and `pipe` implementations implement `AsFd` and `AsRawFd`,
 //! while the Win32 implementation implements `AsHandle` and `AsRawHandle`.
+//!
+//! Whether a signal has been consumed is decided in userspace, so a signal wakes exactly one
+//! waiter on every backend, even if the kernel object wakes several threads. Signals are not
+//! latched more than once: any number of signals before a wait is consumed by that wait.
+//! [`AutoResetEvent::signal_n`] and [`AutoResetEvent::pulse`] release the threads that are blocked
+//! at the time of the call instead. Waits have no spurious wakeups: they only return with a signal,
+//! on timeout, once the event has been closed, or when they are released like that.
+//!
+//! Signalling an event and non-blocking waits are handled in userspace as long as no thread is blocked
+//! on the event. Blocking waits spin for a short time before falling back to the kernel object; the
+//! spin budget adapts to the latency observed on each event.
+//! On Unix, events built with `Builder::lazy` only create their file descriptors once a thread
+//! blocks on them or their fd is requested.
+
+#[cfg(all(unix, feature = "tokio"))]
+mod async_event;
+mod backend;
+mod backpressure;
+mod broadcast;
+mod builder;
+mod callback;
+#[cfg(all(unix, feature = "calloop"))]
+mod calloop;
+#[cfg(feature = "virtual-time")]
+mod clock;
+#[cfg(feature = "condvar")]
+mod condvar;
+#[cfg(all(feature = "tracing", tokio_unstable))]
+mod console;
+#[cfg(windows)]
+mod console_ctrl;
+mod countdown;
+mod debounce;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod dispatch;
+mod error;
+mod event;
+mod event_loop;
+#[cfg(feature = "fault-injection")]
+mod fault;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fs-watch")]
+mod fs_watch;
+mod future;
+mod idle;
+mod instrument;
+mod latency;
+mod lazy;
+#[cfg(feature = "leak-check")]
+mod leak_check;
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(loom)]
+mod model;
+mod mux;
+#[cfg(all(unix, feature = "tokio"))]
+mod notify;
+#[cfg(all(unix, feature = "signal-hook"))]
+mod os_signal;
+mod padded;
+#[cfg(unix)]
+mod pipe;
+mod pool;
+mod priority;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "reactor")]
+mod reactor;
+mod receiver;
+#[cfg(feature = "replay")]
+mod replay;
+mod result;
+mod scope;
+mod set;
+mod snapshot;
+mod state;
+#[cfg(feature = "futures-core")]
+mod stream;
+#[cfg(unix)]
+mod syscall;
+#[cfg(feature = "testing")]
+mod testing;
+mod throttle;
+#[cfg(feature = "crossbeam-channel")]
+mod tick;
+mod timer;
+mod token;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+mod waiter;
+
+#[cfg(all(unix, feature = "tokio"))]
+pub use async_event::AsyncAutoResetEvent;
+pub use backend::{BACKEND_ENV_VAR, Backend};
+pub use backpressure::BackpressureGate;
+pub use broadcast::{Broadcaster, Subscription};
+pub use builder::Builder;
+#[cfg(unix)]
+pub use builder::InterruptPolicy;
+#[cfg(feature = "metrics")]
+pub use builder::MetricsMode;
+pub use callback::Registration;
+#[cfg(all(unix, feature = "calloop"))]
+pub use calloop::CalloopSource;
+#[cfg(feature = "virtual-time")]
+pub use clock::MockClock;
+#[cfg(feature = "condvar")]
+pub use condvar::RawCondvar;
+#[cfg(windows)]
+pub use console_ctrl::ConsoleCtrlEvent;
+pub use countdown::{CountdownEvent, SignalGuard};
+pub use debounce::DebouncedSignaler;
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::dump_state;
+pub use dispatch::{DispatchKey, EventDispatcher};
+pub use error::{EventError, EventErrorKind, EventOperation};
+pub use event::AutoResetEvent;
+pub use event_loop::EventLoopWaker;
+#[cfg(feature = "fault-injection")]
+pub use fault::{Fault, FaultInjector};
+#[cfg(feature = "fs-watch")]
+pub use fs_watch::FsWatchEvent;
+pub use future::{WaitFuture, WaitTimeoutFuture};
+pub use idle::IdleSet;
+pub use latency::HistogramSnapshot;
+pub use lazy::LazyAutoResetEvent;
+#[cfg(feature = "leak-check")]
+pub use leak_check::with_leak_check;
+#[cfg(feature = "mock")]
+pub use mock::MockAutoResetEvent;
+#[cfg(loom)]
+#[doc(hidden)]
+pub use model::ModelEvent;
+pub use mux::{EventMux, MuxSource, MuxSources};
+#[cfg(all(unix, feature = "tokio"))]
+pub use notify::NotifyBridge;
+#[cfg(all(unix, feature = "signal-hook"))]
+pub use os_signal::OsSignalEvent;
+pub use padded::PaddedAutoResetEvent;
+pub use priority::{Priority, PriorityPair};
+#[cfg(feature = "python")]
+pub use python::PyAutoResetEvent;
+pub use receiver::SignalReceiver;
+#[cfg(feature = "replay")]
+pub use replay::{ParseTraceError, Replay, Trace, TraceEntry, TraceOp, record_trace};
+pub use result::WaitResult;
+pub use scope::{CompletionScope, ScopedJoinHandle, completion_scope, completion_scope_for};
+pub use set::EventSet;
+pub use snapshot::EventSnapshot;
+#[cfg(feature = "futures-core")]
+pub use stream::SignalStream;
+#[cfg(feature = "testing")]
+pub use testing::{Operation, OperationKind};
+pub use throttle::Throttle;
+pub use timer::{Timer, TimerWheel};
+pub use token::WaitToken;
 
 // Set on linux/android
-#[cfg(all(
-    unix,
-    not(any(
-        target_os = "macos",
-        target_os = "ios",
-        target_os = "freebsd",
-        target_os = "netbsd",
-        target_os = "openbsd",
-        target_os = "dragonfly"
-    ))
-))]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod futex;
+#[cfg(any(target_os = "linux", target_os = "android"))]
 mod linux;
-#[cfg(all(
-    unix,
-    not(any(
-        target_os = "macos",
-        target_os = "ios",
-        target_os = "freebsd",
-        target_os = "netbsd",
-        target_os = "openbsd",
-        target_os = "dragonfly"
-    ))
-))]
-pub use linux::AutoResetEvent;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use linux as sys;
 
 // Set on macos/ios/freebsd/netbsd/openbsd/dragonfly
 #[cfg(any(
@@ -58,12 +198,12 @@ mod macos;
     target_os = "openbsd",
     target_os = "dragonfly"
 ))]
-pub use macos::AutoResetEvent;
+use macos as sys;
 
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
-pub use windows::AutoResetEvent;
+use windows as sys;
 
 #[cfg(all(
     unix,
@@ -78,7 +218,7 @@ pub use windows::AutoResetEvent;
         target_os = "android"
     ))
 ))]
-mod pipe;
+mod fallback;
 #[cfg(all(
     unix,
     not(any(
@@ -92,4 +232,4 @@ mod pipe;
         target_os = "android"
     ))
 ))]
-pub use pipe::AutoResetEvent;
+use fallback as sys;
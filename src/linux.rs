@@ -1,152 +1,425 @@
 // The initial value of the eventfd
 const EFD_INITIAL_VALUE: u32 = 0;
 
+use std::io;
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr;
 use std::time::Duration;
 
-/// An autoreset event.
-///
-/// See the [module-level documentation](..) for more information.
+use crate::futex::Futex;
+use crate::pipe::{Pipe, poll_any, poll_readable};
+use crate::state::{KernelObject, KernelWaker, LazyKernel, SignalSafeWaker, TryAsFd, unexpected};
+use crate::{AutoResetEvent, Backend, Builder, EventError, EventOperation, syscall};
+
+pub(crate) type Waker = LazyKernel<SysKernel>;
+
+pub(crate) const BACKENDS: &[Backend] = &[Backend::EventFd, Backend::Futex, Backend::Pipe];
+
+pub(crate) fn waker(builder: &Builder, backend: Backend) -> io::Result<Waker> {
+    LazyKernel::with_laziness(backend, builder.lazy)
+}
+
+pub(crate) fn backend(waker: &Waker) -> Backend {
+    waker.config()
+}
+
+pub(crate) fn round_trip(backend: Backend) -> io::Result<Duration> {
+    crate::backend::round_trip::<SysKernel>(backend)
+}
+
+// The kernel side of the event
 #[derive(Debug)]
-pub struct AutoResetEvent {
-    fd: OwnedFd,
+pub(crate) enum SysKernel {
+    EventFd(EventFd),
+    Futex(Futex),
+    Pipe(Pipe),
 }
 
-impl AutoResetEvent {
-    /// Creates a new autoreset event.
-    pub fn new() -> std::io::Result<Self> {
-        let fd = unsafe { libc::eventfd(EFD_INITIAL_VALUE, libc::EFD_CLOEXEC) };
+impl KernelObject for SysKernel {
+    type Config = Backend;
 
-        if fd == -1 {
-            Err(std::io::Error::last_os_error())
-        } else {
-            Ok(Self {
-                fd: unsafe { OwnedFd::from_raw_fd(fd) },
-            })
+    fn create(backend: Backend) -> io::Result<Self> {
+        match backend {
+            Backend::Futex => Futex::create(()).map(SysKernel::Futex),
+            Backend::Pipe => Pipe::create(()).map(SysKernel::Pipe),
+            _ => EventFd::create(()).map(SysKernel::EventFd),
         }
     }
 
-    /// Waits for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return immediately and
-    /// reset the event to the unsignalled state. Otherwise, it will block until another thread
-    /// signals the event.
-    pub fn wait(&self) {
-        let mut value: u64 = 0;
-        let ret = unsafe {
-            libc::read(
-                self.fd.as_raw_fd(),
-                &mut value as *mut _ as *mut libc::c_void,
-                std::mem::size_of::<u64>(),
-            )
-        };
+    fn backend(backend: Backend) -> Backend {
+        backend
+    }
+}
 
-        if ret == -1 {
-            // This should not happen
-            let err = std::io::Error::last_os_error();
-            panic!("read failed with error {}", err);
+impl KernelWaker for SysKernel {
+    fn wake(&self) {
+        match self {
+            SysKernel::EventFd(fd) => fd.wake(),
+            SysKernel::Futex(futex) => futex.wake(),
+            SysKernel::Pipe(pipe) => pipe.wake(),
         }
     }
 
-    /// Tries to wait for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. Otherwise, it will return `false` immediately.
-    pub fn try_wait(&self) -> bool {
-        self.try_wait_for(Duration::from_millis(0))
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        match self {
+            SysKernel::EventFd(fd) => fd.block(timeout),
+            SysKernel::Futex(futex) => futex.block(timeout),
+            SysKernel::Pipe(pipe) => pipe.block(timeout),
+        }
     }
 
-    /// Tries to wait for the event to be signalled for a specified duration.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. If the event is signalled within the timeout,
-    /// it will return `true`. Otherwise, it will return `false`.
-    pub fn try_wait_for(&self, timeout: Duration) -> bool {
-        let mut pollfd = libc::pollfd {
-            fd: self.fd.as_raw_fd(),
-            events: libc::POLLIN,
-            revents: 0,
-        };
+    fn drain(&self) {
+        match self {
+            SysKernel::EventFd(fd) => fd.drain(),
+            SysKernel::Futex(futex) => futex.drain(),
+            SysKernel::Pipe(pipe) => pipe.drain(),
+        }
+    }
+
+    fn block_any<'a>(
+        kernels: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        let futexes = kernels.clone().filter_map(|kernel| {
+            match kernel {
+                SysKernel::Futex(futex) => Some(futex),
+                _ => None,
+            }
+        });
+
+        if kernels
+            .clone()
+            .all(|kernel| matches!(kernel, SysKernel::Futex(_)))
+        {
+            return Futex::block_any(futexes, timeout);
+        }
+
+        // Futexes cannot be polled together with fds, so their eventfds are used instead
+        if Futex::prepare_poll(futexes) {
+            return Ok(true);
+        }
+        poll_any(kernels, SysKernel::as_fd, timeout)
+    }
+
+    #[cfg(feature = "io-uring")]
+    fn wake_many<'a>(kernels: impl Iterator<Item = &'a Self>) {
+        let mut fds = [0; crate::uring::BATCH_SIZE];
+        let mut count = 0;
+        for kernel in kernels {
+            match kernel {
+                SysKernel::EventFd(fd) => {
+                    fds[count] = fd.0.as_raw_fd();
+                    count += 1;
+                    if count == fds.len() {
+                        write_eventfds(&fds);
+                        count = 0;
+                    }
+                }
+                kernel => kernel.wake(),
+            }
+        }
+        write_eventfds(&fds[..count]);
+    }
+}
+
+impl SignalSafeWaker for SysKernel {
+    fn wake_from_handler(&self) {
+        match self {
+            SysKernel::EventFd(fd) => fd.wake_from_handler(),
+            SysKernel::Futex(futex) => futex.wake_from_handler(),
+            SysKernel::Pipe(pipe) => pipe.wake_from_handler(),
+        }
+    }
+}
+
+impl AsFd for SysKernel {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        match self {
+            SysKernel::EventFd(fd) => fd.as_fd(),
+            SysKernel::Futex(futex) => futex.as_fd(),
+            SysKernel::Pipe(pipe) => pipe.as_fd(),
+        }
+    }
+}
+
+impl TryAsFd for SysKernel {
+    fn try_as_fd(&self) -> io::Result<BorrowedFd<'_>> {
+        match self {
+            SysKernel::Futex(futex) => futex.try_as_fd(),
+            _ => Ok(self.as_fd()),
+        }
+    }
+}
+
+// A nonblocking eventfd
+#[derive(Debug)]
+pub(crate) struct EventFd(OwnedFd);
+
+impl KernelObject for EventFd {
+    type Config = ();
+
+    fn create((): ()) -> io::Result<Self> {
+        let fd = syscall::call(
+            "eventfd",
+            format_args!("{}, EFD_CLOEXEC | EFD_NONBLOCK", EFD_INITIAL_VALUE),
+            || unsafe { libc::eventfd(EFD_INITIAL_VALUE, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) },
+        )?;
+        Ok(Self(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    fn backend((): ()) -> Backend {
+        Backend::EventFd
+    }
+}
 
-        let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
-        let ret = unsafe { libc::poll(&mut pollfd, 1, millis) };
+#[cfg(feature = "io-uring")]
+fn write_eventfds(fds: &[RawFd]) {
+    if !crate::uring::write_eventfds(fds) {
+        fds.iter().copied().for_each(write_eventfd);
+    }
+}
+
+fn write_eventfd(fd: RawFd) {
+    let value: u64 = 1;
+    loop {
+        let res = syscall::call("write", format_args!("{}, {}", fd, value), || unsafe {
+            libc::write(
+                fd,
+                &value as *const _ as *const libc::c_void,
+                std::mem::size_of::<u64>(),
+            )
+        });
 
-        if ret == -1 {
-            let err = std::io::Error::last_os_error();
-            panic!("poll failed with error {}", err);
+        match res {
+            // A short write did not add to the counter
+            Ok(len) if (len as usize) < std::mem::size_of::<u64>() => continue,
+            Ok(_) => return,
+            // The counter can only overflow if nobody ever consumes it, in which case the eventfd
+            // is readable anyway
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return unexpected("write", err),
         }
+    }
+}
 
-        if ret > 0 && (pollfd.revents & libc::POLLIN) != 0 {
-            // Read the value to reset the event
-            let mut value: u64 = 0;
-            let ret = unsafe {
+impl EventFd {
+    // Consumes a pending wakeup. Returns `false` if there was none.
+    fn read(&self) -> bool {
+        // Reading a non-semaphore eventfd resets the counter to zero
+        let mut value: u64 = 0;
+        let fd = self.0.as_raw_fd();
+        loop {
+            let res = syscall::call("read", format_args!("{}, 8 bytes", fd), || unsafe {
                 libc::read(
-                    self.fd.as_raw_fd(),
+                    fd,
                     &mut value as *mut _ as *mut libc::c_void,
                     std::mem::size_of::<u64>(),
                 )
-            };
-            if ret == -1 {
-                // This might happen if another thread stole the signal between poll and read,
-                // but for an autoreset event, that's expected behavior in a race.
-                // However, if we are the only one waiting (or if we want to report success),
-                // we should consider what to return.
-                // If read fails with EAGAIN/EWOULDBLOCK, it means it wasn't ready.
-                // But poll said it was.
-                // For now, let's assume if poll returns > 0, we should be able to read.
-                // But to be safe against spurious wakeups or race conditions:
-                let err = std::io::Error::last_os_error();
-                if err.kind() == std::io::ErrorKind::WouldBlock {
+            });
+
+            match res {
+                Ok(_) => return true,
+                // Another thread might have consumed the wakeup first
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return false,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    unexpected("read", err);
                     return false;
                 }
-                panic!("read failed with error {}", err);
             }
-            true
-        } else {
-            false
         }
     }
+}
+
+impl KernelWaker for EventFd {
+    fn wake(&self) {
+        write_eventfd(self.0.as_raw_fd())
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        // The wakeup is often posted while the waiter is about to block, so try to consume it
+        // without polling first
+        if self.read() {
+            return Ok(true);
+        }
+
+        if !poll_readable(self.0.as_fd(), timeout)? {
+            return Ok(false);
+        }
+
+        // If another thread stole the wakeup in the meantime, this is a spurious wakeup: the caller
+        // re-checks the state and blocks again for the remaining time
+        self.read();
+        Ok(true)
+    }
+
+    fn block_any<'a>(
+        fds: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        poll_any(fds, EventFd::as_fd, timeout)
+    }
+
+    fn drain(&self) {
+        self.read();
+    }
+}
 
-    /// Signals the event.
-    ///
-    /// If there is a thread waiting on the event, it will be woken up and the event will be reset
-    /// to the unsignalled state. If there are no threads waiting, the event will remain in the
-    /// signalled state until a thread waits on it.
-    pub fn signal(&self) {
+impl SignalSafeWaker for EventFd {
+    fn wake_from_handler(&self) {
         let value: u64 = 1;
-        let ret = unsafe {
+        let fd = self.0.as_raw_fd();
+        // The counter can only overflow if nobody ever consumes it
+        while unsafe {
             libc::write(
-                self.fd.as_raw_fd(),
+                fd,
                 &value as *const _ as *const libc::c_void,
                 std::mem::size_of::<u64>(),
             )
-        };
-
-        if ret == -1 {
-            // This should not happen
-            let err = std::io::Error::last_os_error();
-            panic!("write failed with error {}", err);
-        }
+        } == -1
+            && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted
+        {}
     }
 }
 
-impl AsRawFd for AutoResetEvent {
-    fn as_raw_fd(&self) -> RawFd {
-        self.fd.as_raw_fd()
+impl AsFd for EventFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
     }
 }
 
-impl AsFd for AutoResetEvent {
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.fd.as_fd()
+// A timerfd, which expires with nanosecond resolution, see `TimerWheel`
+#[derive(Debug)]
+pub(crate) struct KernelTimer(OwnedFd);
+
+impl KernelTimer {
+    pub(crate) fn new(control: &AutoResetEvent) -> Result<Self, EventError> {
+        // The service thread polls the fd of the control event
+        control.try_as_fd()?;
+        let fd = syscall::call(
+            "timerfd_create",
+            format_args!("CLOCK_MONOTONIC, TFD_NONBLOCK | TFD_CLOEXEC"),
+            || unsafe {
+                libc::timerfd_create(
+                    libc::CLOCK_MONOTONIC,
+                    libc::TFD_NONBLOCK | libc::TFD_CLOEXEC,
+                )
+            },
+        )
+        .map_err(|err| EventError::new(EventOperation::Create, control.backend(), err))?;
+        Ok(Self(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    // Blocks until `control` is signalled or `timeout` has elapsed, and consumes the signal of
+    // `control`
+    pub(crate) fn wait(&self, control: &AutoResetEvent, timeout: Duration) -> io::Result<()> {
+        let fd = self.0.as_raw_fd();
+        // A zero value would disarm the timer
+        let timeout = timeout.max(Duration::from_nanos(1));
+        let value = libc::itimerspec {
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: libc::timespec {
+                tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as libc::c_long,
+            },
+        };
+        syscall::call(
+            "timerfd_settime",
+            format_args!("{}, 0, {:?}", fd, timeout),
+            || unsafe { libc::timerfd_settime(fd, 0, &value, ptr::null_mut()) },
+        )?;
+
+        let control_fd = control.as_raw_fd();
+        let mut pollfds = [control_fd, fd].map(|fd| {
+            libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            }
+        });
+        let res = syscall::call(
+            "poll",
+            format_args!("[{}, {}], timeout -1 ms", control_fd, fd),
+            || unsafe { libc::poll(pollfds.as_mut_ptr(), 2, -1) },
+        );
+        match res {
+            // The caller re-checks the deadlines, just like after a wakeup
+            Err(err) if err.kind() != io::ErrorKind::Interrupted => return Err(err),
+            _ => {}
+        }
+
+        // Resets the expiration count, so that the fd is not readable until the next expiration
+        let mut expirations: u64 = 0;
+        let _ = syscall::call("read", format_args!("{}, 8", fd), || unsafe {
+            libc::read(
+                fd,
+                &mut expirations as *mut _ as *mut libc::c_void,
+                std::mem::size_of::<u64>(),
+            )
+        });
+        control.try_wait();
+        Ok(())
     }
 }
 
+// Watches a file or directory for changes via inotify, see `FsWatchEvent`
+#[cfg(feature = "fs-watch")]
+pub(crate) struct PathWatch {
+    fd: OwnedFd,
+}
 
-// It is safe to send an autoreset event to another thread. The underlying file descriptor is a
-// kernel object that can be used from any thread.
-unsafe impl Send for AutoResetEvent {}
+#[cfg(feature = "fs-watch")]
+impl PathWatch {
+    pub(crate) fn new(path: &std::ffi::CStr) -> io::Result<Self> {
+        let fd = syscall::call(
+            "inotify_init1",
+            format_args!("IN_NONBLOCK | IN_CLOEXEC"),
+            || unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) },
+        )?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
 
-// It is safe to share an autoreset event between threads. The underlying file descriptor is a
-// kernel object that is thread-safe.
-unsafe impl Sync for AutoResetEvent {}
+        let mask = libc::IN_MODIFY
+            | libc::IN_ATTRIB
+            | libc::IN_CLOSE_WRITE
+            | libc::IN_CREATE
+            | libc::IN_DELETE
+            | libc::IN_MOVED_FROM
+            | libc::IN_MOVED_TO
+            | libc::IN_DELETE_SELF
+            | libc::IN_MOVE_SELF;
+        syscall::call(
+            "inotify_add_watch",
+            format_args!("{}, {:?}, {:#x}", fd.as_raw_fd(), path, mask),
+            || unsafe { libc::inotify_add_watch(fd.as_raw_fd(), path.as_ptr(), mask) },
+        )?;
+        Ok(Self { fd })
+    }
+
+    /// The fd that is readable while changes are pending.
+    pub(crate) fn fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+
+    /// Discards the pending changes.
+    pub(crate) fn drain(&self) -> io::Result<()> {
+        let mut buf = [0u8; 4096];
+        let fd = self.fd.as_raw_fd();
+        loop {
+            let res = syscall::call(
+                "read",
+                format_args!("{}, {} bytes", fd, buf.len()),
+                || unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) },
+            );
+            match res {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use std::io;
 use std::os::fd::{AsFd, BorrowedFd, FromRawFd, OwnedFd};
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::AsRawFd;
 use std::ptr;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use libc::{EV_ADD, EV_CLEAR, EV_DELETE, EVFILT_USER, c_void, kevent, kqueue, pipe, write};
+use libc::{EV_ADD, EV_CLEAR, EV_DELETE, EVFILT_USER, kevent, kqueue};
+
+use crate::pipe::{Pipe, poll_any};
+use crate::state::{KernelObject, KernelWaker, LazyKernel, SignalSafeWaker, TryAsFd, unexpected};
+use crate::{Backend, Builder, syscall};
 
 #[macro_export]
 macro_rules! EV_SET {
@@ -18,45 +24,294 @@ macro_rules! EV_SET {
     };
 }
 
+pub(crate) type Waker = LazyKernel<SysKernel>;
+
+pub(crate) const BACKENDS: &[Backend] = &[Backend::Kqueue, Backend::Pipe];
+
+pub(crate) fn waker(builder: &Builder, backend: Backend) -> io::Result<Waker> {
+    LazyKernel::with_laziness(backend, builder.lazy)
+}
+
+pub(crate) fn backend(waker: &Waker) -> Backend {
+    waker.config()
+}
+
+pub(crate) fn round_trip(backend: Backend) -> io::Result<Duration> {
+    crate::backend::round_trip::<SysKernel>(backend)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub(crate) fn attach_mach_port(waker: &Waker, port: libc::mach_port_t) -> io::Result<()> {
+    match waker.get() {
+        SysKernel::Kqueue(kqueue) => {
+            kqueue.change(port as usize, libc::EVFILT_MACHPORT, EV_ADD | EV_CLEAR)
+        }
+        SysKernel::Pipe(_) => Err(mach_ports_unsupported()),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub(crate) fn detach_mach_port(waker: &Waker, port: libc::mach_port_t) -> io::Result<()> {
+    match waker.get() {
+        SysKernel::Kqueue(kqueue) => kqueue.change(port as usize, libc::EVFILT_MACHPORT, EV_DELETE),
+        SysKernel::Pipe(_) => Err(mach_ports_unsupported()),
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn mach_ports_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "mach ports require the kqueue backend",
+    )
+}
+
+// The number of ready events that `EventQueue::block` fetches per call
+const QUEUE_EVENTS: usize = 32;
 
-/// An autoreset event.
+/// A kqueue that multiplexes the kernel objects of the events of an `EventSet`.
 ///
-/// See the [module-level documentation](..) for more information.
+/// Registrations are not applied immediately, but submitted as a single changelist together with
+/// the next wait.
+pub(crate) struct EventQueue {
+    kq: OwnedFd,
+    changes: Vec<libc::kevent>,
+}
+
+impl EventQueue {
+    pub(crate) fn new() -> io::Result<Self> {
+        let kq = syscall::call("kqueue", format_args!(""), || unsafe { kqueue() })?;
+
+        Ok(Self {
+            kq: unsafe { OwnedFd::from_raw_fd(kq) },
+            changes: Vec::new(),
+        })
+    }
+
+    /// Registers the kernel object of an event. The waker must outlive the queue.
+    pub(crate) fn insert(&mut self, waker: &Waker) {
+        let fd = waker.get().as_fd().as_raw_fd();
+
+        let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
+        EV_SET!(
+            &mut ke,
+            fd,
+            libc::EVFILT_READ,
+            EV_ADD,
+            0,
+            0,
+            waker as *const Waker
+        );
+        self.changes.push(ke);
+    }
+
+    pub(crate) fn remove(&mut self, waker: &Waker) {
+        let fd = waker.get().as_fd().as_raw_fd() as libc::uintptr_t;
+
+        // An insertion that has not been submitted yet can simply be dropped
+        if let Some(index) = self
+            .changes
+            .iter()
+            .position(|change| change.ident == fd && change.flags & EV_ADD != 0)
+        {
+            self.changes.remove(index);
+            return;
+        }
+
+        let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
+        EV_SET!(
+            &mut ke,
+            fd,
+            libc::EVFILT_READ,
+            EV_DELETE,
+            0,
+            0,
+            ptr::null_mut()
+        );
+        self.changes.push(ke);
+    }
+
+    /// Submits the pending changes, then blocks until one of the kernel objects is readable and
+    /// drains the ready ones. Returns `false` on timeout.
+    pub(crate) fn block(&mut self, timeout: Option<Duration>) -> io::Result<bool> {
+        let ts = timeout.map(|timeout| {
+            libc::timespec {
+                tv_sec: timeout.as_secs() as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as libc::c_long,
+            }
+        });
+
+        let mut events: [libc::kevent; QUEUE_EVENTS] = unsafe { std::mem::zeroed() };
+        let kq = self.kq.as_raw_fd();
+        let res = syscall::call(
+            "kevent",
+            format_args!(
+                "{}, {} changes, {} events, timeout {:?}",
+                kq,
+                self.changes.len(),
+                QUEUE_EVENTS,
+                timeout
+            ),
+            || unsafe {
+                kevent(
+                    kq,
+                    self.changes.as_ptr(),
+                    self.changes.len() as libc::c_int,
+                    events.as_mut_ptr(),
+                    QUEUE_EVENTS as libc::c_int,
+                    ts.as_ref().map_or(ptr::null(), |ts| ts as *const _),
+                )
+            },
+        );
+
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => {
+                // The changes have been applied if the call was interrupted
+                if err.kind() == io::ErrorKind::Interrupted {
+                    self.changes.clear();
+                }
+                return Err(err);
+            }
+        };
+        self.changes.clear();
+
+        for event in &events[..res as usize] {
+            if event.flags & libc::EV_ERROR != 0 {
+                return Err(io::Error::from_raw_os_error(event.data as i32));
+            }
+
+            // Safety: only registered wakers are returned, and they outlive the queue
+            let waker = unsafe { &*(event.udata as *const Waker) };
+            waker.drain();
+        }
+        Ok(res > 0)
+    }
+}
+
+// The raw pointers in the changelist refer to wakers of events that are shared with the queue, so
+// it can be sent to another thread.
+unsafe impl Send for EventQueue {}
+
+impl std::fmt::Debug for EventQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventQueue")
+            .field("kq", &self.kq)
+            .field("pending_changes", &self.changes.len())
+            .finish()
+    }
+}
+
+// The kernel side of the event
+#[derive(Debug)]
+pub(crate) enum SysKernel {
+    Kqueue(Kqueue),
+    Pipe(Pipe),
+}
+
+impl KernelObject for SysKernel {
+    type Config = Backend;
+
+    fn create(backend: Backend) -> io::Result<Self> {
+        match backend {
+            Backend::Pipe => Pipe::create(()).map(SysKernel::Pipe),
+            _ => Kqueue::create(()).map(SysKernel::Kqueue),
+        }
+    }
+
+    fn backend(backend: Backend) -> Backend {
+        backend
+    }
+}
+
+impl KernelWaker for SysKernel {
+    fn wake(&self) {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.wake(),
+            SysKernel::Pipe(pipe) => pipe.wake(),
+        }
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.block(timeout),
+            SysKernel::Pipe(pipe) => pipe.block(timeout),
+        }
+    }
+
+    fn drain(&self) {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.drain(),
+            SysKernel::Pipe(pipe) => pipe.drain(),
+        }
+    }
+
+    fn take_external(&self) -> bool {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.take_external(),
+            SysKernel::Pipe(pipe) => pipe.take_external(),
+        }
+    }
+
+    fn block_any<'a>(
+        kernels: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        poll_any(kernels, SysKernel::as_fd, timeout)
+    }
+}
+
+impl SignalSafeWaker for SysKernel {
+    fn wake_from_handler(&self) {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.wake_from_handler(),
+            SysKernel::Pipe(pipe) => pipe.wake_from_handler(),
+        }
+    }
+}
+
+// The fd that is readable while a wakeup is pending
+impl AsFd for SysKernel {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        match self {
+            SysKernel::Kqueue(kqueue) => kqueue.kq.as_fd(),
+            SysKernel::Pipe(pipe) => pipe.as_fd(),
+        }
+    }
+}
+
+impl TryAsFd for SysKernel {}
+
+// A kqueue with a user event. The kqueue itself is readable while the user event is triggered, so
+// it can be polled by other event loops, and retrieving the event resets it.
 #[derive(Debug)]
-pub struct AutoResetEvent {
+pub(crate) struct Kqueue {
     kq: OwnedFd,
     ident: usize,
-    fds: [OwnedFd; 2],
+    // Set when an attached Mach port received a message
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    mach_port_signalled: AtomicBool,
 }
 
-impl AutoResetEvent {
-    /// Creates a new autoreset event.
-    pub fn new() -> io::Result<Self> {
-        let kq_raw = unsafe { kqueue() };
-        if kq_raw == -1 {
-            return Err(io::Error::last_os_error());
-        }
+impl KernelObject for Kqueue {
+    type Config = ();
+
+    fn create((): ()) -> io::Result<Self> {
+        let kq_raw = syscall::call("kqueue", format_args!(""), || unsafe { kqueue() })?;
         let kq = unsafe { OwnedFd::from_raw_fd(kq_raw) };
 
-        let mut fds_raw = [0; 2];
-        if unsafe { pipe(fds_raw.as_mut_ptr()) } == -1 {
-            return Err(io::Error::last_os_error());
-            // kq is dropped here, closing the fd
-        }
-        let fds = unsafe {
-            [
-                OwnedFd::from_raw_fd(fds_raw[0]),
-                OwnedFd::from_raw_fd(fds_raw[1]),
-            ]
+        let kqueue = Kqueue {
+            kq,
+            ident: 1,
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            mach_port_signalled: AtomicBool::new(false),
         };
 
-        let event = Self { kq, ident: 1, fds };
-
         // Add a new user event to the kqueue.
         let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
         EV_SET!(
             &mut ke,
-            event.ident,
+            kqueue.ident,
             EVFILT_USER,
             EV_ADD | EV_CLEAR,
             0,
@@ -64,75 +319,63 @@ impl AutoResetEvent {
             ptr::null_mut()
         );
 
-        let res = unsafe {
-            kevent(
-                event.kq.as_raw_fd(),
-                &ke,
-                1,
-                ptr::null_mut(),
-                0,
-                ptr::null(),
-            )
-        };
-        if res == -1 {
-            return Err(io::Error::last_os_error());
-        }
+        kqueue.submit(&ke, "EV_ADD EVFILT_USER")?;
+
+        Ok(kqueue)
+    }
 
-        Ok(event)
+    fn backend((): ()) -> Backend {
+        Backend::Kqueue
     }
+}
 
-    /// Waits for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return immediately and
-    /// reset the event to the unsignalled state. Otherwise, it will block until another thread
-    /// signals the event.
-    pub fn wait(&self) {
+impl Kqueue {
+    fn kevent_wait(&self, timeout: Option<&libc::timespec>) -> io::Result<bool> {
         let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
-        let res = unsafe { kevent(self.kq.as_raw_fd(), ptr::null(), 0, &mut ke, 1, ptr::null()) };
+        let kq = self.kq.as_raw_fd();
+        let res = syscall::call(
+            "kevent",
+            format_args!(
+                "{}, 1 event, timeout {:?}",
+                kq,
+                timeout.map(|ts| Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+            ),
+            || unsafe {
+                let timeout = timeout.map_or(ptr::null(), |ts| ts as *const _);
+                kevent(kq, ptr::null(), 0, &mut ke, 1, timeout)
+            },
+        )?;
 
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("kevent failed with error {}", err);
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        if res > 0 && ke.filter == libc::EVFILT_MACHPORT {
+            self.mach_port_signalled.store(true, Ordering::SeqCst);
         }
-    }
 
-    /// Tries to wait for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. Otherwise, it will return `false` immediately.
-    pub fn try_wait(&self) -> bool {
-        self.try_wait_for(Duration::from_millis(0))
+        Ok(res > 0)
     }
 
-    /// Tries to wait for the event to be signalled for a specified duration.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. If the event is signalled within the timeout,
-    /// it will return `true`. Otherwise, it will return `false`.
-    pub fn try_wait_for(&self, timeout: Duration) -> bool {
+    // Adds or removes a filter of the kqueue
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    fn change(&self, ident: usize, filter: i16, flags: u16) -> io::Result<()> {
         let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
-        let ts = libc::timespec {
-            tv_sec: timeout.as_secs() as libc::time_t,
-            tv_nsec: timeout.subsec_nanos() as libc::c_long,
-        };
-        let res = unsafe { kevent(self.kq.as_raw_fd(), ptr::null(), 0, &mut ke, 1, &ts) };
-
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("kevent failed with error {}", err);
-        }
+        EV_SET!(&mut ke, ident, filter, flags, 0, 0, ptr::null_mut());
+        self.submit(&ke, "EVFILT_MACHPORT")
+    }
 
-        res > 0
+    // Applies a single change to the kqueue. `change` describes it for the trace.
+    fn submit(&self, ke: &libc::kevent, change: &str) -> io::Result<()> {
+        let kq = self.kq.as_raw_fd();
+        syscall::call(
+            "kevent",
+            format_args!("{}, {} (flags {:#x}), 0 events", kq, change, ke.flags),
+            || unsafe { kevent(kq, ke, 1, ptr::null_mut(), 0, ptr::null()) },
+        )?;
+        Ok(())
     }
+}
 
-    /// Signals the event.
-    ///
-    /// If there is a thread waiting on the event, it will be woken up and the event will be reset
-    /// to the unsignalled state. If there are no threads waiting, the event will remain in the
-    /// signalled state until a thread waits on it.
-    pub fn signal(&self) {
+impl KernelWaker for Kqueue {
+    fn wake(&self) {
         let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
         EV_SET!(
             &mut ke,
@@ -144,26 +387,75 @@ impl AutoResetEvent {
             ptr::null_mut()
         );
 
-        let res = unsafe { kevent(self.kq.as_raw_fd(), &ke, 1, ptr::null_mut(), 0, ptr::null()) };
-
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("kevent failed with error {}", err);
+        // Triggering an already triggered event has no effect, so wakeups do not accumulate
+        loop {
+            match self.submit(&ke, "NOTE_TRIGGER EVFILT_USER") {
+                Ok(()) => return,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                // This should not happen
+                Err(err) => return unexpected("kevent", err),
+            }
         }
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let ts = timeout.map(|timeout| {
+            libc::timespec {
+                tv_sec: timeout.as_secs() as libc::time_t,
+                tv_nsec: timeout.subsec_nanos() as libc::c_long,
+            }
+        });
+        self.kevent_wait(ts.as_ref())
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    fn take_external(&self) -> bool {
+        self.mach_port_signalled.swap(false, Ordering::SeqCst)
+    }
 
-        // Also write to the pipe.
-        let buf = [0u8; 1];
-        let res = unsafe { write(self.fds[1].as_raw_fd(), buf.as_ptr() as *const c_void, 1) };
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("write failed with error {}", err);
+    fn block_any<'a>(
+        kqueues: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        poll_any(kqueues, |kqueue| kqueue.kq.as_fd(), timeout)
+    }
+
+    fn drain(&self) {
+        let ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        loop {
+            match self.kevent_wait(Some(&ts)) {
+                Ok(_) => return,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return unexpected("kevent", err),
+            }
         }
     }
 }
 
-impl Drop for AutoResetEvent {
+impl SignalSafeWaker for Kqueue {
+    fn wake_from_handler(&self) {
+        let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
+        EV_SET!(
+            &mut ke,
+            self.ident,
+            EVFILT_USER,
+            0,
+            libc::NOTE_FFNOP | libc::NOTE_TRIGGER,
+            0,
+            ptr::null_mut()
+        );
+
+        let kq = self.kq.as_raw_fd();
+        while unsafe { kevent(kq, &ke, 1, ptr::null_mut(), 0, ptr::null()) } == -1
+            && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted
+        {}
+    }
+}
+
+impl Drop for Kqueue {
     fn drop(&mut self) {
         // Remove the user event from the kqueue.
         let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
@@ -177,30 +469,98 @@ impl Drop for AutoResetEvent {
             ptr::null_mut()
         );
 
-        unsafe {
-            kevent(self.kq.as_raw_fd(), &ke, 1, ptr::null_mut(), 0, ptr::null());
-            // OwnedFd fields will be closed automatically
-        }
+        let _ = self.submit(&ke, "EV_DELETE EVFILT_USER");
+        // OwnedFd fields will be closed automatically
     }
 }
 
-impl AsRawFd for AutoResetEvent {
-    fn as_raw_fd(&self) -> RawFd {
-        self.fds[0].as_raw_fd()
-    }
+// Watches a file or directory for changes via `EVFILT_VNODE` on a kqueue of its own, see
+// `FsWatchEvent`
+#[cfg(feature = "fs-watch")]
+pub(crate) struct PathWatch {
+    kq: OwnedFd,
+    // The watched file, which has to stay open
+    _file: OwnedFd,
 }
 
-impl AsFd for AutoResetEvent {
-    fn as_fd(&self) -> BorrowedFd<'_> {
-        self.fds[0].as_fd()
-    }
-}
+#[cfg(feature = "fs-watch")]
+impl PathWatch {
+    pub(crate) fn new(path: &std::ffi::CStr) -> io::Result<Self> {
+        // Opens the file without preventing the unmount of its volume
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        let flags = libc::O_EVTONLY | libc::O_CLOEXEC;
+        #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+        let flags = libc::O_RDONLY | libc::O_CLOEXEC;
 
+        let file = syscall::call(
+            "open",
+            format_args!("{:?}, {:#x}", path, flags),
+            || unsafe { libc::open(path.as_ptr(), flags) },
+        )?;
+        let file = unsafe { OwnedFd::from_raw_fd(file) };
+        let kq = syscall::call("kqueue", format_args!(""), || unsafe { kqueue() })?;
+        let kq = unsafe { OwnedFd::from_raw_fd(kq) };
 
-// It is safe to send an autoreset event to another thread. The underlying kqueue is a kernel
-// object that can be used from any thread.
-unsafe impl Send for AutoResetEvent {}
+        let mut ke: libc::kevent = unsafe { std::mem::zeroed() };
+        EV_SET!(
+            &mut ke,
+            file.as_raw_fd(),
+            libc::EVFILT_VNODE,
+            EV_ADD | EV_CLEAR,
+            libc::NOTE_WRITE
+                | libc::NOTE_EXTEND
+                | libc::NOTE_ATTRIB
+                | libc::NOTE_DELETE
+                | libc::NOTE_RENAME
+                | libc::NOTE_LINK
+                | libc::NOTE_REVOKE,
+            0,
+            ptr::null_mut()
+        );
+        let fd = kq.as_raw_fd();
+        syscall::call(
+            "kevent",
+            format_args!("{}, EV_ADD EVFILT_VNODE {}, 0 events", fd, file.as_raw_fd()),
+            || unsafe { kevent(fd, &ke, 1, ptr::null_mut(), 0, ptr::null()) },
+        )?;
+
+        Ok(Self { kq, _file: file })
+    }
+
+    /// The fd that is readable while changes are pending.
+    pub(crate) fn fd(&self) -> BorrowedFd<'_> {
+        self.kq.as_fd()
+    }
 
-// It is safe to share an autoreset event between threads. The underlying kqueue is a kernel
-// object that is thread-safe.
-unsafe impl Sync for AutoResetEvent {}
+    /// Discards the pending changes.
+    pub(crate) fn drain(&self) -> io::Result<()> {
+        let mut events: [libc::kevent; 8] = unsafe { std::mem::zeroed() };
+        let timeout = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let kq = self.kq.as_raw_fd();
+        loop {
+            let res = syscall::call(
+                "kevent",
+                format_args!("{}, 0 changes, {} events, timeout 0", kq, events.len()),
+                || unsafe {
+                    kevent(
+                        kq,
+                        ptr::null(),
+                        0,
+                        events.as_mut_ptr(),
+                        events.len() as libc::c_int,
+                        &timeout,
+                    )
+                },
+            );
+            match res {
+                Ok(0) => return Ok(()),
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
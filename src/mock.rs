@@ -0,0 +1,231 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::EventError;
+
+/// A deterministic stand-in for [`AutoResetEvent`](crate::AutoResetEvent) in unit tests.
+///
+/// The mock has the same signal and wait functions as a real event, but never blocks: it runs on
+/// a virtual clock that only advances when a wait times out, when a wait is satisfied by a
+/// scheduled signal, or via [`advance`](Self::advance). Signals of other threads are simulated
+/// with [`signal_after`](Self::signal_after), and timeouts can be forced with
+/// [`time_out_next_wait`](Self::time_out_next_wait). Code under test can switch to it via an
+/// import:
+///
+/// ```
+/// #[cfg(not(test))]
+/// use nova_autoreset_event::AutoResetEvent;
+/// #[cfg(test)]
+/// use nova_autoreset_event::MockAutoResetEvent as AutoResetEvent;
+/// ```
+///
+/// A wait that would block forever, i.e. a [`wait`](Self::wait) on an unsignalled event without
+/// a scheduled signal, panics.
+///
+/// ```
+/// use std::time::Duration;
+/// use nova_autoreset_event::MockAutoResetEvent;
+///
+/// let event = MockAutoResetEvent::new().unwrap();
+/// event.signal_after(Duration::from_secs(5));
+///
+/// assert!(!event.try_wait_for(Duration::from_secs(1)));
+/// assert!(event.try_wait_for(Duration::from_secs(10)));
+/// assert_eq!(event.elapsed(), Duration::from_secs(5));
+/// assert_eq!(event.timeouts(), 1);
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub struct MockAutoResetEvent {
+    state: Mutex<MockState>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    now: Duration,
+    signalled: bool,
+    // The virtual times of the scheduled signals, latest first
+    scheduled: Vec<Duration>,
+    forced_timeouts: usize,
+    // The number of signals since the last `wait_count()`
+    pending_signals: u64,
+    signals: u64,
+    coalesced_signals: u64,
+    waits: u64,
+    timeouts: u64,
+}
+
+impl MockState {
+    fn signal(&mut self) {
+        self.coalesced_signals += self.signalled as u64;
+        self.signalled = true;
+        self.signals += 1;
+        self.pending_signals += 1;
+    }
+
+    fn take(&mut self) -> bool {
+        let taken = std::mem::take(&mut self.signalled);
+        self.waits += taken as u64;
+        taken
+    }
+
+    // Advances the clock, delivering the signals that are due until then
+    fn advance_to(&mut self, now: Duration) {
+        self.now = self.now.max(now);
+        while self.scheduled.last().is_some_and(|&at| at <= self.now) {
+            self.scheduled.pop();
+            self.signal();
+        }
+    }
+
+    // Returns the time of the next scheduled signal, if it is due within `timeout`
+    fn next_signal(&self, timeout: Option<Duration>) -> Option<Duration> {
+        let next = *self.scheduled.last()?;
+        match timeout {
+            Some(timeout) if next > self.now.saturating_add(timeout) => None,
+            _ => Some(next),
+        }
+    }
+}
+
+impl MockAutoResetEvent {
+    /// Creates a new mock event. Never fails.
+    pub fn new() -> Result<Self, EventError> {
+        Ok(Self::default())
+    }
+
+    /// Waits for the event to be signalled.
+    ///
+    /// If the event is not signalled, the clock advances to the next scheduled signal, which is
+    /// consumed. Forced timeouts do not apply.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event is not signalled and no signal is scheduled.
+    pub fn wait(&self) {
+        let mut state = self.lock();
+        if state.take() {
+            return;
+        }
+
+        let next = state.next_signal(None).expect(
+            "wait() on an unsignalled mock event without a scheduled signal blocks forever",
+        );
+        state.advance_to(next);
+        state.take();
+    }
+
+    /// Waits for the event to be signalled and returns the number of signals since the previous
+    /// call, like [`AutoResetEvent::wait_count`](crate::AutoResetEvent::wait_count).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event is not signalled and no signal is scheduled.
+    pub fn wait_count(&self) -> u64 {
+        self.wait();
+        std::mem::take(&mut self.lock().pending_signals)
+    }
+
+    /// Consumes the signal if the event is signalled. Does not advance the clock.
+    pub fn try_wait(&self) -> bool {
+        self.lock().take()
+    }
+
+    /// Waits for the event to be signalled for a specified duration of virtual time.
+    ///
+    /// If the event is not signalled, the clock advances to the next scheduled signal if it is due
+    /// within the timeout, and the signal is consumed. Otherwise the wait times out and the clock
+    /// advances by the timeout. A forced timeout makes the wait time out even if a signal is due,
+    /// which is then delivered but not consumed.
+    pub fn try_wait_for(&self, timeout: Duration) -> bool {
+        let mut state = self.lock();
+        if state.take() {
+            return true;
+        }
+        if timeout.is_zero() {
+            return false;
+        }
+
+        if state.forced_timeouts > 0 {
+            state.forced_timeouts -= 1;
+        } else if let Some(next) = state.next_signal(Some(timeout)) {
+            state.advance_to(next);
+            return state.take();
+        }
+
+        let deadline = state.now.saturating_add(timeout);
+        state.advance_to(deadline);
+        state.timeouts += 1;
+        false
+    }
+
+    /// Signals the event.
+    pub fn signal(&self) {
+        self.lock().signal();
+    }
+
+    /// Signals multiple events.
+    pub fn signal_many(events: &[&MockAutoResetEvent]) {
+        for event in events {
+            event.signal();
+        }
+    }
+
+    /// Schedules a signal at `delay` after the current virtual time, as if another thread
+    /// signalled the event then.
+    pub fn signal_after(&self, delay: Duration) {
+        let mut state = self.lock();
+        let at = state.now.saturating_add(delay);
+        let index = state.scheduled.partition_point(|&scheduled| scheduled > at);
+        state.scheduled.insert(index, at);
+    }
+
+    /// Makes the next [`try_wait_for`](Self::try_wait_for) that finds the event unsignalled time
+    /// out, even if a signal is due within its timeout. Calls accumulate.
+    pub fn time_out_next_wait(&self) {
+        self.lock().forced_timeouts += 1;
+    }
+
+    /// Advances the virtual clock by `duration`, delivering the scheduled signals that are due.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.lock();
+        let now = state.now.saturating_add(duration);
+        state.advance_to(now);
+    }
+
+    /// Returns the virtual time since the mock has been created.
+    pub fn elapsed(&self) -> Duration {
+        self.lock().now
+    }
+
+    /// Returns `true` if the event is signalled.
+    pub fn is_signalled(&self) -> bool {
+        self.lock().signalled
+    }
+
+    /// Returns the number of signals, including scheduled signals that have been delivered.
+    pub fn signals(&self) -> u64 {
+        self.lock().signals
+    }
+
+    /// Returns the number of signals that found the event already signalled, like
+    /// [`AutoResetEvent::coalesced_signals`](crate::AutoResetEvent::coalesced_signals).
+    pub fn coalesced_signals(&self) -> u64 {
+        self.lock().coalesced_signals
+    }
+
+    /// Returns the number of waits that consumed a signal.
+    pub fn waits(&self) -> u64 {
+        self.lock().waits
+    }
+
+    /// Returns the number of waits that timed out.
+    pub fn timeouts(&self) -> u64 {
+        self.lock().timeouts
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        // A failed assertion in a test must not poison the mock for the remaining checks
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
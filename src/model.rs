@@ -0,0 +1,148 @@
+// The userspace state machine of an event on a loom model of a kernel object, for the loom tests in
+// `tests/loom.rs`. Only compiled with `--cfg loom`, which swaps the atomics of `GenericEvent` for
+// the ones of loom.
+
+use std::io;
+use std::mem;
+use std::time::Duration;
+
+use loom::sync::{Condvar, Mutex};
+
+use crate::instrument::Instruments;
+use crate::state::{GenericEvent, KernelWaker};
+use crate::{Backend, Builder, WaitResult};
+
+#[cfg(feature = "diagnostics")]
+compile_error!(
+    "the `diagnostics` feature shares the state of events with a global registry and cannot be combined with `--cfg loom`"
+);
+
+/// An event whose kernel object is modelled with loom primitives.
+///
+/// Must be created within `loom::model`. Timeouts are not modelled: a wait with a timeout behaves
+/// as if it timed out as soon as it blocks.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ModelEvent {
+    inner: GenericEvent<ModelWaker>,
+}
+
+impl ModelEvent {
+    pub fn new() -> Self {
+        let builder = Builder::default();
+        Self {
+            inner: GenericEvent::new(
+                ModelWaker::default(),
+                Instruments::new(&builder, Backend::native()),
+            ),
+        }
+    }
+
+    pub fn signal(&self) {
+        self.inner.signal()
+    }
+
+    pub fn signal_n(&self, n: u32) {
+        self.inner.signal_n(n)
+    }
+
+    pub fn pulse(&self) {
+        self.inner.pulse()
+    }
+
+    pub fn wait(&self) {
+        self.inner.wait();
+    }
+
+    pub fn wait_result(&self) -> WaitResult {
+        self.inner.wait()
+    }
+
+    pub fn try_wait(&self) -> bool {
+        self.inner.try_wait()
+    }
+
+    pub fn try_wait_for(&self, timeout: Duration) -> bool {
+        self.inner.try_wait_for(timeout) == WaitResult::Signalled
+    }
+
+    pub fn wait_any(events: &[&ModelEvent]) -> usize {
+        GenericEvent::wait_any(events.iter().map(|event| &event.inner), None)
+            .expect("untimed waits cannot time out")
+    }
+
+    pub fn reset(&self) -> bool {
+        self.inner.reset()
+    }
+
+    pub fn drain(&self) -> usize {
+        self.inner.drain()
+    }
+
+    pub fn close(&self) -> bool {
+        self.inner.close()
+    }
+
+    pub fn waiter_count(&self) -> u32 {
+        self.inner.waiters()
+    }
+
+    pub fn is_signalled(&self) -> bool {
+        self.inner.is_signalled()
+    }
+}
+
+impl Default for ModelEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A kernel object that behaves like an eventfd in semaphore-free mode: wakeups are coalesced into
+// a single pending flag that `block` consumes
+#[derive(Debug, Default)]
+struct ModelWaker {
+    pending: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl KernelWaker for ModelWaker {
+    fn wake(&self) {
+        *self.pending.lock().unwrap() = true;
+        self.condvar.notify_one();
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let mut pending = self.pending.lock().unwrap();
+        if timeout.is_none() {
+            while !*pending {
+                pending = self.condvar.wait(pending).unwrap();
+            }
+        }
+        Ok(mem::take(&mut *pending))
+    }
+
+    fn drain(&self) {
+        *self.pending.lock().unwrap() = false;
+    }
+
+    // A thread cannot wait for several condition variables, so it polls the wakers and yields to
+    // loom in between, which loom explores like a blocked thread
+    fn block_any<'a>(
+        wakers: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool>
+    where
+        Self: 'a,
+    {
+        loop {
+            let woken = wakers.clone().fold(false, |woken, waker| {
+                mem::take(&mut *waker.pending.lock().unwrap()) | woken
+            });
+            if woken || timeout.is_some() {
+                return Ok(woken);
+            }
+            loom::thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{AutoResetEvent, EventError, WaitResult};
+
+/// Multiplexes many sources onto a single event, and tells the consumer which sources fired.
+///
+/// Each [`MuxSource`] owns a bit of a shared mask. Signalling a source sets its bit and signals
+/// the event of the multiplexer, so the consumer blocks on a single kernel object however many
+/// sources there are, and a wait returns the sources that fired since the previous one. A mux
+/// has up to 64 sources; the id of a dropped source is handed out again.
+///
+/// ```
+/// use nova_autoreset_event::EventMux;
+///
+/// let mux = EventMux::new().unwrap();
+/// let a = mux.add_source().unwrap();
+/// let b = mux.add_source().unwrap();
+///
+/// a.signal();
+/// b.signal();
+/// let fired = mux.wait();
+/// assert!(fired.contains(a.id()) && fired.contains(b.id()));
+/// assert!(mux.try_wait().is_empty());
+/// ```
+pub struct EventMux {
+    shared: Arc<Shared>,
+}
+
+/// The producer side of an [`EventMux`], see [`EventMux::add_source`].
+pub struct MuxSource {
+    id: u32,
+    shared: Arc<Shared>,
+}
+
+/// The sources of an [`EventMux`] that fired, as returned by its waits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MuxSources {
+    bits: u64,
+}
+
+struct Shared {
+    event: AutoResetEvent,
+    // The sources that fired since the last wait, one bit per id
+    pending: AtomicU64,
+    // The ids that are in use
+    allocated: AtomicU64,
+}
+
+impl EventMux {
+    /// Creates a multiplexer without sources.
+    pub fn new() -> Result<Self, EventError> {
+        Ok(Self {
+            shared: Arc::new(Shared {
+                event: AutoResetEvent::new()?,
+                pending: AtomicU64::new(0),
+                allocated: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Adds a source with the lowest free id. Returns `None` if all 64 ids are in use.
+    pub fn add_source(&self) -> Option<MuxSource> {
+        let prev = self
+            .shared
+            .allocated
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |allocated| {
+                (allocated != u64::MAX).then(|| allocated | (allocated + 1))
+            })
+            .ok()?;
+        Some(MuxSource {
+            id: prev.trailing_ones(),
+            shared: self.shared.clone(),
+        })
+    }
+
+    /// Waits until a source fires, and returns all sources that fired since the previous wait.
+    ///
+    /// Returns an empty set once the event of the multiplexer has been
+    /// [closed](AutoResetEvent::close).
+    pub fn wait(&self) -> MuxSources {
+        loop {
+            let sources = self.take();
+            if !sources.is_empty() {
+                return sources;
+            }
+            // A stale signal, whose sources have been taken by the previous wait, ends the wait
+            // as well, so the mask is checked again
+            if self.shared.event.wait_result() == WaitResult::Closed {
+                return self.take();
+            }
+        }
+    }
+
+    /// Returns the sources that fired since the previous wait without blocking.
+    pub fn try_wait(&self) -> MuxSources {
+        self.take()
+    }
+
+    /// Like [`wait`](Self::wait), but returns an empty set if no source fires within `timeout`.
+    pub fn try_wait_for(&self, timeout: Duration) -> MuxSources {
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let sources = self.take();
+            if !sources.is_empty() {
+                return sources;
+            }
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            match self.shared.event.try_wait_for_result(remaining) {
+                // An interrupted wait waits again for the rest of the timeout
+                WaitResult::Signalled | WaitResult::Interrupted => {}
+                WaitResult::TimedOut | WaitResult::Closed => return self.take(),
+            }
+        }
+    }
+
+    /// Returns the event that is signalled when a source fires, e.g. to wait for it together
+    /// with other events via [`AutoResetEvent::wait_any`]. Once it has been signalled, call
+    /// [`try_wait`](Self::try_wait) to learn which sources fired.
+    pub fn event(&self) -> &AutoResetEvent {
+        &self.shared.event
+    }
+
+    fn take(&self) -> MuxSources {
+        MuxSources {
+            bits: self.shared.pending.swap(0, Ordering::AcqRel),
+        }
+    }
+}
+
+impl MuxSource {
+    /// Returns the id of the source, between 0 and 63.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Marks the source as fired and signals the event of the multiplexer.
+    pub fn signal(&self) {
+        let bit = 1 << self.id;
+        // The event only needs to be signalled if the bit was not pending yet
+        if self.shared.pending.fetch_or(bit, Ordering::AcqRel) & bit == 0 {
+            self.shared.event.signal();
+        }
+    }
+}
+
+impl Drop for MuxSource {
+    fn drop(&mut self) {
+        self.shared
+            .allocated
+            .fetch_and(!(1 << self.id), Ordering::AcqRel);
+    }
+}
+
+impl MuxSources {
+    /// Returns `true` if no source fired.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns the number of sources that fired.
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns `true` if the source with the id fired.
+    pub fn contains(&self, id: u32) -> bool {
+        id < 64 && self.bits & (1 << id) != 0
+    }
+
+    /// Returns the sources as a bitmask, with bit `i` set if the source with id `i` fired.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Returns the ids of the sources that fired, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + use<> {
+        let mut bits = self.bits;
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+            let id = bits.trailing_zeros();
+            bits &= bits - 1;
+            Some(id)
+        })
+    }
+}
+
+impl fmt::Debug for EventMux {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventMux")
+            .field(
+                "sources",
+                &self.shared.allocated.load(Ordering::Relaxed).count_ones(),
+            )
+            .field(
+                "pending",
+                &MuxSources {
+                    bits: self.shared.pending.load(Ordering::Relaxed),
+                },
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for MuxSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MuxSource")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
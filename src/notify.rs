@@ -0,0 +1,107 @@
+use std::io;
+use std::os::fd::{AsRawFd, RawFd};
+use std::sync::Arc;
+
+use tokio::io::unix::AsyncFd;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::async_event::wait_readable;
+use crate::{AutoResetEvent, WaitResult};
+
+/// Forwards the signals of an [`AutoResetEvent`] to a Tokio [`Notify`], or the notifications of a
+/// `Notify` to an event.
+///
+/// Code that is structured around `Notify` can thereby interoperate with components that signal
+/// or wait for the event outside the runtime, e.g. C code that writes to the fd of the event, or
+/// threads that call [`AutoResetEvent::signal`]. The forwarding runs in a task on the current
+/// runtime, which is aborted when the bridge is dropped.
+///
+/// Both sides hold at most one pending signal or permit, so signals that arrive before the other
+/// side has consumed the forwarded one are coalesced, as for the event itself.
+///
+/// ```
+/// use std::sync::Arc;
+/// use nova_autoreset_event::{AutoResetEvent, NotifyBridge};
+/// use tokio::sync::Notify;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// let notify = Arc::new(Notify::new());
+/// let _bridge = NotifyBridge::event_to_notify(event.clone(), notify.clone()).unwrap();
+///
+/// std::thread::spawn(move || event.signal());
+/// notify.notified().await;
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "tokio"))))]
+#[derive(Debug)]
+pub struct NotifyBridge {
+    task: JoinHandle<()>,
+}
+
+// Declared in this order, so that the fd is deregistered before the event may close it
+struct Forward {
+    fd: AsyncFd<RawFd>,
+    event: Arc<AutoResetEvent>,
+}
+
+impl NotifyBridge {
+    /// Spawns a task that consumes every signal of `event` and calls
+    /// [`notify_one`](Notify::notify_one) for it.
+    ///
+    /// The event should not be waited on otherwise, as the signals that other waiters consume are
+    /// not forwarded. The task ends once the event has been [closed](AutoResetEvent::close), after
+    /// calling [`notify_waiters`](Notify::notify_waiters), so that the tasks that wait for the
+    /// `Notify` can check [`AutoResetEvent::is_closed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, like [`tokio::spawn`].
+    pub fn event_to_notify(event: Arc<AutoResetEvent>, notify: Arc<Notify>) -> io::Result<Self> {
+        let forward = Forward {
+            fd: AsyncFd::new(event.as_raw_fd())?,
+            event,
+        };
+        let task = tokio::spawn(async move {
+            while wait_readable(&forward.fd, &forward.event).await == WaitResult::Signalled {
+                notify.notify_one();
+            }
+            notify.notify_waiters();
+        });
+        Ok(Self { task })
+    }
+
+    /// Spawns a task that waits for `notify` and signals `event` for every notification.
+    ///
+    /// The task ends once the event has been [closed](AutoResetEvent::close), at the next
+    /// notification.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, like [`tokio::spawn`].
+    pub fn notify_to_event(notify: Arc<Notify>, event: Arc<AutoResetEvent>) -> Self {
+        let task = tokio::spawn(async move {
+            loop {
+                notify.notified().await;
+                if event.is_closed() {
+                    break;
+                }
+                event.signal();
+            }
+        });
+        Self { task }
+    }
+
+    /// Returns `true` once the forwarding has ended because the event has been closed.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for NotifyBridge {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
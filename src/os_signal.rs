@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use libc::c_int;
+use signal_hook::SigId;
+use signal_hook::consts::FORBIDDEN;
+
+use crate::AutoResetEvent;
+
+/// An [`AutoResetEvent`] that is signalled when the process receives one of a set of Unix
+/// signals.
+///
+/// The handlers are registered via `signal-hook`, so they coexist with other handlers of the same
+/// signals, and only call [`AutoResetEvent::signal_from_handler`], which is async-signal-safe.
+/// Threads wait for the signals with the same functions as for any other event, e.g. together
+/// with other events via [`AutoResetEvent::wait_any`], and learn which signals have arrived via
+/// [`take_received`](Self::take_received). The handlers are unregistered when the
+/// `OsSignalEvent` is dropped.
+///
+/// ```no_run
+/// use nova_autoreset_event::OsSignalEvent;
+///
+/// let shutdown = OsSignalEvent::new(&[libc::SIGTERM, libc::SIGINT]).unwrap();
+/// shutdown.wait();
+/// println!("received {:?}", shutdown.take_received());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "signal-hook"))))]
+pub struct OsSignalEvent {
+    shared: Arc<Shared>,
+    ids: Vec<SigId>,
+}
+
+struct Shared {
+    event: AutoResetEvent,
+    // A bit per signal number below 128
+    received: [AtomicU64; 2],
+}
+
+impl OsSignalEvent {
+    /// Creates an event and registers handlers that signal it for `signals`.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if one of the signals cannot be handled, e.g.
+    /// `SIGKILL` or `SIGSEGV`. Also fails if the event cannot be created or a handler cannot be
+    /// registered.
+    pub fn new(signals: &[c_int]) -> io::Result<Self> {
+        if let Some(signal) = signals.iter().find(|signal| FORBIDDEN.contains(signal)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("signal {} cannot be handled", signal),
+            ));
+        }
+
+        let shared = Arc::new(Shared {
+            event: AutoResetEvent::new()?,
+            received: [AtomicU64::new(0), AtomicU64::new(0)],
+        });
+        let mut event = Self {
+            shared,
+            ids: Vec::with_capacity(signals.len()),
+        };
+        for &signal in signals {
+            let shared = event.shared.clone();
+            let action = move || shared.on_signal(signal);
+            // The action is async-signal-safe, see `on_signal`. On failure, `event` unregisters the
+            // handlers that have already been registered.
+            let id = unsafe { signal_hook::low_level::register(signal, action)? };
+            event.ids.push(id);
+        }
+        Ok(event)
+    }
+
+    /// Returns the signals that have been received since the last call, in ascending order.
+    ///
+    /// Signals are coalesced like the signals of the event: a signal that arrives several times
+    /// between two calls is returned once.
+    pub fn take_received(&self) -> Vec<c_int> {
+        let mut received = Vec::new();
+        for (word, bits) in self.shared.received.iter().enumerate() {
+            let mut bits = bits.swap(0, Ordering::AcqRel);
+            while bits != 0 {
+                received.push((word * 64) as c_int + bits.trailing_zeros() as c_int);
+                bits &= bits - 1;
+            }
+        }
+        received
+    }
+}
+
+impl Shared {
+    // Runs in the signal handler, so it may only use atomics and `signal_from_handler`
+    fn on_signal(&self, signal: c_int) {
+        if let Some(bits) = self.received.get(signal as usize / 64) {
+            bits.fetch_or(1 << (signal % 64), Ordering::AcqRel);
+        }
+        self.event.signal_from_handler();
+    }
+}
+
+impl Deref for OsSignalEvent {
+    type Target = AutoResetEvent;
+
+    fn deref(&self) -> &AutoResetEvent {
+        &self.shared.event
+    }
+}
+
+impl Drop for OsSignalEvent {
+    fn drop(&mut self) {
+        for &id in &self.ids {
+            signal_hook::low_level::unregister(id);
+        }
+    }
+}
+
+impl fmt::Debug for OsSignalEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OsSignalEvent")
+            .field("event", &self.shared.event)
+            .finish_non_exhaustive()
+    }
+}
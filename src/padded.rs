@@ -0,0 +1,65 @@
+use std::ops::Deref;
+
+use crate::{AutoResetEvent, EventError};
+
+/// An [`AutoResetEvent`] that occupies its own cache line.
+///
+/// Events that are stored next to each other, e.g. in a `Vec`, share cache lines. If they are
+/// used by different threads, every signal or wait on one event invalidates the cache line of
+/// its neighbours on the other cores. Padding each event to the cache line size avoids this
+/// false sharing, at the cost of memory.
+///
+/// The alignment is 128 bytes on x86-64, aarch64 and powerpc64, where adjacent cache lines are
+/// fetched in pairs, and 64 bytes elsewhere.
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    )),
+    repr(align(64))
+)]
+#[derive(Debug)]
+pub struct PaddedAutoResetEvent {
+    event: AutoResetEvent,
+}
+
+impl PaddedAutoResetEvent {
+    /// Creates a new autoreset event.
+    pub fn new() -> Result<Self, EventError> {
+        AutoResetEvent::new().map(Self::from)
+    }
+
+    /// Returns the wrapped event.
+    pub fn into_inner(self) -> AutoResetEvent {
+        self.event
+    }
+}
+
+impl From<AutoResetEvent> for PaddedAutoResetEvent {
+    fn from(event: AutoResetEvent) -> Self {
+        Self { event }
+    }
+}
+
+impl Deref for PaddedAutoResetEvent {
+    type Target = AutoResetEvent;
+
+    fn deref(&self) -> &AutoResetEvent {
+        &self.event
+    }
+}
+
+impl AsRef<AutoResetEvent> for PaddedAutoResetEvent {
+    fn as_ref(&self) -> &AutoResetEvent {
+        &self.event
+    }
+}
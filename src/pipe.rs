@@ -1,132 +1,289 @@
 use std::io;
 use std::os::fd::{AsFd, BorrowedFd, FromRawFd, OwnedFd};
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::io::AsRawFd;
 use std::time::Duration;
 
 use libc::{c_void, pipe, read, write};
+use smallvec::SmallVec;
 
-/// An autoreset event.
-///
-/// See the [module-level documentation](..) for more information.
-#[derive(Debug)]
-pub struct AutoResetEvent {
-    fds: [OwnedFd; 2],
+use crate::state::{KernelObject, KernelWaker, SignalSafeWaker, TryAsFd, unexpected};
+use crate::{Backend, syscall};
+
+fn set_nonblocking_cloexec(fd: &OwnedFd) -> io::Result<()> {
+    let fd = fd.as_raw_fd();
+
+    let flags = syscall::call("fcntl", format_args!("{}, F_GETFL", fd), || unsafe {
+        libc::fcntl(fd, libc::F_GETFL)
+    })?;
+    let flags = flags | libc::O_NONBLOCK;
+    syscall::call(
+        "fcntl",
+        format_args!("{}, F_SETFL, {:#x}", fd, flags),
+        || unsafe { libc::fcntl(fd, libc::F_SETFL, flags) },
+    )?;
+
+    let flags = syscall::call("fcntl", format_args!("{}, F_GETFD", fd), || unsafe {
+        libc::fcntl(fd, libc::F_GETFD)
+    })?;
+    let flags = flags | libc::FD_CLOEXEC;
+    syscall::call(
+        "fcntl",
+        format_args!("{}, F_SETFD, {:#x}", fd, flags),
+        || unsafe { libc::fcntl(fd, libc::F_SETFD, flags) },
+    )?;
+    Ok(())
 }
 
-impl AutoResetEvent {
-    /// Creates a new autoreset event.
-    pub fn new() -> io::Result<Self> {
-        let mut fds_raw = [0; 2];
-        let res = unsafe { pipe(fds_raw.as_mut_ptr()) };
-
-        if res == -1 {
-            Err(io::Error::last_os_error())
-        } else {
-            let fds = unsafe {
-                [
-                    OwnedFd::from_raw_fd(fds_raw[0]),
-                    OwnedFd::from_raw_fd(fds_raw[1]),
-                ]
-            };
-            Ok(Self { fds })
+/// Waits until the fd is readable or the timeout elapses. Returns `false` on timeout.
+pub(crate) fn poll_readable(fd: BorrowedFd<'_>, timeout: Option<Duration>) -> io::Result<bool> {
+    let fd = fd.as_raw_fd();
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout = poll_timeout(timeout);
+
+    let ret = syscall::call(
+        "poll",
+        format_args!("[{}], timeout {} ms", fd, timeout),
+        || unsafe { libc::poll(&mut pollfd, 1, timeout) },
+    )?;
+    Ok(ret > 0)
+}
+
+// The number of fds that `poll_any` can wait for without allocating
+const INLINE_POLL_FDS: usize = 32;
+
+/// Waits until one of the fds is readable or the timeout elapses, then drains the kernel objects
+/// that are readable. Returns `false` on timeout.
+pub(crate) fn poll_any<'a, K: KernelWaker + 'a>(
+    kernels: impl Iterator<Item = &'a K> + Clone,
+    fd: impl Fn(&K) -> BorrowedFd<'_>,
+    timeout: Option<Duration>,
+) -> io::Result<bool> {
+    let mut pollfds: SmallVec<[libc::pollfd; INLINE_POLL_FDS]> = kernels
+        .clone()
+        .map(|kernel| {
+            libc::pollfd {
+                fd: fd(kernel).as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            }
+        })
+        .collect();
+
+    let count = pollfds.len();
+    let timeout = poll_timeout(timeout);
+
+    let ret = syscall::call(
+        "poll",
+        format_args!("{} fds, timeout {} ms", count, timeout),
+        || unsafe { libc::poll(pollfds.as_mut_ptr(), count as libc::nfds_t, timeout) },
+    )?;
+
+    for (kernel, pollfd) in kernels.zip(&pollfds) {
+        if pollfd.revents != 0 {
+            kernel.drain();
         }
     }
+    Ok(ret > 0)
+}
+
+/// Blocks until one of the fds is readable or until the timeout elapses, and returns the indices of
+/// the readable fds. Their wakeups are not consumed.
+#[cfg(feature = "reactor")]
+pub(crate) fn wait_ready(
+    fds: &[BorrowedFd<'_>],
+    timeout: Option<Duration>,
+) -> io::Result<Vec<usize>> {
+    let mut pollfds: Vec<libc::pollfd> = fds
+        .iter()
+        .map(|fd| {
+            libc::pollfd {
+                fd: fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            }
+        })
+        .collect();
+
+    let count = pollfds.len();
+    let timeout = poll_timeout(timeout);
+    syscall::call(
+        "poll",
+        format_args!("{} fds, timeout {} ms", count, timeout),
+        || unsafe { libc::poll(pollfds.as_mut_ptr(), count as libc::nfds_t, timeout) },
+    )?;
 
-    /// Waits for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return immediately and
-    /// reset the event to the unsignalled state. Otherwise, it will block until another thread
-    /// signals the event.
-    pub fn wait(&self) {
-        let mut buf = [0u8; 1];
-        let res = unsafe { read(self.fds[0].as_raw_fd(), buf.as_mut_ptr() as *mut c_void, 1) };
-
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("read failed with error {}", err);
+    Ok(pollfds
+        .iter()
+        .enumerate()
+        .filter(|(_, pollfd)| pollfd.revents != 0)
+        .map(|(index, _)| index)
+        .collect())
+}
+
+// Converts a timeout to the argument of `poll`. It is rounded up to whole milliseconds, so that a
+// poll never times out early and a sub-millisecond timeout still blocks.
+fn poll_timeout(timeout: Option<Duration>) -> libc::c_int {
+    match timeout {
+        Some(timeout) => {
+            timeout
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .min(libc::c_int::MAX as u128) as libc::c_int
         }
+        None => -1,
     }
+}
 
-    /// Tries to wait for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. Otherwise, it will return `false` immediately.
-    pub fn try_wait(&self) -> bool {
-        self.try_wait_for(Duration::from_millis(0))
-    }
+// The kernel side of the event: a nonblocking pipe. Every wakeup writes a byte, and consuming a
+// wakeup drains all of them, so bytes do not accumulate across waits and coalesced signals do not
+// turn the event into a semaphore.
+#[derive(Debug)]
+pub(crate) struct Pipe {
+    fds: [OwnedFd; 2],
+}
+
+impl KernelObject for Pipe {
+    type Config = ();
+
+    fn create((): ()) -> io::Result<Self> {
+        let mut fds_raw = [0; 2];
+        syscall::call("pipe", format_args!(""), || unsafe {
+            pipe(fds_raw.as_mut_ptr())
+        })?;
 
-    /// Tries to wait for the event to be signalled for a specified duration.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. If the event is signalled within the timeout,
-    /// it will return `true`. Otherwise, it will return `false`.
-    pub fn try_wait_for(&self, timeout: Duration) -> bool {
-        let mut pollfd = libc::pollfd {
-            fd: self.fds[0].as_raw_fd(),
-            events: libc::POLLIN,
-            revents: 0,
+        let fds = unsafe {
+            [
+                OwnedFd::from_raw_fd(fds_raw[0]),
+                OwnedFd::from_raw_fd(fds_raw[1]),
+            ]
         };
 
-        let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
-        let ret = unsafe { libc::poll(&mut pollfd, 1, millis) };
+        for fd in &fds {
+            set_nonblocking_cloexec(fd)?;
+        }
+
+        Ok(Self { fds })
+    }
+
+    fn backend((): ()) -> Backend {
+        Backend::Pipe
+    }
+}
 
-        if ret == -1 {
-            let err = io::Error::last_os_error();
-            panic!("poll failed with error {}", err);
+impl Pipe {
+    // Takes ownership of the read and write end of a pipe created by `create`
+    #[cfg(feature = "ffi")]
+    pub(crate) unsafe fn from_raw_fds(fds: [std::os::fd::RawFd; 2]) -> Self {
+        Self {
+            fds: fds.map(|fd| unsafe { OwnedFd::from_raw_fd(fd) }),
         }
+    }
+
+    #[cfg(feature = "ffi")]
+    pub(crate) fn into_raw_fds(self) -> [std::os::fd::RawFd; 2] {
+        self.fds.map(std::os::fd::IntoRawFd::into_raw_fd)
+    }
+
+    // Consumes all pending wakeups. Returns `false` if there were none.
+    fn read(&self) -> bool {
+        let mut buf = [0u8; 64];
+        let fd = self.fds[0].as_raw_fd();
+        let mut consumed = false;
+        loop {
+            let res = syscall::call(
+                "read",
+                format_args!("{}, {} bytes", fd, buf.len()),
+                || unsafe { read(fd, buf.as_mut_ptr() as *mut c_void, buf.len()) },
+            );
 
-        if ret > 0 && (pollfd.revents & libc::POLLIN) != 0 {
-            // Read the value to reset the event
-            let mut buf = [0u8; 1];
-            let res = unsafe { read(self.fds[0].as_raw_fd(), buf.as_mut_ptr() as *mut c_void, 1) };
-            if res == -1 {
-                let err = io::Error::last_os_error();
-                if err.kind() == io::ErrorKind::WouldBlock {
-                    return false;
+            match res {
+                Ok(len) => {
+                    consumed = true;
+                    if (len as usize) < buf.len() {
+                        return consumed;
+                    }
+                }
+                // Another thread might have consumed the wakeup first
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return consumed,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    unexpected("read", err);
+                    return consumed;
                 }
-                panic!("read failed with error {}", err);
             }
-            true
-        } else {
-            false
         }
     }
+}
 
-    /// Signals the event.
-    ///
-    /// If there is a thread waiting on the event, it will be woken up and the event will be reset
-    /// to the unsignalled state. If there are no threads waiting, the event will remain in the
-    /// signalled state until a thread waits on it.
-    pub fn signal(&self) {
+impl KernelWaker for Pipe {
+    fn wake(&self) {
         let buf = [0u8; 1];
-        let res = unsafe { write(self.fds[1].as_raw_fd(), buf.as_ptr() as *const c_void, 1) };
+        let fd = self.fds[1].as_raw_fd();
+        loop {
+            let res = syscall::call("write", format_args!("{}, 1 byte", fd), || unsafe {
+                write(fd, buf.as_ptr() as *const c_void, 1)
+            });
 
-        if res == -1 {
-            // This should not happen
-            let err = io::Error::last_os_error();
-            panic!("write failed with error {}", err);
+            match res {
+                // A short write did not write the byte
+                Ok(0) => continue,
+                Ok(_) => return,
+                // A full pipe is readable anyway
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return unexpected("write", err),
+            }
         }
     }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        // The wakeup is often posted while the waiter is about to block, so try to consume it
+        // without polling first
+        if self.read() {
+            return Ok(true);
+        }
+
+        if !poll_readable(self.fds[0].as_fd(), timeout)? {
+            return Ok(false);
+        }
+
+        // If another thread stole the wakeup in the meantime, this is a spurious wakeup: the caller
+        // re-checks the state and blocks again for the remaining time
+        self.read();
+        Ok(true)
+    }
+
+    fn block_any<'a>(
+        pipes: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        poll_any(pipes, Pipe::as_fd, timeout)
+    }
+
+    fn drain(&self) {
+        self.read();
+    }
 }
 
-impl AsRawFd for AutoResetEvent {
-    fn as_raw_fd(&self) -> RawFd {
-        self.fds[0].as_raw_fd()
+impl SignalSafeWaker for Pipe {
+    fn wake_from_handler(&self) {
+        let buf = [0u8; 1];
+        let fd = self.fds[1].as_raw_fd();
+        // A full pipe is readable anyway
+        while unsafe { write(fd, buf.as_ptr() as *const c_void, 1) } == -1
+            && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted
+        {}
     }
 }
 
-impl AsFd for AutoResetEvent {
+impl AsFd for Pipe {
     fn as_fd(&self) -> BorrowedFd<'_> {
         self.fds[0].as_fd()
     }
 }
 
-
-// It is safe to send an autoreset event to another thread. The underlying file descriptors are
-// kernel objects that can be used from any thread.
-unsafe impl Send for AutoResetEvent {}
-
-// It is safe to share an autoreset event between threads. The underlying file descriptors are
-// kernel objects that are thread-safe.
-unsafe impl Sync for AutoResetEvent {}
+impl TryAsFd for Pipe {}
@@ -0,0 +1,110 @@
+// The background threads that wait for the events of `on_signal`, `tick_receiver` and the
+// reactor. Each pool spawns its threads on demand, and hands every new entry to a thread that
+// waits for fewer than `ENTRIES_PER_THREAD` of them.
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use crate::AutoResetEvent;
+
+// The entries of a thread, plus its control event. `WaitForMultipleObjects` waits for at most 64
+// handles; the other backends are limited to bound the cost of a wakeup.
+const ENTRIES_PER_THREAD: usize = 63;
+
+pub(crate) struct Pool<T> {
+    name: &'static str,
+    run: fn(Arc<Worker<T>>),
+    workers: Mutex<Vec<Arc<Worker<T>>>>,
+}
+
+pub(crate) struct Worker<T> {
+    // Signalled when an entry has been added, and by the owners of the entries to make the thread
+    // check them again
+    pub(crate) control: AutoResetEvent,
+    added: Mutex<Vec<T>>,
+    // The number of entries, including the added ones. Only increased under the lock of the pool.
+    len: AtomicUsize,
+}
+
+impl<T: Send + 'static> Pool<T> {
+    // Creates a pool whose threads are called `name` and run `run`
+    pub(crate) const fn new(name: &'static str, run: fn(Arc<Worker<T>>)) -> Self {
+        Self {
+            name,
+            run,
+            workers: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Hands `entry` to a thread with room for it, which is spawned if there is none, and returns
+    // the thread
+    pub(crate) fn add(&self, entry: T) -> io::Result<Arc<Worker<T>>> {
+        let mut workers = self.lock();
+        let worker = match workers
+            .iter()
+            .find(|worker| worker.len.load(Ordering::Relaxed) < ENTRIES_PER_THREAD)
+        {
+            Some(worker) => worker.clone(),
+            None => {
+                let worker = Arc::new(Worker {
+                    control: AutoResetEvent::new()?,
+                    added: Mutex::new(Vec::new()),
+                    len: AtomicUsize::new(0),
+                });
+                let (thread, run) = (worker.clone(), self.run);
+                thread::Builder::new()
+                    .name(self.name.into())
+                    .spawn(move || run(thread))?;
+                workers.push(worker.clone());
+                worker
+            }
+        };
+
+        worker.len.fetch_add(1, Ordering::Relaxed);
+        lock(&worker.added).push(entry);
+        worker.control.signal();
+        Ok(worker)
+    }
+
+    // Signals the control events of all threads
+    #[cfg(feature = "reactor")]
+    pub(crate) fn wake_all(&self) {
+        for worker in self.lock().iter() {
+            worker.control.signal();
+        }
+    }
+
+    // Removes a thread without entries from the pool, after which it has to exit. Returns `false`
+    // if an entry has been added to it in the meantime.
+    #[cfg(feature = "reactor")]
+    pub(crate) fn remove(&self, worker: &Arc<Worker<T>>) -> bool {
+        let mut workers = self.lock();
+        if worker.len.load(Ordering::Relaxed) > 0 {
+            return false;
+        }
+        workers.retain(|other| !Arc::ptr_eq(other, worker));
+        true
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Arc<Worker<T>>>> {
+        lock(&self.workers)
+    }
+}
+
+impl<T> Worker<T> {
+    // Moves the entries that have been added since the last call to `entries`
+    pub(crate) fn take_added(&self, entries: &mut Vec<T>) {
+        entries.append(&mut lock(&self.added));
+    }
+
+    // Records that the thread has dropped `n` of its entries
+    pub(crate) fn removed(&self, n: usize) {
+        self.len.fetch_sub(n, Ordering::Relaxed);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
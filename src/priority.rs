@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use crate::{AutoResetEvent, EventError};
+
+/// The lane of a [`PriorityPair`] that a wait consumed a signal of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// The high-priority event.
+    High,
+    /// The low-priority event.
+    Low,
+}
+
+/// A high- and a low-priority event, where a wait always consumes the high-priority signal first.
+///
+/// [`AutoResetEvent::wait_any`] already prefers the events earlier in the list, but it checks
+/// them one after the other: a high-priority signal that arrives after the high-priority event
+/// has been checked is overtaken by a pending low-priority one. The waits of the pair check the
+/// high-priority event again after consuming a low-priority signal, and hand the low-priority
+/// signal back if a high-priority one has arrived in the meantime. So whenever both are pending
+/// when a wait returns, it returns [`Priority::High`].
+///
+/// The priority is strict: as long as high-priority signals keep arriving, low-priority ones wait.
+///
+/// ```
+/// use nova_autoreset_event::{Priority, PriorityPair};
+///
+/// let pair = PriorityPair::new().unwrap();
+/// pair.low().signal();
+/// pair.high().signal();
+/// assert_eq!(pair.wait(), Some(Priority::High));
+/// assert_eq!(pair.wait(), Some(Priority::Low));
+///
+/// pair.high().close();
+/// assert_eq!(pair.wait(), None);
+/// ```
+#[derive(Debug)]
+pub struct PriorityPair {
+    high: AutoResetEvent,
+    low: AutoResetEvent,
+}
+
+impl PriorityPair {
+    /// Creates a pair of events.
+    pub fn new() -> Result<Self, EventError> {
+        Ok(Self {
+            high: AutoResetEvent::new()?,
+            low: AutoResetEvent::new()?,
+        })
+    }
+
+    /// Returns the high-priority event, which producers signal.
+    pub fn high(&self) -> &AutoResetEvent {
+        &self.high
+    }
+
+    /// Returns the low-priority event, which producers signal.
+    pub fn low(&self) -> &AutoResetEvent {
+        &self.low
+    }
+
+    /// Returns `true` if one of the events has been [closed](AutoResetEvent::close).
+    pub fn is_closed(&self) -> bool {
+        self.high.is_closed() || self.low.is_closed()
+    }
+
+    /// Waits until one of the events is signalled, and consumes the high-priority signal if both
+    /// are.
+    ///
+    /// Returns `None` without consuming a signal if the wait has been ended by a
+    /// [closed](AutoResetEvent::close) event, like [`AutoResetEvent::wait_any`]. As a closed event
+    /// ends every later wait right away, consumers stop waiting once they get `None`.
+    pub fn wait(&self) -> Option<Priority> {
+        let index = AutoResetEvent::wait_any(&[&self.high, &self.low]);
+        self.lane(index)
+    }
+
+    /// Consumes a signal without blocking, preferring the high-priority one. Returns `None` if
+    /// neither event is signalled.
+    pub fn try_wait(&self) -> Option<Priority> {
+        if self.high.try_wait() {
+            Some(Priority::High)
+        } else if self.low.try_wait() {
+            Some(self.prefer_high(1))
+        } else {
+            None
+        }
+    }
+
+    /// Like [`wait`](Self::wait), but also returns `None` if neither event is signalled within
+    /// `timeout`. [`is_closed`](Self::is_closed) tells the two apart.
+    pub fn try_wait_for(&self, timeout: Duration) -> Option<Priority> {
+        AutoResetEvent::try_wait_any_for(&[&self.high, &self.low], timeout)
+            .and_then(|index| self.lane(index))
+    }
+
+    // Turns the index of the event that ended a wait into its lane, or `None` if the event has
+    // been closed and therefore not signalled
+    fn lane(&self, index: usize) -> Option<Priority> {
+        let event = if index == 0 { &self.high } else { &self.low };
+        (!event.is_closed()).then(|| self.prefer_high(index))
+    }
+
+    // A consumed low-priority signal is handed back if a high-priority one has arrived in the
+    // meantime
+    fn prefer_high(&self, index: usize) -> Priority {
+        if index == 0 {
+            return Priority::High;
+        }
+        if !self.low.is_closed() && self.high.try_wait() {
+            self.low.signal();
+            return Priority::High;
+        }
+        Priority::Low
+    }
+}
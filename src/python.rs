@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{AutoResetEvent, WaitResult};
+
+// Blocking waits wake up at this interval to let Python handle signals, e.g. a `KeyboardInterrupt`
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An [`AutoResetEvent`] exposed to Python as `nova_autoreset_event.AutoResetEvent`.
+///
+/// The waits release the GIL, so other Python threads keep running while a thread is blocked,
+/// and raise `KeyboardInterrupt` and other exceptions of signal handlers. Timeouts are given in
+/// seconds, like those of `threading.Event.wait`.
+///
+/// An event that is created in Rust can be handed to Python via `From<Arc<AutoResetEvent>>`, so
+/// Rust worker threads and Python code can synchronize on the same event:
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use nova_autoreset_event::{AutoResetEvent, PyAutoResetEvent};
+/// use pyo3::prelude::*;
+///
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// Python::attach(|py| {
+///     let event = Py::new(py, PyAutoResetEvent::from(event.clone())).unwrap();
+///     // pass `event` to Python
+/// });
+/// ```
+///
+/// Building the crate as a `cdylib` with the `python` feature yields an extension module named
+/// `nova_autoreset_event`.
+///
+/// Events are shared between Rust and Python within one process only. Named events that other
+/// processes, e.g. of `multiprocessing`, can open are not supported.
+#[pyclass(name = "AutoResetEvent", module = "nova_autoreset_event", frozen)]
+#[cfg_attr(docsrs, doc(cfg(feature = "python")))]
+pub struct PyAutoResetEvent {
+    event: Arc<AutoResetEvent>,
+}
+
+impl PyAutoResetEvent {
+    /// Returns the wrapped event.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+}
+
+impl From<Arc<AutoResetEvent>> for PyAutoResetEvent {
+    fn from(event: Arc<AutoResetEvent>) -> Self {
+        Self { event }
+    }
+}
+
+#[pymethods]
+impl PyAutoResetEvent {
+    #[new]
+    fn new() -> PyResult<Self> {
+        let event = AutoResetEvent::new().map_err(std::io::Error::from)?;
+        Ok(Arc::new(event).into())
+    }
+
+    /// Signals the event, see `AutoResetEvent::signal`.
+    fn signal(&self) {
+        self.event.signal()
+    }
+
+    /// Waits for the event to be signalled, see `AutoResetEvent::wait`.
+    fn wait(&self, py: Python<'_>) -> PyResult<()> {
+        while py.detach(|| self.event.try_wait_for_result(SIGNAL_CHECK_INTERVAL))
+            == WaitResult::TimedOut
+        {
+            py.check_signals()?;
+        }
+        Ok(())
+    }
+
+    /// Consumes the signal if the event is signalled, see `AutoResetEvent::try_wait`.
+    fn try_wait(&self) -> bool {
+        self.event.try_wait()
+    }
+
+    /// Waits for the event to be signalled for at most `timeout` seconds, see
+    /// `AutoResetEvent::try_wait_for`.
+    fn try_wait_for(&self, py: Python<'_>, timeout: f64) -> PyResult<bool> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .map_err(|_| PyValueError::new_err("timeout must be a non-negative number"))?;
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            // The time spent outside of the waits, e.g. in signal handlers, counts towards the
+            // timeout as well
+            let remaining = deadline.map_or(SIGNAL_CHECK_INTERVAL, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            let chunk = remaining.min(SIGNAL_CHECK_INTERVAL);
+            match py.detach(|| self.event.try_wait_for_result(chunk)) {
+                WaitResult::TimedOut => {}
+                result => return Ok(result == WaitResult::Signalled),
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(false);
+            }
+            py.check_signals()?;
+        }
+    }
+
+    /// Closes the event, see `AutoResetEvent::close`.
+    fn close(&self) -> bool {
+        self.event.close()
+    }
+
+    /// Returns whether the event has been closed.
+    fn is_closed(&self) -> bool {
+        self.event.is_closed()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.event)
+    }
+}
+
+/// The `nova_autoreset_event` extension module.
+#[pymodule]
+#[pyo3(name = "nova_autoreset_event")]
+fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyAutoResetEvent>()
+}
@@ -0,0 +1,126 @@
+// A background thread that watches the kernel objects of the events that tasks poll, with the
+// `reactor` feature.
+//
+// `poll_wait` registers the wakers of a task with the event, which `signal` wakes in userspace.
+// Wakeups that bypass it, like `signal_from_handler` or external sources on macOS, only reach the
+// kernel object, so the reactor polls the kernel objects of pending events and wakes their tasks
+// once they become ready. An event is watched until its kernel object has been ready once; the
+// tasks that poll it again register it again.
+
+use std::io;
+#[cfg(unix)]
+use std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsHandle, BorrowedHandle, OwnedHandle};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::AutoResetEvent;
+use crate::pool::{Pool, Worker};
+use crate::state::TaskWakers;
+
+#[cfg(unix)]
+use crate::pipe::wait_ready;
+#[cfg(windows)]
+use crate::windows::wait_ready;
+
+// A reactor thread exits once it has not watched an event for this long
+const LINGER: Duration = Duration::from_secs(1);
+
+// The running reactor threads, which exit when they are idle. Their control events are signalled
+// when an event has been added or dropped.
+static REACTORS: Pool<Watch> = Pool::new("nova-autoreset-event-reactor", react);
+
+#[cfg(unix)]
+type OwnedKernel = OwnedFd;
+#[cfg(windows)]
+type OwnedKernel = OwnedHandle;
+
+struct Watch {
+    // A duplicate of the fd or handle of the event, which stays valid if the event is dropped
+    kernel: OwnedKernel,
+    tasks: Arc<TaskWakers>,
+}
+
+/// Watches the kernel object of `event` until it becomes ready, and then fires `tasks`.
+pub(crate) fn watch(event: &AutoResetEvent, tasks: &Arc<TaskWakers>) {
+    if let Err(err) = try_watch(event, tasks) {
+        // The tasks are still woken by `signal`
+        crate::state::unexpected("watch", err);
+        tasks.fire();
+    }
+}
+
+/// Wakes all reactor threads, so that they stop watching the events that have been dropped.
+pub(crate) fn release() {
+    REACTORS.wake_all();
+}
+
+fn try_watch(event: &AutoResetEvent, tasks: &Arc<TaskWakers>) -> io::Result<()> {
+    let watch = Watch {
+        kernel: borrow(event).try_clone_to_owned()?,
+        tasks: tasks.clone(),
+    };
+
+    REACTORS.add(watch)?;
+    Ok(())
+}
+
+// The loop of a reactor thread
+fn react(reactor: Arc<Worker<Watch>>) {
+    let mut watches: Vec<Watch> = Vec::new();
+    loop {
+        reactor.take_added(&mut watches);
+
+        // The reactor holds the last reference to the tasks of a dropped event
+        let len = watches.len();
+        watches.retain(|watch| Arc::strong_count(&watch.tasks) > 1);
+        reactor.removed(len - watches.len());
+
+        if watches.is_empty() {
+            // Waits for an event to be added, and exits if none has been added for a while
+            if !reactor.control.try_wait_for(LINGER) && REACTORS.remove(&reactor) {
+                return;
+            }
+            continue;
+        }
+
+        let kernels: Vec<_> = std::iter::once(borrow(&reactor.control))
+            .chain(watches.iter().map(|watch| borrow(&watch.kernel)))
+            .collect();
+        let ready = match wait_ready(&kernels, None) {
+            Ok(ready) => ready,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                crate::state::unexpected("wait", err);
+                // Let the tasks fall back to the wakeups of `signal`
+                watches.iter().for_each(|watch| watch.tasks.fire());
+                reactor.removed(watches.len());
+                watches.clear();
+                continue;
+            }
+        };
+        drop(kernels);
+
+        // In descending order, so that the indices stay valid
+        for &index in ready.iter().rev() {
+            if index == 0 {
+                reactor.control.try_wait();
+            } else {
+                let watch = watches.swap_remove(index - 1);
+                watch.tasks.fire();
+                reactor.removed(1);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn borrow(kernel: &impl AsFd) -> BorrowedFd<'_> {
+    kernel.as_fd()
+}
+
+#[cfg(windows)]
+fn borrow(kernel: &impl AsHandle) -> BorrowedHandle<'_> {
+    kernel.as_handle()
+}
@@ -0,0 +1,116 @@
+use std::iter;
+use std::sync::Arc;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::{AutoResetEvent, WaitResult};
+
+/// Receives the signals of an [`AutoResetEvent`] with the interface of a
+/// [`std::sync::mpsc::Receiver<()>`].
+///
+/// Every received message consumes a signal. Signals that arrive while one is pending are
+/// coalesced, so a burst of signals may be received as a single message. Closing the event takes
+/// the role of dropping the last sender: once the event has been [closed](AutoResetEvent::close),
+/// the receive functions fail with the `Disconnected` errors. A signal that is pending when the
+/// event is closed is dropped, unlike the messages of a channel.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::mpsc::TryRecvError;
+/// use nova_autoreset_event::{AutoResetEvent, SignalReceiver};
+///
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// let receiver = SignalReceiver::new(event.clone());
+///
+/// event.signal();
+/// event.signal();
+/// assert_eq!(receiver.try_recv(), Ok(()));
+/// assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+///
+/// event.close();
+/// assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SignalReceiver {
+    event: Arc<AutoResetEvent>,
+}
+
+impl SignalReceiver {
+    /// Creates a receiver for the signals of an event.
+    pub fn new(event: Arc<AutoResetEvent>) -> Self {
+        Self { event }
+    }
+
+    /// Returns the event.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+
+    /// Waits for a signal, like [`Receiver::recv`](std::sync::mpsc::Receiver::recv).
+    ///
+    /// Fails if the event has been closed.
+    pub fn recv(&self) -> Result<(), RecvError> {
+        loop {
+            match self.event.wait_result() {
+                WaitResult::Closed => return Err(RecvError),
+                // An event that reports interruptions, see `InterruptPolicy`, waits again
+                WaitResult::Interrupted => {}
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Waits for a signal for at most `timeout`, like
+    /// [`Receiver::recv_timeout`](std::sync::mpsc::Receiver::recv_timeout).
+    ///
+    /// Fails with [`RecvTimeoutError::Timeout`] if the event is not signalled within the timeout,
+    /// and with [`RecvTimeoutError::Disconnected`] if it has been closed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<(), RecvTimeoutError> {
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => Duration::MAX,
+            };
+            match self.event.try_wait_for_result(remaining) {
+                WaitResult::Signalled => return Ok(()),
+                WaitResult::TimedOut => return Err(RecvTimeoutError::Timeout),
+                WaitResult::Closed => return Err(RecvTimeoutError::Disconnected),
+                // Like `recv`, waits again for the rest of the timeout
+                WaitResult::Interrupted => {}
+            }
+        }
+    }
+
+    /// Consumes a pending signal without blocking, like
+    /// [`Receiver::try_recv`](std::sync::mpsc::Receiver::try_recv).
+    ///
+    /// Fails with [`TryRecvError::Empty`] if the event is not signalled, and with
+    /// [`TryRecvError::Disconnected`] if it has been closed.
+    pub fn try_recv(&self) -> Result<(), TryRecvError> {
+        match self.event.try_wait_for_result(Duration::ZERO) {
+            WaitResult::Signalled => Ok(()),
+            WaitResult::TimedOut | WaitResult::Interrupted => Err(TryRecvError::Empty),
+            WaitResult::Closed => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    /// Returns an iterator that waits for signals until the event is closed, like
+    /// [`Receiver::iter`](std::sync::mpsc::Receiver::iter).
+    pub fn iter(&self) -> impl Iterator<Item = ()> + '_ {
+        iter::from_fn(move || self.recv().ok())
+    }
+
+    /// Returns an iterator over the pending signal, like
+    /// [`Receiver::try_iter`](std::sync::mpsc::Receiver::try_iter). It yields at most one item.
+    pub fn try_iter(&self) -> impl Iterator<Item = ()> + '_ {
+        iter::from_fn(move || self.try_recv().ok())
+    }
+}
+
+impl From<Arc<AutoResetEvent>> for SignalReceiver {
+    fn from(event: Arc<AutoResetEvent>) -> Self {
+        Self::new(event)
+    }
+}
@@ -0,0 +1,350 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::MockAutoResetEvent;
+
+// The virtual time that a replayed timeout waits for
+const REPLAY_TIMEOUT: Duration = Duration::from_millis(1);
+
+thread_local! {
+    // The active `record_trace` scopes of the thread, innermost last
+    static RECORDINGS: RefCell<Vec<Arc<Recording>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// An operation in a [`Trace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub enum TraceOp {
+    /// The event has been signalled, also if it was already signalled. Signals to a closed event
+    /// are ignored and not recorded.
+    Signal,
+    /// A wait consumed the signal of the event.
+    Wake,
+    /// A blocking wait on the event timed out.
+    Timeout,
+    /// The event has been closed.
+    Close,
+}
+
+/// An operation on an event, as recorded in a [`Trace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub struct TraceEntry {
+    /// The logical time of the operation: its position in the trace.
+    pub time: u64,
+    /// The event, numbered in the order in which the events have been created.
+    pub event: u32,
+    /// What happened.
+    pub op: TraceOp,
+}
+
+/// The order of the operations on the events created in [`record_trace`].
+///
+/// A trace is written in a compact text form via `Display`, one token per operation: `s`, `w`,
+/// `t` or `c` for [signal](TraceOp::Signal), [wake](TraceOp::Wake),
+/// [timeout](TraceOp::Timeout) or [close](TraceOp::Close), followed by the number of the event,
+/// e.g. `s0 w0 t1 c1`. It is read back via `FromStr`, e.g. from the log of a production run, and
+/// re-executed deterministically via [`Replay`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub struct Trace {
+    entries: Vec<TraceEntry>,
+}
+
+/// The error of parsing a [`Trace`] from its text form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub struct ParseTraceError {
+    token: String,
+}
+
+/// Re-executes a [`Trace`] against [`MockAutoResetEvent`]s, one operation at a time.
+///
+/// Every event of the trace is replaced by a mock event, which a test can inspect between the
+/// steps via [`event`](Self::event). A step panics if the recorded operation is impossible in the
+/// replayed state, e.g. a wake of an unsignalled event, which points at a lost or duplicated
+/// signal in the recorded run.
+///
+/// The operations are recorded in the order in which the threads recorded them, right after they
+/// took effect. Operations that race with each other may therefore be recorded in a different
+/// order than they took effect, in which case a replay may panic although the run was correct.
+///
+/// ```
+/// use nova_autoreset_event::{AutoResetEvent, Replay, record_trace};
+///
+/// let ((), trace) = record_trace(|| {
+///     let event = AutoResetEvent::new().unwrap();
+///     event.signal();
+///     event.signal();
+///     assert!(event.try_wait());
+/// });
+/// assert_eq!(trace.to_string(), "s0 s0 w0");
+///
+/// let mut replay = Replay::new(trace);
+/// replay.step();
+/// assert!(replay.event(0).is_signalled());
+/// replay.run();
+/// assert!(!replay.event(0).is_signalled());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub struct Replay {
+    trace: Trace,
+    events: Vec<MockAutoResetEvent>,
+    closed: Vec<bool>,
+    position: usize,
+}
+
+/// Runs `f` and returns the trace of the operations on the events that have been created within
+/// it.
+///
+/// Only events that are created on the calling thread are recorded, so that tests running in
+/// parallel do not interfere with each other, but their operations are recorded on all threads.
+/// Scopes can be nested, in which case the events are recorded by the innermost one.
+///
+/// With the `replay` feature, the events created in a scope record every operation under a lock,
+/// so the feature is meant for tests and debug builds.
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub fn record_trace<R>(f: impl FnOnce() -> R) -> (R, Trace) {
+    let scope = Scope::enter();
+    let result = f();
+    let trace = Trace {
+        entries: scope.recording.lock().clone(),
+    };
+    (result, trace)
+}
+
+// The operations of the events of a `record_trace` scope
+#[derive(Debug, Default)]
+struct Recording {
+    entries: Mutex<Vec<TraceEntry>>,
+    next_event: AtomicU32,
+}
+
+impl Recording {
+    fn lock(&self) -> MutexGuard<'_, Vec<TraceEntry>> {
+        // A panicking test thread must not hide the trace from the others
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+// Owned by an event that is created in a `record_trace` scope
+#[derive(Debug)]
+pub(crate) struct Recorded {
+    recording: Arc<Recording>,
+    event: u32,
+}
+
+impl Recorded {
+    // Attaches a new event to the innermost scope of the calling thread, if any
+    pub(crate) fn attach() -> Option<Self> {
+        let recording = RECORDINGS.with_borrow(|recordings| recordings.last().cloned())?;
+        let event = recording.next_event.fetch_add(1, Ordering::Relaxed);
+        Some(Self { recording, event })
+    }
+
+    pub(crate) fn record(&self, op: TraceOp) {
+        let mut entries = self.recording.lock();
+        let time = entries.len() as u64;
+        entries.push(TraceEntry {
+            time,
+            event: self.event,
+            op,
+        });
+    }
+}
+
+// Pops the scope, also if the closure panics
+struct Scope {
+    recording: Arc<Recording>,
+}
+
+impl Scope {
+    fn enter() -> Self {
+        let recording = Arc::new(Recording::default());
+        RECORDINGS.with_borrow_mut(|recordings| recordings.push(recording.clone()));
+        Self { recording }
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        RECORDINGS.with_borrow_mut(|recordings| recordings.pop());
+    }
+}
+
+impl Trace {
+    /// Returns the recorded operations, in the order of their logical times.
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of events that appear in the trace.
+    pub fn events(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|entry| entry.event as usize + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            let op = match entry.op {
+                TraceOp::Signal => 's',
+                TraceOp::Wake => 'w',
+                TraceOp::Timeout => 't',
+                TraceOp::Close => 'c',
+            };
+            write!(f, "{}{}", op, entry.event)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Trace {
+    type Err = ParseTraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let entries = s
+            .split_whitespace()
+            .enumerate()
+            .map(|(time, token)| {
+                let error = || {
+                    ParseTraceError {
+                        token: token.to_owned(),
+                    }
+                };
+                let op = match token.as_bytes()[0] {
+                    b's' => TraceOp::Signal,
+                    b'w' => TraceOp::Wake,
+                    b't' => TraceOp::Timeout,
+                    b'c' => TraceOp::Close,
+                    _ => return Err(error()),
+                };
+                let event = token[1..].parse().map_err(|_| error())?;
+                Ok(TraceEntry {
+                    time: time as u64,
+                    event,
+                    op,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+}
+
+impl fmt::Display for ParseTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid trace operation `{}`", self.token)
+    }
+}
+
+impl Error for ParseTraceError {}
+
+impl Replay {
+    /// Prepares the replay of a trace, with a mock event per event of the trace.
+    pub fn new(trace: Trace) -> Self {
+        let events = trace.events();
+        Self {
+            events: (0..events).map(|_| MockAutoResetEvent::default()).collect(),
+            closed: vec![false; events],
+            trace,
+            position: 0,
+        }
+    }
+
+    /// Returns the mock event that stands in for an event of the trace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trace has no event with the number.
+    pub fn event(&self, event: u32) -> &MockAutoResetEvent {
+        &self.events[event as usize]
+    }
+
+    /// Returns `true` if the event has been closed by the replayed operations.
+    pub fn is_closed(&self, event: u32) -> bool {
+        self.closed[event as usize]
+    }
+
+    /// Returns the logical time of the next operation, which is the number of replayed ones.
+    pub fn time(&self) -> u64 {
+        self.position as u64
+    }
+
+    /// Replays the next operation and returns it, or `None` at the end of the trace.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operation is impossible in the replayed state.
+    pub fn step(&mut self) -> Option<TraceEntry> {
+        let entry = *self.trace.entries.get(self.position)?;
+        self.position += 1;
+
+        let index = entry.event as usize;
+        let event = &self.events[index];
+        let closed = self.closed[index];
+        match entry.op {
+            TraceOp::Signal => {
+                if closed {
+                    self.diverged(entry, "the event is closed, so the signal would be ignored");
+                }
+                event.signal();
+            }
+            TraceOp::Wake => {
+                if closed || !event.try_wait() {
+                    self.diverged(entry, "the event is not signalled");
+                }
+            }
+            TraceOp::Timeout => {
+                if closed {
+                    self.diverged(entry, "the event is closed, so the wait would not block");
+                }
+                event.time_out_next_wait();
+                if event.try_wait_for(REPLAY_TIMEOUT) {
+                    self.diverged(entry, "the event is signalled");
+                }
+            }
+            TraceOp::Close => self.closed[index] = true,
+        }
+        Some(entry)
+    }
+
+    /// Replays the operations before the logical time `time`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an operation is impossible in the replayed state.
+    pub fn run_until(&mut self, time: u64) {
+        while self.time() < time && self.step().is_some() {}
+    }
+
+    /// Replays the remaining operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an operation is impossible in the replayed state.
+    pub fn run(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    #[cold]
+    fn diverged(&self, entry: TraceEntry, reason: &str) -> ! {
+        panic!(
+            "replay diverged at time {}: {:?} of event {}, but {}",
+            entry.time, entry.op, entry.event, reason
+        );
+    }
+}
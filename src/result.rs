@@ -0,0 +1,20 @@
+/// How a wait on an event ended, as returned by
+/// [`AutoResetEvent::wait_result`](crate::AutoResetEvent::wait_result),
+/// [`AutoResetEvent::try_wait_for_result`](crate::AutoResetEvent::try_wait_for_result) and
+/// [`AutoResetEvent::try_wait_for_interruptible`](crate::AutoResetEvent::try_wait_for_interruptible).
+///
+/// New variants may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WaitResult {
+    /// The wait consumed a signal.
+    Signalled,
+    /// The timeout elapsed before the event was signalled.
+    TimedOut,
+    /// The event has been [closed](crate::AutoResetEvent::close). No signal was consumed.
+    Closed,
+    /// A Unix signal interrupted the wait before the event was signalled, see
+    /// [`AutoResetEvent::try_wait_for_interruptible`](crate::AutoResetEvent::try_wait_for_interruptible)
+    /// and `InterruptPolicy::Report`. No signal was consumed.
+    Interrupted,
+}
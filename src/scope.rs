@@ -0,0 +1,171 @@
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle, Thread};
+use std::time::{Duration, Instant};
+
+use crate::{CountdownEvent, EventError};
+
+/// Runs `f` with a [`CompletionScope`] and waits until all threads spawned in it have completed.
+///
+/// Unlike [`std::thread::scope`], the threads cannot borrow from the caller, which allows
+/// [`completion_scope_for`] to stop waiting after a timeout. Like it, a panic of a thread that has
+/// not been [joined](ScopedJoinHandle::join) is resumed once all threads have completed, as is a
+/// panic of `f`.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use nova_autoreset_event::completion_scope;
+///
+/// let done = Arc::new(AtomicUsize::new(0));
+/// completion_scope(|scope| {
+///     for _ in 0..4 {
+///         let done = done.clone();
+///         scope.spawn(move || done.fetch_add(1, Ordering::Relaxed)).unwrap();
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(done.load(Ordering::Relaxed), 4);
+/// ```
+pub fn completion_scope<F, T>(f: F) -> Result<T, EventError>
+where
+    F: FnOnce(&CompletionScope) -> T,
+{
+    let scope = CompletionScope::new()?;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    scope.countdown.wait();
+    Ok(scope.resume(result))
+}
+
+/// Like [`completion_scope`], but waits at most `timeout` for the spawned threads.
+///
+/// Returns the result of `f` and whether all threads have completed within the timeout. Threads
+/// that are still running keep running after the scope has been left, and their panics are not
+/// resumed.
+pub fn completion_scope_for<F, T>(timeout: Duration, f: F) -> Result<(T, bool), EventError>
+where
+    F: FnOnce(&CompletionScope) -> T,
+{
+    let scope = CompletionScope::new()?;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+    let deadline = Instant::now().checked_add(timeout);
+    let completed = scope.countdown.wait_until(deadline);
+    Ok((scope.resume(result), completed))
+}
+
+/// Spawns threads whose completion is awaited by [`completion_scope`].
+///
+/// Every thread holds a [`SignalGuard`](crate::SignalGuard) of the [`CountdownEvent`] that the
+/// scope waits for, so it counts as completed once it has returned or panicked.
+pub struct CompletionScope {
+    countdown: Arc<CountdownEvent>,
+    // The panics of the spawned threads, until they are joined
+    panics: Mutex<Vec<Arc<PanicSlot>>>,
+}
+
+/// A handle to join a thread spawned by [`CompletionScope::spawn`].
+///
+/// Joining a thread that has panicked returns its panic, which the scope then no longer resumes.
+pub struct ScopedJoinHandle<T> {
+    // Returns `None` if the thread has panicked, in which case the panic is in `panic`
+    handle: JoinHandle<Option<T>>,
+    panic: Arc<PanicSlot>,
+}
+
+type PanicSlot = Mutex<Option<Box<dyn Any + Send + 'static>>>;
+
+impl CompletionScope {
+    fn new() -> Result<Self, EventError> {
+        Ok(Self {
+            countdown: Arc::new(CountdownEvent::new(0)?),
+            panics: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Spawns a thread that runs `f`. The scope is left once `f` has returned or panicked.
+    pub fn spawn<F, R>(&self, f: F) -> io::Result<ScopedJoinHandle<R>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let guard = self.countdown.guard();
+        let panic = Arc::new(PanicSlot::default());
+        let slot = panic.clone();
+        // On failure, the closure and with it the guard are dropped
+        let handle = thread::Builder::new().spawn(move || {
+            // Dropped last, so that the panic is recorded before the thread counts as completed
+            let _guard = guard;
+            match panic::catch_unwind(AssertUnwindSafe(f)) {
+                Ok(result) => Some(result),
+                Err(payload) => {
+                    *lock(&slot) = Some(payload);
+                    None
+                }
+            }
+        })?;
+        lock(&self.panics).push(panic.clone());
+        Ok(ScopedJoinHandle { handle, panic })
+    }
+
+    /// Returns the number of spawned threads that have not completed yet.
+    pub fn pending(&self) -> usize {
+        self.countdown.count()
+    }
+
+    // Resumes the panic of `f`, or otherwise the first panic of a thread that has not been joined
+    fn resume<T>(&self, result: thread::Result<T>) -> T {
+        let result = result.unwrap_or_else(|payload| panic::resume_unwind(payload));
+        let panic = lock(&self.panics).iter().find_map(|slot| lock(slot).take());
+        if let Some(payload) = panic {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+}
+
+impl<T> ScopedJoinHandle<T> {
+    /// Waits for the thread to complete and returns its result, or its panic.
+    pub fn join(self) -> thread::Result<T> {
+        match self.handle.join()? {
+            Some(result) => Ok(result),
+            None => {
+                Err(lock(&self.panic)
+                    .take()
+                    .expect("the panic is only taken once"))
+            }
+        }
+    }
+
+    /// Returns the handle of the thread.
+    pub fn thread(&self) -> &Thread {
+        self.handle.thread()
+    }
+
+    /// Returns `true` if the thread has completed.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl fmt::Debug for CompletionScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompletionScope")
+            .field("pending", &self.pending())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Debug for ScopedJoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedJoinHandle")
+            .field("thread", self.thread())
+            .finish_non_exhaustive()
+    }
+}
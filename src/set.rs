@@ -0,0 +1,180 @@
+use std::fmt;
+use std::io;
+use std::ptr;
+use std::time::Duration;
+
+use crate::AutoResetEvent;
+use crate::state::GenericEvent;
+
+/// A reusable set of events that can be waited on together.
+///
+/// This is equivalent to [`AutoResetEvent::wait_any`] over the events of the set, but the set can
+/// keep kernel state between waits. On macOS and BSD, the events are registered on a kqueue of the
+/// set; insertions and removals are queued and submitted together with the next wait, so that
+/// changing the set does not cost any extra syscalls.
+///
+/// ```
+/// use nova_autoreset_event::{AutoResetEvent, EventSet};
+///
+/// let a = AutoResetEvent::new().unwrap();
+/// let b = AutoResetEvent::new().unwrap();
+///
+/// let mut set = EventSet::new().unwrap();
+/// set.insert(&a);
+/// set.insert(&b);
+///
+/// b.signal();
+/// assert!(std::ptr::eq(set.wait(), &b));
+/// ```
+pub struct EventSet<'a> {
+    events: Vec<&'a AutoResetEvent>,
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    ))]
+    queue: crate::sys::EventQueue,
+}
+
+impl<'a> EventSet<'a> {
+    /// Creates an empty set.
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            events: Vec::new(),
+            #[cfg(any(
+                target_os = "macos",
+                target_os = "ios",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ))]
+            queue: crate::sys::EventQueue::new()?,
+        })
+    }
+
+    /// Adds an event to the set. Returns `false` if it is already part of the set.
+    pub fn insert(&mut self, event: &'a AutoResetEvent) -> bool {
+        if self.contains(event) {
+            return false;
+        }
+
+        self.events.push(event);
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        ))]
+        self.queue.insert(event.inner.waker());
+        true
+    }
+
+    /// Removes an event from the set. Returns `false` if it is not part of the set.
+    pub fn remove(&mut self, event: &AutoResetEvent) -> bool {
+        let Some(index) = self.position(event) else {
+            return false;
+        };
+
+        self.events.remove(index);
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        ))]
+        self.queue.remove(event.inner.waker());
+        true
+    }
+
+    /// Returns `true` if the event is part of the set.
+    pub fn contains(&self, event: &AutoResetEvent) -> bool {
+        self.position(event).is_some()
+    }
+
+    /// Returns the number of events in the set.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Waits for one of the events to be signalled, consumes its signal and returns it.
+    ///
+    /// If several events are signalled, the one that was inserted first is chosen. A
+    /// [closed](AutoResetEvent::close) event is returned without consuming a signal, like in
+    /// [`AutoResetEvent::wait_any`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set is empty, or, on Windows, if it contains more than 64 events.
+    pub fn wait(&mut self) -> &'a AutoResetEvent {
+        self.wait_for(None).expect("untimed waits cannot time out")
+    }
+
+    /// Waits for one of the events to be signalled for a specified duration.
+    ///
+    /// Like [`wait`](Self::wait), but returns `None` if none of the events is signalled within the
+    /// timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set is empty, or, on Windows, if it contains more than 64 events.
+    pub fn try_wait_for(&mut self, timeout: Duration) -> Option<&'a AutoResetEvent> {
+        self.wait_for(Some(timeout))
+    }
+
+    fn wait_for(&mut self, timeout: Option<Duration>) -> Option<&'a AutoResetEvent> {
+        assert!(!self.is_empty(), "cannot wait for an empty set of events");
+        let events = self.events.iter().map(|event| &event.inner);
+
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        ))]
+        let index = {
+            let queue = &mut self.queue;
+            GenericEvent::wait_any_with(events, timeout, |timeout| queue.block(timeout))
+        };
+
+        #[cfg(not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "dragonfly"
+        )))]
+        let index = GenericEvent::wait_any(events, timeout);
+
+        index.map(|index| self.events[index])
+    }
+
+    fn position(&self, event: &AutoResetEvent) -> Option<usize> {
+        self.events
+            .iter()
+            .position(|member| ptr::eq(*member, event))
+    }
+}
+
+impl fmt::Debug for EventSet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSet")
+            .field("events", &self.events)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::Backend;
+
+/// A snapshot of the state of an event, as returned by
+/// [`AutoResetEvent::snapshot`](crate::AutoResetEvent::snapshot).
+///
+/// New fields may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EventSnapshot {
+    /// Whether the event was signalled.
+    pub signalled: bool,
+    /// Whether the event had been [closed](crate::AutoResetEvent::close).
+    pub closed: bool,
+    /// The number of threads that were blocked on the event, or about to block.
+    pub waiters: u32,
+    /// The number of signals since the event was created, including coalesced ones.
+    pub total_signals: u64,
+    /// The number of waits that consumed a signal since the event was created.
+    pub total_waits: u64,
+    /// The number of signals that found the event already signalled, see
+    /// [`AutoResetEvent::coalesced_signals`](crate::AutoResetEvent::coalesced_signals).
+    pub coalesced_signals: u64,
+    /// The backend of the event.
+    pub backend: Backend,
+}
@@ -0,0 +1,1601 @@
+// The platform-independent core of an event.
+//
+// The signalled flag and the number of blocked waiters live in a single atomic word, so that
+// `signal()` and `try_wait()` can complete without a syscall whenever no thread is blocked. The
+// kernel object of a backend is only used to park and wake threads. Its own state is merely a hint:
+// a waiter woken by the kernel always re-checks the userspace flag and blocks again if another
+// thread consumed the signal first.
+
+use std::cell::RefCell;
+use std::hint;
+use std::io;
+#[cfg(unix)]
+use std::os::fd::{AsFd, BorrowedFd};
+#[cfg(feature = "reactor")]
+use std::sync::Arc;
+#[cfg(unix)]
+use std::sync::OnceLock;
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+#[cfg(feature = "virtual-time")]
+use crate::MockClock;
+use crate::instrument::{BlockingWait, Instruments};
+use crate::{Backend, EventSnapshot, WaitResult};
+#[cfg(unix)]
+use crate::{EventError, EventOperation};
+
+/// The raw kernel operations a backend provides.
+pub(crate) trait KernelWaker {
+    /// Wakes a thread blocked in [`KernelWaker::block`], or makes the next call return
+    /// immediately.
+    fn wake(&self);
+
+    /// Blocks until woken or until the timeout elapses. Returns `false` on timeout.
+    ///
+    /// Spurious wakeups are allowed. Errors of kind [`io::ErrorKind::Interrupted`] are treated as
+    /// spurious wakeups by the caller.
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool>;
+
+    /// Consumes a pending wakeup without blocking.
+    fn drain(&self);
+
+    /// Creates the kernel object if it is created on demand. Called before a thread registers as
+    /// a waiter or the kernel object is handed out.
+    fn prepare(&self) {}
+
+    /// Returns `true` once for each wakeup that was caused by an external source instead of
+    /// [`KernelWaker::wake`], e.g. a Mach port message. It is treated as a signal of the event.
+    fn take_external(&self) -> bool {
+        false
+    }
+
+    /// Blocks until one of the kernel objects is woken or until the timeout elapses. Returns
+    /// `false` on timeout.
+    ///
+    /// The pending wakeups of the kernel objects that are ready may be consumed. Must not allocate
+    /// for small numbers of kernel objects.
+    fn block_any<'a>(
+        wakers: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool>
+    where
+        Self: 'a;
+
+    /// Wakes each of the kernel objects. Backends can override this to batch the syscalls.
+    fn wake_many<'a>(wakers: impl Iterator<Item = &'a Self>)
+    where
+        Self: 'a,
+    {
+        wakers.for_each(Self::wake)
+    }
+}
+
+/// A kernel object that can be woken from a signal handler.
+#[cfg(unix)]
+pub(crate) trait SignalSafeWaker {
+    /// Like [`KernelWaker::wake`], but async-signal-safe: it makes plain syscalls on existing
+    /// kernel objects, and neither allocates, locks, logs nor panics. Errors are ignored.
+    fn wake_from_handler(&self);
+}
+
+/// A kernel object that can be created on demand.
+pub(crate) trait KernelObject: KernelWaker + Sized {
+    /// The parameters the kernel object is created with.
+    type Config: Copy + std::fmt::Debug;
+
+    fn create(config: Self::Config) -> io::Result<Self>;
+
+    /// The backend of a kernel object that is created with `config`, for error messages.
+    #[cfg(unix)]
+    fn backend(config: Self::Config) -> Backend;
+}
+
+/// A kernel object that can be created when it is first needed: when a thread blocks, or when the
+/// fd is handed out. Events that never block then do not consume any kernel resources.
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct LazyKernel<K: KernelObject> {
+    kernel: OnceLock<K>,
+    config: K::Config,
+}
+
+#[cfg(unix)]
+impl<K: KernelObject> LazyKernel<K> {
+    pub(crate) const fn new(config: K::Config) -> Self {
+        Self {
+            kernel: OnceLock::new(),
+            config,
+        }
+    }
+
+    // Creates the kernel object right away unless `lazy` is set, so that a failure is reported to
+    // the caller rather than by the first wait
+    pub(crate) fn with_laziness(config: K::Config, lazy: bool) -> io::Result<Self> {
+        let waker = Self::new(config);
+        if !lazy {
+            waker.try_get()?;
+        }
+        Ok(waker)
+    }
+
+    // Unused on platforms with a single backend
+    #[allow(dead_code)]
+    pub(crate) fn config(&self) -> K::Config {
+        self.config
+    }
+
+    // Returns the kernel object, creating it if necessary. Panics if it cannot be created.
+    pub(crate) fn get(&self) -> &K {
+        self.try_get().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    // Returns the kernel object, creating it if necessary. A failure carries the `EventError` of
+    // the creation, so that a checked wait that creates the kernel object reports it as such, see
+    // `EventError::from_io`.
+    pub(crate) fn try_get(&self) -> io::Result<&K> {
+        if let Some(kernel) = self.kernel.get() {
+            return Ok(kernel);
+        }
+        // Threads that race to create the kernel object create one each, all but the first one
+        // are dropped again
+        let kernel = K::create(self.config)
+            .map_err(|err| EventError::new(EventOperation::Create, K::backend(self.config), err))?;
+        Ok(self.kernel.get_or_init(|| kernel))
+    }
+}
+
+#[cfg(unix)]
+impl<K: KernelObject> KernelWaker for LazyKernel<K> {
+    fn wake(&self) {
+        // Waiters and exports create the kernel object before they become visible, so there is
+        // nothing to wake if it does not exist yet
+        if let Some(kernel) = self.kernel.get() {
+            kernel.wake()
+        }
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        self.try_get()?.block(timeout)
+    }
+
+    fn drain(&self) {
+        if let Some(kernel) = self.kernel.get() {
+            kernel.drain()
+        }
+    }
+
+    fn prepare(&self) {
+        // A failure is reported by `block`
+        let _ = self.try_get();
+    }
+
+    fn take_external(&self) -> bool {
+        self.kernel.get().is_some_and(K::take_external)
+    }
+
+    fn block_any<'a>(
+        wakers: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool>
+    where
+        Self: 'a,
+    {
+        for waker in wakers.clone() {
+            waker.try_get()?;
+        }
+        K::block_any(wakers.map(Self::get), timeout)
+    }
+
+    fn wake_many<'a>(wakers: impl Iterator<Item = &'a Self>)
+    where
+        Self: 'a,
+    {
+        K::wake_many(wakers.filter_map(|waker| waker.kernel.get()))
+    }
+}
+
+#[cfg(unix)]
+impl<K: KernelObject + SignalSafeWaker> SignalSafeWaker for LazyKernel<K> {
+    fn wake_from_handler(&self) {
+        // Waiters and exports create the kernel object before they become visible, so there is
+        // nothing to wake if it does not exist yet
+        if let Some(kernel) = self.kernel.get() {
+            kernel.wake_from_handler()
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<K: KernelObject + AsFd> AsFd for LazyKernel<K> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.get().as_fd()
+    }
+}
+
+/// A kernel object whose fd may have to be created when it is requested, see `Futex`.
+#[cfg(unix)]
+pub(crate) trait TryAsFd: AsFd {
+    /// Like [`AsFd::as_fd`], but fails instead of panicking if the fd cannot be created.
+    fn try_as_fd(&self) -> io::Result<BorrowedFd<'_>> {
+        Ok(self.as_fd())
+    }
+}
+
+#[cfg(unix)]
+impl<K: KernelObject + TryAsFd> TryAsFd for LazyKernel<K> {
+    fn try_as_fd(&self) -> io::Result<BorrowedFd<'_>> {
+        self.try_get()?.try_as_fd()
+    }
+}
+
+// The wakers of the tasks that poll an event. All of them are woken by a signal, as a task that is
+// woken may have been dropped without polling again; the first one to poll consumes the signal.
+#[derive(Debug)]
+pub(crate) struct TaskWakers {
+    // Set while wakers are registered, so that signals do not lock without polling tasks
+    registered: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+    // Set while the reactor watches the kernel object of the event for the tasks
+    #[cfg(feature = "reactor")]
+    watched: AtomicBool,
+}
+
+impl TaskWakers {
+    fn new() -> Self {
+        Self {
+            registered: AtomicBool::new(false),
+            wakers: Mutex::new(Vec::new()),
+            #[cfg(feature = "reactor")]
+            watched: AtomicBool::new(false),
+        }
+    }
+
+    /// Wakes the tasks once the reactor has seen the kernel object of the event become ready.
+    /// The tasks that poll again register the event with the reactor again.
+    #[cfg(feature = "reactor")]
+    pub(crate) fn fire(&self) {
+        self.watched.store(false, Ordering::SeqCst);
+        self.wake();
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.lock();
+        if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+        self.registered.store(true, Ordering::SeqCst);
+    }
+
+    #[inline(always)]
+    fn wake(&self) {
+        if !self.registered.load(Ordering::SeqCst) {
+            return;
+        }
+        let wakers = {
+            let mut wakers = self.lock();
+            self.registered.store(false, Ordering::SeqCst);
+            std::mem::take(&mut *wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Waker>> {
+        self.wakers.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+// The event is signalled
+const SIGNALLED: u32 = 1;
+// The event has been closed. It is never cleared, and it overrides the signalled flag.
+const CLOSED: u32 = 2;
+// One blocked waiter. The waiter count is stored in the remaining bits.
+const WAITER: u32 = 4;
+
+// The state word of an event. With the `diagnostics` feature, it is shared with the registry of
+// live events.
+#[cfg(feature = "diagnostics")]
+pub(crate) type State = std::sync::Arc<AtomicU32>;
+#[cfg(not(feature = "diagnostics"))]
+pub(crate) type State = AtomicU32;
+
+// Returns `true` if a state word has the signalled flag set and the event is not closed
+pub(crate) fn is_signalled(state: u32) -> bool {
+    state & (SIGNALLED | CLOSED) == SIGNALLED
+}
+
+// Returns `true` if a state word has the closed flag set
+pub(crate) fn is_closed(state: u32) -> bool {
+    state & CLOSED != 0
+}
+
+// Returns the number of waiters of a state word
+pub(crate) fn waiters(state: u32) -> u32 {
+    state / WAITER
+}
+
+// How the calling thread is registered as a waiter of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Waiter {
+    // Not registered, e.g. in `try_wait`
+    Unregistered,
+    // Registered by a wait on several events, or for an external event loop. Such waiters are not
+    // released by `pulse` and `signal_n`.
+    Uncounted,
+    // Registered by a wait on this event in the given epoch, see `Releases`
+    Epoch(u32),
+}
+
+// The waiters that `pulse` and `signal_n` release.
+//
+// The waits on an event register in the current epoch, which `pulse` and `signal_n` end. Only the
+// waiters of earlier epochs are released, so that a released thread that waits again blocks like
+// any other waiter. The generation word of the event holds the current epoch in the upper 32 bits
+// and the number of waiters registered in it in the lower ones. Once the epoch has ended, its
+// waiters are tracked here, under a lock that also covers the end of an epoch.
+#[derive(Debug, Default)]
+struct Releases {
+    // All waiters of epochs before this one are released by `pulse`
+    pulse_epoch: u32,
+    // The number of registered waiters that `pulse` releases
+    pulsed: u32,
+    // The number of the other registered waiters of ended epochs
+    stale: u32,
+    // The number of them that `signal_n` releases. Never more than `stale`.
+    budget: u32,
+}
+
+// How a waiter has been released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Release {
+    Pulse,
+    // The waiter consumes one of the signals of `signal_n`
+    Signal,
+}
+
+impl Releases {
+    // Returns `true` if `epoch` is released by `pulse`, which handles the wrap-around of epochs
+    fn is_pulsed(&self, epoch: u32) -> bool {
+        (self.pulse_epoch.wrapping_sub(epoch) as i32) > 0
+    }
+
+    // Ends the epoch before `epoch`, in which `waiters` registered, and releases all waiters
+    fn pulse(&mut self, epoch: u32, waiters: u32) {
+        self.pulse_epoch = epoch;
+        self.pulsed += self.stale + waiters;
+        self.stale = 0;
+        self.budget = 0;
+    }
+
+    // Ends an epoch in which `waiters` registered, and releases up to `n` waiters. Returns the
+    // number of waiters that are released in addition to the ones that already were.
+    fn signal(&mut self, waiters: u32, n: u32) -> u32 {
+        self.stale += waiters;
+        let budget = self.budget.saturating_add(n).min(self.stale);
+        let added = budget - self.budget;
+        self.budget = budget;
+        added
+    }
+
+    // Releases a waiter of an ended epoch. Returns `None` if it has to wait on.
+    fn release(&mut self, epoch: u32) -> Option<Release> {
+        if self.is_pulsed(epoch) {
+            self.pulsed -= 1;
+            Some(Release::Pulse)
+        } else if self.budget != 0 {
+            self.budget -= 1;
+            self.stale -= 1;
+            Some(Release::Signal)
+        } else {
+            None
+        }
+    }
+
+    // Forgets a waiter of an ended epoch that left for another reason
+    fn departed(&mut self, epoch: u32) {
+        if self.is_pulsed(epoch) {
+            self.pulsed -= 1;
+        } else {
+            self.stale -= 1;
+            self.budget = self.budget.min(self.stale);
+        }
+    }
+
+    // Returns `true` while registered waiters are still to be released
+    fn pending(&self) -> bool {
+        self.pulsed != 0 || self.budget != 0
+    }
+}
+
+// The lock is taken while other threads run the protocol, so loom has to see it
+#[cfg(not(loom))]
+type ReleaseLock = Mutex<Releases>;
+#[cfg(loom)]
+type ReleaseLock = loom::sync::Mutex<Releases>;
+
+// Lets other threads run instead of blocking, see `GenericEvent::yield_to_releases`
+fn yield_now() {
+    #[cfg(loom)]
+    loom::thread::yield_now();
+    #[cfg(not(loom))]
+    std::thread::yield_now();
+}
+
+/// The platform-independent part of an event: waiter accounting, timeouts and the userspace fast
+/// path. The waker only needs to provide the raw kernel operations.
+#[derive(Debug)]
+pub(crate) struct GenericEvent<W: KernelWaker> {
+    waker: W,
+    state: State,
+    // Set once the fd/handle has been handed out. From then on, the kernel object mirrors the
+    // signalled flag, so that it can be polled by other event loops.
+    exported: AtomicBool,
+    // The number of signals since the last `wait_count()`, including coalesced ones
+    signals: AtomicU64,
+    // The number of signals that the signalled flag stands for, see `drain`. A signal is counted
+    // before it sets the flag, and the count is taken after the flag has been cleared.
+    pending: AtomicUsize,
+    // The number of signals and consumed signals since the creation of the event
+    total_signals: AtomicU64,
+    total_waits: AtomicU64,
+    // The number of signals that found the event already signalled
+    coalesced_signals: AtomicU64,
+    // The current epoch and the number of waiters registered in it, see `Releases`
+    generation: AtomicU64,
+    releases: ReleaseLock,
+    // Mirrors `Releases::pending`, so that waiters need not lock to check it
+    releasing: AtomicBool,
+    spin: AdaptiveSpin,
+    // The tasks that poll the event, see `poll_wait`. The reactor holds them while it watches the
+    // kernel object.
+    #[cfg(feature = "reactor")]
+    tasks: Arc<TaskWakers>,
+    #[cfg(not(feature = "reactor"))]
+    tasks: TaskWakers,
+    instruments: Instruments,
+    // Timeouts are measured on this clock instead of the real one
+    #[cfg(feature = "virtual-time")]
+    clock: Option<MockClock>,
+    // Set if an interrupted blocking call ends the wait, see `InterruptPolicy`
+    interruptible: bool,
+}
+
+impl<W: KernelWaker> GenericEvent<W> {
+    pub(crate) fn new(waker: W, instruments: Instruments) -> Self {
+        Self {
+            waker,
+            state: instruments.state(),
+            exported: AtomicBool::new(false),
+            signals: AtomicU64::new(0),
+            pending: AtomicUsize::new(0),
+            total_signals: AtomicU64::new(0),
+            total_waits: AtomicU64::new(0),
+            coalesced_signals: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+            releases: ReleaseLock::new(Releases::default()),
+            releasing: AtomicBool::new(false),
+            spin: AdaptiveSpin::new(),
+            #[cfg(feature = "reactor")]
+            tasks: Arc::new(TaskWakers::new()),
+            #[cfg(not(feature = "reactor"))]
+            tasks: TaskWakers::new(),
+            instruments,
+            #[cfg(feature = "virtual-time")]
+            clock: None,
+            interruptible: false,
+        }
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn with_interruptible(mut self, interruptible: bool) -> Self {
+        self.interruptible = interruptible;
+        self
+    }
+
+    #[cfg(feature = "virtual-time")]
+    pub(crate) fn with_clock(mut self, clock: Option<MockClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[cfg(feature = "virtual-time")]
+    pub(crate) fn clock(&self) -> Option<&MockClock> {
+        self.clock.as_ref()
+    }
+
+    pub(crate) fn waker(&self) -> &W {
+        &self.waker
+    }
+
+    pub(crate) fn instruments(&self) -> &Instruments {
+        &self.instruments
+    }
+
+    pub(crate) fn is_signalled(&self) -> bool {
+        is_signalled(self.state.load(Ordering::SeqCst))
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        is_closed(self.state.load(Ordering::SeqCst))
+    }
+
+    // The number of threads that are blocked on the event, or about to block
+    pub(crate) fn waiters(&self) -> u32 {
+        waiters(self.state.load(Ordering::SeqCst))
+    }
+
+    pub(crate) fn snapshot(&self, backend: Backend) -> EventSnapshot {
+        let state = self.state.load(Ordering::SeqCst);
+        EventSnapshot {
+            signalled: is_signalled(state),
+            closed: is_closed(state),
+            waiters: waiters(state),
+            total_signals: self.total_signals.load(Ordering::Relaxed),
+            total_waits: self.total_waits.load(Ordering::Relaxed),
+            coalesced_signals: self.coalesced_signals(),
+            backend,
+        }
+    }
+
+    pub(crate) fn coalesced_signals(&self) -> u64 {
+        self.coalesced_signals.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn signal(&self) {
+        self.instruments.signal();
+        self.signals.fetch_add(1, Ordering::SeqCst);
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.total_signals.fetch_add(1, Ordering::Relaxed);
+        if self.set_signalled() {
+            self.instruments.wake(|| self.waker.wake());
+        }
+        self.tasks.wake();
+    }
+
+    /// Signals the event only if a waiter is registered and it is not closed. Returns `true` if it
+    /// did.
+    pub(crate) fn signal_if_waiting(&self) -> bool {
+        let state = self.state.load(Ordering::SeqCst);
+        if waiters(state) == 0 || is_closed(state) {
+            return false;
+        }
+        self.signal();
+        true
+    }
+
+    /// Signals the event for up to `n` of the waits that are registered right now, see
+    /// `Releases`. Signals the event once if none of them is released, like `signal`.
+    pub(crate) fn signal_n(&self, n: u32) {
+        if n == 0 {
+            return;
+        }
+        let released = {
+            let mut releases = self.lock_releases();
+            let (_, waiters) = self.end_epoch();
+            let released = releases.signal(waiters, n);
+            self.releasing.store(releases.pending(), Ordering::SeqCst);
+            released
+        };
+        self.instruments.signal();
+        self.signals.fetch_add(u64::from(n), Ordering::SeqCst);
+        self.total_signals
+            .fetch_add(u64::from(n), Ordering::Relaxed);
+        if released != 0 {
+            // The released waiters consume the signals, see `release`, and the remaining ones are
+            // lost. One waiter is woken, which passes the wakeup on while there are more, see
+            // `block`.
+            self.instruments.wake(|| self.waker.wake());
+        } else {
+            self.pending.fetch_add(n as usize, Ordering::SeqCst);
+            if self.set_signalled() {
+                self.instruments.wake(|| self.waker.wake());
+            }
+            self.tasks.wake();
+        }
+    }
+
+    /// Releases the waits on the event that are registered right now, without setting the
+    /// signalled flag. Waits that register later block as usual, see `Releases`.
+    pub(crate) fn pulse(&self) {
+        if self.is_closed() {
+            return;
+        }
+        let waiters = {
+            let mut releases = self.lock_releases();
+            let (epoch, waiters) = self.end_epoch();
+            releases.pulse(epoch, waiters);
+            self.releasing.store(releases.pending(), Ordering::SeqCst);
+            waiters
+        };
+        // Wakes one waiter, which passes the wakeup on while there are more, see `block`
+        if waiters != 0 {
+            self.instruments.wake(|| self.waker.wake());
+        }
+    }
+
+    /// Like [`GenericEvent::signal`], but async-signal-safe. The instruments do not see the
+    /// signal, and polling tasks are not woken.
+    #[cfg(unix)]
+    pub(crate) fn signal_from_handler(&self)
+    where
+        W: SignalSafeWaker,
+    {
+        self.signals.fetch_add(1, Ordering::SeqCst);
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        self.total_signals.fetch_add(1, Ordering::Relaxed);
+        let prev = self.state.fetch_or(SIGNALLED, Ordering::SeqCst);
+        if is_signalled(prev) {
+            self.coalesced_signals.fetch_add(1, Ordering::Relaxed);
+        } else if !is_closed(prev) && (prev >= WAITER || self.exported.load(Ordering::SeqCst)) {
+            self.waker.wake_from_handler();
+        }
+    }
+
+    /// Closes the event and wakes all blocked waiters. Returns `false` if it was already closed.
+    pub(crate) fn close(&self) -> bool {
+        let prev = self.state.fetch_or(CLOSED, Ordering::SeqCst);
+        if is_closed(prev) {
+            return false;
+        }
+        self.instruments.closed();
+
+        // Wakes one waiter, which passes the wakeup on when it leaves, see `leave`
+        if prev >= WAITER || self.exported.load(Ordering::SeqCst) {
+            self.waker.wake();
+        }
+        self.tasks.wake();
+        true
+    }
+
+    pub(crate) fn signal_many<'a>(events: impl IntoIterator<Item = &'a Self>)
+    where
+        W: 'a,
+    {
+        W::wake_many(
+            events
+                .into_iter()
+                .filter(|event| {
+                    event.instruments.signal();
+                    event.signals.fetch_add(1, Ordering::SeqCst);
+                    event.pending.fetch_add(1, Ordering::SeqCst);
+                    event.total_signals.fetch_add(1, Ordering::Relaxed);
+                    let wake = event.set_signalled();
+                    event.tasks.wake();
+                    wake
+                })
+                .map(|event| &event.waker),
+        )
+    }
+
+    pub(crate) fn try_wait(&self) -> bool {
+        self.take(Waiter::Unregistered)
+    }
+
+    /// Discards the pending signal, including a pending wakeup by an external source. Returns
+    /// `true` if the event was signalled.
+    pub(crate) fn reset(&self) -> bool {
+        self.check_external();
+        let reset = self.clear_signalled(false);
+        if reset {
+            self.pending.store(0, Ordering::SeqCst);
+        }
+        reset
+    }
+
+    /// Consumes the signal, or registers the waker of the task to be woken by the next signal or
+    /// by closing the event.
+    pub(crate) fn poll_wait(&self, cx: &mut Context<'_>) -> Poll<WaitResult> {
+        // The reactor wakes the task for wakeups of the kernel object that bypass `signal`
+        #[cfg(feature = "reactor")]
+        self.check_external();
+        if let Some(result) = self.poll_take() {
+            return Poll::Ready(result);
+        }
+        // A signal after the registration wakes the task, one before it is seen by the check
+        // after it
+        self.tasks.register(cx.waker());
+        match self.poll_take() {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Returns the tasks that poll the event if the reactor does not watch the kernel object for
+    /// them yet, in which case the caller has to hand them to the reactor.
+    #[cfg(feature = "reactor")]
+    pub(crate) fn watch(&self) -> Option<&Arc<TaskWakers>> {
+        (!self.tasks.watched.swap(true, Ordering::SeqCst)).then_some(&self.tasks)
+    }
+
+    /// Waits until the event is signalled or closed. Never returns [`WaitResult::TimedOut`].
+    pub(crate) fn wait(&self) -> WaitResult {
+        if self.is_closed() {
+            return WaitResult::Closed;
+        }
+        if self.spin.run(|| self.take(Waiter::Unregistered)) {
+            return WaitResult::Signalled;
+        }
+
+        self.instruments.check_deadlock();
+        #[cfg(madsim)]
+        simulation_would_block();
+
+        let waiter = self.register(true);
+        let mut wait = self.instruments.start_wait(None);
+        let result = loop {
+            if let Some(result) = self.poll_registered(waiter) {
+                break result;
+            }
+            let blocked = self.block_with(wait.clip(None), self.interruptible);
+            wait.check();
+            if blocked.is_none() {
+                break self.interrupted(waiter);
+            }
+            if failed() {
+                break self.cancel_wait(waiter);
+            }
+
+            #[cfg(feature = "deadlock-detection")]
+            if wait.deadlocked() {
+                self.leave(waiter);
+                self.instruments
+                    .report_deadlock("it is the only thread that has ever signalled the event");
+            }
+        };
+        wait.finish(result == WaitResult::Signalled);
+        result
+    }
+
+    /// Consumes the pending signal without blocking, and returns the number of signals it stood
+    /// for. Returns 0 if the event is not signalled.
+    pub(crate) fn drain(&self) -> usize {
+        self.check_external();
+        let Some(count) = self.take_pending(Waiter::Unregistered) else {
+            return 0;
+        };
+        // The discarded signals are not reported by `wait_count` either
+        let _ = self
+            .signals
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |signals| {
+                Some(signals.saturating_sub(count as u64))
+            });
+        count
+    }
+
+    /// Waits for the event and returns the number of signals since the last call. Once the event
+    /// is closed, the count may be 0.
+    pub(crate) fn wait_count(&self) -> u64 {
+        loop {
+            let closed = self.wait() == WaitResult::Closed;
+
+            // A signal is counted before it sets the flag, so the count may already have been
+            // reported by the previous call. The flag is stale then, and we wait again.
+            let count = self.signals.swap(0, Ordering::SeqCst);
+            if count != 0 || closed {
+                return count;
+            }
+        }
+    }
+
+    /// Waits until at least `n` signals have been counted since the last `wait_count()`, and
+    /// consumes exactly `n` of them. The remaining ones stay counted, and the flag is set for
+    /// them. Once all counted signals have been taken, the flag may still be set, just like after
+    /// `wait_count()`.
+    pub(crate) fn wait_many(&self, n: u64) -> WaitResult {
+        loop {
+            let taken = self
+                .signals
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                    count.checked_sub(n)
+                });
+            if let Ok(count) = taken {
+                if count > n {
+                    // The wait below may have consumed the flag of a signal that is still
+                    // counted, which must not leave the next `wait_count()` blocked
+                    self.pending
+                        .fetch_max((count - n) as usize, Ordering::SeqCst);
+                    self.resignal();
+                }
+                return WaitResult::Signalled;
+            }
+
+            // A signal is counted before it sets the flag, so a signal that arrives after the
+            // count has been checked ends the wait, and the count is checked again
+            if self.wait() == WaitResult::Closed {
+                return WaitResult::Closed;
+            }
+        }
+    }
+
+    pub(crate) fn try_wait_for(&self, timeout: Duration) -> WaitResult {
+        self.try_wait_for_with(timeout, self.interruptible)
+    }
+
+    /// Like [`GenericEvent::try_wait_for`], but ends the wait with [`WaitResult::Interrupted`]
+    /// when a blocking call is interrupted, instead of restarting it.
+    pub(crate) fn try_wait_for_interruptible(&self, timeout: Duration) -> WaitResult {
+        self.try_wait_for_with(timeout, true)
+    }
+
+    fn try_wait_for_with(&self, timeout: Duration, interruptible: bool) -> WaitResult {
+        if timeout.is_zero() || self.is_closed() {
+            return self.try_wait_result();
+        }
+
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        let deadline = Instant::now().checked_add(timeout);
+
+        if self.spin.run(|| self.take(Waiter::Unregistered)) {
+            return WaitResult::Signalled;
+        }
+
+        let waiter = self.register(true);
+        let mut wait = self.instruments.start_wait(Some(timeout));
+        let result = self.wait_until(waiter, deadline, &mut wait, interruptible);
+        wait.finish(result == WaitResult::Signalled);
+        result
+    }
+
+    // Consumes the signal without blocking
+    fn try_wait_result(&self) -> WaitResult {
+        if self.take(Waiter::Unregistered) {
+            WaitResult::Signalled
+        } else if self.is_closed() {
+            WaitResult::Closed
+        } else {
+            WaitResult::TimedOut
+        }
+    }
+
+    // Consumes the signal, or leaves if the event is closed or the wait has been released by
+    // `pulse` or `signal_n`. Returns `None` if the calling thread has to block. The calling thread
+    // must be registered as `waiter`.
+    fn poll_registered(&self, waiter: Waiter) -> Option<WaitResult> {
+        if self.take(waiter) {
+            Some(WaitResult::Signalled)
+        } else if self.is_closed() {
+            self.leave(waiter);
+            Some(WaitResult::Closed)
+        } else if self.release(waiter) {
+            Some(WaitResult::Signalled)
+        } else {
+            None
+        }
+    }
+
+    // Blocks until the event is signalled or closed, or the deadline has passed, or, if
+    // `interruptible` is set, a blocking call has been interrupted. The calling thread must be
+    // registered as `waiter`.
+    fn wait_until(
+        &self,
+        waiter: Waiter,
+        deadline: Option<Instant>,
+        wait: &mut BlockingWait<'_>,
+        interruptible: bool,
+    ) -> WaitResult {
+        loop {
+            if let Some(result) = self.poll_registered(waiter) {
+                return result;
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return self.cancel_wait(waiter);
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            let timeout = chunk(wait.clip(remaining));
+            let Some(woken) = self.block_with(timeout, interruptible) else {
+                return self.interrupted(waiter);
+            };
+            wait.check();
+            if failed() {
+                return self.cancel_wait(waiter);
+            }
+            // A kernel timeout may have been a chunk of the remaining time, or may have ended
+            // early, so the deadline is checked at the top of the loop. Simulated and modelled
+            // waits time out without blocking.
+            if cfg!(any(loom, madsim)) && !woken && timeout == remaining {
+                return self.cancel_wait(waiter);
+            }
+        }
+    }
+
+    /// Waits until one of the events is signalled and consumes its signal. Returns the index of the
+    /// event, or `None` on timeout. Events earlier in the list take precedence.
+    pub(crate) fn wait_any<'a>(
+        events: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> Option<usize>
+    where
+        W: 'a,
+    {
+        let wakers = events.clone().map(|event| &event.waker);
+        Self::wait_any_with(events, timeout, |timeout| {
+            W::block_any(wakers.clone(), timeout)
+        })
+    }
+
+    /// Like [`GenericEvent::wait_any`], but blocks via `block`, which has the semantics of
+    /// [`KernelWaker::block_any`]. A closed event also ends the wait, without consuming a signal.
+    pub(crate) fn wait_any_with<'a>(
+        events: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+        mut block: impl FnMut(Option<Duration>) -> io::Result<bool>,
+    ) -> Option<usize>
+    where
+        W: 'a,
+    {
+        if let Some(index) = Self::take_any(events.clone(), Waiter::Unregistered) {
+            return Some(index);
+        }
+        if let Some(index) = events.clone().position(Self::is_closed) {
+            return Some(index);
+        }
+        // The kernel objects are not used, see `simulated_block`
+        #[cfg(madsim)]
+        let block = {
+            let _ = &mut block;
+            if timeout.is_none() {
+                simulation_would_block();
+            }
+            simulated_block
+        };
+        if timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return None;
+        }
+
+        // `None` if there is no timeout or the deadline is not representable
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+
+        for event in events.clone() {
+            event.register(false);
+        }
+        let wait = BlockingWait::start_any(events.clone().count(), timeout);
+
+        // The index of the event that ended the wait, and whether it has been closed rather than
+        // signalled
+        let poll = || {
+            Self::take_any(events.clone(), Waiter::Uncounted)
+                .map(|index| (index, false))
+                .or_else(|| {
+                    events
+                        .clone()
+                        .position(Self::is_closed)
+                        .map(|index| (index, true))
+                })
+        };
+        let result = loop {
+            if let Some(result) = poll() {
+                break Some(result);
+            }
+
+            let remaining = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break None;
+                    }
+                    Some(deadline - now)
+                }
+                None => None,
+            };
+
+            // See `yield_to_releases`
+            let timeout = chunk(wait.clip(remaining));
+            let woken = if !cfg!(madsim) && events.clone().any(|event| event.is_releasing()) {
+                yield_now();
+                true
+            } else {
+                check_block(block(timeout))
+            };
+            events.clone().for_each(Self::check_external);
+            if woken {
+                events.clone().for_each(Self::pass_on_release);
+            }
+
+            // See `wait_until`
+            if cfg!(any(loom, madsim)) && !woken && timeout == remaining {
+                break poll();
+            }
+        };
+
+        let index = result.map(|(index, _)| index);
+        let mut woken = None;
+        for (i, event) in events.enumerate() {
+            if result == Some((i, false)) {
+                woken = Some(&event.instruments);
+            } else {
+                event.leave(Waiter::Uncounted);
+            }
+        }
+        wait.finish_any(woken);
+        index
+    }
+
+    /// Registers the calling thread as a waiter for an external event loop, so that a signal from
+    /// now on wakes the kernel object. Returns `true` if the event is already signalled or closed.
+    pub(crate) fn prepare_wait(&self) -> bool {
+        self.register(false);
+        self.state.load(Ordering::SeqCst) & (SIGNALLED | CLOSED) != 0
+    }
+
+    /// Ends a wait prepared by [`GenericEvent::prepare_wait`]: consumes the signal, or reports
+    /// [`WaitResult::TimedOut`] if there is none.
+    pub(crate) fn commit_wait(&self) -> WaitResult {
+        self.check_external();
+        self.cancel_wait(Waiter::Uncounted)
+    }
+
+    /// Ends a wait prepared by [`GenericEvent::prepare_wait`] without consuming the signal.
+    pub(crate) fn abandon_wait(&self) {
+        self.leave(Waiter::Uncounted);
+    }
+
+    /// Marks the kernel object as visible to other event loops and returns the waker.
+    pub(crate) fn export(&self) -> &W {
+        self.waker.prepare();
+        if !self.exported.swap(true, Ordering::SeqCst)
+            && self.state.load(Ordering::SeqCst) & (SIGNALLED | CLOSED) != 0
+        {
+            // The event was signalled or closed in userspace only, so the kernel object needs to
+            // catch up
+            self.waker.wake();
+        }
+        &self.waker
+    }
+
+    // Registers the calling thread as a waiter, in the current epoch if `counted` is set, see
+    // `Releases`. The kernel object is created first, so that a signal that observes the waiter
+    // never has to create it, see `signal_from_handler`.
+    fn register(&self, counted: bool) -> Waiter {
+        self.waker.prepare();
+        let waiter = if counted {
+            let generation = self.generation.fetch_add(1, Ordering::SeqCst);
+            Waiter::Epoch((generation >> 32) as u32)
+        } else {
+            Waiter::Uncounted
+        };
+        self.state.fetch_add(WAITER, Ordering::SeqCst);
+        waiter
+    }
+
+    // Starts a new epoch. Returns it and the number of waiters that registered in the one it ends.
+    // The releases must be locked.
+    fn end_epoch(&self) -> (u32, u32) {
+        let prev = self
+            .generation
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |generation| {
+                Some(((generation >> 32) + 1) << 32)
+            })
+            .unwrap_or_else(|generation| generation);
+        (((prev >> 32) as u32).wrapping_add(1), prev as u32)
+    }
+
+    fn lock_releases(&self) -> impl std::ops::DerefMut<Target = Releases> + '_ {
+        self.releases.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn is_releasing(&self) -> bool {
+        self.releasing.load(Ordering::SeqCst)
+    }
+
+    // Releases the calling thread if `pulse` or `signal_n` have ended its epoch and release it,
+    // and removes it from the waiter count. Returns `false` if it has to wait on.
+    fn release(&self, waiter: Waiter) -> bool {
+        let Waiter::Epoch(epoch) = waiter else {
+            return false;
+        };
+        if (self.generation.load(Ordering::SeqCst) >> 32) as u32 == epoch {
+            return false;
+        }
+        let release = {
+            let mut releases = self.lock_releases();
+            let release = releases.release(epoch);
+            self.releasing.store(releases.pending(), Ordering::SeqCst);
+            release
+        };
+        let Some(release) = release else {
+            return false;
+        };
+        self.unregister();
+        if release == Release::Signal {
+            // The signal never set the flag, so it is consumed here
+            self.instruments.signalled(false);
+            self.total_waits.fetch_add(1, Ordering::Relaxed);
+            self.instruments.consumed();
+        }
+        true
+    }
+
+    // Removes a waiter that left from the count of its epoch, after it has been removed from the
+    // waiter count
+    fn departed(&self, waiter: Waiter) {
+        let Waiter::Epoch(epoch) = waiter else {
+            return;
+        };
+        let mut generation = self.generation.load(Ordering::SeqCst);
+        while (generation >> 32) as u32 == epoch {
+            match self.generation.compare_exchange_weak(
+                generation,
+                generation - 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(current) => generation = current,
+            }
+        }
+
+        // The epoch has ended in the meantime
+        let mut releases = self.lock_releases();
+        releases.departed(epoch);
+        self.releasing.store(releases.pending(), Ordering::SeqCst);
+    }
+
+    // While `pulse` or `signal_n` release waiters, a kernel wakeup is passed from one released
+    // waiter to the next. Other waiters must not take it, so they yield instead of blocking, which
+    // counts as a spurious wakeup. Returns `false` if the calling thread may block.
+    fn yield_to_releases(&self) -> bool {
+        if cfg!(madsim) || !self.is_releasing() {
+            return false;
+        }
+        yield_now();
+        true
+    }
+
+    // Passes on a kernel wakeup that the calling thread may have taken from a released waiter
+    fn pass_on_release(&self) {
+        if self.is_releasing() {
+            self.waker.wake();
+        }
+    }
+
+    // Sets the signalled flag. Returns `true` if the kernel object needs to be woken.
+    fn set_signalled(&self) -> bool {
+        let prev = self.state.fetch_or(SIGNALLED, Ordering::SeqCst);
+        if is_closed(prev) {
+            // Waiters have been woken by `close`, and signals are ignored from now on
+            return false;
+        }
+        self.instruments.signalled(is_signalled(prev));
+        if is_signalled(prev) {
+            // Already signalled, the signal is coalesced
+            self.coalesced_signals.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        prev >= WAITER || self.exported.load(Ordering::SeqCst)
+    }
+
+    // Sets the flag again for signals that are still counted, without counting a new signal
+    fn resignal(&self) {
+        let prev = self.state.fetch_or(SIGNALLED, Ordering::SeqCst);
+        if !is_signalled(prev)
+            && !is_closed(prev)
+            && (prev >= WAITER || self.exported.load(Ordering::SeqCst))
+        {
+            self.waker.wake();
+        }
+        self.tasks.wake();
+    }
+
+    fn poll_take(&self) -> Option<WaitResult> {
+        if self.take(Waiter::Unregistered) {
+            Some(WaitResult::Signalled)
+        } else if self.is_closed() {
+            Some(WaitResult::Closed)
+        } else {
+            None
+        }
+    }
+
+    // Blocks on the kernel object. Returns `false` on timeout.
+    fn block(&self, timeout: Option<Duration>) -> bool {
+        if self.yield_to_releases() {
+            return true;
+        }
+        let woken = check_block(self.instruments.block(|| self.kernel_block(timeout)));
+        self.check_external();
+        if woken {
+            self.pass_on_release();
+        }
+        woken
+    }
+
+    // Like `block`, but returns `None` if `interruptible` is set and the blocking call has been
+    // interrupted
+    fn block_with(&self, timeout: Option<Duration>, interruptible: bool) -> Option<bool> {
+        if !interruptible {
+            return Some(self.block(timeout));
+        }
+        if self.yield_to_releases() {
+            return Some(true);
+        }
+        let result = self.instruments.block(|| self.kernel_block(timeout));
+        let woken = match result {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => None,
+            result => Some(check_block(result)),
+        };
+        self.check_external();
+        if woken != Some(false) {
+            self.pass_on_release();
+        }
+        woken
+    }
+
+    // Removes the calling thread from the waiter count after an interrupted blocking call. A
+    // signal or closing the event that raced with the interruption is reported instead.
+    fn interrupted(&self, waiter: Waiter) -> WaitResult {
+        match self.cancel_wait(waiter) {
+            WaitResult::TimedOut => WaitResult::Interrupted,
+            result => result,
+        }
+    }
+
+    #[cfg(not(madsim))]
+    fn kernel_block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        self.waker.block(timeout)
+    }
+
+    #[cfg(madsim)]
+    fn kernel_block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        simulated_block(timeout)
+    }
+
+    // Turns a wakeup by an external source into a signal
+    fn check_external(&self) {
+        if self.waker.take_external() {
+            self.signals.fetch_add(1, Ordering::SeqCst);
+            self.pending.fetch_add(1, Ordering::SeqCst);
+            let prev = self.state.fetch_or(SIGNALLED, Ordering::SeqCst);
+            self.instruments.signalled(is_signalled(prev));
+            if is_signalled(prev) {
+                self.coalesced_signals.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    // Consumes the signal of the first signalled event
+    fn take_any<'a>(mut events: impl Iterator<Item = &'a Self>, waiter: Waiter) -> Option<usize>
+    where
+        W: 'a,
+    {
+        events.position(|event| event.take(waiter))
+    }
+
+    // Removes the calling thread from the waiter count without consuming the signal
+    fn leave(&self, waiter: Waiter) {
+        self.unregister();
+        self.departed(waiter);
+    }
+
+    // Like `leave`, but keeps the calling thread in the count of its epoch
+    fn unregister(&self) {
+        let prev = self.state.fetch_sub(WAITER, Ordering::SeqCst);
+
+        // The calling thread may have consumed the kernel wakeup of a signal that it leaves to
+        // other waiters, or of closing the event, so it is passed on
+        if prev & (SIGNALLED | CLOSED) != 0
+            && (prev - WAITER >= WAITER || self.exported.load(Ordering::SeqCst))
+        {
+            self.waker.wake();
+        }
+    }
+
+    // Consumes the signal if the event is signalled and not closed. If the calling thread is
+    // registered, it is removed from the waiter count on success.
+    fn take(&self, waiter: Waiter) -> bool {
+        self.take_pending(waiter).is_some()
+    }
+
+    // Like `take`, but returns the number of signals that the consumed flag stood for
+    fn take_pending(&self, waiter: Waiter) -> Option<usize> {
+        if !self.clear_signalled(waiter != Waiter::Unregistered) {
+            return None;
+        }
+        self.departed(waiter);
+        self.total_waits.fetch_add(1, Ordering::Relaxed);
+        self.instruments.consumed();
+        // A signal that raced with clearing the flag may be taken along although its flag stays
+        // set. The next consumer then takes a flag without a count.
+        Some(self.pending.swap(0, Ordering::SeqCst))
+    }
+
+    // Like `take`, but without counting the signal as consumed by a wait
+    fn clear_signalled(&self, waiter: bool) -> bool {
+        let waiter = if waiter { WAITER } else { 0 };
+        let mut state = self.state.load(Ordering::SeqCst);
+        loop {
+            if !is_signalled(state) {
+                return false;
+            }
+
+            if self.exported.load(Ordering::SeqCst) {
+                // Consume the pending wakeup before clearing the flag, so that pollers do not see
+                // a stale readiness. A concurrent `signal()` cannot post a new wakeup in between,
+                // as it still observes the flag as set.
+                self.waker.drain();
+            }
+
+            match self.state.compare_exchange_weak(
+                state,
+                (state & !SIGNALLED) - waiter,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    // Removes the calling thread from the waiter count after a timeout. The event may have been
+    // signalled or closed in the meantime, which is reported instead.
+    fn cancel_wait(&self, waiter: Waiter) -> WaitResult {
+        loop {
+            if let Some(result) = self.poll_registered(waiter) {
+                return result;
+            }
+
+            let state = self.state.load(Ordering::SeqCst);
+            if state & (SIGNALLED | CLOSED) == 0
+                && self
+                    .state
+                    .compare_exchange_weak(
+                        state,
+                        state - WAITER,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    )
+                    .is_ok()
+            {
+                self.departed(waiter);
+                return WaitResult::TimedOut;
+            }
+        }
+    }
+}
+
+// The reactor holds the tasks of a watched event until its kernel object becomes ready, which
+// never happens once the event is gone, so it has to prune them
+#[cfg(feature = "reactor")]
+impl<W: KernelWaker> Drop for GenericEvent<W> {
+    fn drop(&mut self) {
+        if self.tasks.watched.load(Ordering::SeqCst) {
+            crate::reactor::release();
+        }
+    }
+}
+
+// Waits on a virtual clock, which wakes the waker from the thread that advances it
+#[cfg(feature = "virtual-time")]
+impl<W: KernelWaker + Sync> GenericEvent<W> {
+    // Like `try_wait_for`, but the timeout is measured on a virtual clock. Does not spin, as the
+    // virtual time does not pass while spinning.
+    pub(crate) fn try_wait_for_virtual(&self, clock: &MockClock, timeout: Duration) -> WaitResult {
+        if timeout.is_zero() || self.is_closed() {
+            return self.try_wait_result();
+        }
+        if self.take(Waiter::Unregistered) {
+            return WaitResult::Signalled;
+        }
+
+        // `None` if the deadline is not representable, which we treat as an infinite timeout
+        let deadline = clock.now().checked_add(timeout);
+
+        let waiter = self.register(true);
+        let mut wait = self.instruments.start_wait(Some(timeout));
+        // Registered before the deadline is checked, so that an advance in between wakes us
+        let registration = clock.register(&self.waker);
+        let result = loop {
+            if let Some(result) = self.poll_registered(waiter) {
+                break result;
+            }
+            if deadline.is_some_and(|deadline| clock.now() >= deadline) {
+                break self.cancel_wait(waiter);
+            }
+
+            self.block(wait.clip(None));
+            wait.check();
+            if failed() {
+                break self.cancel_wait(waiter);
+            }
+        };
+        drop(registration);
+        wait.finish(result == WaitResult::Signalled);
+        result
+    }
+}
+
+// Returns the result of a blocking call, `false` on timeout
+fn check_block(result: io::Result<bool>) -> bool {
+    match result {
+        Ok(woken) => woken,
+        // The caller re-checks the state and the remaining time, just like after a spurious
+        // wakeup
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => true,
+        Err(err) => {
+            unexpected("waiting for the event", err);
+            // Only reached with the `no-panic` feature or in a checked wait, which ends right
+            // away. Otherwise the kernel object is unusable, so the caller polls the state
+            // instead.
+            if !failed() {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            true
+        }
+    }
+}
+
+// The longest timeout that is passed to the kernel. `poll` takes an `int` of milliseconds, which
+// is the smallest limit of all kernel APIs, and a `timespec` for kqueue and futexes overflows
+// beyond `time_t::MAX` seconds. Longer waits block in chunks of this length and re-check the
+// deadline in between.
+const MAX_BLOCK: Duration = Duration::from_millis(i32::MAX as u64);
+
+// Limits a timeout to what the kernel APIs support, see `MAX_BLOCK`. Simulated waits do not reach
+// the kernel and time out as a whole.
+fn chunk(timeout: Option<Duration>) -> Option<Duration> {
+    if cfg!(madsim) {
+        return timeout;
+    }
+    timeout.map(|timeout| timeout.min(MAX_BLOCK))
+}
+
+// How often a waiter re-checks the state of an event whose kernel object failed. With the
+// `no-panic` feature, blocking waits also wake up at this interval, as a wakeup may have been lost
+// to a failed syscall.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Handles an OS error that should not happen, e.g. a failed `write` to an eventfd. `operation`
+/// describes what failed.
+///
+/// Panics, unless the `no-panic` feature is enabled. Then the error is logged with the `log`
+/// feature and otherwise ignored: the caller carries on as if the operation had succeeded, and
+/// waiters fall back to polling the state word. Within [`checked`], the error is recorded instead.
+#[cold]
+#[inline(never)]
+pub(crate) fn unexpected(operation: &str, err: io::Error) {
+    // A checked operation returns the error instead, see `checked`
+    let Some(err) = CHECKED.with_borrow_mut(|checked| {
+        match checked {
+            Some(first) => {
+                first.get_or_insert(err);
+                None
+            }
+            None => Some(err),
+        }
+    }) else {
+        return;
+    };
+
+    #[cfg(not(feature = "no-panic"))]
+    panic!("{} failed with error {}", operation, err);
+
+    #[cfg(all(feature = "no-panic", feature = "log"))]
+    log::error!(
+        target: "nova_autoreset_event",
+        "{} failed with error {}",
+        operation,
+        err
+    );
+
+    #[cfg(all(feature = "no-panic", not(feature = "log")))]
+    let _ = (operation, err);
+}
+
+thread_local! {
+    // The first error of the checked operation that runs on this thread. `None` outside of checked
+    // operations.
+    static CHECKED: RefCell<Option<Option<io::Error>>> = const { RefCell::new(None) };
+}
+
+/// Runs `operation` and returns the first error that it reports via [`unexpected`], instead of
+/// panicking or ignoring it. Blocking waits end early once an error has been reported, see
+/// [`failed`].
+pub(crate) fn checked<T>(operation: impl FnOnce() -> T) -> (T, Option<io::Error>) {
+    // Restores the enclosing checked operation, also if `operation` panics
+    struct Restore(Option<Option<io::Error>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CHECKED.set(self.0.take());
+        }
+    }
+
+    let _restore = Restore(CHECKED.replace(Some(None)));
+    let value = operation();
+    (value, CHECKED.replace(Some(None)).flatten())
+}
+
+/// Returns `true` if the checked operation that runs on this thread has reported an error.
+fn failed() -> bool {
+    CHECKED.with_borrow(|checked| matches!(checked, Some(Some(_))))
+}
+
+// Under madsim, all simulated nodes run on one thread, so blocking it would stall the simulation:
+// there is no other thread that could signal the event in the meantime. Timed waits therefore time
+// out without blocking, and waits without a timeout panic.
+#[cfg(madsim)]
+fn simulated_block(timeout: Option<Duration>) -> io::Result<bool> {
+    if timeout.is_none() {
+        simulation_would_block();
+    }
+    Ok(false)
+}
+
+#[cfg(madsim)]
+#[cold]
+fn simulation_would_block() {
+    panic!(
+        "waiting for an unsignalled event without a timeout would block the madsim simulation forever"
+    )
+}
+
+// Bounds of the spin budget, in iterations
+const MIN_SPIN: u32 = 4;
+const MAX_SPIN: u32 = 4096;
+const INITIAL_SPIN: u32 = 128;
+
+// Spin budget that adapts to the observed signal latency, similar to adaptive mutexes: when a
+// spinning waiter succeeds, the budget moves towards twice the number of iterations it took, when
+// it fails, the budget shrinks.
+#[derive(Debug)]
+struct AdaptiveSpin {
+    budget: AtomicU32,
+}
+
+impl AdaptiveSpin {
+    fn new() -> Self {
+        Self {
+            budget: AtomicU32::new(INITIAL_SPIN),
+        }
+    }
+
+    fn run(&self, mut f: impl FnMut() -> bool) -> bool {
+        if f() {
+            return true;
+        }
+        // Spinning does not change the protocol, it only multiplies the interleavings loom
+        // explores. Under madsim, no other thread can signal the event while spinning.
+        if cfg!(any(loom, madsim)) {
+            return false;
+        }
+
+        let budget = self.budget.load(Ordering::Relaxed);
+        for spins in 1..=budget {
+            hint::spin_loop();
+            if f() {
+                let target = (spins * 2).clamp(MIN_SPIN, MAX_SPIN);
+                self.adjust(budget, target);
+                return true;
+            }
+        }
+
+        self.adjust(budget, MIN_SPIN);
+        false
+    }
+
+    // Moves the budget 1/8th of the way towards `target`. Concurrent updates may get lost, which is
+    // fine for a heuristic.
+    fn adjust(&self, budget: u32, target: u32) {
+        let budget = if target > budget {
+            budget + (target - budget).div_ceil(8)
+        } else {
+            budget - (budget - target) / 8
+        };
+        self.budget.store(budget, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,66 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::{AutoResetEvent, WaitResult};
+
+/// Yields an item for every signal of an [`AutoResetEvent`], as a [`Stream`].
+///
+/// Every item consumes a signal, so signals that arrive while one is pending are coalesced into
+/// a single item. The stream ends once the event has been [closed](AutoResetEvent::close). It is
+/// driven by [`AutoResetEvent::poll_wait`], so it works with any executor, and `next()` is
+/// cancel-safe: a signal is only consumed by the poll that yields its item.
+///
+/// ```
+/// use std::sync::Arc;
+/// use futures_util::StreamExt;
+/// use nova_autoreset_event::{AutoResetEvent, SignalStream};
+///
+/// # futures_executor::block_on(async {
+/// let event = Arc::new(AutoResetEvent::new().unwrap());
+/// let mut signals = SignalStream::new(event.clone());
+///
+/// event.signal();
+/// assert_eq!(signals.next().await, Some(()));
+/// event.close();
+/// assert_eq!(signals.next().await, None);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-core")))]
+#[derive(Debug, Clone)]
+pub struct SignalStream {
+    event: Arc<AutoResetEvent>,
+}
+
+impl SignalStream {
+    /// Creates a stream of the signals of an event.
+    pub fn new(event: Arc<AutoResetEvent>) -> Self {
+        Self { event }
+    }
+
+    /// Returns the event.
+    pub fn event(&self) -> &Arc<AutoResetEvent> {
+        &self.event
+    }
+}
+
+impl Stream for SignalStream {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.event.poll_wait_result(cx).map(|result| {
+            match result {
+                WaitResult::Closed => None,
+                _ => Some(()),
+            }
+        })
+    }
+}
+
+impl FusedStream for SignalStream {
+    fn is_terminated(&self) -> bool {
+        self.event.is_closed()
+    }
+}
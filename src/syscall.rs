@@ -0,0 +1,112 @@
+// The libc calls of the Unix backends.
+//
+// With the `syscall-trace` feature, every call is logged with a summary of its arguments, its
+// result and its duration, at trace level with the target `nova_autoreset_event::syscall`. With the
+// `fault-injection` feature, reads and writes can fail without being made, see `FaultInjector`.
+
+use std::fmt;
+use std::io;
+#[cfg(feature = "syscall-trace")]
+use std::time::{Duration, Instant};
+
+// The return type of a libc call that returns -1 on failure
+pub(crate) trait Ret: Copy + fmt::Display {
+    #[cfg(feature = "fault-injection")]
+    const ZERO: Self;
+
+    fn is_error(self) -> bool;
+}
+
+impl Ret for i32 {
+    #[cfg(feature = "fault-injection")]
+    const ZERO: Self = 0;
+
+    fn is_error(self) -> bool {
+        self == -1
+    }
+}
+
+impl Ret for i64 {
+    #[cfg(feature = "fault-injection")]
+    const ZERO: Self = 0;
+
+    fn is_error(self) -> bool {
+        self == -1
+    }
+}
+
+impl Ret for isize {
+    #[cfg(feature = "fault-injection")]
+    const ZERO: Self = 0;
+
+    fn is_error(self) -> bool {
+        self == -1
+    }
+}
+
+// Makes a libc call. `name` and `args` describe the call for the trace.
+#[inline(always)]
+pub(crate) fn call<T: Ret>(
+    name: &'static str,
+    args: fmt::Arguments<'_>,
+    call: impl FnOnce() -> T,
+) -> io::Result<T> {
+    #[cfg(feature = "syscall-trace")]
+    let start = Instant::now();
+
+    #[cfg(feature = "fault-injection")]
+    let result = crate::fault::syscall(name).unwrap_or_else(|| invoke(call));
+    #[cfg(not(feature = "fault-injection"))]
+    let result = invoke(call);
+
+    #[cfg(feature = "syscall-trace")]
+    trace(name, args, &result, start.elapsed());
+
+    #[cfg(not(feature = "syscall-trace"))]
+    let _ = (name, args);
+
+    result
+}
+
+#[inline(always)]
+fn invoke<T: Ret>(call: impl FnOnce() -> T) -> io::Result<T> {
+    let ret = call();
+    // errno has to be read before anything else can overwrite it
+    if ret.is_error() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+#[cfg(feature = "syscall-trace")]
+const TARGET: &str = "nova_autoreset_event::syscall";
+
+#[cfg(feature = "syscall-trace")]
+#[cold]
+fn trace<T: Ret>(name: &str, args: fmt::Arguments<'_>, result: &io::Result<T>, duration: Duration) {
+    let result = match result {
+        Ok(ret) => ret.to_string(),
+        Err(err) => format!("error: {}", err),
+    };
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        target: TARGET,
+        syscall = name,
+        args = %args,
+        result,
+        duration_ns = duration.as_nanos() as u64,
+        "syscall"
+    );
+
+    #[cfg(not(feature = "tracing"))]
+    log::trace!(
+        target: TARGET,
+        "{}({}) = {} in {:?}",
+        name,
+        args,
+        result,
+        duration
+    );
+}
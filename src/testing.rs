@@ -0,0 +1,94 @@
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::{self, ThreadId};
+
+// Orders the operations of all events
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// An operation on an event, as recorded with the `testing` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub struct Operation {
+    /// The position of the operation in the process-wide sequence of operations on all events.
+    ///
+    /// The number is taken right after the operation, so operations that race with each other may
+    /// be recorded in a different order than they took effect.
+    pub seq: u64,
+    /// The thread that performed the operation.
+    pub thread: ThreadId,
+    /// What happened.
+    pub kind: OperationKind,
+}
+
+/// The kind of an [`Operation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub enum OperationKind {
+    /// The event has been signalled and was unsignalled before.
+    Signal,
+    /// The event has been signalled while it was already signalled, so the signal has been
+    /// coalesced with the pending one.
+    CoalescedSignal,
+    /// A wait consumed the signal of the event.
+    Consume,
+}
+
+// The operation log of an event
+#[derive(Debug, Default)]
+pub(crate) struct OperationLog {
+    operations: Mutex<Vec<Operation>>,
+}
+
+impl OperationLog {
+    pub(crate) fn record(&self, kind: OperationKind) {
+        let operation = Operation {
+            seq: NEXT_SEQ.fetch_add(1, Ordering::SeqCst),
+            thread: thread::current().id(),
+            kind,
+        };
+        self.lock().push(operation);
+    }
+
+    pub(crate) fn operations(&self) -> Vec<Operation> {
+        self.lock().clone()
+    }
+
+    pub(crate) fn assert_no_lost_signals(&self, label: Option<&str>) {
+        let operations = self.lock();
+        let count = |kind| {
+            operations
+                .iter()
+                .filter(|operation| operation.kind == kind)
+                .count()
+        };
+        let signals = count(OperationKind::Signal);
+        let consumed = count(OperationKind::Consume);
+        if signals == consumed {
+            return;
+        }
+
+        let mut message = format!(
+            "event {}: {} signals, but {} consumed",
+            label.unwrap_or("<unlabelled>"),
+            signals,
+            consumed
+        );
+        for operation in operations.iter() {
+            let _ = write!(
+                message,
+                "\n  #{} {:?} on {:?}",
+                operation.seq, operation.kind, operation.thread
+            );
+        }
+        panic!("{}", message);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Operation>> {
+        // A panicking test thread must not hide the log from the teardown checks
+        self.operations
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
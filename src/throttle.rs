@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::waiter::parker;
+use crate::{EventError, TimerWheel};
+
+/// Limits the rate at which permits are handed out.
+///
+/// Permits are spaced evenly: [`acquire`](Self::acquire) blocks until the next permit time, which
+/// is one interval after the previous one. A throttle that has not been used for a while hands out
+/// the next permit right away, but does not accumulate a burst.
+///
+/// The waits block until a timer of the crate's shared [`TimerWheel`] expires at the permit time,
+/// so they share its kernel timer and are not rounded up to the millisecond timeouts of the kernel
+/// APIs on Linux and Windows.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use nova_autoreset_event::Throttle;
+///
+/// let throttle = Throttle::new(100).unwrap();
+/// let start = Instant::now();
+/// for _ in 0..3 {
+///     throttle.acquire();
+/// }
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// ```
+pub struct Throttle {
+    interval: Duration,
+    // The earliest time at which the next permit is handed out, `None` before the first one
+    next: Mutex<Option<Instant>>,
+    wheel: &'static TimerWheel,
+}
+
+impl Throttle {
+    /// Creates a throttle that hands out `rate` permits per second.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    pub fn new(rate: u32) -> Result<Self, EventError> {
+        assert!(rate > 0, "the rate of a throttle must not be zero");
+        Self::with_interval(Duration::from_secs(1) / rate)
+    }
+
+    /// Creates a throttle that hands out a permit every `interval`.
+    ///
+    /// Fails if the shared timer wheel cannot be created.
+    pub fn with_interval(interval: Duration) -> Result<Self, EventError> {
+        Ok(Self {
+            interval,
+            next: Mutex::new(None),
+            wheel: TimerWheel::shared()?,
+        })
+    }
+
+    /// Returns the interval between two permits.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Blocks until the next permit time and returns it.
+    ///
+    /// Concurrent callers are handed consecutive permits in the order in which they reserve them.
+    pub fn acquire(&self) -> Instant {
+        let permit = {
+            let mut next = self.lock();
+            let permit = next.map_or_else(Instant::now, |next| next.max(Instant::now()));
+            *next = Some(self.after(permit));
+            permit
+        };
+
+        if Instant::now() < permit {
+            // The wheel signals the event once the permit time has passed
+            let timer = parker();
+            self.wheel.signal_at(&timer, permit);
+            timer.wait();
+        }
+        permit
+    }
+
+    /// Takes a permit if one is available right away.
+    pub fn try_acquire(&self) -> bool {
+        let mut next = self.lock();
+        let now = Instant::now();
+        if next.is_some_and(|next| next > now) {
+            return false;
+        }
+        *next = Some(self.after(now));
+        true
+    }
+
+    fn after(&self, permit: Instant) -> Instant {
+        // Far beyond any practical deadline
+        permit
+            .checked_add(self.interval)
+            .unwrap_or_else(|| permit + Duration::from_secs(100 * 365 * 24 * 60 * 60))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.next.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for Throttle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Throttle")
+            .field("interval", &self.interval)
+            .field("next", &*self.lock())
+            .finish_non_exhaustive()
+    }
+}
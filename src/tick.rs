@@ -0,0 +1,51 @@
+use std::io;
+use std::iter;
+use std::sync::Arc;
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+
+use crate::AutoResetEvent;
+use crate::pool::{Pool, Worker};
+
+// The forwarder threads, which never exit
+static FORWARDERS: Pool<Tick> = Pool::new("nova-autoreset-event-tick", forward);
+
+struct Tick {
+    event: Arc<AutoResetEvent>,
+    sender: Sender<()>,
+}
+
+pub(crate) fn tick_receiver(event: Arc<AutoResetEvent>) -> io::Result<Receiver<()>> {
+    // A bound of one coalesces the signals that arrive while a tick is pending
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    FORWARDERS.add(Tick { event, sender })?;
+    Ok(receiver)
+}
+
+// The loop of a forwarder thread
+fn forward(forwarder: Arc<Worker<Tick>>) {
+    let mut ticks = Vec::new();
+    loop {
+        forwarder.take_added(&mut ticks);
+
+        let events: Vec<&AutoResetEvent> = iter::once(&forwarder.control)
+            .chain(ticks.iter().map(|tick: &Tick| &*tick.event))
+            .collect();
+        let index = match AutoResetEvent::wait_any(&events) {
+            0 => continue,
+            index => index - 1,
+        };
+
+        // A closed event disconnects the channel, and a dropped receiver ends the forwarding
+        let tick = &ticks[index];
+        if tick.event.is_closed()
+            || matches!(
+                tick.sender.try_send(()),
+                Err(TrySendError::Disconnected(()))
+            )
+        {
+            ticks.swap_remove(index);
+            forwarder.removed(1);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::linux::KernelTimer;
+use crate::state::unexpected;
+#[cfg(windows)]
+use crate::windows::KernelTimer;
+use crate::{AutoResetEvent, EventError, EventOperation};
+
+// The wheel shared by the helpers of the crate, which is created on demand and never dropped
+static SHARED: Mutex<Option<&'static TimerWheel>> = Mutex::new(None);
+
+/// Signals events at their deadlines, for many pending timeouts at once.
+///
+/// A single service thread keeps the timers ordered by deadline and arms one kernel timer for the
+/// earliest one, so thousands of timeouts, e.g. of network connections, cost one blocked thread and
+/// one kernel timer instead of a kernel timer or a timed wait each. A thread that waits for an
+/// event, possibly together with others via [`AutoResetEvent::wait_any`], then also wakes up when
+/// the event's timer expires.
+///
+/// Timers are signalled in the order of their deadlines, not before. Scheduling and cancelling a
+/// timer takes `O(log n)`. The kernel timer is a timerfd on Linux, with a resolution of
+/// nanoseconds, and a high-resolution waitable timer on Windows, with a resolution of 100 ns. On
+/// other platforms, the service thread sleeps in timed waits, whose timeouts are rounded up to whole
+/// milliseconds.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use nova_autoreset_event::{AutoResetEvent, TimerWheel};
+///
+/// let wheel = TimerWheel::new().unwrap();
+/// let timeout = Arc::new(AutoResetEvent::new().unwrap());
+///
+/// let timer = wheel.signal_after(&timeout, Duration::from_millis(10));
+/// timeout.wait();
+/// assert!(!timer.cancel());
+/// ```
+pub struct TimerWheel {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// A timer that has been scheduled on a [`TimerWheel`].
+///
+/// Dropping the handle does not cancel the timer.
+pub struct Timer {
+    key: (Instant, u64),
+    shared: Weak<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    // Signalled when the earliest deadline has changed or the wheel is dropped
+    control: AutoResetEvent,
+    // Expires at the earliest deadline
+    timer: KernelTimer,
+}
+
+#[derive(Default)]
+struct State {
+    // Keyed by deadline and a sequence number, which keeps timers with the same deadline apart
+    timers: BTreeMap<(Instant, u64), Arc<AutoResetEvent>>,
+    next_seq: u64,
+    stopped: bool,
+}
+
+impl TimerWheel {
+    /// Creates a timer wheel and starts its service thread.
+    ///
+    /// Fails if the kernel objects of the wheel cannot be created, or if its thread cannot be
+    /// spawned.
+    pub fn new() -> Result<Self, EventError> {
+        let control = AutoResetEvent::new()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            timer: KernelTimer::new(&control)?,
+            control,
+        });
+        let backend = shared.control.backend();
+        let thread = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("nova-autoreset-event-timer".into())
+                .spawn(move || shared.run())
+                .map_err(|err| EventError::new(EventOperation::Create, backend, err))?
+        };
+
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    // Returns the wheel shared by the helpers of the crate, e.g. `DebouncedSignaler`
+    pub(crate) fn shared() -> Result<&'static TimerWheel, EventError> {
+        let mut shared = SHARED.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(wheel) = *shared {
+            return Ok(wheel);
+        }
+        let wheel = Box::leak(Box::new(TimerWheel::new()?));
+        *shared = Some(wheel);
+        Ok(wheel)
+    }
+
+    /// Signals `event` at `deadline`. A deadline in the past signals it right away.
+    pub fn signal_at(&self, event: &Arc<AutoResetEvent>, deadline: Instant) -> Timer {
+        let mut state = self.shared.lock();
+        let key = (deadline, state.next_seq);
+        state.next_seq += 1;
+
+        let earliest = state
+            .timers
+            .first_key_value()
+            .is_none_or(|(first, _)| key < *first);
+        state.timers.insert(key, event.clone());
+        drop(state);
+
+        if earliest {
+            self.shared.control.signal();
+        }
+        Timer {
+            key,
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    /// Signals `event` once `timeout` has elapsed.
+    ///
+    /// A timeout that cannot be represented as an [`Instant`] never expires, but the timer can
+    /// still be cancelled.
+    pub fn signal_after(&self, event: &Arc<AutoResetEvent>, timeout: Duration) -> Timer {
+        let now = Instant::now();
+        // Far beyond any practical deadline
+        let deadline = now
+            .checked_add(timeout)
+            .unwrap_or_else(|| now + Duration::from_secs(100 * 365 * 24 * 60 * 60));
+        self.signal_at(event, deadline)
+    }
+
+    /// Returns the number of pending timers.
+    pub fn len(&self) -> usize {
+        self.shared.lock().timers.len()
+    }
+
+    /// Returns `true` if no timer is pending.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Timer {
+    /// Returns the deadline of the timer.
+    pub fn deadline(&self) -> Instant {
+        self.key.0
+    }
+
+    /// Cancels the timer. Returns `false` if it has already expired, has been cancelled, or if
+    /// the wheel has been dropped.
+    pub fn cancel(&self) -> bool {
+        match self.shared.upgrade() {
+            Some(shared) => shared.lock().timers.remove(&self.key).is_some(),
+            None => false,
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn run(&self) {
+        let mut expired = Vec::new();
+        loop {
+            let now = Instant::now();
+            let next = {
+                let mut state = self.lock();
+                if state.stopped {
+                    return;
+                }
+                while let Some(entry) = state.timers.first_entry() {
+                    if entry.key().0 > now {
+                        break;
+                    }
+                    expired.push(entry.remove());
+                }
+                state.timers.first_key_value().map(|(key, _)| key.0)
+            };
+
+            // Signalled without holding the lock, in the order of the deadlines
+            for event in expired.drain(..) {
+                event.signal();
+            }
+            match next {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    if let Err(err) = self.timer.wait(&self.control, timeout) {
+                        unexpected("waiting for the timer", err);
+                        // Only reached with the `no-panic` feature
+                        self.control.try_wait_for(timeout);
+                    }
+                }
+                None => self.control.wait(),
+            }
+        }
+    }
+}
+
+impl Drop for TimerWheel {
+    fn drop(&mut self) {
+        self.shared.lock().stopped = true;
+        self.shared.control.signal();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl fmt::Debug for TimerWheel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerWheel")
+            .field("timers", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timer")
+            .field("deadline", &self.key.0)
+            .finish_non_exhaustive()
+    }
+}
+
+// Sleeps in timed waits on the control event, on platforms without a kernel timer
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+#[derive(Debug)]
+struct KernelTimer;
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+impl KernelTimer {
+    fn new(_control: &AutoResetEvent) -> Result<Self, EventError> {
+        Ok(Self)
+    }
+
+    fn wait(&self, control: &AutoResetEvent, timeout: Duration) -> io::Result<()> {
+        control.try_wait_for(timeout);
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+use crate::AutoResetEvent;
+
+/// A wait that has been prepared via
+/// [`AutoResetEvent::prepare_wait`](crate::AutoResetEvent::prepare_wait).
+///
+/// The token keeps the calling thread registered as a waiter, so that a signal wakes the kernel
+/// object of the event. Pass it to [`commit_wait`](crate::AutoResetEvent::commit_wait) or
+/// [`cancel_wait`](crate::AutoResetEvent::cancel_wait) of the same event; dropping it cancels the
+/// wait.
+#[must_use = "dropping a wait token cancels the wait"]
+pub struct WaitToken<'a> {
+    pub(crate) event: &'a AutoResetEvent,
+    pub(crate) ready: bool,
+}
+
+impl WaitToken<'_> {
+    /// Returns `true` if the event was already signalled or closed when the wait was prepared.
+    ///
+    /// The event loop should not block then, but commit the wait right away.
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+}
+
+impl Drop for WaitToken<'_> {
+    fn drop(&mut self) {
+        self.event.inner.abandon_wait();
+    }
+}
+
+impl fmt::Debug for WaitToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitToken")
+            .field("event", &self.event.label())
+            .field("ready", &self.ready)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,82 @@
+// Batched eventfd writes via io_uring, used by `AutoResetEvent::signal_many`, and the poll entries
+// of `AutoResetEvent::uring_poll_entry`.
+
+use std::cell::RefCell;
+use std::io;
+use std::os::fd::RawFd;
+
+use io_uring::{IoUring, opcode, squeue, types};
+
+/// The number of eventfds that are written with a single `io_uring_enter` call.
+pub(crate) const BATCH_SIZE: usize = 64;
+
+// The value written to each eventfd. It is static, so that it outlives every submitted write.
+static ONE: u64 = 1;
+
+thread_local! {
+    // `None` if io_uring is not available, e.g. because of seccomp filters
+    static RING: RefCell<Option<IoUring>> = RefCell::new(IoUring::new(BATCH_SIZE as u32).ok());
+}
+
+/// Writes `1` to each of the eventfds. Returns `false` if io_uring cannot be used, in which case
+/// the caller has to fall back to plain `write` calls. Some of the fds may have been written
+/// already at that point, which only causes spurious wakeups.
+pub(crate) fn write_eventfds(fds: &[RawFd]) -> bool {
+    debug_assert!(fds.len() <= BATCH_SIZE);
+    if fds.is_empty() {
+        return true;
+    }
+
+    RING.with(|ring| {
+        let mut ring = ring.borrow_mut();
+        let Some(uring) = ring.as_mut() else {
+            return false;
+        };
+
+        if submit(uring, fds).is_err() {
+            // Unsubmitted entries must never reach the kernel, as the fds might be closed later
+            *ring = None;
+            return false;
+        }
+        true
+    })
+}
+
+/// Builds a multishot poll for the readability of the fd of an event.
+pub(crate) fn poll_entry(fd: RawFd) -> squeue::Entry {
+    opcode::PollAdd::new(types::Fd(fd), libc::POLLIN as u32)
+        .multi(true)
+        .build()
+}
+
+fn submit(ring: &mut IoUring, fds: &[RawFd]) -> io::Result<()> {
+    {
+        let mut submission = ring.submission();
+        for fd in fds {
+            let entry = opcode::Write::new(
+                types::Fd(*fd),
+                &ONE as *const u64 as *const u8,
+                std::mem::size_of::<u64>() as u32,
+            )
+            .build();
+
+            // The ring has room for a full batch and is empty between calls
+            unsafe { submission.push(&entry) }.map_err(io::Error::other)?;
+        }
+    }
+
+    ring.submit_and_wait(fds.len())?;
+
+    for entry in ring.completion() {
+        let res = entry.result();
+        if res < 0 {
+            // The counter can only overflow if nobody ever consumes it, in which case the
+            // eventfd is readable anyway
+            let err = io::Error::from_raw_os_error(-res);
+            if err.kind() != io::ErrorKind::WouldBlock {
+                crate::state::unexpected("write", err);
+            }
+        }
+    }
+    Ok(())
+}
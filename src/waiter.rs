@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::AutoResetEvent;
+
+thread_local! {
+    // The event that a thread blocks on in a `WaiterQueue`, or until a timer of the crate expires.
+    // It is only signalled while the thread is queued or its timer is pending, and each signal is
+    // consumed before the operation that waits for it returns.
+    static PARKER: Arc<AutoResetEvent> = Arc::new(
+        AutoResetEvent::new().unwrap_or_else(|err| panic!("{}", err))
+    );
+}
+
+// Returns the event of the calling thread
+pub(crate) fn parker() -> Arc<AutoResetEvent> {
+    PARKER.with(Arc::clone)
+}
+
+// A FIFO queue of blocked threads, each of which waits on an event of its own. `S` is state that
+// is protected by the lock of the queue, e.g. the permit of an `IdleSet`.
+pub(crate) struct WaiterQueue<S = ()> {
+    inner: Mutex<Waiters<S>>,
+}
+
+pub(crate) struct Waiters<S> {
+    queue: VecDeque<Arc<AutoResetEvent>>,
+    pub(crate) state: S,
+}
+
+impl<S> WaiterQueue<S> {
+    pub(crate) const fn new(state: S) -> Self {
+        Self {
+            inner: Mutex::new(Waiters {
+                queue: VecDeque::new(),
+                state,
+            }),
+        }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Waiters<S>> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Waits for at most `timeout` on an event returned by `Waiters::push`. Returns `false` if the
+    // wait timed out, in which case the event is no longer queued.
+    pub(crate) fn wait_timeout(&self, waiter: &Arc<AutoResetEvent>, timeout: Duration) -> bool {
+        waiter.try_wait_for(timeout) || !self.remove(waiter)
+    }
+
+    // Removes a waiter that timed out from the queue. Returns `false` if it has been woken in the
+    // meantime, in which case its signal is consumed.
+    fn remove(&self, waiter: &Arc<AutoResetEvent>) -> bool {
+        let mut waiters = self.lock();
+        match waiters
+            .queue
+            .iter()
+            .position(|queued| Arc::ptr_eq(queued, waiter))
+        {
+            Some(index) => {
+                waiters.queue.remove(index);
+                true
+            }
+            None => {
+                drop(waiters);
+                // The waker has signalled the event under the lock
+                waiter.wait();
+                false
+            }
+        }
+    }
+}
+
+impl<S: Default> Default for WaiterQueue<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S> Waiters<S> {
+    // Queues the event of the calling thread and returns it
+    pub(crate) fn push(&mut self) -> Arc<AutoResetEvent> {
+        let waiter = parker();
+        self.queue.push_back(waiter.clone());
+        waiter
+    }
+
+    // Wakes the thread that has been waiting the longest. Returns `false` if no thread is waiting.
+    pub(crate) fn wake_one(&mut self) -> bool {
+        // Signalled under the lock, so that a timed out waiter that is no longer queued knows that
+        // the signal is on its way
+        match self.queue.pop_front() {
+            Some(waiter) => {
+                waiter.signal();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Wakes all waiting threads and returns their number
+    pub(crate) fn wake_all(&mut self) -> usize {
+        let events: Vec<&AutoResetEvent> = self.queue.iter().map(|waiter| &**waiter).collect();
+        AutoResetEvent::signal_many(&events);
+        let woken = events.len();
+        self.queue.clear();
+        woken
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
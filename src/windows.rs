@@ -7,121 +7,350 @@ use std::os::windows::io::{
 use std::ptr;
 use std::time::Duration;
 
-use winapi::shared::minwindef::{FALSE, TRUE};
+use winapi::shared::minwindef::{DWORD, FALSE, TRUE};
+use winapi::shared::ntdef::LARGE_INTEGER;
 use winapi::shared::winerror::WAIT_TIMEOUT;
-use winapi::um::errhandlingapi::GetLastError;
 use winapi::um::handleapi::INVALID_HANDLE_VALUE;
-use winapi::um::synchapi::{CreateEventW, SetEvent, WaitForSingleObject};
-use winapi::um::winbase::WAIT_OBJECT_0;
-use winapi::um::winnt::HANDLE;
+use winapi::um::processthreadsapi::{
+    GetCurrentThread, GetThreadPriorityBoost, SetThreadPriorityBoost,
+};
+use winapi::um::synchapi::{
+    CreateEventW, CreateWaitableTimerExW, SetEvent, SetWaitableTimer, WaitForMultipleObjects,
+    WaitForSingleObject,
+};
+use winapi::um::winbase::{INFINITE, WAIT_FAILED, WAIT_OBJECT_0};
+use winapi::um::winnt::{HANDLE, MAXIMUM_WAIT_OBJECTS, TIMER_ALL_ACCESS};
+
+use crate::state::{KernelObject, KernelWaker, unexpected};
+use crate::{AutoResetEvent, Backend, Builder, EventError, EventOperation};
+
+pub(crate) type Waker = EventHandle;
+
+pub(crate) const BACKENDS: &[Backend] = &[Backend::Win32Event];
+
+pub(crate) fn waker(builder: &Builder, _backend: Backend) -> io::Result<Waker> {
+    EventHandle::create(builder.priority_boost)
+}
+
+pub(crate) fn backend(_waker: &Waker) -> Backend {
+    Backend::Win32Event
+}
+
+pub(crate) fn round_trip(_backend: Backend) -> io::Result<Duration> {
+    crate::backend::round_trip::<EventHandle>(None)
+}
 
-/// An autoreset event.
-///
-/// See the [module-level documentation](..) for more information.
+// The kernel side of the event: a Win32 autoreset event
 #[derive(Debug)]
-pub struct AutoResetEvent {
+pub(crate) struct EventHandle {
     handle: OwnedHandle,
+    // Priority boost of the blocked thread, `None` to leave it untouched
+    priority_boost: Option<bool>,
 }
 
-impl AutoResetEvent {
-    /// Creates a new autoreset event.
-    pub fn new() -> io::Result<Self> {
+impl KernelObject for EventHandle {
+    type Config = Option<bool>;
+
+    fn create(priority_boost: Option<bool>) -> io::Result<Self> {
         let handle = unsafe { CreateEventW(ptr::null_mut(), FALSE, FALSE, ptr::null()) };
 
-        if handle == ptr::null_mut() || handle == INVALID_HANDLE_VALUE {
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
             Err(io::Error::last_os_error())
         } else {
             Ok(Self {
                 handle: unsafe { OwnedHandle::from_raw_handle(handle as RawHandle) },
+                priority_boost,
             })
         }
     }
+}
 
-    /// Waits for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return immediately and
-    /// reset the event to the unsignalled state. Otherwise, it will block until another thread
-    /// signals the event.
-    pub fn wait(&self) {
-        let res = unsafe { WaitForSingleObject(self.handle.as_raw_handle() as HANDLE, u32::MAX) };
-
-        if res != WAIT_OBJECT_0 {
-            // This should not happen
-            let err = unsafe { GetLastError() };
-            panic!("WaitForSingleObject failed with error {}", err);
+impl EventHandle {
+    // Takes ownership of a handle created by `create`
+    #[cfg(feature = "ffi")]
+    pub(crate) unsafe fn from_raw_handle(handle: RawHandle) -> Self {
+        Self {
+            handle: unsafe { OwnedHandle::from_raw_handle(handle) },
+            priority_boost: None,
         }
     }
 
-    /// Tries to wait for the event to be signalled.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. Otherwise, it will return `false` immediately.
-    pub fn try_wait(&self) -> bool {
-        let res = unsafe { WaitForSingleObject(self.handle.as_raw_handle() as HANDLE, 0) };
-
-        if res == WAIT_OBJECT_0 {
-            true
-        } else if res == WAIT_TIMEOUT {
-            false
-        } else {
-            // This should not happen
-            let err = unsafe { GetLastError() };
-            panic!("WaitForSingleObject failed with error {}", err);
-        }
+    #[cfg(feature = "ffi")]
+    pub(crate) fn into_raw_handle(self) -> RawHandle {
+        std::os::windows::io::IntoRawHandle::into_raw_handle(self.handle)
     }
 
-    /// Tries to wait for the event to be signalled for a specified duration.
-    ///
-    /// If the event is already in the signalled state, this function will return `true` immediately
-    /// and reset the event to the unsignalled state. If the event is signalled within the timeout,
-    /// it will return `true`. Otherwise, it will return `false`.
-    pub fn try_wait_for(&self, timeout: Duration) -> bool {
-        let millis = timeout.as_millis().min(u32::MAX as u128) as u32;
+    fn wait_for(&self, millis: u32) -> io::Result<bool> {
         let res = unsafe { WaitForSingleObject(self.handle.as_raw_handle() as HANDLE, millis) };
 
         if res == WAIT_OBJECT_0 {
-            true
+            Ok(true)
         } else if res == WAIT_TIMEOUT {
-            false
+            Ok(false)
         } else {
-            // This should not happen
-            let err = unsafe { GetLastError() };
-            panic!("WaitForSingleObject failed with error {}", err);
+            Err(io::Error::last_os_error())
         }
     }
+}
 
-    /// Signals the event.
-    ///
-    /// If there is a thread waiting on the event, it will be woken up and the event will be reset
-    /// to the unsignalled state. If there are no threads waiting, the event will remain in the
-    /// signalled state until a thread waits on it.
-    pub fn signal(&self) {
+impl AsHandle for EventHandle {
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.handle.as_handle()
+    }
+}
+
+impl KernelWaker for EventHandle {
+    fn wake(&self) {
         let res = unsafe { SetEvent(self.handle.as_raw_handle() as HANDLE) };
 
         if res != TRUE {
             // This should not happen
-            let err = unsafe { GetLastError() };
-            panic!("SetEvent failed with error {}", err);
+            unexpected("SetEvent", io::Error::last_os_error());
+        }
+    }
+
+    fn block(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        with_priority_boost(self.priority_boost, || self.wait_for(wait_millis(timeout)))
+    }
+
+    fn block_any<'a>(
+        handles: impl Iterator<Item = &'a Self> + Clone,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        let priority_boost = handles
+            .clone()
+            .next()
+            .and_then(|handle| handle.priority_boost);
+
+        let mut raw = [ptr::null_mut(); MAXIMUM_WAIT_OBJECTS as usize];
+        let mut count = 0;
+        for handle in handles {
+            assert!(
+                count < raw.len(),
+                "cannot wait for more than {} events",
+                MAXIMUM_WAIT_OBJECTS
+            );
+            raw[count] = handle.handle.as_raw_handle() as HANDLE;
+            count += 1;
+        }
+
+        with_priority_boost(priority_boost, || {
+            let res = unsafe {
+                WaitForMultipleObjects(count as u32, raw.as_ptr(), FALSE, wait_millis(timeout))
+            };
+
+            if res < WAIT_OBJECT_0 + count as u32 {
+                Ok(true)
+            } else if res == WAIT_TIMEOUT {
+                Ok(false)
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        })
+    }
+
+    fn drain(&self) {
+        if let Err(err) = self.wait_for(0) {
+            // This should not happen
+            unexpected("WaitForSingleObject", err);
         }
     }
 }
 
-impl AsRawHandle for AutoResetEvent {
-    fn as_raw_handle(&self) -> RawHandle {
-        self.handle.as_raw_handle()
+/// Blocks until one of the handles is signalled or until the timeout elapses, and returns the
+/// indices of the signalled handles. Their wakeups are not consumed.
+#[cfg(feature = "reactor")]
+pub(crate) fn wait_ready(
+    handles: &[BorrowedHandle<'_>],
+    timeout: Option<Duration>,
+) -> io::Result<Vec<usize>> {
+    let raw: Vec<HANDLE> = handles
+        .iter()
+        .map(|handle| handle.as_raw_handle() as HANDLE)
+        .collect();
+    let count = raw.len() as u32;
+
+    let res = unsafe { WaitForMultipleObjects(count, raw.as_ptr(), FALSE, wait_millis(timeout)) };
+    if res < WAIT_OBJECT_0 + count {
+        let index = (res - WAIT_OBJECT_0) as usize;
+        // The wait has reset the autoreset event, so the wakeup is passed on to its waiters
+        unsafe { SetEvent(raw[index]) };
+        Ok(vec![index])
+    } else if res == WAIT_TIMEOUT {
+        Ok(Vec::new())
+    } else {
+        Err(io::Error::last_os_error())
     }
 }
 
-impl AsHandle for AutoResetEvent {
-    fn as_handle(&self) -> BorrowedHandle<'_> {
-        self.handle.as_handle()
+// Converts a timeout to the argument of the wait functions. It is rounded up to whole milliseconds,
+// so that a wait never times out early and a sub-millisecond timeout still blocks.
+fn wait_millis(timeout: Option<Duration>) -> u32 {
+    match timeout {
+        Some(timeout) => {
+            timeout
+                .as_nanos()
+                .div_ceil(1_000_000)
+                .min((INFINITE - 1) as u128) as u32
+        }
+        None => INFINITE,
     }
 }
 
-// It is safe to send an autoreset event to another thread. The underlying handle is a kernel
-// object that can be used from any thread.
-unsafe impl Send for AutoResetEvent {}
+// Runs a wait with the priority boost of the current thread enabled or disabled, `None` to leave
+// it untouched
+fn with_priority_boost<R>(enable: Option<bool>, wait: impl FnOnce() -> R) -> R {
+    let Some(enable) = enable else {
+        return wait();
+    };
+
+    // The boost is applied when the wait is satisfied, so the setting of the thread only needs to
+    // be changed for the duration of the wait
+    let thread = unsafe { GetCurrentThread() };
+    let mut disabled = FALSE;
+    let disable = if enable { FALSE } else { TRUE };
+    let changed = unsafe { GetThreadPriorityBoost(thread, &mut disabled) } != FALSE
+        && disabled != disable
+        && unsafe { SetThreadPriorityBoost(thread, disable) } != FALSE;
+
+    let res = wait();
+
+    if changed {
+        unsafe { SetThreadPriorityBoost(thread, disabled) };
+    }
+    res
+}
+
+// Watches a file or directory for changes via `FindFirstChangeNotificationW`, see `FsWatchEvent`
+#[cfg(feature = "fs-watch")]
+pub(crate) struct PathWatch {
+    change: HANDLE,
+    // A manual-reset event that is set to stop the watch
+    stop: OwnedHandle,
+}
+
+// The change notification handle may be used from any thread
+#[cfg(feature = "fs-watch")]
+unsafe impl Send for PathWatch {}
+#[cfg(feature = "fs-watch")]
+unsafe impl Sync for PathWatch {}
 
-// It is safe to share an autoreset event between threads. The underlying handle is a kernel
-// object that is thread-safe.
-unsafe impl Sync for AutoResetEvent {}
+#[cfg(feature = "fs-watch")]
+impl PathWatch {
+    pub(crate) fn new(path: &std::path::Path) -> io::Result<Self> {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::fileapi::FindFirstChangeNotificationW;
+        use winapi::um::winnt::{
+            FILE_NOTIFY_CHANGE_ATTRIBUTES, FILE_NOTIFY_CHANGE_DIR_NAME,
+            FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_CHANGE_SIZE,
+        };
+
+        let stop = unsafe { CreateEventW(ptr::null_mut(), TRUE, FALSE, ptr::null()) };
+        if stop.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let stop = unsafe { OwnedHandle::from_raw_handle(stop as RawHandle) };
+
+        // Only directories can be watched, so a file is watched via its directory
+        let directory = match path.parent() {
+            _ if path.is_dir() => path,
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let directory: Vec<u16> = directory.as_os_str().encode_wide().chain(Some(0)).collect();
+        let filter = FILE_NOTIFY_CHANGE_FILE_NAME
+            | FILE_NOTIFY_CHANGE_DIR_NAME
+            | FILE_NOTIFY_CHANGE_ATTRIBUTES
+            | FILE_NOTIFY_CHANGE_SIZE
+            | FILE_NOTIFY_CHANGE_LAST_WRITE;
+        let change = unsafe { FindFirstChangeNotificationW(directory.as_ptr(), FALSE, filter) };
+        if change == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { change, stop })
+    }
+
+    /// Blocks until the directory changes or the watch is stopped. Returns `false` once it has
+    /// been stopped.
+    pub(crate) fn wait(&self) -> io::Result<bool> {
+        use winapi::um::fileapi::FindNextChangeNotification;
+
+        let handles = [self.change, self.stop.as_raw_handle() as HANDLE];
+        let res = unsafe { WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE) };
+        if res == WAIT_OBJECT_0 + 1 {
+            Ok(false)
+        } else if res == WAIT_OBJECT_0 {
+            if unsafe { FindNextChangeNotification(self.change) } == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(true)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// Makes the current and all later calls of `wait` return `false`.
+    pub(crate) fn stop(&self) {
+        unsafe { SetEvent(self.stop.as_raw_handle() as HANDLE) };
+    }
+}
+
+#[cfg(feature = "fs-watch")]
+impl Drop for PathWatch {
+    fn drop(&mut self) {
+        unsafe { winapi::um::fileapi::FindCloseChangeNotification(self.change) };
+    }
+}
+
+// Not exported by winapi. Available since Windows 10, version 1803.
+const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: DWORD = 0x0000_0002;
+
+// A waitable timer, which expires with a resolution of 100 ns where high-resolution timers are
+// available, see `TimerWheel`
+#[derive(Debug)]
+pub(crate) struct KernelTimer(OwnedHandle);
+
+impl KernelTimer {
+    pub(crate) fn new(control: &AutoResetEvent) -> Result<Self, EventError> {
+        let create = |flags| unsafe {
+            CreateWaitableTimerExW(ptr::null_mut(), ptr::null(), flags, TIMER_ALL_ACCESS)
+        };
+        let mut handle = create(CREATE_WAITABLE_TIMER_HIGH_RESOLUTION);
+        if handle.is_null() {
+            // Older versions only have timers with the resolution of the system timer
+            handle = create(0);
+        }
+        if handle.is_null() {
+            return Err(EventError::new(
+                EventOperation::Create,
+                control.backend(),
+                io::Error::last_os_error(),
+            ));
+        }
+        Ok(Self(unsafe {
+            OwnedHandle::from_raw_handle(handle as RawHandle)
+        }))
+    }
+
+    // Blocks until `control` is signalled or `timeout` has elapsed, and consumes the signal of
+    // `control`
+    pub(crate) fn wait(&self, control: &AutoResetEvent, timeout: Duration) -> io::Result<()> {
+        let timer = self.0.as_raw_handle() as HANDLE;
+        // Relative due times are negative, in units of 100 ns
+        let ticks = timeout.as_nanos().div_ceil(100).clamp(1, i64::MAX as u128) as i64;
+        let mut due: LARGE_INTEGER = unsafe { std::mem::zeroed() };
+        unsafe { *due.QuadPart_mut() = -ticks };
+        if unsafe { SetWaitableTimer(timer, &due, 0, None, ptr::null_mut(), FALSE) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let handles = [control.as_raw_handle() as HANDLE, timer];
+        let res = unsafe { WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE) };
+        if res == WAIT_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        // The wait consumed the wakeup of the kernel object, not the signal of the event
+        control.try_wait();
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Waiting for events from Tokio tasks via `AsyncAutoResetEvent`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(unix, feature = "tokio", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AsyncAutoResetEvent, AutoResetEvent, WaitResult};
+
+#[tokio::test]
+async fn wait_consumes_signals() {
+    let event = Arc::new(AsyncAutoResetEvent::new().unwrap());
+
+    for _ in 0..3 {
+        assert!(!event.try_wait());
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        event.wait().await;
+        signaller.join().unwrap();
+    }
+
+    // A signal before the wait is consumed without waiting for the reactor
+    event.event().signal();
+    event.wait().await;
+    assert!(!event.try_wait());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn each_signal_wakes_one_task() {
+    let event = Arc::new(AsyncAutoResetEvent::new().unwrap());
+    let tasks: Vec<_> = (0..2)
+        .map(|_| {
+            let event = event.clone();
+            tokio::spawn(async move { event.wait().await })
+        })
+        .collect();
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            }
+        })
+    };
+    signaller.join().unwrap();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert!(!event.try_wait());
+}
+
+#[tokio::test]
+async fn close_ends_wait() {
+    let event = Arc::new(AsyncAutoResetEvent::from_event(AutoResetEvent::new().unwrap()).unwrap());
+    let closer = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.event().close();
+        })
+    };
+    assert_eq!(event.wait_result().await, WaitResult::Closed);
+    closer.join().unwrap();
+
+    let event = Arc::into_inner(event).unwrap().into_event();
+    assert!(event.is_closed());
+}
+
+#[tokio::test]
+async fn wait_is_cancel_safe() {
+    let event = AsyncAutoResetEvent::new().unwrap();
+    let other = AsyncAutoResetEvent::new().unwrap();
+
+    other.signal();
+    tokio::select! {
+        biased;
+        _ = other.wait() => {}
+        _ = event.wait() => panic!("the event has not been signalled"),
+    }
+
+    // The wait that lost the select did not consume this signal
+    event.signal();
+    tokio::select! {
+        biased;
+        _ = other.wait() => panic!("the other event has not been signalled"),
+        _ = event.wait() => {}
+    }
+    assert!(!event.try_wait());
+}
+
+#[tokio::test]
+async fn wait_timeout() {
+    let event = Arc::new(AsyncAutoResetEvent::new().unwrap());
+    let start = Instant::now();
+    assert!(!event.wait_timeout(Duration::from_millis(20)).await);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+    assert!(
+        event
+            .wait_deadline(Instant::now() + Duration::from_secs(10))
+            .await
+    );
+    signaller.join().unwrap();
+
+    // A signal that arrives after the timeout is left to the next wait
+    assert!(!event.wait_timeout(Duration::ZERO).await);
+    event.signal();
+    assert!(event.try_wait());
+}
@@ -1,8 +1,16 @@
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
 use std::sync::Arc;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
 use std::thread;
 use std::time::Duration;
 
-use nova_autoreset_event::AutoResetEvent;
+use nova_autoreset_event::{
+    AutoResetEvent, Backend, EventErrorKind, EventOperation, EventSet, LazyAutoResetEvent,
+    PaddedAutoResetEvent, SignalReceiver, WaitResult,
+};
 
 #[test]
 fn test_autoreset_event() {
@@ -43,6 +51,237 @@ fn test_wait_does_not_return_early() {
     );
 }
 
+#[test]
+fn test_wait_count() {
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    event.signal();
+    AutoResetEvent::signal_many(&[&event]);
+    assert_eq!(event.wait_count(), 3);
+    assert!(!event.try_wait());
+
+    const SIGNALS: u64 = 10000;
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let producer = {
+        let event = event.clone();
+        thread::spawn(move || {
+            for _ in 0..SIGNALS {
+                event.signal();
+            }
+        })
+    };
+
+    let mut received = 0;
+    while received < SIGNALS {
+        let count = event.wait_count();
+        assert!(count >= 1);
+        received += count;
+    }
+    producer.join().unwrap();
+    assert_eq!(received, SIGNALS);
+}
+
+#[test]
+fn test_poll_wait() {
+    use std::task::{Context, Poll, Wake, Waker};
+
+    // Unparks the polling thread
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(event.poll_wait(&mut cx), Poll::Pending);
+    event.signal();
+    assert_eq!(event.poll_wait(&mut cx), Poll::Ready(()));
+    assert!(!event.try_wait());
+
+    for _ in 0..3 {
+        let event2 = event.clone();
+        let signaller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event2.signal();
+        });
+        while event.poll_wait(&mut cx).is_pending() {
+            thread::park();
+        }
+        signaller.join().unwrap();
+    }
+
+    let event2 = event.clone();
+    let closer = thread::spawn(move || event2.close());
+    while event.poll_wait(&mut cx).is_pending() {
+        thread::park();
+    }
+    closer.join().unwrap();
+    assert!(event.is_closed());
+}
+
+#[test]
+fn test_wait_async_cancel_safe() {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    let event = AutoResetEvent::new().unwrap();
+    let mut cx = Context::from_waker(Waker::noop());
+
+    // A future that is dropped after `Pending` leaves the signal to the next wait
+    {
+        let mut first = pin!(event.wait_async());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+    }
+    event.signal();
+    assert!(event.try_wait());
+
+    // A signal that wakes two tasks is consumed by the one that polls, also if the other one is
+    // dropped
+    let mut second = pin!(event.wait_async());
+    {
+        let mut third = pin!(event.wait_async());
+        assert!(third.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        event.signal();
+    }
+    assert_eq!(second.poll(&mut cx), Poll::Ready(WaitResult::Signalled));
+    assert!(!event.try_wait());
+}
+
+#[test]
+fn test_wait_async_timeout() {
+    use futures_executor::block_on;
+    use std::time::Instant;
+
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let start = Instant::now();
+    assert!(!block_on(
+        event.wait_async_timeout(Duration::from_millis(20))
+    ));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    let event2 = event.clone();
+    let signaller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        event2.signal();
+    });
+    assert!(block_on(
+        event.wait_async_deadline(Instant::now() + Duration::from_secs(10))
+    ));
+    signaller.join().unwrap();
+
+    // A deadline that is not representable never expires
+    event.signal();
+    assert!(block_on(event.wait_async_timeout(Duration::MAX)));
+    event.close();
+    assert!(!block_on(event.wait_async_timeout(Duration::from_secs(10))));
+}
+
+#[test]
+fn test_wait_many() {
+    let event = AutoResetEvent::new().unwrap();
+    assert_eq!(event.wait_many(0), WaitResult::Signalled);
+    for _ in 0..5 {
+        event.signal();
+    }
+    assert_eq!(event.wait_many(2), WaitResult::Signalled);
+    assert_eq!(event.wait_many(3), WaitResult::Signalled);
+
+    const CHUNK: u64 = 100;
+    const SIGNALS: u64 = 100 * CHUNK;
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let producer = {
+        let event = event.clone();
+        thread::spawn(move || {
+            for _ in 0..SIGNALS + 1 {
+                event.signal();
+            }
+        })
+    };
+
+    for _ in 0..SIGNALS / CHUNK {
+        assert_eq!(event.wait_many(CHUNK), WaitResult::Signalled);
+    }
+    producer.join().unwrap();
+    // The last signal is left over
+    assert_eq!(event.wait_count(), 1);
+
+    event.close();
+    assert_eq!(event.wait_many(1), WaitResult::Closed);
+}
+
+#[test]
+fn test_snapshot() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let snapshot = event.snapshot();
+    assert!(!snapshot.signalled);
+    assert_eq!(snapshot.waiters, 0);
+    assert_eq!(snapshot.total_signals, 0);
+    assert_eq!(snapshot.total_waits, 0);
+    assert_eq!(snapshot.coalesced_signals, 0);
+    assert_eq!(snapshot.backend, event.backend());
+
+    event.signal();
+    event.signal();
+    let snapshot = event.snapshot();
+    assert!(snapshot.signalled);
+    assert_eq!(snapshot.total_signals, 2);
+    assert_eq!(snapshot.coalesced_signals, 1);
+
+    assert!(event.try_wait());
+    let waiter = {
+        let event = event.clone();
+        thread::spawn(move || event.wait())
+    };
+    thread::sleep(Duration::from_millis(50));
+    let snapshot = event.snapshot();
+    assert!(!snapshot.signalled);
+    assert_eq!(snapshot.waiters, 1);
+    assert_eq!(snapshot.total_waits, 1);
+
+    event.signal();
+    waiter.join().unwrap();
+    let snapshot = event.snapshot();
+    assert_eq!(snapshot.waiters, 0);
+    assert_eq!(snapshot.total_signals, 3);
+    assert_eq!(snapshot.total_waits, 2);
+    assert_eq!(snapshot.coalesced_signals, 1);
+}
+
+#[test]
+fn test_coalesced_signals() {
+    let event = AutoResetEvent::new().unwrap();
+    let other = AutoResetEvent::new().unwrap();
+
+    // The consumer keeps up
+    for _ in 0..3 {
+        event.signal();
+        assert!(event.try_wait());
+    }
+    assert_eq!(event.coalesced_signals(), 0);
+
+    // The consumer falls behind
+    for _ in 0..3 {
+        event.signal();
+    }
+    AutoResetEvent::signal_many(&[&event, &other]);
+    assert_eq!(event.coalesced_signals(), 3);
+    assert_eq!(other.coalesced_signals(), 0);
+    assert!(event.try_wait());
+
+    // Signals to a closed event are ignored, not coalesced
+    event.close();
+    event.signal();
+    event.signal();
+    assert_eq!(event.coalesced_signals(), 3);
+}
+
 #[test]
 fn test_try_wait() {
     let event = AutoResetEvent::new().unwrap();
@@ -53,6 +292,75 @@ fn test_try_wait() {
     assert!(!event.try_wait());
 }
 
+#[test]
+fn test_reset() {
+    for &backend in Backend::available() {
+        let event = AutoResetEvent::builder().backend(backend).build().unwrap();
+        assert!(!event.reset(), "{}", backend);
+
+        event.signal();
+        assert!(event.reset(), "{}", backend);
+        assert!(!event.try_wait(), "{}", backend);
+        // Not counted as a wait
+        assert_eq!(event.snapshot().total_waits, 0, "{}", backend);
+
+        // The kernel object of a handed out fd is drained as well
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+
+            let mut pollfd = libc::pollfd {
+                fd: event.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            event.signal();
+            assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 0) }, 1, "{}", backend);
+            assert!(event.reset(), "{}", backend);
+            assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 0) }, 0, "{}", backend);
+        }
+
+        event.signal();
+        event.close();
+        assert!(!event.reset(), "{}", backend);
+    }
+}
+
+#[test]
+fn test_is_signalled() {
+    for &backend in Backend::available() {
+        let event = Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap());
+        assert!(!event.is_signalled(), "{}", backend);
+
+        event.signal();
+        // Peeking does not consume the signal, neither in userspace nor in the kernel object
+        assert!(event.is_signalled(), "{}", backend);
+        assert!(event.is_signalled(), "{}", backend);
+        assert!(
+            event.try_wait_for(Duration::from_millis(100)),
+            "{}",
+            backend
+        );
+        assert!(!event.is_signalled(), "{}", backend);
+
+        // A blocked waiter is still woken by the next signal
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.try_wait_for(Duration::from_secs(10)))
+        };
+        while event.snapshot().waiters == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!event.is_signalled(), "{}", backend);
+        event.signal();
+        assert!(waiter.join().unwrap(), "{}", backend);
+
+        event.signal();
+        event.close();
+        assert!(!event.is_signalled(), "{}", backend);
+    }
+}
+
 #[test]
 fn test_try_wait_for() {
     let event = Arc::new(AutoResetEvent::new().unwrap());
@@ -68,6 +376,1011 @@ fn test_try_wait_for() {
     assert!(!event.try_wait());
 }
 
+#[test]
+fn test_signal_receiver() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let receiver = SignalReceiver::new(event.clone());
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_millis(10)),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    // Signals are coalesced
+    event.signal();
+    event.signal();
+    assert_eq!(receiver.try_iter().count(), 1);
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            }
+            thread::sleep(Duration::from_millis(20));
+            event.close();
+        })
+    };
+    assert_eq!(receiver.recv(), Ok(()));
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(()));
+    // Ends once the event has been closed
+    assert_eq!(receiver.iter().count(), 1);
+    signaller.join().unwrap();
+
+    assert_eq!(receiver.recv(), Err(RecvError));
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+    assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn test_two_phase_wait() {
+    let event = AutoResetEvent::new().unwrap();
+
+    let token = event.prepare_wait();
+    assert!(!token.is_ready());
+    assert_eq!(event.snapshot().waiters, 1);
+    assert_eq!(event.commit_wait(token), WaitResult::TimedOut);
+    assert_eq!(event.snapshot().waiters, 0);
+
+    // A signal between the check and the commit is not lost, and is consumed only once
+    let first = event.prepare_wait();
+    let second = event.prepare_wait();
+    event.signal();
+    assert_eq!(event.commit_wait(first), WaitResult::Signalled);
+    assert_eq!(event.commit_wait(second), WaitResult::TimedOut);
+
+    // Cancelling, also by dropping the token, leaves the signal to other waiters
+    event.signal();
+    let token = event.prepare_wait();
+    assert!(token.is_ready());
+    event.cancel_wait(token);
+    drop(event.prepare_wait());
+    assert_eq!(event.snapshot().waiters, 0);
+    assert!(event.try_wait());
+
+    let token = event.prepare_wait();
+    event.close();
+    assert_eq!(event.commit_wait(token), WaitResult::Closed);
+    assert_eq!(event.snapshot().waiters, 0);
+}
+
+#[test]
+#[should_panic(expected = "prepared on another event")]
+fn test_two_phase_wait_other_event() {
+    let event = AutoResetEvent::new().unwrap();
+    let other = AutoResetEvent::new().unwrap();
+    event.commit_wait(other.prepare_wait());
+}
+
+#[test]
+fn test_lazy_event() {
+    static EVENT: LazyAutoResetEvent = LazyAutoResetEvent::new();
+    assert!(LazyAutoResetEvent::get(&EVENT).is_none());
+
+    let waiter = thread::spawn(|| EVENT.wait());
+    thread::sleep(Duration::from_millis(20));
+    EVENT.signal();
+    waiter.join().unwrap();
+
+    assert!(LazyAutoResetEvent::get(&EVENT).is_some());
+    assert!(!EVENT.try_wait());
+}
+
+#[test]
+fn test_many_events_without_blocking() {
+    // Lazy events that never block do not need a kernel object, so this does not run into fd
+    // limits
+    let events = AutoResetEvent::builder()
+        .lazy(true)
+        .build_many(100_000)
+        .unwrap();
+
+    for event in &events {
+        event.signal();
+    }
+
+    for event in &events {
+        assert!(event.try_wait());
+        assert!(!event.try_wait());
+    }
+}
+
+#[test]
+fn test_ping_pong() {
+    // Alternates between spinning and blocking, as the spin budget adapts to the other thread
+    let ping = Arc::new(AutoResetEvent::new().unwrap());
+    let pong = Arc::new(AutoResetEvent::new().unwrap());
+
+    let thread = {
+        let ping = ping.clone();
+        let pong = pong.clone();
+
+        thread::spawn(move || {
+            for i in 0..10_000 {
+                ping.wait();
+                if i % 1000 == 0 {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                pong.signal();
+            }
+        })
+    };
+
+    for _ in 0..10_000 {
+        ping.signal();
+        assert!(pong.try_wait_for(Duration::from_secs(10)));
+    }
+
+    thread.join().unwrap();
+    assert!(!ping.try_wait());
+    assert!(!pong.try_wait());
+}
+
+#[test]
+fn test_signal_many() {
+    let events: Vec<_> = (0..100)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+
+    // Every other event has a blocked waiter, the others are signalled in userspace
+    let threads: Vec<_> = events
+        .iter()
+        .step_by(2)
+        .map(|event| {
+            let event = event.clone();
+            thread::spawn(move || event.wait())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(50));
+
+    let refs: Vec<&AutoResetEvent> = events.iter().map(|event| &**event).collect();
+    AutoResetEvent::signal_many(&refs);
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    for (index, event) in events.iter().enumerate() {
+        assert_eq!(event.try_wait(), index % 2 == 1);
+    }
+}
+
+#[test]
+fn test_wait_any() {
+    let events: Vec<_> = (0..4)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+
+    // Already signalled: the first signalled event wins, the other one stays signalled
+    events[3].signal();
+    events[1].signal();
+    let refs: Vec<&AutoResetEvent> = events.iter().map(|event| &**event).collect();
+    assert_eq!(AutoResetEvent::wait_any(&refs), 1);
+    assert_eq!(AutoResetEvent::wait_any(&refs), 3);
+
+    // Blocking, while another thread waits on one of the events as well
+    let waiter = {
+        let event = events[2].clone();
+        thread::spawn(move || event.wait())
+    };
+    let any = {
+        let events = events.clone();
+        thread::spawn(move || {
+            let refs: Vec<&AutoResetEvent> = events.iter().map(|event| &**event).collect();
+            AutoResetEvent::wait_any(&refs)
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+
+    events[2].signal();
+    thread::sleep(Duration::from_millis(50));
+    events[2].signal();
+    assert_eq!(any.join().unwrap(), 2);
+    waiter.join().unwrap();
+
+    for event in &events {
+        assert!(!event.try_wait());
+    }
+}
+
+#[test]
+fn test_try_wait_any_for() {
+    let events: Vec<_> = (0..2).map(|_| AutoResetEvent::new().unwrap()).collect();
+    let refs: Vec<&AutoResetEvent> = events.iter().collect();
+
+    assert_eq!(
+        AutoResetEvent::try_wait_any_for(&refs, Duration::from_millis(10)),
+        None
+    );
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(50));
+            events[1].signal();
+        });
+        assert_eq!(
+            AutoResetEvent::try_wait_any_for(&refs, Duration::from_secs(5)),
+            Some(1)
+        );
+    });
+    assert!(!events[0].try_wait());
+    assert!(!events[1].try_wait());
+}
+
+#[test]
+fn test_wait_any_backends() {
+    for &first in Backend::available() {
+        for &second in Backend::available() {
+            let events = [
+                AutoResetEvent::builder().backend(first).build().unwrap(),
+                AutoResetEvent::builder().backend(second).build().unwrap(),
+            ];
+            let refs: Vec<&AutoResetEvent> = events.iter().collect();
+
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    events[1].signal();
+                });
+                assert_eq!(AutoResetEvent::wait_any(&refs), 1);
+            });
+            assert!(!events[0].try_wait());
+            assert!(!events[1].try_wait());
+        }
+    }
+}
+
+#[test]
+fn test_event_set() {
+    let events: Vec<_> = (0..3).map(|_| AutoResetEvent::new().unwrap()).collect();
+    let mut set = EventSet::new().unwrap();
+    assert!(set.is_empty());
+
+    for event in &events {
+        assert!(set.insert(event));
+    }
+    assert!(!set.insert(&events[0]));
+    assert_eq!(set.len(), 3);
+
+    assert!(set.try_wait_for(Duration::from_millis(10)).is_none());
+
+    thread::scope(|scope| {
+        // Changes the set between waits, while other threads signal the events
+        for round in 0..100 {
+            let index = round % events.len();
+            assert!(set.remove(&events[index]));
+            assert!(!set.contains(&events[index]));
+            assert!(set.insert(&events[index]));
+
+            let event = &events[index];
+            scope.spawn(move || event.signal());
+            let signalled = set.try_wait_for(Duration::from_secs(5)).unwrap();
+            assert!(std::ptr::eq(signalled, event));
+        }
+    });
+
+    // Removed events are ignored
+    set.remove(&events[0]);
+    events[0].signal();
+    assert!(set.try_wait_for(Duration::from_millis(10)).is_none());
+    assert!(events[0].try_wait());
+}
+
+#[test]
+fn test_builder() {
+    let event = Arc::new(AutoResetEvent::builder().build().unwrap());
+
+    let event2 = event.clone();
+    let thread = thread::spawn(move || event2.wait());
+
+    thread::sleep(Duration::from_millis(50));
+    event.signal();
+    thread.join().unwrap();
+    assert!(!event.try_wait());
+}
+
+#[test]
+fn test_create_many() {
+    let events = AutoResetEvent::create_many(1000).unwrap();
+    assert_eq!(events.len(), 1000);
+
+    events[10].signal();
+    assert!(events[10].try_wait());
+    assert!(!events[11].try_wait());
+
+    for &backend in Backend::available() {
+        let events = AutoResetEvent::builder()
+            .backend(backend)
+            .build_many(4)
+            .unwrap();
+        assert!(events.iter().all(|event| event.backend() == backend));
+    }
+}
+
+#[test]
+fn test_label() {
+    let event = AutoResetEvent::builder()
+        .label("gpu-fence")
+        .build()
+        .unwrap();
+    assert_eq!(event.label(), Some("gpu-fence"));
+    assert_eq!(AutoResetEvent::new().unwrap().label(), None);
+
+    event.signal();
+    let debug = format!("{:?}", event);
+    assert!(debug.contains("label: Some(\"gpu-fence\")"), "{}", debug);
+    assert!(debug.contains("signalled: true"), "{}", debug);
+    assert!(debug.contains("waiters: 0"), "{}", debug);
+    assert!(
+        debug.contains(&format!("backend: {:?}", event.backend())),
+        "{}",
+        debug
+    );
+
+    let events = AutoResetEvent::builder()
+        .label(format!("worker-{}", 1))
+        .build_many(2)
+        .unwrap();
+    assert!(events.iter().all(|event| event.label() == Some("worker-1")));
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics() {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let event = metrics::with_local_recorder(&recorder, || {
+        AutoResetEvent::builder().label("metrics").build().unwrap()
+    });
+
+    event.signal();
+    assert!(event.try_wait());
+    assert!(!event.try_wait_for(Duration::from_millis(1)));
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        });
+        event.wait();
+    });
+
+    let metrics: std::collections::HashMap<_, _> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let key = key.key();
+            let label = |name: &str| {
+                key.labels()
+                    .find(|label| label.key() == name)
+                    .map(|label| label.value().to_owned())
+            };
+            assert_eq!(label("event").as_deref(), Some("metrics"));
+            assert_eq!(label("backend").as_deref(), Some(event.backend().name()));
+            ((key.name().to_owned(), label("outcome")), value)
+        })
+        .collect();
+
+    let counter = |name: &str, outcome: Option<&str>| {
+        match metrics[&(name.to_owned(), outcome.map(str::to_owned))] {
+            DebugValue::Counter(value) => value,
+            _ => panic!("{} is not a counter", name),
+        }
+    };
+    assert_eq!(counter("nova_autoreset_event_signals_total", None), 2);
+    let waits = "nova_autoreset_event_blocking_waits_total";
+    assert_eq!(counter(waits, Some("signalled")), 1);
+    assert_eq!(counter(waits, Some("timeout")), 1);
+    match &metrics[&("nova_autoreset_event_wake_latency_seconds".to_owned(), None)] {
+        DebugValue::Histogram(values) => assert_eq!(values.len(), 1),
+        _ => panic!("wake latency is not a histogram"),
+    }
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics_mode() {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use nova_autoreset_event::MetricsMode;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let events = metrics::with_local_recorder(&recorder, || {
+        let aggregated = AutoResetEvent::builder()
+            .label("worker-1")
+            .metrics(MetricsMode::Aggregate)
+            .build_many(2)
+            .unwrap();
+        let disabled = AutoResetEvent::builder()
+            .label("disabled")
+            .metrics(MetricsMode::Disabled)
+            .build()
+            .unwrap();
+        (aggregated, disabled)
+    });
+
+    for event in events.0.iter().chain([&events.1]) {
+        event.signal();
+    }
+
+    let snapshot = snapshotter.snapshot().into_hashmap();
+    assert!(
+        snapshot
+            .keys()
+            .all(|key| key.key().labels().all(|label| label.key() != "event"))
+    );
+    let signals = snapshot
+        .iter()
+        .find(|(key, _)| key.key().name() == "nova_autoreset_event_signals_total")
+        .map(|(_, (_, _, value))| value);
+    assert_eq!(signals, Some(&DebugValue::Counter(2)));
+}
+
+#[test]
+fn test_latency_stats() {
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    event.wait();
+    assert!(event.latency_stats().is_empty());
+
+    let event = AutoResetEvent::builder()
+        .latency_stats(true)
+        .build()
+        .unwrap();
+
+    // Waits that do not block are not recorded
+    event.signal();
+    event.wait();
+    assert!(event.latency_stats().is_empty());
+
+    thread::scope(|scope| {
+        for _ in 0..3 {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                event.signal();
+            });
+            event.wait();
+        }
+    });
+
+    let stats = event.latency_stats();
+    assert_eq!(stats.count(), 3);
+    assert!(stats.min() <= stats.mean() && stats.mean() <= stats.max());
+    assert!(stats.value_at_quantile(0.0) >= stats.min());
+    assert!(stats.value_at_quantile(0.5) <= stats.max());
+    assert_eq!(stats.value_at_quantile(1.0), stats.max());
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn test_dump_state() {
+    let event = Arc::new(
+        AutoResetEvent::builder()
+            .label("diagnostics-dump")
+            .build()
+            .unwrap(),
+    );
+    let line = || {
+        nova_autoreset_event::dump_state()
+            .lines()
+            .find(|line| line.contains("diagnostics-dump"))
+            .map(str::to_owned)
+    };
+
+    event.signal();
+    assert!(
+        line()
+            .unwrap()
+            .contains(": signalled, 0 waiters, 1 signals")
+    );
+
+    assert!(event.try_wait());
+    let waiter = {
+        let event = event.clone();
+        thread::Builder::new()
+            .name("dump-waiter".into())
+            .spawn(move || event.wait())
+            .unwrap()
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert!(line().unwrap().contains(": unsignalled, 1 waiters"));
+    #[cfg(feature = "debug-backtraces")]
+    assert!(nova_autoreset_event::dump_state().contains("blocked thread dump-waiter:"));
+
+    event.signal();
+    waiter.join().unwrap();
+    #[cfg(feature = "debug-backtraces")]
+    assert!(!nova_autoreset_event::dump_state().contains("blocked thread dump-waiter:"));
+    assert!(
+        line()
+            .unwrap()
+            .contains("2 signals, 1 blocking waits, 0 timeouts")
+    );
+
+    drop(event);
+    assert_eq!(line(), None);
+}
+
+// Returns the messages logged so far, installing a capturing logger on the first call
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+fn captured_logs() -> Vec<String> {
+    use std::sync::{Mutex, Once};
+
+    struct Logger(Mutex<Vec<String>>);
+
+    impl log::Log for Logger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+    });
+    LOGGER.0.lock().unwrap().clone()
+}
+
+#[cfg(all(feature = "log", not(feature = "tracing")))]
+#[test]
+fn test_watchdog() {
+    captured_logs();
+
+    let event = AutoResetEvent::builder()
+        .label("watched")
+        .warn_if_waiting_longer_than(Duration::from_millis(20))
+        .build()
+        .unwrap();
+    let warnings = || {
+        captured_logs()
+            .iter()
+            .filter(|message| message.contains("event watched"))
+            .count()
+    };
+
+    // Waits that end before the threshold do not warn
+    assert!(!event.try_wait_for(Duration::from_millis(5)));
+    assert_eq!(warnings(), 0);
+
+    // The wait does not end when the watchdog warns
+    let start = std::time::Instant::now();
+    assert!(!event.try_wait_for(Duration::from_millis(100)));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(warnings(), 1);
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(100));
+            event.signal();
+        });
+        event.wait();
+    });
+    assert_eq!(warnings(), 2);
+}
+
+#[cfg(all(
+    feature = "syscall-trace",
+    not(feature = "tracing"),
+    target_os = "linux"
+))]
+#[test]
+fn test_syscall_trace() {
+    use std::os::fd::AsRawFd;
+
+    captured_logs();
+    let event = AutoResetEvent::builder()
+        .backend(Backend::EventFd)
+        .build()
+        .unwrap();
+    assert!(!event.try_wait_for(Duration::from_millis(20)));
+
+    let poll = format!("poll([{}], timeout ", event.as_raw_fd());
+    let logs = captured_logs();
+    assert!(
+        logs.iter()
+            .any(|message| message.starts_with("eventfd(0, "))
+    );
+    assert!(
+        logs.iter()
+            .any(|message| message.starts_with(&poll) && message.contains(") = 0 in ")),
+        "{:#?}",
+        logs
+    );
+}
+
+#[cfg(feature = "deadlock-detection")]
+#[test]
+fn test_deadlock_detection() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let message = |result: thread::Result<()>| {
+        *result
+            .unwrap_err()
+            .downcast::<String>()
+            .expect("panic message")
+    };
+
+    // The only signaller waits for itself
+    let event = AutoResetEvent::builder().label("self").build().unwrap();
+    event.signal();
+    event.wait();
+    let err = message(catch_unwind(AssertUnwindSafe(|| event.wait())));
+    assert!(err.contains("event self"), "{}", err);
+    assert!(err.contains("only thread"), "{}", err);
+
+    // Signalled from several threads, so another thread may signal it again
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    thread::scope(|scope| {
+        scope.spawn(|| event.signal());
+    });
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        });
+        event.wait();
+    });
+
+    // The declared signaller waits for itself
+    let event = AutoResetEvent::new().unwrap();
+    event.declare_signaller(thread::current().id());
+    let err = message(catch_unwind(AssertUnwindSafe(|| event.wait())));
+    assert!(err.contains("declared signaller"), "{}", err);
+
+    // Timed waits are not checked
+    assert!(!event.try_wait_for(Duration::from_millis(1)));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn test_no_lost_signals() {
+    use nova_autoreset_event::OperationKind;
+
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    event.signal();
+    assert!(event.try_wait());
+    let kinds: Vec<_> = event
+        .operations()
+        .iter()
+        .map(|operation| operation.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            OperationKind::Signal,
+            OperationKind::CoalescedSignal,
+            OperationKind::Consume
+        ]
+    );
+    event.assert_no_lost_signals();
+
+    for &backend in Backend::available() {
+        let event = AutoResetEvent::builder().backend(backend).build().unwrap();
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        event.signal();
+                    }
+                });
+            }
+            scope.spawn(|| while event.try_wait_for(Duration::from_millis(100)) {});
+        });
+
+        // The consumer may have given up before the last signal
+        event.try_wait();
+        event.assert_no_lost_signals();
+    }
+
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        event.assert_no_lost_signals()
+    }));
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_mock() {
+    use nova_autoreset_event::MockAutoResetEvent;
+
+    let event = MockAutoResetEvent::new().unwrap();
+    event.signal();
+    event.signal();
+    assert!(event.try_wait());
+    assert!(!event.try_wait());
+    assert!(!event.try_wait_for(Duration::from_millis(100)));
+    assert_eq!(event.elapsed(), Duration::from_millis(100));
+
+    // Scheduled signals are delivered as the clock advances
+    event.signal_after(Duration::from_secs(2));
+    event.signal_after(Duration::from_secs(1));
+    event.wait();
+    assert_eq!(event.elapsed(), Duration::from_millis(1100));
+    event.advance(Duration::from_secs(5));
+    assert!(event.is_signalled());
+    assert_eq!(event.wait_count(), 4);
+
+    // A forced timeout delivers the due signal without consuming it
+    event.signal_after(Duration::from_millis(10));
+    event.time_out_next_wait();
+    assert!(!event.try_wait_for(Duration::from_secs(1)));
+    assert!(event.try_wait());
+
+    assert_eq!(event.signals(), 5);
+    assert_eq!(event.coalesced_signals(), 1);
+    assert_eq!(event.waits(), 4);
+    assert_eq!(event.timeouts(), 2);
+
+    let result = std::panic::catch_unwind(|| event.wait());
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "virtual-time")]
+#[test]
+fn test_virtual_time() {
+    use nova_autoreset_event::MockClock;
+
+    let clock = MockClock::new();
+    let event = AutoResetEvent::builder()
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| event.try_wait_for(Duration::from_secs(10)));
+
+        // The wait does not time out in real time, only once the clock passes its deadline
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        clock.advance(Duration::from_secs(9));
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        clock.advance(Duration::from_secs(1));
+        assert!(!waiter.join().unwrap());
+    });
+    assert_eq!(clock.now(), Duration::from_secs(10));
+
+    // Signals still wake waiters without advancing the clock
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| event.try_wait_for(Duration::from_secs(1)));
+        thread::sleep(Duration::from_millis(50));
+        event.signal();
+        assert!(waiter.join().unwrap());
+    });
+    assert!(!event.try_wait());
+}
+
+// A timeout beyond the limits of the kernel APIs does not end early
+#[cfg(feature = "virtual-time")]
+#[test]
+fn test_virtual_time_huge_timeout() {
+    use nova_autoreset_event::MockClock;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    let clock = MockClock::new();
+    let event = AutoResetEvent::builder()
+        .clock(clock.clone())
+        .build()
+        .unwrap();
+
+    thread::scope(|scope| {
+        let waiter = scope.spawn(|| event.try_wait_for(60 * DAY));
+        while event.snapshot().waiters == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // Past the limits of `poll` (~24.8 days) and of the Win32 wait functions (~49.7 days)
+        for _ in 0..59 {
+            clock.advance(DAY);
+        }
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        clock.advance(DAY);
+        assert!(!waiter.join().unwrap());
+    });
+    assert_eq!(clock.now(), 60 * DAY);
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn test_fault_injection() {
+    use nova_autoreset_event::{Fault, FaultInjector};
+
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        faults.set_signal_delay(Duration::from_millis(20));
+        let event = Arc::new(
+            AutoResetEvent::builder()
+                .backend(backend)
+                .faults(faults.clone())
+                .build()
+                .unwrap(),
+        );
+
+        for fault in [
+            Fault::Interrupt,
+            Fault::SpuriousWakeup,
+            Fault::ReadWouldBlock,
+            Fault::ShortWrite,
+            Fault::DelayedSignal,
+        ] {
+            faults.inject(fault, 2);
+            let waiter = {
+                let event = event.clone();
+                thread::spawn(move || event.wait())
+            };
+            thread::sleep(Duration::from_millis(50));
+
+            // The blocked waiter consumes the signal despite the faults
+            event.signal();
+            waiter.join().unwrap();
+            assert!(!event.try_wait(), "{} {:?}", backend, fault);
+            faults.clear();
+        }
+
+        // Every wait blocks and every signal wakes, so these faults are injected on all backends
+        assert_eq!(faults.injected(Fault::Interrupt), 2, "{}", backend);
+        assert_eq!(faults.injected(Fault::SpuriousWakeup), 2, "{}", backend);
+        assert_eq!(faults.injected(Fault::DelayedSignal), 1, "{}", backend);
+    }
+}
+
+#[cfg(feature = "leak-check")]
+#[test]
+fn test_leak_check() {
+    use nova_autoreset_event::with_leak_check;
+
+    let outer = with_leak_check(|| {
+        let event = Arc::new(AutoResetEvent::new().unwrap());
+        event.signal();
+        event.wait();
+
+        // Events that are returned from the closure leak out of it
+        let result = std::panic::catch_unwind(|| {
+            with_leak_check(|| AutoResetEvent::builder().label("leaked").build().unwrap())
+        });
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("1 events"), "{}", message);
+        assert!(message.contains("leaked"), "{}", message);
+
+        42
+    });
+    assert_eq!(outer, 42);
+}
+
+#[cfg(feature = "timestamps")]
+#[test]
+fn test_timestamps() {
+    use std::time::Instant;
+
+    let event = AutoResetEvent::new().unwrap();
+    assert_eq!(event.last_signal_instant(), None);
+    assert_eq!(event.last_wake_instant(), None);
+
+    let before = Instant::now();
+    event.signal();
+    let signalled = event.last_signal_instant().unwrap();
+    assert!(signalled >= before && signalled <= Instant::now());
+    assert_eq!(event.last_wake_instant(), None);
+
+    thread::sleep(Duration::from_millis(10));
+    assert!(event.try_wait());
+    let woken = event.last_wake_instant().unwrap();
+    assert!(woken >= signalled + Duration::from_millis(10));
+    assert_eq!(event.last_signal_instant(), Some(signalled));
+}
+
+#[test]
+fn test_padded() {
+    let events: Vec<PaddedAutoResetEvent> = (0..2)
+        .map(|_| PaddedAutoResetEvent::new().unwrap())
+        .collect();
+    assert!(std::mem::align_of::<PaddedAutoResetEvent>() >= 64);
+    assert!(
+        (&*events[1] as *const AutoResetEvent as usize)
+            - (&*events[0] as *const AutoResetEvent as usize)
+            >= 64
+    );
+
+    events[0].signal();
+    assert!(!events[1].try_wait());
+    assert!(events[0].try_wait());
+    assert!(!events[0].try_wait());
+}
+
+#[test]
+fn test_backends() {
+    for &backend in Backend::available() {
+        let event = Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap());
+        assert_eq!(event.backend(), backend);
+
+        let event2 = event.clone();
+        let thread = thread::spawn(move || event2.wait());
+
+        thread::sleep(Duration::from_millis(50));
+        event.signal();
+        thread.join().unwrap();
+        assert!(!event.try_wait());
+    }
+}
+
+#[test]
+fn test_fastest_backend() {
+    let backend = Backend::fastest();
+    assert!(backend.is_available());
+    assert_eq!(Backend::fastest(), backend);
+
+    let event = AutoResetEvent::builder().backend(backend).build().unwrap();
+    event.signal();
+    assert!(event.try_wait());
+}
+
+#[test]
+fn test_unavailable_backend() {
+    let backend = [
+        Backend::EventFd,
+        Backend::Futex,
+        Backend::Kqueue,
+        Backend::Pipe,
+        Backend::Win32Event,
+    ]
+    .into_iter()
+    .find(|backend| !backend.is_available())
+    .unwrap();
+
+    let err = AutoResetEvent::builder()
+        .backend(backend)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), EventErrorKind::Unsupported);
+    assert_eq!(err.operation(), EventOperation::Create);
+    assert_eq!(err.backend(), backend);
+    assert!(err.to_string().contains(backend.name()), "{}", err);
+    assert_eq!(
+        std::io::Error::from(err).kind(),
+        std::io::ErrorKind::Unsupported
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn test_priority_boost() {
+    for enable in [false, true] {
+        let event = Arc::new(
+            AutoResetEvent::builder()
+                .priority_boost(enable)
+                .build()
+                .unwrap(),
+        );
+
+        let event2 = event.clone();
+        let thread = thread::spawn(move || event2.try_wait_for(Duration::from_secs(10)));
+
+        thread::sleep(Duration::from_millis(50));
+        event.signal();
+        assert!(thread.join().unwrap());
+    }
+}
+
 #[cfg(unix)]
 #[tokio::test]
 async fn test_tokio() {
@@ -106,3 +1419,83 @@ async fn test_tokio() {
         thread.join().unwrap();
     }
 }
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_mach_port() {
+    use std::mem;
+
+    #[repr(C)]
+    struct MachMsgHeader {
+        bits: u32,
+        size: u32,
+        remote_port: u32,
+        local_port: u32,
+        voucher_port: u32,
+        id: i32,
+    }
+
+    unsafe extern "C" {
+        static mach_task_self_: u32;
+        fn mach_port_allocate(task: u32, right: u32, name: *mut u32) -> i32;
+        fn mach_port_mod_refs(task: u32, name: u32, right: u32, delta: i32) -> i32;
+        fn mach_msg(
+            msg: *mut MachMsgHeader,
+            option: i32,
+            send_size: u32,
+            rcv_size: u32,
+            rcv_name: u32,
+            timeout: u32,
+            notify: u32,
+        ) -> i32;
+    }
+
+    const MACH_PORT_RIGHT_RECEIVE: u32 = 1;
+    const MACH_MSG_TYPE_MAKE_SEND: u32 = 20;
+    const MACH_SEND_MSG: i32 = 1;
+
+    let task = unsafe { mach_task_self_ };
+    let mut port = 0;
+    assert_eq!(
+        unsafe { mach_port_allocate(task, MACH_PORT_RIGHT_RECEIVE, &mut port) },
+        0
+    );
+
+    let event = Arc::new(
+        AutoResetEvent::builder()
+            .backend(Backend::Kqueue)
+            .build()
+            .unwrap(),
+    );
+    event.attach_mach_port(port).unwrap();
+
+    let waiter = {
+        let event = event.clone();
+        thread::spawn(move || event.try_wait_for(Duration::from_secs(5)))
+    };
+    thread::sleep(Duration::from_millis(50));
+
+    let mut msg = MachMsgHeader {
+        bits: MACH_MSG_TYPE_MAKE_SEND,
+        size: mem::size_of::<MachMsgHeader>() as u32,
+        remote_port: port,
+        local_port: 0,
+        voucher_port: 0,
+        id: 0,
+    };
+    let res = unsafe { mach_msg(&mut msg, MACH_SEND_MSG, msg.size, 0, 0, 0, 0) };
+    assert_eq!(res, 0);
+
+    assert!(waiter.join().unwrap());
+    event.detach_mach_port(port).unwrap();
+    unsafe { mach_port_mod_refs(task, port, MACH_PORT_RIGHT_RECEIVE, -1) };
+
+    let pipe_event = AutoResetEvent::builder()
+        .backend(Backend::Pipe)
+        .build()
+        .unwrap();
+    assert_eq!(
+        pipe_event.attach_mach_port(port).unwrap_err().kind(),
+        std::io::ErrorKind::Unsupported
+    );
+}
@@ -0,0 +1,89 @@
+//! Bounding the items in flight via `BackpressureGate`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::BackpressureGate;
+
+#[test]
+fn enter_blocks_at_capacity() {
+    let gate = Arc::new(BackpressureGate::new(1).unwrap());
+    gate.enter();
+
+    let producer = {
+        let gate = gate.clone();
+        thread::spawn(move || {
+            let start = Instant::now();
+            gate.enter();
+            start.elapsed()
+        })
+    };
+    thread::sleep(Duration::from_millis(50));
+    gate.leave();
+    assert!(producer.join().unwrap() >= Duration::from_millis(40));
+    assert_eq!(gate.in_flight(), 1);
+}
+
+#[test]
+fn try_enter_for() {
+    let gate = BackpressureGate::new(1).unwrap();
+    assert!(gate.try_enter_for(Duration::from_millis(10)));
+
+    let start = Instant::now();
+    assert!(!gate.try_enter_for(Duration::from_millis(20)));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(gate.in_flight(), 1);
+}
+
+#[test]
+fn in_flight_stays_bounded() {
+    const CAPACITY: usize = 3;
+    const ITEMS: usize = 2_000;
+
+    let gate = Arc::new(BackpressureGate::new(CAPACITY).unwrap());
+    let (sender, receiver) = std::sync::mpsc::channel::<usize>();
+    let received = Arc::new(AtomicUsize::new(0));
+
+    let producers: Vec<_> = (0..4)
+        .map(|_| {
+            let (gate, sender) = (gate.clone(), sender.clone());
+            thread::spawn(move || {
+                for item in 0..ITEMS {
+                    gate.enter();
+                    assert!(gate.in_flight() <= CAPACITY);
+                    sender.send(item).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(sender);
+
+    let consumer = {
+        let (gate, received) = (gate.clone(), received.clone());
+        thread::spawn(move || {
+            for _ in receiver {
+                received.fetch_add(1, Ordering::Relaxed);
+                gate.leave();
+            }
+        })
+    };
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    consumer.join().unwrap();
+    assert_eq!(received.load(Ordering::Relaxed), 4 * ITEMS);
+    assert_eq!(gate.in_flight(), 0);
+}
+
+#[test]
+#[should_panic(expected = "without a matching enter")]
+fn leave_without_enter() {
+    BackpressureGate::new(1).unwrap().leave();
+}
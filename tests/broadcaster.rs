@@ -0,0 +1,60 @@
+//! Fan-out via `Broadcaster`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, Broadcaster};
+
+#[test]
+fn broadcast_signals_all_subscribers() {
+    let broadcaster = Broadcaster::new();
+    broadcaster.broadcast();
+
+    let events: Vec<_> = (0..4)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let mut subscriptions: Vec<_> = events
+        .iter()
+        .map(|event| broadcaster.subscribe(event.clone()))
+        .collect();
+    assert_eq!(broadcaster.len(), 4);
+
+    broadcaster.broadcast();
+    assert!(events.iter().all(|event| event.try_wait()));
+
+    // Unsubscribed events are no longer signalled
+    subscriptions.remove(0).unsubscribe();
+    drop(subscriptions.pop());
+    assert_eq!(broadcaster.len(), 2);
+    broadcaster.broadcast();
+    let signalled: Vec<_> = events.iter().map(|event| event.try_wait()).collect();
+    assert_eq!(signalled, [false, true, true, false]);
+
+    // Subscriptions outlive the broadcaster
+    drop(broadcaster);
+    drop(subscriptions);
+}
+
+#[test]
+fn broadcast_wakes_blocked_subscribers() {
+    let broadcaster = Broadcaster::new();
+    let subscribers: Vec<_> = (0..8)
+        .map(|_| {
+            let event = Arc::new(AutoResetEvent::new().unwrap());
+            let subscription = broadcaster.subscribe(event.clone());
+            let thread = thread::spawn(move || event.try_wait_for(Duration::from_secs(10)));
+            (subscription, thread)
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(20));
+    broadcaster.broadcast();
+    for (_subscription, thread) in subscribers {
+        assert!(thread.join().unwrap());
+    }
+}
@@ -0,0 +1,100 @@
+//! Dispatching the signals of events in a calloop event loop via `CalloopSource`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(unix, feature = "calloop", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use calloop::EventLoop;
+use nova_autoreset_event::{AutoResetEvent, CalloopSource};
+
+fn event_loop(event: &Arc<AutoResetEvent>) -> EventLoop<'static, usize> {
+    let event_loop = EventLoop::try_new().unwrap();
+    event_loop
+        .handle()
+        .insert_source(CalloopSource::new(event.clone()), |(), &mut (), wakeups| {
+            *wakeups += 1;
+        })
+        .unwrap();
+    event_loop
+}
+
+#[test]
+fn test_dispatch_signal_of_other_thread() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut event_loop = event_loop(&event);
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+
+    let mut wakeups = 0;
+    while wakeups == 0 {
+        event_loop
+            .dispatch(Duration::from_secs(5), &mut wakeups)
+            .unwrap();
+    }
+    signaller.join().unwrap();
+    assert!(!event.try_wait());
+
+    // The signal has been consumed, so the source is not ready anymore
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    assert_eq!(wakeups, 1);
+}
+
+#[test]
+fn test_signals_are_coalesced() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut event_loop = event_loop(&event);
+
+    event.signal();
+    event.signal();
+    let mut wakeups = 0;
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    assert_eq!(wakeups, 1);
+}
+
+#[test]
+fn test_signal_consumed_by_other_waiter() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut event_loop = event_loop(&event);
+
+    event.signal();
+    assert!(event.try_wait());
+    let mut wakeups = 0;
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    assert_eq!(wakeups, 0);
+}
+
+#[test]
+fn test_close_removes_source() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut event_loop = event_loop(&event);
+
+    event.close();
+    let mut wakeups = 0;
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    // A removed source is not polled again, so the closed fd does not keep the loop busy
+    event_loop
+        .dispatch(Duration::from_millis(20), &mut wakeups)
+        .unwrap();
+    assert_eq!(wakeups, 0);
+}
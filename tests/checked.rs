@@ -0,0 +1,106 @@
+//! The checked API returns unexpected OS errors instead of panicking. The errors are injected via
+//! the `fault-injection` feature.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "fault-injection", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, Backend, EventOperation, Fault, FaultInjector};
+
+// Calls `check` with a new event of every backend and its fault injector
+fn for_each_backend(check: impl Fn(Arc<AutoResetEvent>, &FaultInjector, Backend)) {
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        let event = AutoResetEvent::builder()
+            .backend(backend)
+            .faults(faults.clone())
+            .build()
+            .unwrap();
+        check(Arc::new(event), &faults, backend);
+    }
+}
+
+#[test]
+fn succeeds_without_errors() {
+    let event = AutoResetEvent::new().unwrap();
+    event.signal_checked().unwrap();
+    event.wait_checked().unwrap();
+    assert!(
+        !event
+            .try_wait_for_checked(Duration::from_millis(10))
+            .unwrap()
+    );
+    event.signal_checked().unwrap();
+    assert!(
+        event
+            .try_wait_for_checked(Duration::from_millis(10))
+            .unwrap()
+    );
+}
+
+#[test]
+fn failed_waits_return_error() {
+    for_each_backend(|event, faults, backend| {
+        faults.inject(Fault::WaitError, 1);
+        let err = event
+            .try_wait_for_checked(Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(err.operation(), EventOperation::Wait, "{}", backend);
+        assert_eq!(err.backend(), backend);
+        assert_eq!(
+            err.io_error().to_string(),
+            "injected wait error",
+            "{}",
+            backend
+        );
+        assert!(err.to_string().contains(backend.name()), "{}", err);
+
+        faults.inject(Fault::WaitError, 1);
+        assert!(event.wait_checked().is_err(), "{}", backend);
+
+        // The failed waits have left, so a signal is not lost
+        assert_eq!(event.snapshot().waiters, 0, "{}", backend);
+        event.signal();
+        assert!(
+            event.try_wait_for_checked(Duration::ZERO).unwrap(),
+            "{}",
+            backend
+        );
+        assert_eq!(faults.pending(Fault::WaitError), 0, "{}", backend);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_signals_return_error() {
+    use std::os::fd::AsRawFd;
+
+    for_each_backend(|event, faults, backend| {
+        // A signal writes to the kernel object once the fd has been handed out. The kqueue backend
+        // does not write.
+        let _ = event.as_raw_fd();
+        faults.inject(Fault::WriteError, 1);
+        let result = event.signal_checked();
+        if backend == Backend::Kqueue {
+            faults.clear();
+            assert!(result.is_ok());
+        } else {
+            let err = result.unwrap_err();
+            assert_eq!(err.operation(), EventOperation::Signal, "{}", backend);
+            assert_eq!(
+                err.io_error().raw_os_error(),
+                Some(libc::EIO),
+                "{}",
+                backend
+            );
+        }
+
+        // The event is signalled in userspace anyway
+        assert!(event.try_wait(), "{}", backend);
+        event.signal_checked().unwrap();
+        assert!(event.try_wait(), "{}", backend);
+    });
+}
@@ -0,0 +1,109 @@
+//! Waiting for spawned threads via `completion_scope`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, completion_scope, completion_scope_for};
+
+#[test]
+fn scope_waits_for_all_threads() {
+    let done = Arc::new(AtomicUsize::new(0));
+    let result = completion_scope(|scope| {
+        for i in 0..16 {
+            let done = done.clone();
+            scope
+                .spawn(move || {
+                    thread::sleep(Duration::from_millis(i));
+                    done.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+        }
+        42
+    })
+    .unwrap();
+    assert_eq!(result, 42);
+    assert_eq!(done.load(Ordering::Relaxed), 16);
+
+    // An empty scope does not block
+    completion_scope(|scope| assert_eq!(scope.pending(), 0)).unwrap();
+}
+
+#[test]
+fn panicking_threads_complete() {
+    completion_scope(|scope| {
+        let handle = scope.spawn(|| panic!("expected")).unwrap();
+        assert!(handle.join().is_err());
+        assert_eq!(scope.pending(), 0);
+        scope.spawn(|| ()).unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn unjoined_panics_are_resumed() {
+    let done = Arc::new(AtomicUsize::new(0));
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+        completion_scope(|scope| {
+            scope.spawn(|| panic::panic_any("first")).unwrap();
+            let done = done.clone();
+            scope
+                .spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    done.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+        })
+    }))
+    .unwrap_err();
+    assert_eq!(*payload.downcast::<&str>().unwrap(), "first");
+    // The panic is only resumed once all threads have completed
+    assert_eq!(done.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn panics_of_the_scope_are_resumed_after_the_threads_completed() {
+    let done = Arc::new(AtomicUsize::new(0));
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+        completion_scope(|scope| {
+            let done = done.clone();
+            scope
+                .spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    done.fetch_add(1, Ordering::Relaxed);
+                })
+                .unwrap();
+            panic::panic_any("scope");
+        })
+    }))
+    .unwrap_err();
+    assert_eq!(*payload.downcast::<&str>().unwrap(), "scope");
+    assert_eq!(done.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn scope_times_out() {
+    let release = Arc::new(AutoResetEvent::new().unwrap());
+    let ((), completed) = completion_scope_for(Duration::from_millis(20), |scope| {
+        scope.spawn(|| ()).unwrap();
+        let release = release.clone();
+        scope.spawn(move || release.wait()).unwrap();
+    })
+    .unwrap();
+    assert!(!completed);
+    release.signal();
+
+    let ((), completed) = completion_scope_for(Duration::from_secs(10), |scope| {
+        scope
+            .spawn(|| thread::sleep(Duration::from_millis(10)))
+            .unwrap();
+    })
+    .unwrap();
+    assert!(completed);
+}
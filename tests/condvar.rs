@@ -0,0 +1,92 @@
+//! Condition variables via `RawCondvar`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "condvar", not(any(loom, madsim))))]
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::RawCondvar;
+
+#[test]
+fn notify_one_wakes_one_waiter() {
+    let shared = Arc::new((Mutex::new(0), RawCondvar::new()));
+    assert!(!shared.1.notify_one());
+
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let (mutex, condvar) = &*shared;
+                let mut tokens = mutex.lock().unwrap();
+                while *tokens == 0 {
+                    tokens = condvar.wait(tokens, mutex);
+                }
+                *tokens -= 1;
+            })
+        })
+        .collect();
+
+    let (mutex, condvar) = &*shared;
+    for _ in 0..4 {
+        *mutex.lock().unwrap() += 1;
+        // A waiter that has not started waiting yet finds the token without a notification
+        condvar.notify_one();
+    }
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+    assert_eq!(*mutex.lock().unwrap(), 0);
+}
+
+#[test]
+fn notify_all_wakes_all_waiters() {
+    let shared = Arc::new((Mutex::new(false), RawCondvar::new()));
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                let (mutex, condvar) = &*shared;
+                let mut ready = mutex.lock().unwrap();
+                while !*ready {
+                    ready = condvar.wait(ready, mutex);
+                }
+            })
+        })
+        .collect();
+
+    thread::sleep(Duration::from_millis(20));
+    let (mutex, condvar) = &*shared;
+    *mutex.lock().unwrap() = true;
+    condvar.notify_all();
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+    assert_eq!(condvar.notify_all(), 0);
+}
+
+#[test]
+fn wait_timeout() {
+    let mutex = Mutex::new(());
+    let condvar = RawCondvar::new();
+
+    let start = Instant::now();
+    let (guard, timed_out) =
+        condvar.wait_timeout(mutex.lock().unwrap(), &mutex, Duration::from_millis(20));
+    assert!(timed_out);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    drop(guard);
+    // The timed out waiter is no longer queued
+    assert!(!condvar.notify_one());
+}
+
+#[test]
+#[should_panic(expected = "does not belong to the mutex")]
+fn guard_of_another_mutex() {
+    let mutex = Mutex::new(());
+    let other = Mutex::new(());
+    let condvar = RawCondvar::new();
+    drop(condvar.wait(other.lock().unwrap(), &mutex));
+}
@@ -0,0 +1,879 @@
+//! Semantics that every backend has to provide in the same way.
+//!
+//! Each check runs against every backend that is available on the host, once with the kernel
+//! object private to the event and once after its fd/handle has been handed out, which makes the
+//! kernel object mirror the state of the event.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, Backend, WaitResult};
+
+// Calls `check` with a new event of every configuration, and a description for assertion messages
+fn for_each_event(check: impl Fn(Arc<AutoResetEvent>, &str)) {
+    for &backend in Backend::available() {
+        for exported in [false, true] {
+            let event = AutoResetEvent::builder().backend(backend).build().unwrap();
+            if exported {
+                #[cfg(unix)]
+                let _ = event.as_raw_fd();
+                #[cfg(windows)]
+                let _ = event.as_raw_handle();
+            }
+
+            let description = format!("{} (exported: {})", backend, exported);
+            check(Arc::new(event), &description);
+        }
+    }
+}
+
+// Waits until `condition` holds, for at most 5s
+fn wait_until(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    true
+}
+
+// Returns `true` if the fd of the event is readable, which exports it
+#[cfg(unix)]
+fn is_readable(event: &AutoResetEvent) -> bool {
+    let mut pollfd = libc::pollfd {
+        fd: event.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    unsafe { libc::poll(&mut pollfd, 1, 0) > 0 }
+}
+
+#[test]
+fn autoreset() {
+    for_each_event(|event, description| {
+        assert!(!event.try_wait(), "{}", description);
+
+        event.signal();
+        assert!(event.try_wait(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+
+        // A signal without a waiter is kept until the next wait
+        event.signal();
+        event.wait();
+        assert!(!event.try_wait(), "{}", description);
+    });
+}
+
+#[test]
+fn coalescing() {
+    for_each_event(|event, description| {
+        for _ in 0..3 {
+            event.signal();
+        }
+        assert_eq!(event.wait_count(), 3, "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+
+        // A single wait consumes all signals, also on backends whose kernel object counts them
+        for _ in 0..3 {
+            event.signal();
+        }
+        assert!(event.try_wait(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+        #[cfg(unix)]
+        assert!(!is_readable(&event), "{}", description);
+
+        // Coalesced signals wake a single waiter
+        for _ in 0..3 {
+            event.signal();
+        }
+        let woken = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    if event.try_wait_for(Duration::from_millis(100)) {
+                        woken.fetch_add(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+        assert_eq!(woken.load(Ordering::SeqCst), 1, "{}", description);
+    });
+}
+
+#[test]
+fn signals_do_not_accumulate() {
+    const SIGNALS: usize = 1000;
+
+    for_each_event(|event, description| {
+        for _ in 0..SIGNALS {
+            event.signal();
+        }
+        assert!(event.try_wait(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+
+        // Signals while a thread is blocked
+        thread::scope(|scope| {
+            let waiter = scope.spawn(|| event.try_wait_for(Duration::from_secs(5)));
+            assert!(
+                wait_until(|| event.snapshot().waiters == 1),
+                "{}",
+                description
+            );
+            for _ in 0..SIGNALS {
+                event.signal();
+            }
+            assert!(waiter.join().unwrap(), "{}", description);
+        });
+        // The signals after the one that woke the waiter leave at most one pending
+        event.try_wait();
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+
+        // No wakeup is left behind in the kernel object
+        #[cfg(unix)]
+        assert!(!is_readable(&event), "{}", description);
+    });
+}
+
+// Every signal of an exported event posts a wakeup to the kernel object, which must not fill up
+#[test]
+fn soak_signals() {
+    const SIGNALS: usize = 1_000_000;
+
+    for_each_event(|event, description| {
+        for _ in 0..SIGNALS {
+            event.signal();
+            assert!(event.try_wait(), "{}", description);
+        }
+        assert!(!event.try_wait(), "{}", description);
+    });
+}
+
+#[test]
+fn timeouts() {
+    for_each_event(|event, description| {
+        let start = Instant::now();
+        assert!(
+            !event.try_wait_for(Duration::from_millis(50)),
+            "{}",
+            description
+        );
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(50) && elapsed < Duration::from_secs(5),
+            "{}: timed out after {:?}",
+            description,
+            elapsed
+        );
+
+        // A signal within the timeout ends the wait
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        assert!(
+            event.try_wait_for(Duration::from_secs(5)),
+            "{}",
+            description
+        );
+        signaller.join().unwrap();
+        assert!(!event.try_wait(), "{}", description);
+
+        // A zero timeout does not block
+        assert!(!event.try_wait_for(Duration::ZERO), "{}", description);
+    });
+}
+
+// A Unix signal interrupts a blocking system call if its handler is installed without `SA_RESTART`
+#[cfg(unix)]
+#[test]
+fn interrupted_waits() {
+    use std::os::unix::thread::JoinHandleExt;
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    extern "C" fn handler(_: libc::c_int) {}
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as *const () as libc::sighandler_t;
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+
+    for_each_event(|event, description| {
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let waiter = {
+            let event = event.clone();
+            let timed_out = timed_out.clone();
+            thread::spawn(move || {
+                let start = Instant::now();
+                let signalled = event.try_wait_for(TIMEOUT);
+                let elapsed = start.elapsed();
+                timed_out.store(true, Ordering::SeqCst);
+                event.wait();
+                (signalled, elapsed)
+            })
+        };
+        // The thread is not joined before the loops are done, so it can be signalled even if it
+        // has failed
+        let interrupt = || unsafe {
+            libc::pthread_kill(waiter.as_pthread_t(), libc::SIGUSR1);
+            thread::sleep(Duration::from_millis(1));
+        };
+
+        while !timed_out.load(Ordering::SeqCst) && !waiter.is_finished() {
+            interrupt();
+        }
+        while event.snapshot().waiters == 0 && !waiter.is_finished() {
+            interrupt();
+        }
+        for _ in 0..10 {
+            interrupt();
+        }
+        event.signal();
+
+        let (signalled, elapsed) = waiter.join().unwrap();
+        assert!(!signalled, "{}", description);
+        // Interrupted waits would return after a millisecond
+        assert!(
+            elapsed >= TIMEOUT / 2,
+            "{}: timed out after {:?}",
+            description,
+            elapsed
+        );
+        assert!(!event.try_wait(), "{}", description);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn signal_from_handler() {
+    use std::sync::atomic::AtomicPtr;
+
+    // The event that the handler signals
+    static EVENT: AtomicPtr<AutoResetEvent> = AtomicPtr::new(std::ptr::null_mut());
+
+    extern "C" fn handler(_: libc::c_int) {
+        let event = EVENT.load(Ordering::SeqCst);
+        if !event.is_null() {
+            unsafe { &*event }.signal_from_handler();
+        }
+    }
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as *const () as libc::sighandler_t;
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR2, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+    // Runs the handler on the calling thread
+    let raise = || assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+
+    for_each_event(|event, description| {
+        EVENT.store(Arc::as_ptr(&event) as *mut _, Ordering::SeqCst);
+
+        raise();
+        assert!(event.try_wait(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+
+        // Wakes a blocked waiter
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.try_wait_for(Duration::from_secs(5)))
+        };
+        assert!(
+            wait_until(|| event.snapshot().waiters == 1),
+            "{}",
+            description
+        );
+        raise();
+        assert!(waiter.join().unwrap(), "{}", description);
+
+        // The kernel object of an exported event mirrors the signal
+        raise();
+        assert!(is_readable(&event), "{}", description);
+        assert!(event.try_wait(), "{}", description);
+        assert!(!is_readable(&event), "{}", description);
+
+        EVENT.store(std::ptr::null_mut(), Ordering::SeqCst);
+    });
+}
+
+#[test]
+fn one_waiter_per_signal() {
+    const WAITERS: usize = 4;
+
+    for_each_event(|event, description| {
+        let woken = Arc::new(AtomicUsize::new(0));
+        let waiters: Vec<_> = (0..WAITERS)
+            .map(|_| {
+                let event = event.clone();
+                let woken = woken.clone();
+                thread::spawn(move || {
+                    event.wait();
+                    woken.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        assert!(
+            wait_until(|| event.snapshot().waiters == WAITERS as u32),
+            "{}",
+            description
+        );
+
+        for signals in 1..=WAITERS {
+            event.signal();
+            assert!(
+                wait_until(|| woken.load(Ordering::SeqCst) == signals),
+                "{}: {} signals woke {} waiters",
+                description,
+                signals,
+                woken.load(Ordering::SeqCst)
+            );
+            // No further waiter wakes up
+            thread::sleep(Duration::from_millis(20));
+            assert_eq!(woken.load(Ordering::SeqCst), signals, "{}", description);
+        }
+
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert!(!event.try_wait(), "{}", description);
+    });
+}
+
+#[test]
+fn stress_one_waiter_per_signal() {
+    const WAITERS: usize = 8;
+    const SIGNALS: usize = 200;
+
+    for_each_event(|event, description| {
+        let woken = AtomicUsize::new(0);
+        let done = AtomicBool::new(false);
+        thread::scope(|scope| {
+            let waiters: Vec<_> = (0..WAITERS)
+                .map(|i| {
+                    let (event, woken, done) = (&event, &woken, &done);
+                    scope.spawn(move || {
+                        while !done.load(Ordering::SeqCst) {
+                            // Mix short and long timed waits
+                            let timeout = if i % 2 == 0 {
+                                Duration::from_millis(1)
+                            } else {
+                                Duration::from_secs(5)
+                            };
+                            if event.try_wait_for(timeout) {
+                                woken.fetch_add(1, Ordering::SeqCst);
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            // Each signal is only sent once the previous one has been consumed, so that none is
+            // coalesced
+            for signals in 1..=SIGNALS {
+                event.signal();
+                assert!(
+                    wait_until(|| woken.load(Ordering::SeqCst) >= signals),
+                    "{}: signal {} was not consumed",
+                    description,
+                    signals
+                );
+                assert_eq!(
+                    woken.load(Ordering::SeqCst),
+                    signals,
+                    "{}: one signal woke several waiters",
+                    description
+                );
+            }
+
+            // Ends the long waits
+            done.store(true, Ordering::SeqCst);
+            while waiters.iter().any(|waiter| !waiter.is_finished()) {
+                event.signal();
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+    });
+}
+
+#[test]
+fn no_spurious_wakeups() {
+    const SIGNALS: u64 = 1000;
+
+    for_each_event(|event, description| {
+        // Each signal wakes the blocked waiters, but is often consumed by the signalling thread
+        // before they re-check the state. They have to block again without reporting a wakeup.
+        let woken = Arc::new(AtomicUsize::new(0));
+        let waiters: Vec<_> = (0..2)
+            .map(|i| {
+                let event = event.clone();
+                let woken = woken.clone();
+                thread::spawn(move || {
+                    loop {
+                        let result = if i == 0 {
+                            event.wait_result()
+                        } else {
+                            event.try_wait_for_result(Duration::from_secs(5))
+                        };
+                        match result {
+                            WaitResult::Signalled => woken.fetch_add(1, Ordering::SeqCst),
+                            WaitResult::Closed => break,
+                            result => panic!("unexpected {:?}", result),
+                        };
+                    }
+                })
+            })
+            .collect();
+
+        let mut consumed = 0;
+        for _ in 0..SIGNALS {
+            event.signal();
+            if event.try_wait() {
+                consumed += 1;
+            }
+        }
+        // A signal that the signalling thread did not consume has been consumed by a waiter
+        assert!(
+            wait_until(|| consumed + woken.load(Ordering::SeqCst) as u64 == SIGNALS),
+            "{}: {} signals, {} consumed, {} woken",
+            description,
+            SIGNALS,
+            consumed,
+            woken.load(Ordering::SeqCst)
+        );
+
+        event.close();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert_eq!(
+            consumed + woken.load(Ordering::SeqCst) as u64,
+            SIGNALS,
+            "{}",
+            description
+        );
+    });
+}
+
+#[test]
+fn drop_while_waiting() {
+    for_each_event(|event, description| {
+        // The waiter keeps the event alive after the other handle has been dropped
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.try_wait_for(Duration::from_millis(100)))
+        };
+        assert!(
+            wait_until(|| event.snapshot().waiters == 1),
+            "{}",
+            description
+        );
+        drop(event);
+        assert!(!waiter.join().unwrap(), "{}", description);
+    });
+
+    // An owner that goes away closes the event first to end the waits of the other owners, which
+    // then drop the last handle
+    for_each_event(|event, description| {
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait_result())
+        };
+        assert!(
+            wait_until(|| event.snapshot().waiters == 1),
+            "{}",
+            description
+        );
+        event.close();
+        drop(event);
+        assert_eq!(
+            waiter.join().unwrap(),
+            WaitResult::Closed,
+            "{}",
+            description
+        );
+    });
+
+    // A signalled event can be dropped, also with a pending wakeup in the kernel object
+    for_each_event(|event, _| {
+        event.signal();
+        drop(event);
+    });
+}
+
+#[test]
+fn close_wakes_all_waiters() {
+    for_each_event(|event, description| {
+        let plain = {
+            let event = event.clone();
+            thread::spawn(move || event.wait_result())
+        };
+        let timed = {
+            let event = event.clone();
+            thread::spawn(move || event.try_wait_for_result(Duration::from_secs(60)))
+        };
+        let other = Arc::new(AutoResetEvent::new().unwrap());
+        let any = {
+            let (event, other) = (event.clone(), other.clone());
+            thread::spawn(move || AutoResetEvent::wait_any(&[&other, &event]))
+        };
+        assert!(
+            wait_until(|| event.snapshot().waiters == 3),
+            "{}",
+            description
+        );
+
+        assert!(event.close(), "{}", description);
+        assert_eq!(plain.join().unwrap(), WaitResult::Closed, "{}", description);
+        assert_eq!(timed.join().unwrap(), WaitResult::Closed, "{}", description);
+        assert_eq!(any.join().unwrap(), 1, "{}", description);
+        assert!(!other.is_closed(), "{}", description);
+
+        let snapshot = event.snapshot();
+        assert!(snapshot.closed && snapshot.waiters == 0, "{:?}", snapshot);
+    });
+}
+
+#[test]
+fn closed_event_ignores_signals() {
+    for_each_event(|event, description| {
+        // A pending signal is discarded
+        event.signal();
+        assert!(event.close(), "{}", description);
+        assert!(!event.close(), "{}", description);
+        assert!(event.is_closed(), "{}", description);
+
+        event.signal();
+        assert!(!event.try_wait(), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+        assert_eq!(event.wait_result(), WaitResult::Closed, "{}", description);
+        assert_eq!(
+            event.try_wait_for_result(Duration::ZERO),
+            WaitResult::Closed,
+            "{}",
+            description
+        );
+        assert_eq!(
+            AutoResetEvent::try_wait_any_for(&[&event], Duration::from_millis(10)),
+            Some(0),
+            "{}",
+            description
+        );
+        assert!(!event.snapshot().signalled, "{}", description);
+
+        // Pollers of the fd notice the shutdown as well, also after waits on the closed event
+        #[cfg(unix)]
+        assert!(is_readable(&event), "{}", description);
+    });
+}
+
+#[test]
+fn waiter_count() {
+    for_each_event(|event, description| {
+        assert_eq!(event.waiter_count(), 0, "{}", description);
+
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let event = event.clone();
+                thread::spawn(move || event.wait())
+            })
+            .collect();
+        assert!(wait_until(|| event.waiter_count() == 3), "{}", description);
+        assert_eq!(event.snapshot().waiters, 3, "{}", description);
+
+        // Waiters that consume a signal or time out are no longer counted
+        event.signal();
+        assert!(wait_until(|| event.waiter_count() == 2), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+        assert_eq!(event.waiter_count(), 2, "{}", description);
+
+        event.close();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert_eq!(event.waiter_count(), 0, "{}", description);
+    });
+}
+
+#[test]
+fn signal_if_waiting() {
+    for_each_event(|event, description| {
+        // Without a waiter, nothing is latched or counted
+        assert!(!event.signal_if_waiting(), "{}", description);
+        assert!(!event.is_signalled(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+        assert_eq!(event.snapshot().total_signals, 0, "{}", description);
+
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.try_wait_for(Duration::from_secs(10)))
+        };
+        assert!(wait_until(|| event.waiter_count() == 1), "{}", description);
+        assert!(event.signal_if_waiting(), "{}", description);
+        assert!(waiter.join().unwrap(), "{}", description);
+        assert!(!event.is_signalled(), "{}", description);
+        assert_eq!(event.snapshot().total_signals, 1, "{}", description);
+
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait_result())
+        };
+        assert!(wait_until(|| event.waiter_count() == 1), "{}", description);
+        event.close();
+        assert!(!event.signal_if_waiting(), "{}", description);
+        assert_eq!(
+            waiter.join().unwrap(),
+            WaitResult::Closed,
+            "{}",
+            description
+        );
+    });
+}
+
+#[test]
+fn signal_n() {
+    for_each_event(|event, description| {
+        // Without waiters, the signals are coalesced like those of `signal`
+        event.signal_n(5);
+        assert_eq!(event.wait_count(), 5, "{}", description);
+        event.signal_n(5);
+        assert!(event.try_wait(), "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+        event.signal_n(0);
+        assert!(!event.is_signalled(), "{}", description);
+
+        let woken = Arc::new(AtomicUsize::new(0));
+        let waiters: Vec<_> = (0..4)
+            .map(|_| {
+                let event = event.clone();
+                let woken = woken.clone();
+                thread::spawn(move || {
+                    if event.try_wait_for(Duration::from_secs(10)) {
+                        woken.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        assert!(wait_until(|| event.waiter_count() == 4), "{}", description);
+
+        event.signal_n(3);
+        assert!(
+            wait_until(|| woken.load(Ordering::SeqCst) == 3),
+            "{}",
+            description
+        );
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(woken.load(Ordering::SeqCst), 3, "{}", description);
+        assert!(!event.is_signalled(), "{}", description);
+
+        event.signal();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert_eq!(woken.load(Ordering::SeqCst), 4, "{}", description);
+    });
+}
+
+#[test]
+fn pulse() {
+    for_each_event(|event, description| {
+        // Without a waiter, the pulse is lost
+        event.pulse();
+        assert!(!event.is_signalled(), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let event = event.clone();
+                thread::spawn(move || event.try_wait_for(Duration::from_secs(10)))
+            })
+            .collect();
+        let other = Arc::new(AutoResetEvent::new().unwrap());
+        let any = {
+            let event = event.clone();
+            let other = other.clone();
+            thread::spawn(move || AutoResetEvent::wait_any(&[&other, &event]))
+        };
+        assert!(wait_until(|| event.waiter_count() == 4), "{}", description);
+
+        event.pulse();
+        for waiter in waiters {
+            assert!(waiter.join().unwrap(), "{}", description);
+        }
+
+        // A wait on several events is not pulsed
+        thread::sleep(Duration::from_millis(10));
+        assert!(!any.is_finished(), "{}", description);
+        other.signal();
+        assert_eq!(any.join().unwrap(), 0, "{}", description);
+        assert_eq!(event.waiter_count(), 0, "{}", description);
+
+        // Nothing is left for later waits
+        assert!(!event.is_signalled(), "{}", description);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            description
+        );
+        assert_eq!(event.snapshot().total_signals, 0, "{}", description);
+    });
+}
+
+// Releases looping workers with `release` round by round, and checks that each of them is released
+// once per round, even though the released workers wait again right away
+fn assert_released_once_per_round(
+    event: Arc<AutoResetEvent>,
+    description: &str,
+    release: impl Fn(&AutoResetEvent),
+) {
+    const WORKERS: usize = 4;
+    const ROUNDS: usize = 20;
+
+    let rounds: Arc<Vec<AtomicUsize>> =
+        Arc::new((0..WORKERS).map(|_| AtomicUsize::new(0)).collect());
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|i| {
+            let event = event.clone();
+            let rounds = rounds.clone();
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    assert!(event.try_wait_for(Duration::from_secs(10)));
+                    rounds[i].fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        })
+        .collect();
+
+    for round in 1..=ROUNDS {
+        assert!(
+            wait_until(|| event.waiter_count() == WORKERS as u32),
+            "{}: round {}",
+            description,
+            round
+        );
+        release(&event);
+
+        assert!(
+            wait_until(|| rounds.iter().all(|n| n.load(Ordering::SeqCst) >= round)),
+            "{}: round {}",
+            description,
+            round
+        );
+        let counts: Vec<_> = rounds.iter().map(|n| n.load(Ordering::SeqCst)).collect();
+        assert_eq!(counts, [round; WORKERS], "{}", description);
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert!(!event.is_signalled(), "{}", description);
+}
+
+#[test]
+fn pulse_releases_each_waiter_once() {
+    for_each_event(|event, description| {
+        assert_released_once_per_round(event, description, AutoResetEvent::pulse);
+    });
+}
+
+#[test]
+fn signal_n_releases_each_waiter_once() {
+    for_each_event(|event, description| {
+        assert_released_once_per_round(event, description, |event| event.signal_n(4));
+    });
+}
+
+#[test]
+fn drain() {
+    for_each_event(|event, description| {
+        assert_eq!(event.drain(), 0, "{}", description);
+
+        // Discarded signals are not counted by `wait_count`
+        event.signal();
+        event.signal();
+        assert_eq!(event.drain(), 2, "{}", description);
+        event.signal();
+        assert_eq!(event.wait_count(), 1, "{}", description);
+
+        for _ in 0..5 {
+            event.signal();
+        }
+        assert_eq!(event.drain(), 5, "{}", description);
+        assert_eq!(event.drain(), 0, "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+
+        // Signals consumed by a wait in between are not pending anymore
+        event.signal();
+        event.signal();
+        event.wait();
+        event.signal();
+        assert_eq!(event.drain(), 1, "{}", description);
+        assert!(!event.try_wait(), "{}", description);
+        event.signal();
+        event.signal();
+        assert!(event.try_wait(), "{}", description);
+        assert_eq!(event.drain(), 0, "{}", description);
+
+        // No signal is lost while a producer keeps signalling
+        const SIGNALS: usize = 10000;
+        let producer = {
+            let event = event.clone();
+            thread::spawn(move || {
+                for _ in 0..SIGNALS {
+                    event.signal();
+                }
+            })
+        };
+        let mut received = 0;
+        while received < SIGNALS {
+            received += event.drain();
+        }
+        producer.join().unwrap();
+        assert_eq!(received, SIGNALS, "{}", description);
+        assert_eq!(event.drain(), 0, "{}", description);
+    });
+}
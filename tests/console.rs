@@ -0,0 +1,116 @@
+//! The tokio-console instrumentation of events and their futures, which is only emitted with the
+//! `tracing` feature and `--cfg tokio_unstable`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "tracing", tokio_unstable, not(any(loom, madsim))))]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nova_autoreset_event::AutoResetEvent;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// Records the names of the spans that are created, and the targets of the events
+#[derive(Default)]
+struct Recorder {
+    next_id: AtomicU64,
+    names: Mutex<Vec<String>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.names
+            .lock()
+            .unwrap()
+            .push(span.metadata().name().to_owned());
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.names
+            .lock()
+            .unwrap()
+            .push(event.metadata().target().to_owned());
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+// Runs `f` with a recorder as the subscriber of the current thread, and returns what it recorded
+fn record(f: impl FnOnce()) -> Vec<String> {
+    let recorder = Arc::new(Recorder::default());
+    tracing::subscriber::with_default(recorder.clone(), f);
+    recorder.names.lock().unwrap().clone()
+}
+
+fn count(names: &[String], name: &str) -> usize {
+    names.iter().filter(|recorded| *recorded == name).count()
+}
+
+#[test]
+fn events_are_resources() {
+    let names = record(|| {
+        let event = AutoResetEvent::new().unwrap();
+        event.signal();
+        assert!(event.try_wait());
+    });
+
+    assert_eq!(count(&names, "runtime.resource"), 1);
+    // Setting and consuming the signal
+    assert_eq!(count(&names, "runtime::resource::state_update"), 2);
+}
+
+#[test]
+fn async_waits_are_async_ops() {
+    let names = record(|| {
+        let event = AutoResetEvent::new().unwrap();
+        event.signal();
+        futures_executor::block_on(event.wait_async());
+        // Signalled before the wait, so that neither future registers with a reactor, which
+        // creates resources of its own
+        event.signal();
+        assert!(futures_executor::block_on(
+            event.wait_async_timeout(Duration::from_secs(1))
+        ));
+    });
+
+    assert_eq!(count(&names, "runtime.resource"), 1);
+    assert_eq!(count(&names, "runtime.resource.async_op"), 2);
+    assert_eq!(count(&names, "runtime.resource.async_op.poll"), 2);
+    assert_eq!(count(&names, "runtime::resource::poll_op"), 2);
+}
+
+#[cfg(all(unix, feature = "tokio"))]
+#[test]
+fn async_events_are_resources() {
+    use nova_autoreset_event::AsyncAutoResetEvent;
+
+    let names = record(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let event = AsyncAutoResetEvent::new().unwrap();
+            event.signal();
+            event.wait().await;
+        });
+    });
+
+    // The event that the async event wraps is a resource as well
+    assert_eq!(count(&names, "runtime.resource"), 2);
+    assert!(count(&names, "runtime.resource.async_op") >= 1);
+}
@@ -0,0 +1,28 @@
+//! Console control events delivered via `ConsoleCtrlEvent`.
+
+#![cfg(all(windows, not(any(loom, madsim))))]
+
+use std::time::Duration;
+
+use nova_autoreset_event::ConsoleCtrlEvent;
+
+// `GenerateConsoleCtrlEvent` would reach every process attached to the console, including cargo,
+// so the control events themselves are not generated
+#[test]
+fn events_are_installed_and_removed() {
+    let first = ConsoleCtrlEvent::new().unwrap();
+    let second = ConsoleCtrlEvent::new().unwrap();
+    assert!(!first.try_wait_for(Duration::from_millis(10)));
+    assert!(first.take_received().is_empty());
+
+    // Signalling one event directly does not affect the other
+    second.signal();
+    assert!(!first.try_wait());
+    assert!(second.try_wait());
+
+    drop(first);
+    drop(second);
+    // The handler is installed again for a new event
+    let third = ConsoleCtrlEvent::new().unwrap();
+    assert!(!third.try_wait());
+}
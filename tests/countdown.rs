@@ -0,0 +1,77 @@
+//! Waiting for a count of pending operations via `CountdownEvent`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::CountdownEvent;
+
+#[test]
+fn waits_until_the_count_is_zero() {
+    let countdown = Arc::new(CountdownEvent::new(2).unwrap());
+    assert!(!countdown.try_wait_for(Duration::from_millis(10)));
+
+    assert!(!countdown.signal());
+    assert!(countdown.signal());
+    countdown.wait();
+    assert!(countdown.try_wait_for(Duration::ZERO));
+
+    // The event can be reused
+    countdown.add(1);
+    assert!(!countdown.try_wait_for(Duration::from_millis(10)));
+    let signaller = {
+        let countdown = countdown.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            countdown.signal()
+        })
+    };
+    countdown.wait();
+    assert!(signaller.join().unwrap());
+}
+
+#[test]
+fn releases_all_waiters() {
+    let countdown = Arc::new(CountdownEvent::new(1).unwrap());
+    let waiters: Vec<_> = (0..4)
+        .map(|_| {
+            let countdown = countdown.clone();
+            thread::spawn(move || countdown.wait())
+        })
+        .collect();
+    thread::sleep(Duration::from_millis(20));
+    countdown.signal();
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+}
+
+#[test]
+fn guards_signal_when_dropped() {
+    let countdown = Arc::new(CountdownEvent::new(0).unwrap());
+    let guard = countdown.guard();
+    let panicking = {
+        let guard = countdown.guard();
+        thread::spawn(move || {
+            let _guard = guard;
+            panic!("expected");
+        })
+    };
+    assert_eq!(countdown.count(), 2);
+
+    assert!(panicking.join().is_err());
+    assert_eq!(countdown.count(), 1);
+    drop(guard);
+    countdown.wait();
+}
+
+#[test]
+#[should_panic(expected = "signalled more often than its count")]
+fn signalling_below_zero_panics() {
+    let countdown = CountdownEvent::new(0).unwrap();
+    countdown.signal();
+}
@@ -0,0 +1,60 @@
+//! Rate-coalesced signalling via `DebouncedSignaler`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, DebouncedSignaler};
+
+#[test]
+fn signals_are_coalesced_per_interval() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let signaler = DebouncedSignaler::new(&event, Duration::from_millis(50)).unwrap();
+    assert_eq!(signaler.min_interval(), Duration::from_millis(50));
+
+    // The leading signal is delivered right away
+    signaler.signal();
+    let start = Instant::now();
+    assert!(event.try_wait());
+
+    // The trailing one at the end of the interval
+    for _ in 0..1000 {
+        signaler.signal();
+    }
+    assert!(!event.try_wait());
+    assert!(event.try_wait_for(Duration::from_secs(10)));
+    assert!(start.elapsed() >= Duration::from_millis(45));
+    assert!(!event.try_wait_for(Duration::from_millis(100)));
+
+    // After a quiet period, the next signal is delivered right away again
+    signaler.signal();
+    assert!(event.try_wait());
+}
+
+#[test]
+fn wakeups_are_rate_limited() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let signaler = Arc::new(DebouncedSignaler::new(&event, Duration::from_millis(20)).unwrap());
+    let producer = {
+        let signaler = signaler.clone();
+        thread::spawn(move || {
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_millis(200) {
+                signaler.signal();
+                thread::yield_now();
+            }
+        })
+    };
+
+    let mut wakeups = 0;
+    while event.try_wait_for(Duration::from_millis(500)) {
+        wakeups += 1;
+    }
+    producer.join().unwrap();
+    // One per interval, plus the leading and trailing ones
+    assert!((2..=13).contains(&wakeups), "{} wakeups", wakeups);
+}
@@ -0,0 +1,150 @@
+//! Dispatching the signals of many events to callbacks via `EventDispatcher`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, EventDispatcher};
+
+fn counter(dispatcher: &mut EventDispatcher, event: &Arc<AutoResetEvent>) -> Arc<AtomicUsize> {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    dispatcher.add(event.clone(), move || {
+        counted.fetch_add(1, Ordering::SeqCst);
+    });
+    calls
+}
+
+#[test]
+fn runs_callbacks_of_signalled_events() {
+    let events: Vec<_> = (0..3)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let calls: Vec<_> = events
+        .iter()
+        .map(|event| counter(&mut dispatcher, event))
+        .collect();
+
+    events[2].signal();
+    events[0].signal();
+    assert_eq!(dispatcher.dispatch(), 2);
+    let counts: Vec<_> = calls.iter().map(|c| c.load(Ordering::SeqCst)).collect();
+    assert_eq!(counts, [1, 0, 1]);
+
+    // The signals have been consumed
+    assert_eq!(dispatcher.try_dispatch_for(Duration::from_millis(10)), 0);
+    assert!(events.iter().all(|event| !event.try_wait()));
+}
+
+#[test]
+fn remove_drops_callback() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let key = {
+        let calls = calls.clone();
+        dispatcher.add(event.clone(), move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+        })
+    };
+    assert_eq!(dispatcher.len(), 1);
+
+    assert!(dispatcher.remove(key));
+    assert!(!dispatcher.remove(key));
+    assert!(dispatcher.is_empty());
+    assert_eq!(Arc::strong_count(&calls), 1);
+
+    event.signal();
+    assert_eq!(dispatcher.try_dispatch_for(Duration::from_millis(10)), 0);
+    assert!(event.try_wait());
+}
+
+#[test]
+fn closed_events_are_removed() {
+    let closed = Arc::new(AutoResetEvent::new().unwrap());
+    let open = Arc::new(AutoResetEvent::new().unwrap());
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let closed_calls = counter(&mut dispatcher, &closed);
+    let open_calls = counter(&mut dispatcher, &open);
+
+    closed.close();
+    assert_eq!(dispatcher.dispatch(), 0);
+    assert_eq!(dispatcher.len(), 1);
+    assert_eq!(Arc::strong_count(&closed_calls), 1);
+
+    open.signal();
+    assert_eq!(dispatcher.dispatch(), 1);
+    assert_eq!(open_calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn run_until_stopped() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let calls = counter(&mut dispatcher, &event);
+    let stop = dispatcher.stop_event().clone();
+
+    let runner = thread::spawn(move || dispatcher.run());
+    for expected in 1..=3 {
+        event.signal();
+        while calls.load(Ordering::SeqCst) < expected {
+            thread::yield_now();
+        }
+    }
+    stop.signal();
+    runner.join().unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn busy_event_does_not_starve_others() {
+    let busy = Arc::new(AutoResetEvent::new().unwrap());
+    let quiet = Arc::new(AutoResetEvent::new().unwrap());
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    for (name, event) in [("busy", &busy), ("quiet", &quiet)] {
+        let order = order.clone();
+        let resignal = event.clone();
+        dispatcher.add(event.clone(), move || {
+            order.lock().unwrap().push(name);
+            // Keeps the busy event signalled all the time
+            if name == "busy" {
+                resignal.signal();
+            }
+        });
+    }
+
+    busy.signal();
+    quiet.signal();
+    assert_eq!(dispatcher.dispatch(), 2);
+    assert_eq!(*order.lock().unwrap(), ["busy", "quiet"]);
+}
+
+#[test]
+fn many_events() {
+    let events: Vec<_> = (0..60)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let mut dispatcher = EventDispatcher::new().unwrap();
+    let calls: Vec<_> = events
+        .iter()
+        .map(|event| counter(&mut dispatcher, event))
+        .collect();
+
+    for event in events.iter().step_by(2) {
+        event.signal();
+    }
+    let mut dispatched = 0;
+    while dispatched < events.len() / 2 {
+        dispatched += dispatcher.dispatch();
+    }
+    for (index, calls) in calls.iter().enumerate() {
+        assert_eq!(calls.load(Ordering::SeqCst), usize::from(index % 2 == 0));
+    }
+}
@@ -0,0 +1,32 @@
+//! Waking an event loop together with an event via `EventLoopWaker`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+
+use nova_autoreset_event::{AutoResetEvent, EventLoopWaker};
+
+#[test]
+fn signal_wakes_event_and_loop() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    let sender = std::sync::Mutex::new(sender);
+    let waker = EventLoopWaker::new(event.clone(), move || {
+        sender.lock().unwrap().send(()).unwrap();
+    });
+
+    let background = {
+        let waker = waker.clone();
+        thread::spawn(move || waker.signal())
+    };
+
+    // The event is signalled before the loop is woken
+    receiver.recv().unwrap();
+    assert!(event.try_wait());
+    background.join().unwrap();
+    assert!(Arc::ptr_eq(waker.event(), &event));
+}
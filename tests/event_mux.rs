@@ -0,0 +1,72 @@
+//! Fan-in via `EventMux`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, EventMux};
+
+#[test]
+fn sources_are_identified() {
+    let mux = EventMux::new().unwrap();
+    let sources: Vec<_> = (0..64).map(|_| mux.add_source().unwrap()).collect();
+    assert!(mux.add_source().is_none());
+    assert!(mux.try_wait().is_empty());
+
+    sources[3].signal();
+    sources[63].signal();
+    sources[3].signal();
+    let fired = mux.wait();
+    assert_eq!(fired.len(), 2);
+    assert_eq!(fired.iter().collect::<Vec<_>>(), [3, 63]);
+    assert_eq!(fired.bits(), 1 << 3 | 1 << 63);
+    assert!(mux.try_wait_for(Duration::from_millis(10)).is_empty());
+
+    // Ids of dropped sources are reused
+    drop(sources);
+    assert_eq!(mux.add_source().unwrap().id(), 0);
+}
+
+#[test]
+fn producers_wake_the_consumer() {
+    let mux = EventMux::new().unwrap();
+    let producers: Vec<_> = (0..8)
+        .map(|_| {
+            let source = mux.add_source().unwrap();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    source.signal();
+                    thread::yield_now();
+                }
+                source.id()
+            })
+        })
+        .collect();
+
+    let mut seen = 0;
+    while seen != 0xff {
+        let fired = mux.try_wait_for(Duration::from_secs(10));
+        assert!(!fired.is_empty());
+        seen |= fired.bits();
+    }
+    for producer in producers {
+        assert!(producer.join().unwrap() < 8);
+    }
+}
+
+#[test]
+fn mux_event_with_wait_any() {
+    let mux = EventMux::new().unwrap();
+    let other = AutoResetEvent::new().unwrap();
+    let source = mux.add_source().unwrap();
+
+    source.signal();
+    assert_eq!(AutoResetEvent::wait_any(&[&other, mux.event()]), 1);
+    assert!(mux.try_wait().contains(source.id()));
+
+    mux.event().close();
+    assert!(mux.wait().is_empty());
+}
@@ -0,0 +1,81 @@
+//! Creating a kernel object when the process has run out of file descriptors. Runs in its own
+//! process, as it lowers the fd limit.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(unix, not(any(loom, madsim))))]
+
+use std::fs::File;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, Backend, EventErrorKind, EventOperation};
+
+#[test]
+fn fd_exhaustion_is_diagnosed() {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) },
+        0
+    );
+    let lowered = libc::rlimit {
+        rlim_cur: limit.rlim_cur.min(256),
+        ..limit
+    };
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &lowered) }, 0);
+
+    // Lazy events are built before the fds run out, and only create their kernel objects then
+    let lazy_events: Vec<_> = Backend::available()
+        .iter()
+        .map(|&backend| {
+            AutoResetEvent::builder()
+                .backend(backend)
+                .lazy(true)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let mut files = Vec::new();
+    while let Ok(file) = File::open("/dev/null") {
+        files.push(file);
+    }
+
+    for &backend in Backend::available() {
+        let err = match AutoResetEvent::builder().backend(backend).build() {
+            // A futex only needs an fd once one is requested
+            Ok(event) => {
+                assert_eq!(backend, Backend::Futex);
+                event.try_as_fd().unwrap_err()
+            }
+            Err(err) => err,
+        };
+        assert_eq!(err.backend(), backend);
+        assert_eq!(err.operation(), EventOperation::Create);
+        assert_eq!(err.kind(), EventErrorKind::ResourceExhausted, "{}", err);
+        assert!(err.to_string().contains("too many open files"), "{}", err);
+    }
+    for event in &lazy_events {
+        let mut errors = vec![event.try_as_fd().unwrap_err()];
+        // A futex does not need an fd to block on
+        if event.backend() != Backend::Futex {
+            errors.push(event.wait_checked().unwrap_err());
+            errors.push(
+                event
+                    .try_wait_for_checked(Duration::from_millis(10))
+                    .unwrap_err(),
+            );
+        }
+        for err in errors {
+            assert_eq!(err.backend(), event.backend());
+            assert_eq!(err.operation(), EventOperation::Create);
+            assert_eq!(err.kind(), EventErrorKind::ResourceExhausted, "{}", err);
+            assert!(err.to_string().contains("too many open files"), "{}", err);
+        }
+        // The failed waits have left
+        assert_eq!(event.snapshot().waiters, 0);
+    }
+    drop(files);
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) }, 0);
+}
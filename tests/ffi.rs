@@ -0,0 +1,116 @@
+//! The C interface of the `ffi` feature, and its generated header.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "ffi", not(any(loom, madsim))))]
+
+use std::mem::MaybeUninit;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::ffi::*;
+
+// Lets the raw pointer cross threads, the event itself is `Sync`
+#[derive(Clone, Copy)]
+struct EventPtr(*const NovaAutoResetEvent);
+
+unsafe impl Send for EventPtr {}
+
+#[test]
+fn signal_and_wait() {
+    let event = nova_are_create();
+    assert!(!event.is_null());
+
+    unsafe {
+        assert!(!nova_are_try_wait(event));
+        assert!(!nova_are_try_wait_for(event, 1_000_000));
+
+        nova_are_signal(event);
+        assert!(nova_are_try_wait(event));
+        assert!(!nova_are_try_wait(event));
+
+        let ptr = EventPtr(event);
+        let signaller = thread::spawn(move || {
+            let ptr = ptr;
+            thread::sleep(Duration::from_millis(20));
+            nova_are_signal(ptr.0);
+            thread::sleep(Duration::from_millis(20));
+            nova_are_signal(ptr.0);
+        });
+        nova_are_wait(event);
+        assert!(nova_are_try_wait_for(event, 10_000_000_000));
+        signaller.join().unwrap();
+
+        nova_are_destroy(event);
+        nova_are_destroy(std::ptr::null_mut());
+    }
+}
+
+// Lets the raw pointer cross threads, the functions access the event atomically
+#[derive(Clone, Copy)]
+struct RawEventPtr(*mut RawAutoResetEvent);
+
+unsafe impl Send for RawEventPtr {}
+
+#[test]
+fn raw_event() {
+    // Two 32-bit words and two pointer-sized handles, as documented
+    assert_eq!(
+        std::mem::size_of::<RawAutoResetEvent>(),
+        8 + 2 * std::mem::size_of::<isize>()
+    );
+
+    let mut storage = MaybeUninit::<RawAutoResetEvent>::uninit();
+    let event = storage.as_mut_ptr();
+
+    unsafe {
+        assert!(nova_raw_are_init(event));
+        assert!(!nova_raw_are_try_wait(event));
+        assert!(!nova_raw_are_try_wait_for(event, 1_000_000));
+
+        nova_raw_are_signal(event);
+        nova_raw_are_signal(event);
+        assert!(nova_raw_are_try_wait(event));
+        assert!(!nova_raw_are_try_wait(event));
+
+        let ptr = RawEventPtr(event);
+        let signaller = thread::spawn(move || {
+            let ptr = ptr;
+            thread::sleep(Duration::from_millis(20));
+            nova_raw_are_signal(ptr.0);
+            thread::sleep(Duration::from_millis(20));
+            nova_raw_are_signal(ptr.0);
+        });
+        nova_raw_are_wait(event);
+        assert!(nova_raw_are_try_wait_for(event, 10_000_000_000));
+        signaller.join().unwrap();
+
+        nova_raw_are_destroy(event);
+    }
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = env!("CARGO_MANIFEST_DIR");
+    let header = Path::new(crate_dir).join("include/nova_autoreset_event.h");
+    let config = cbindgen::Config::from_root_or_default(crate_dir);
+
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        std::fs::write(&header, &generated).unwrap();
+    }
+    let committed = std::fs::read(&header).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "{} is out of date, regenerate it with `UPDATE_HEADER=1 cargo test --features ffi --test ffi`",
+        header.display()
+    );
+}
@@ -0,0 +1,74 @@
+//! File system changes delivered via `FsWatchEvent`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "fs-watch", not(any(loom, madsim))))]
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, FsWatchEvent};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// A new empty directory for a test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "nova-autoreset-event-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn directory_changes() {
+    let dir = test_dir("directory");
+    let watch = FsWatchEvent::new(&dir).unwrap();
+    assert_eq!(watch.path(), dir);
+    assert!(!watch.try_wait_for(Duration::from_millis(50)));
+
+    let file = dir.join("config.toml");
+    fs::write(&file, "a = 1").unwrap();
+    assert!(watch.try_wait_for(TIMEOUT));
+
+    // Replacing the file, as editors do
+    let temp = dir.join("config.toml.tmp");
+    fs::write(&temp, "a = 2").unwrap();
+    fs::rename(&temp, &file).unwrap();
+    assert!(watch.try_wait_for(TIMEOUT));
+
+    fs::remove_file(&file).unwrap();
+    assert!(watch.try_wait_for(TIMEOUT));
+
+    drop(watch);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_changes() {
+    let dir = test_dir("file");
+    let file = dir.join("config.toml");
+    fs::write(&file, "a = 1").unwrap();
+
+    let watch = FsWatchEvent::new(&file).unwrap();
+    let other = AutoResetEvent::new().unwrap();
+    fs::write(&file, "a = 2").unwrap();
+    assert_eq!(
+        AutoResetEvent::try_wait_any_for(&[&other, &watch], TIMEOUT),
+        Some(1)
+    );
+
+    drop(watch);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_path() {
+    let dir = test_dir("missing");
+    assert!(FsWatchEvent::new(dir.join("missing")).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
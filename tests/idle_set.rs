@@ -0,0 +1,103 @@
+//! Parking and waking workers via `IdleSet`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::IdleSet;
+
+#[test]
+fn wake_without_parked_worker_leaves_permit() {
+    let idle = IdleSet::new();
+    assert!(!idle.wake_one());
+    assert!(!idle.wake_one());
+    // Permits are not counted
+    idle.park();
+    assert!(!idle.park_timeout(Duration::from_millis(10)));
+}
+
+#[test]
+fn park_timeout() {
+    let idle = IdleSet::new();
+    let start = Instant::now();
+    assert!(!idle.park_timeout(Duration::from_millis(20)));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(idle.parked(), 0);
+}
+
+#[test]
+fn wake_one_wakes_one_worker() {
+    let idle = Arc::new(IdleSet::new());
+    let woken = Arc::new(AtomicUsize::new(0));
+    let workers: Vec<_> = (0..3)
+        .map(|_| {
+            let (idle, woken) = (idle.clone(), woken.clone());
+            thread::spawn(move || {
+                idle.park();
+                woken.fetch_add(1, Ordering::SeqCst);
+            })
+        })
+        .collect();
+
+    while idle.parked() < 3 {
+        thread::yield_now();
+    }
+    assert!(idle.wake_one());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(woken.load(Ordering::SeqCst), 1);
+    assert_eq!(idle.parked(), 2);
+
+    assert_eq!(idle.wake_all(), 2);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert_eq!(woken.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn no_lost_jobs() {
+    const JOBS: usize = 10_000;
+
+    let idle = Arc::new(IdleSet::new());
+    let jobs = Arc::new(Mutex::new(VecDeque::new()));
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let (idle, jobs, done) = (idle.clone(), jobs.clone(), done.clone());
+            thread::spawn(move || {
+                loop {
+                    let job = jobs.lock().unwrap().pop_front();
+                    match job {
+                        Some(true) => {
+                            done.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Some(false) => return,
+                        None => idle.park(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for _ in 0..JOBS {
+        jobs.lock().unwrap().push_back(true);
+        idle.wake_one();
+    }
+    while done.load(Ordering::SeqCst) < JOBS {
+        thread::yield_now();
+    }
+    for _ in &workers {
+        jobs.lock().unwrap().push_back(false);
+    }
+    idle.wake_all();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+//! Timed waits that end when a Unix signal interrupts them, via
+//! `AutoResetEvent::try_wait_for_interruptible`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, WaitResult};
+
+#[test]
+fn reports_signal_timeout_and_close() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    assert_eq!(
+        event.try_wait_for_interruptible(Duration::from_millis(10)),
+        WaitResult::TimedOut
+    );
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+    assert_eq!(
+        event.try_wait_for_interruptible(Duration::from_secs(10)),
+        WaitResult::Signalled
+    );
+    signaller.join().unwrap();
+
+    event.close();
+    assert_eq!(
+        event.try_wait_for_interruptible(Duration::from_secs(10)),
+        WaitResult::Closed
+    );
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn injected_interrupt() {
+    use nova_autoreset_event::{Backend, Fault, FaultInjector};
+
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        let event = AutoResetEvent::builder()
+            .backend(backend)
+            .faults(faults.clone())
+            .build()
+            .unwrap();
+
+        faults.inject(Fault::Interrupt, 1);
+        assert_eq!(
+            event.try_wait_for_interruptible(Duration::from_secs(10)),
+            WaitResult::Interrupted,
+            "{}",
+            backend
+        );
+        assert_eq!(event.snapshot().waiters, 0, "{}", backend);
+
+        // The other waits restart
+        faults.inject(Fault::Interrupt, 1);
+        assert!(
+            !event.try_wait_for(Duration::from_millis(10)),
+            "{}",
+            backend
+        );
+        assert_eq!(faults.injected(Fault::Interrupt), 2, "{}", backend);
+    }
+}
+
+#[cfg(feature = "fault-injection")]
+#[test]
+fn report_policy() {
+    use nova_autoreset_event::{Backend, Fault, FaultInjector, InterruptPolicy, SignalReceiver};
+
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        let event = Arc::new(
+            AutoResetEvent::builder()
+                .backend(backend)
+                .faults(faults.clone())
+                .on_interrupt(InterruptPolicy::Report)
+                .build()
+                .unwrap(),
+        );
+
+        faults.inject(Fault::Interrupt, 1);
+        assert_eq!(event.wait_result(), WaitResult::Interrupted, "{}", backend);
+        faults.inject(Fault::Interrupt, 1);
+        assert_eq!(
+            event.try_wait_for_result(Duration::from_secs(10)),
+            WaitResult::Interrupted,
+            "{}",
+            backend
+        );
+        assert_eq!(event.snapshot().waiters, 0, "{}", backend);
+
+        // The counting waits restart
+        faults.inject(Fault::Interrupt, 1);
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        assert_eq!(event.wait_count(), 1, "{}", backend);
+        signaller.join().unwrap();
+        assert_eq!(faults.injected(Fault::Interrupt), 3, "{}", backend);
+
+        // So do the waits of a receiver, also with a timeout
+        let receiver = SignalReceiver::new(event.clone());
+        faults.inject(Fault::Interrupt, 1);
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(10)),
+            Ok(()),
+            "{}",
+            backend
+        );
+        signaller.join().unwrap();
+        assert_eq!(faults.injected(Fault::Interrupt), 4, "{}", backend);
+    }
+}
+
+// Sends `SIGUSR1` to a thread that waits via `wait` once it blocks, and returns the result
+#[cfg(unix)]
+fn interrupt_wait(
+    event: &Arc<AutoResetEvent>,
+    wait: fn(&AutoResetEvent) -> WaitResult,
+) -> WaitResult {
+    use std::os::unix::thread::JoinHandleExt;
+
+    extern "C" fn on_sigusr1(_: libc::c_int) {}
+
+    // Without `SA_RESTART`, so that the blocking call fails with `EINTR`
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigusr1 as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        assert_eq!(
+            libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()),
+            0
+        );
+    }
+
+    let waiter = {
+        let event = event.clone();
+        thread::spawn(move || wait(&event))
+    };
+    while event.snapshot().waiters == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Repeated, as a signal that arrives before the waiter blocks in the kernel does not
+    // interrupt it, e.g. on a loaded machine
+    for _ in 0..10 {
+        thread::sleep(Duration::from_millis(20));
+        if waiter.is_finished() {
+            break;
+        }
+        unsafe {
+            assert_eq!(libc::pthread_kill(waiter.as_pthread_t(), libc::SIGUSR1), 0);
+        }
+    }
+    // Ends a wait that has been restarted
+    thread::sleep(Duration::from_millis(20));
+    event.signal();
+    waiter.join().unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_signal_interrupts_wait() {
+    use nova_autoreset_event::{Backend, InterruptPolicy};
+
+    for &backend in Backend::available() {
+        let event = Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap());
+        assert_eq!(
+            interrupt_wait(&event, |event| {
+                event.try_wait_for_interruptible(Duration::from_secs(10))
+            }),
+            WaitResult::Interrupted,
+            "{}",
+            backend
+        );
+        assert!(event.try_wait(), "{}", backend);
+
+        // Restarted by default
+        assert_eq!(
+            interrupt_wait(&event, AutoResetEvent::wait_result),
+            WaitResult::Signalled,
+            "{}",
+            backend
+        );
+
+        let event = Arc::new(
+            AutoResetEvent::builder()
+                .backend(backend)
+                .on_interrupt(InterruptPolicy::Report)
+                .build()
+                .unwrap(),
+        );
+        assert_eq!(
+            interrupt_wait(&event, AutoResetEvent::wait_result),
+            WaitResult::Interrupted,
+            "{}",
+            backend
+        );
+        assert!(event.try_wait(), "{}", backend);
+    }
+}
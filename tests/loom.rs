@@ -0,0 +1,251 @@
+//! Exhaustive checks of the userspace state machine of an event with loom.
+//!
+//! Run with `RUSTFLAGS="--cfg loom" cargo test --release --test loom`. A lost wakeup shows up as a
+//! deadlock that loom reports, a double wakeup as a failed assertion.
+
+#![cfg(loom)]
+
+use std::time::Duration;
+
+use loom::sync::Arc;
+use loom::sync::atomic::{AtomicUsize, Ordering};
+use loom::thread;
+use nova_autoreset_event::{ModelEvent, WaitResult};
+
+#[test]
+fn signal_wakes_waiter() {
+    loom::model(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let signaller = thread::spawn({
+            let event = event.clone();
+            move || event.signal()
+        });
+
+        event.wait();
+        signaller.join().unwrap();
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn signal_is_consumed_once() {
+    loom::model(|| {
+        let event = Arc::new(ModelEvent::new());
+        let consumed = Arc::new(AtomicUsize::new(0));
+        event.signal();
+
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let event = event.clone();
+                let consumed = consumed.clone();
+                thread::spawn(move || {
+                    if event.try_wait() {
+                        consumed.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+
+        assert_eq!(consumed.load(Ordering::SeqCst), 1);
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn blocked_waiters_are_woken_once_per_signal() {
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(3);
+    model.check(|| {
+        let event = Arc::new(ModelEvent::new());
+        let woken = Arc::new(ModelEvent::new());
+
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let event = event.clone();
+                let woken = woken.clone();
+                thread::spawn(move || {
+                    event.wait();
+                    woken.signal();
+                })
+            })
+            .collect();
+
+        // Signals are coalesced, so each signal waits until it has been consumed
+        for _ in 0..2 {
+            event.signal();
+            woken.wait();
+        }
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn timed_out_wait_does_not_lose_signal() {
+    loom::model(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let signaller = thread::spawn({
+            let event = event.clone();
+            move || event.signal()
+        });
+
+        // The wait times out as soon as it blocks, racing with the signal
+        let consumed = event.try_wait_for(Duration::from_secs(1));
+        signaller.join().unwrap();
+        assert_ne!(consumed, event.try_wait());
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn timed_out_wait_does_not_steal_wakeup() {
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(3);
+    model.check(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let waiter = thread::spawn({
+            let event = event.clone();
+            move || event.wait()
+        });
+        let timed = thread::spawn({
+            let event = event.clone();
+            move || event.try_wait_for(Duration::from_secs(1))
+        });
+
+        event.signal();
+        if timed.join().unwrap() {
+            event.signal();
+        }
+        waiter.join().unwrap();
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn close_wakes_all_waiters() {
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(3);
+    model.check(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let event = event.clone();
+                thread::spawn(move || event.wait_result())
+            })
+            .collect();
+
+        event.close();
+        for waiter in waiters {
+            assert_eq!(waiter.join().unwrap(), WaitResult::Closed);
+        }
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn wait_any_is_woken_by_either_event() {
+    loom::model(|| {
+        let first = Arc::new(ModelEvent::new());
+        let second = Arc::new(ModelEvent::new());
+
+        let signaller = thread::spawn({
+            let second = second.clone();
+            move || second.signal()
+        });
+
+        assert_eq!(ModelEvent::wait_any(&[&first, &second]), 1);
+        signaller.join().unwrap();
+        assert!(!first.is_signalled());
+        assert!(!second.is_signalled());
+    });
+}
+
+#[test]
+fn signal_n_releases_each_blocked_waiter_once() {
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(2);
+    model.check(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        // Each waiter waits again once it has been released, which must not take the release of
+        // the other one
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let event = event.clone();
+                thread::spawn(move || {
+                    event.wait();
+                    event.try_wait_for(Duration::from_secs(1))
+                })
+            })
+            .collect();
+        while event.waiter_count() < 2 {
+            thread::yield_now();
+        }
+
+        event.signal_n(2);
+        for waiter in waiters {
+            assert!(!waiter.join().unwrap());
+        }
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn pulse_releases_each_blocked_waiter_once() {
+    let mut model = loom::model::Builder::new();
+    model.preemption_bound = Some(2);
+    model.check(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let waiters: Vec<_> = (0..2)
+            .map(|_| {
+                let event = event.clone();
+                thread::spawn(move || {
+                    event.wait();
+                    event.try_wait_for(Duration::from_secs(1))
+                })
+            })
+            .collect();
+        while event.waiter_count() < 2 {
+            thread::yield_now();
+        }
+
+        event.pulse();
+        for waiter in waiters {
+            assert!(!waiter.join().unwrap());
+        }
+        assert!(!event.is_signalled());
+    });
+}
+
+#[test]
+fn reset_and_drain_discard_the_signal() {
+    loom::model(|| {
+        let event = Arc::new(ModelEvent::new());
+
+        let signaller = thread::spawn({
+            let event = event.clone();
+            move || {
+                event.signal();
+                event.signal();
+            }
+        });
+
+        // Whatever the interleaving, the signals are either discarded or still pending
+        let reset = event.reset();
+        let drained = event.drain();
+        signaller.join().unwrap();
+        let pending = event.try_wait();
+        assert!(reset || drained != 0 || pending);
+        assert!(!event.is_signalled());
+    });
+}
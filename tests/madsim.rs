@@ -0,0 +1,56 @@
+//! The behaviour of events under madsim, where blocking would stall the simulation.
+//!
+//! Run with `RUSTFLAGS="--cfg madsim" cargo test --test madsim`.
+
+#![cfg(madsim)]
+
+use std::panic;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, Backend};
+
+#[test]
+fn signalled_events() {
+    let event = AutoResetEvent::new().unwrap();
+    event.signal();
+    event.signal();
+    assert_eq!(event.wait_count(), 2);
+
+    event.signal();
+    assert!(event.try_wait_for(Duration::from_secs(60)));
+    event.signal();
+    assert_eq!(AutoResetEvent::wait_any(&[&event]), 0);
+    assert!(!event.try_wait());
+}
+
+#[test]
+fn timed_waits_do_not_block() {
+    let event = AutoResetEvent::new().unwrap();
+    let other = AutoResetEvent::new().unwrap();
+
+    let start = Instant::now();
+    assert!(!event.try_wait_for(Duration::from_secs(60)));
+    assert_eq!(
+        AutoResetEvent::try_wait_any_for(&[&event, &other], Duration::from_secs(60)),
+        None
+    );
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(event.snapshot().waiters, 0);
+}
+
+#[test]
+fn waits_without_timeout_panic() {
+    let event = AutoResetEvent::new().unwrap();
+    assert!(panic::catch_unwind(|| event.wait()).is_err());
+    assert!(panic::catch_unwind(|| AutoResetEvent::wait_any(&[&event])).is_err());
+
+    // The event is still usable
+    assert_eq!(event.snapshot().waiters, 0);
+    event.signal();
+    assert!(event.try_wait());
+}
+
+#[test]
+fn fastest_backend_is_not_measured() {
+    assert_eq!(Backend::fastest(), Backend::native());
+}
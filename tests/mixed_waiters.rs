@@ -0,0 +1,119 @@
+//! Threads that block in `wait()` and tasks that await `wait_async()` on the same event.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures_executor::block_on;
+use nova_autoreset_event::{AutoResetEvent, Backend, WaitResult};
+
+const SIGNALS: usize = 2_000;
+
+// Signals the event once the previous signal has been consumed, so that no signal is coalesced,
+// and checks that each one is consumed exactly once
+fn signal_one_by_one(event: &AutoResetEvent, consumed: &AtomicUsize) {
+    for signal in 1..=SIGNALS {
+        event.signal();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while consumed.load(Ordering::SeqCst) < signal {
+            assert!(Instant::now() < deadline, "signal {} has been lost", signal);
+            thread::yield_now();
+        }
+        assert_eq!(
+            consumed.load(Ordering::SeqCst),
+            signal,
+            "signal {} has been consumed twice",
+            signal
+        );
+    }
+}
+
+#[test]
+fn each_signal_wakes_one_sync_or_async_waiter() {
+    for &backend in Backend::available() {
+        let event = Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap());
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let sync_wakeups = Arc::new(AtomicUsize::new(0));
+        let async_wakeups = Arc::new(AtomicUsize::new(0));
+
+        let mut consumers = Vec::new();
+        for _ in 0..2 {
+            let (event, consumed, wakeups) =
+                (event.clone(), consumed.clone(), sync_wakeups.clone());
+            consumers.push(thread::spawn(move || {
+                while event.wait_result() == WaitResult::Signalled {
+                    wakeups.fetch_add(1, Ordering::Relaxed);
+                    consumed.fetch_add(1, Ordering::SeqCst);
+                }
+            }));
+        }
+        for _ in 0..2 {
+            let (event, consumed, wakeups) =
+                (event.clone(), consumed.clone(), async_wakeups.clone());
+            consumers.push(thread::spawn(move || {
+                block_on(async {
+                    while event.wait_async().await == WaitResult::Signalled {
+                        wakeups.fetch_add(1, Ordering::Relaxed);
+                        consumed.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+            }));
+        }
+
+        signal_one_by_one(&event, &consumed);
+        event.close();
+        for consumer in consumers {
+            consumer.join().unwrap();
+        }
+        assert_eq!(
+            sync_wakeups.load(Ordering::Relaxed) + async_wakeups.load(Ordering::Relaxed),
+            SIGNALS,
+            "{}",
+            backend
+        );
+    }
+}
+
+#[test]
+fn timed_sync_and_async_waits() {
+    for &backend in Backend::available() {
+        let event = Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap());
+        let consumed = Arc::new(AtomicUsize::new(0));
+
+        let mut consumers = Vec::new();
+        {
+            let (event, consumed) = (event.clone(), consumed.clone());
+            consumers.push(thread::spawn(move || {
+                while !event.is_closed() {
+                    if event.try_wait_for(Duration::from_micros(50)) {
+                        consumed.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }));
+        }
+        {
+            let (event, consumed) = (event.clone(), consumed.clone());
+            consumers.push(thread::spawn(move || {
+                block_on(async {
+                    while !event.is_closed() {
+                        if event.wait_async_timeout(Duration::from_micros(50)).await {
+                            consumed.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                })
+            }));
+        }
+
+        signal_one_by_one(&event, &consumed);
+        event.close();
+        for consumer in consumers {
+            consumer.join().unwrap();
+        }
+        assert_eq!(consumed.load(Ordering::SeqCst), SIGNALS, "{}", backend);
+    }
+}
@@ -0,0 +1,95 @@
+//! With the `no-panic` feature, unexpected OS errors neither panic nor lose signals. The errors are
+//! injected via the `fault-injection` feature.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(
+    feature = "no-panic",
+    feature = "fault-injection",
+    not(any(loom, madsim))
+))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, Backend, Fault, FaultInjector};
+
+// Calls `check` with a new event of every backend and its fault injector
+fn for_each_backend(check: impl Fn(Arc<AutoResetEvent>, &FaultInjector, Backend)) {
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        let event = AutoResetEvent::builder()
+            .backend(backend)
+            .faults(faults.clone())
+            .build()
+            .unwrap();
+        check(Arc::new(event), &faults, backend);
+    }
+}
+
+#[test]
+fn failed_waits_poll() {
+    for_each_backend(|event, faults, backend| {
+        faults.inject(Fault::WaitError, u32::MAX);
+
+        let start = Instant::now();
+        assert!(
+            !event.try_wait_for(Duration::from_millis(50)),
+            "{}",
+            backend
+        );
+        assert!(start.elapsed() >= Duration::from_millis(50), "{}", backend);
+
+        // A signal is still consumed by a wait without a timeout
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        event.wait();
+        signaller.join().unwrap();
+
+        assert!(faults.injected(Fault::WaitError) > 0, "{}", backend);
+        assert!(!event.try_wait(), "{}", backend);
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_wakeups_are_recovered() {
+    use std::os::fd::AsRawFd;
+
+    for_each_backend(|event, faults, backend| {
+        let waiter = {
+            let event = event.clone();
+            thread::spawn(move || event.wait())
+        };
+        while event.snapshot().waiters == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        // Gives the waiter time to block in the kernel
+        thread::sleep(Duration::from_millis(20));
+
+        // The wakeup is lost, but the waiter re-checks the state. The kqueue backend does not write
+        // to wake a waiter.
+        faults.inject(Fault::WriteError, 1);
+        event.signal();
+        waiter.join().unwrap();
+
+        // Also if the fd has been handed out
+        let _ = event.as_raw_fd();
+        faults.inject(Fault::WriteError, 1);
+        event.signal();
+        assert!(event.try_wait(), "{}", backend);
+        assert!(!event.try_wait(), "{}", backend);
+        assert!(
+            backend == Backend::Kqueue || faults.injected(Fault::WriteError) > 0,
+            "{}",
+            backend
+        );
+        faults.clear();
+    });
+}
@@ -0,0 +1,93 @@
+//! Forwarding signals between events and `tokio::sync::Notify` via `NotifyBridge`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(unix, feature = "tokio", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, NotifyBridge, WaitResult};
+use tokio::sync::Notify;
+
+#[tokio::test]
+async fn event_to_notify_forwards_signals() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let notify = Arc::new(Notify::new());
+    let _bridge = NotifyBridge::event_to_notify(event.clone(), notify.clone()).unwrap();
+
+    for _ in 0..3 {
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal();
+            })
+        };
+        notify.notified().await;
+        signaller.join().unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn event_to_notify_forwards_signals_from_handlers() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let notify = Arc::new(Notify::new());
+    let _bridge = NotifyBridge::event_to_notify(event.clone(), notify.clone()).unwrap();
+
+    // Signals from signal handlers do not wake tasks that poll the event, but the bridge waits for
+    // the fd
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal_from_handler();
+        })
+    };
+    notify.notified().await;
+    signaller.join().unwrap();
+}
+
+#[tokio::test]
+async fn event_to_notify_ends_on_close() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let notify = Arc::new(Notify::new());
+    let bridge = NotifyBridge::event_to_notify(event.clone(), notify.clone()).unwrap();
+
+    let notified = notify.notified();
+    event.close();
+    notified.await;
+    while !bridge.is_finished() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn notify_to_event_forwards_notifications() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let notify = Arc::new(Notify::new());
+    let bridge = NotifyBridge::notify_to_event(notify.clone(), event.clone());
+
+    for _ in 0..3 {
+        notify.notify_one();
+        assert_eq!(event.wait_async().await, WaitResult::Signalled);
+    }
+
+    event.close();
+    notify.notify_one();
+    while !bridge.is_finished() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn drop_stops_forwarding() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let notify = Arc::new(Notify::new());
+    drop(NotifyBridge::notify_to_event(notify.clone(), event.clone()));
+
+    notify.notify_one();
+    tokio::task::yield_now().await;
+    assert!(!event.try_wait());
+}
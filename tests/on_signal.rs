@@ -0,0 +1,165 @@
+//! Running callbacks on signals via `AutoResetEvent::on_signal`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::AutoResetEvent;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn callback_runs_per_signal() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    let _registration = event.on_signal(move || sender.send(()).unwrap()).unwrap();
+
+    for _ in 0..3 {
+        event.signal();
+        receiver.recv_timeout(TIMEOUT).unwrap();
+    }
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_millis(50)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn drop_detaches_callback() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let calls = Arc::new(AtomicUsize::new(0));
+    let registration = {
+        let calls = calls.clone();
+        event
+            .on_signal(move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap()
+    };
+
+    event.signal();
+    while calls.load(Ordering::SeqCst) == 0 {
+        thread::yield_now();
+    }
+    drop(registration);
+
+    event.signal();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    // The signal is left to other waiters
+    assert!(event.try_wait());
+}
+
+#[test]
+fn drop_waits_for_running_callback() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let (started, running) = mpsc::channel();
+    let finished = Arc::new(AtomicUsize::new(0));
+    let registration = {
+        let finished = finished.clone();
+        event
+            .on_signal(move || {
+                started.send(()).unwrap();
+                thread::sleep(Duration::from_millis(50));
+                finished.fetch_add(1, Ordering::SeqCst);
+            })
+            .unwrap()
+    };
+
+    event.signal();
+    running.recv_timeout(TIMEOUT).unwrap();
+    drop(registration);
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn callback_drops_own_registration() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let slot = Arc::new(Mutex::new(None));
+    let (sender, receiver) = mpsc::channel();
+    let registration = {
+        let slot = slot.clone();
+        event
+            .on_signal(move || {
+                drop(slot.lock().unwrap().take());
+                sender.send(()).unwrap();
+            })
+            .unwrap()
+    };
+    *slot.lock().unwrap() = Some(registration);
+
+    event.signal();
+    receiver.recv_timeout(TIMEOUT).unwrap();
+    // The callback is not called again, and it is released together with the sender
+    event.signal();
+    assert_eq!(
+        receiver.recv_timeout(TIMEOUT),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn close_detaches_callback() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let registration = event.on_signal(|| {}).unwrap();
+    assert!(registration.is_attached());
+
+    event.close();
+    assert!(!registration.is_attached());
+}
+
+#[test]
+fn panicking_callback_is_detached() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let registration = event.on_signal(|| panic!("callback panicked")).unwrap();
+
+    event.signal();
+    for _ in 0..1000 {
+        if !registration.is_attached() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!registration.is_attached());
+
+    // The callbacks of other events keep running
+    let other = Arc::new(AutoResetEvent::new().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    let _registration = other.on_signal(move || sender.send(()).unwrap()).unwrap();
+    other.signal();
+    receiver.recv_timeout(TIMEOUT).unwrap();
+}
+
+#[test]
+fn many_events() {
+    // More events than a single dispatcher thread handles
+    let events: Vec<_> = (0..200)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let (sender, receiver) = mpsc::channel();
+    let _registrations: Vec<_> = events
+        .iter()
+        .enumerate()
+        .map(|(index, event)| {
+            let sender = sender.clone();
+            event
+                .on_signal(move || sender.send(index).unwrap())
+                .unwrap()
+        })
+        .collect();
+
+    for event in &events {
+        event.signal();
+    }
+    let mut received: Vec<usize> = (0..events.len())
+        .map(|_| receiver.recv_timeout(TIMEOUT).unwrap())
+        .collect();
+    received.sort();
+    assert_eq!(received, (0..events.len()).collect::<Vec<_>>());
+}
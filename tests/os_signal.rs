@@ -0,0 +1,74 @@
+//! Unix signals delivered via `OsSignalEvent`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(unix, feature = "signal-hook", not(any(loom, madsim))))]
+
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, OsSignalEvent};
+
+// Every test uses signals of its own, as the tests run in parallel
+
+#[test]
+fn signals_wake_waiters() {
+    let event = Arc::new(OsSignalEvent::new(&[libc::SIGUSR1, libc::SIGHUP]).unwrap());
+    assert!(!event.try_wait());
+    assert!(event.take_received().is_empty());
+
+    let waiter = {
+        let event = event.clone();
+        thread::spawn(move || event.wait())
+    };
+    while event.snapshot().waiters == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) }, 0);
+    waiter.join().unwrap();
+    assert_eq!(event.take_received(), [libc::SIGUSR1]);
+
+    // Signals are coalesced
+    unsafe {
+        libc::raise(libc::SIGHUP);
+        libc::raise(libc::SIGUSR1);
+        libc::raise(libc::SIGHUP);
+    }
+    assert!(event.try_wait_for(Duration::from_secs(10)));
+    assert!(!event.try_wait());
+    let mut received = event.take_received();
+    received.sort();
+    let mut expected = [libc::SIGUSR1, libc::SIGHUP];
+    expected.sort();
+    assert_eq!(received, expected);
+    assert!(event.take_received().is_empty());
+}
+
+#[test]
+fn wait_any_with_other_events() {
+    let signals = OsSignalEvent::new(&[libc::SIGUSR2]).unwrap();
+    let other = AutoResetEvent::new().unwrap();
+
+    unsafe { libc::raise(libc::SIGUSR2) };
+    assert_eq!(AutoResetEvent::wait_any(&[&other, &signals]), 1);
+    assert_eq!(signals.take_received(), [libc::SIGUSR2]);
+}
+
+#[test]
+fn drop_keeps_other_handlers() {
+    let kept = OsSignalEvent::new(&[libc::SIGWINCH]).unwrap();
+    drop(OsSignalEvent::new(&[libc::SIGWINCH]).unwrap());
+
+    unsafe { libc::raise(libc::SIGWINCH) };
+    assert!(kept.try_wait_for(Duration::from_secs(10)));
+    assert_eq!(kept.take_received(), [libc::SIGWINCH]);
+}
+
+#[test]
+fn forbidden_signals() {
+    let err = OsSignalEvent::new(&[libc::SIGTERM, libc::SIGKILL]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
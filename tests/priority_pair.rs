@@ -0,0 +1,76 @@
+//! Two-lane waits via `PriorityPair`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{Priority, PriorityPair};
+
+#[test]
+fn high_priority_first() {
+    let pair = PriorityPair::new().unwrap();
+    assert_eq!(pair.try_wait(), None);
+    assert_eq!(pair.try_wait_for(Duration::from_millis(10)), None);
+
+    pair.low().signal();
+    pair.high().signal();
+    assert_eq!(pair.try_wait(), Some(Priority::High));
+    assert_eq!(pair.try_wait(), Some(Priority::Low));
+    assert_eq!(pair.try_wait(), None);
+
+    pair.high().signal();
+    pair.low().signal();
+    assert_eq!(pair.try_wait_for(Duration::ZERO), Some(Priority::High));
+    assert_eq!(pair.wait(), Some(Priority::Low));
+}
+
+#[test]
+fn blocked_wait_is_woken() {
+    let pair = Arc::new(PriorityPair::new().unwrap());
+    let waiter = {
+        let pair = pair.clone();
+        thread::spawn(move || (pair.wait(), pair.wait()))
+    };
+
+    thread::sleep(Duration::from_millis(20));
+    pair.high().signal();
+    thread::sleep(Duration::from_millis(20));
+    pair.low().signal();
+    assert_eq!(
+        waiter.join().unwrap(),
+        (Some(Priority::High), Some(Priority::Low))
+    );
+}
+
+#[test]
+fn closing_an_event_ends_the_waits() {
+    let pair = Arc::new(PriorityPair::new().unwrap());
+    let waiter = {
+        let pair = pair.clone();
+        thread::spawn(move || pair.wait())
+    };
+
+    thread::sleep(Duration::from_millis(20));
+    pair.low().signal();
+    assert_eq!(waiter.join().unwrap(), Some(Priority::Low));
+    assert!(!pair.is_closed());
+
+    let waiter = {
+        let pair = pair.clone();
+        thread::spawn(move || pair.wait())
+    };
+    thread::sleep(Duration::from_millis(20));
+    pair.low().close();
+    assert_eq!(waiter.join().unwrap(), None);
+    assert!(pair.is_closed());
+
+    // Also the later waits, which are not reported as signals of the open event
+    assert_eq!(pair.wait(), None);
+    assert_eq!(pair.try_wait_for(Duration::from_secs(10)), None);
+    pair.high().signal();
+    assert_eq!(pair.try_wait(), Some(Priority::High));
+}
@@ -0,0 +1,101 @@
+//! The Python bindings of the `python` feature, driven from an embedded interpreter.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "python", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, PyAutoResetEvent};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+#[test]
+fn python_waits_for_rust_signals() {
+    Python::initialize();
+
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let ready = Arc::new(AutoResetEvent::new().unwrap());
+    let signaller = {
+        let event = event.clone();
+        let ready = ready.clone();
+        thread::spawn(move || {
+            // The Python thread blocks on the event first
+            ready.wait();
+            while event.snapshot().waiters == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            // Python code only runs in the meantime if the wait has released the GIL
+            thread::sleep(Duration::from_millis(50));
+            event.signal();
+        })
+    };
+
+    Python::attach(|py| {
+        let globals = PyDict::new(py);
+        globals
+            .set_item(
+                "event",
+                Py::new(py, PyAutoResetEvent::from(event.clone())).unwrap(),
+            )
+            .unwrap();
+        globals
+            .set_item(
+                "ready",
+                Py::new(py, PyAutoResetEvent::from(ready.clone())).unwrap(),
+            )
+            .unwrap();
+        py.run(
+            cr#"
+import threading
+import time
+
+assert not event.try_wait()
+assert not event.try_wait_for(0.01)
+
+# Spans several signal checks, none of which may end the wait early
+start = time.monotonic()
+assert not event.try_wait_for(0.25)
+assert time.monotonic() - start >= 0.25
+
+# Another Python thread runs while the waits release the GIL
+ticks = []
+stop = threading.Event()
+def tick():
+    while not stop.is_set():
+        ticks.append(1)
+        stop.wait(0.001)
+ticker = threading.Thread(target=tick)
+ticker.start()
+
+ready.signal()
+event.wait()
+stop.set()
+ticker.join()
+assert ticks
+
+event.signal()
+assert event.try_wait_for(1.0)
+assert not event.try_wait()
+
+try:
+    event.try_wait_for(-1.0)
+    assert False
+except ValueError:
+    pass
+
+assert event.close()
+assert event.is_closed()
+assert not event.try_wait_for(10.0)
+event.wait()
+"#,
+            Some(&globals),
+            None,
+        )
+        .unwrap();
+    });
+    signaller.join().unwrap();
+    assert!(event.is_closed());
+}
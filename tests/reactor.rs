@@ -0,0 +1,105 @@
+//! Waking polling tasks from the background reactor of the `reactor` feature.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "reactor", not(any(loom, madsim))))]
+
+use std::future::poll_fn;
+use std::sync::Arc;
+use std::task::Poll;
+use std::thread;
+use std::time::Duration;
+
+use futures_executor::block_on;
+use nova_autoreset_event::{AutoResetEvent, WaitResult};
+
+#[test]
+fn signal_from_handler_wakes_task() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+
+    for _ in 0..3 {
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                // Only reaches the kernel object, see `AutoResetEvent::signal_from_handler`
+                event.signal_from_handler();
+            })
+        };
+        assert_eq!(block_on(event.wait_async()), WaitResult::Signalled);
+        signaller.join().unwrap();
+    }
+}
+
+#[test]
+fn many_events() {
+    // More events than a single reactor thread watches
+    let events: Vec<_> = (0..100)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+
+    block_on(async {
+        // Registers every event with the reactor
+        poll_fn(|cx| {
+            for event in &events {
+                assert!(event.poll_wait(cx).is_pending());
+            }
+            Poll::Ready(())
+        })
+        .await;
+
+        let signaller = {
+            let events = events.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                events.iter().for_each(|event| event.signal_from_handler());
+            })
+        };
+        for event in &events {
+            assert_eq!(event.wait_async().await, WaitResult::Signalled);
+        }
+        signaller.join().unwrap();
+    });
+}
+
+#[test]
+fn dropped_events_are_released() {
+    block_on(poll_fn(|cx| {
+        for _ in 0..10 {
+            let event = AutoResetEvent::new().unwrap();
+            assert!(event.poll_wait(cx).is_pending());
+        }
+        Poll::Ready(())
+    }));
+
+    // The reactor keeps working after pruning the dropped events
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal_from_handler();
+        })
+    };
+    assert_eq!(block_on(event.wait_async()), WaitResult::Signalled);
+    signaller.join().unwrap();
+}
+
+#[test]
+fn reactor_restarts_after_idle() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    for _ in 0..2 {
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(20));
+                event.signal_from_handler();
+            })
+        };
+        assert_eq!(block_on(event.wait_async()), WaitResult::Signalled);
+        signaller.join().unwrap();
+
+        // Longer than the reactor thread lingers without events
+        thread::sleep(Duration::from_millis(1500));
+    }
+}
@@ -0,0 +1,94 @@
+//! Recording and replaying traces via `record_trace` and `Replay`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "replay", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use nova_autoreset_event::{AutoResetEvent, Replay, Trace, TraceOp, record_trace};
+
+#[test]
+fn records_operations_of_all_threads() {
+    let (_, trace) = record_trace(|| {
+        let event = Arc::new(AutoResetEvent::new().unwrap());
+        let other = AutoResetEvent::new().unwrap();
+        assert!(!other.try_wait_for(Duration::from_millis(1)));
+
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || event.signal())
+        };
+        event.wait();
+        signaller.join().unwrap();
+        other.close();
+        // Ignored, and therefore not recorded
+        other.signal();
+    });
+
+    assert_eq!(trace.to_string(), "t1 s0 w0 c1");
+    assert_eq!(trace.events(), 2);
+    let ops: Vec<_> = trace.entries().iter().map(|entry| entry.op).collect();
+    assert_eq!(
+        ops,
+        [
+            TraceOp::Timeout,
+            TraceOp::Signal,
+            TraceOp::Wake,
+            TraceOp::Close
+        ]
+    );
+    assert!(
+        trace
+            .entries()
+            .iter()
+            .enumerate()
+            .all(|(time, entry)| entry.time == time as u64)
+    );
+
+    let mut replay = Replay::new(trace);
+    replay.run_until(2);
+    assert!(replay.event(0).is_signalled());
+    replay.run();
+    assert!(!replay.event(0).is_signalled());
+    assert!(replay.is_closed(1));
+    assert_eq!(replay.event(1).timeouts(), 1);
+}
+
+#[test]
+fn only_events_of_the_scope_are_recorded() {
+    let outside = AutoResetEvent::new().unwrap();
+    let (_, trace) = record_trace(|| {
+        outside.signal();
+        let (_, inner) = record_trace(|| AutoResetEvent::new().unwrap().signal());
+        assert_eq!(inner.to_string(), "s0");
+        AutoResetEvent::new().unwrap().signal();
+    });
+    assert_eq!(trace.to_string(), "s0");
+}
+
+#[test]
+fn parse() {
+    let trace: Trace = "s0 s0  w0\nt2 c1".parse().unwrap();
+    assert_eq!(trace.to_string(), "s0 s0 w0 t2 c1");
+    assert_eq!(trace.events(), 3);
+    assert_eq!("".parse::<Trace>().unwrap(), Trace::default());
+
+    let error = "s0 x1".parse::<Trace>().unwrap_err();
+    assert_eq!(error.to_string(), "invalid trace operation `x1`");
+    assert!("w".parse::<Trace>().is_err());
+}
+
+#[test]
+#[should_panic(
+    expected = "replay diverged at time 2: Wake of event 0, but the event is not signalled"
+)]
+fn replay_detects_lost_signal() {
+    let trace: Trace = "s0 w0 w0".parse().unwrap();
+    let mut replay = Replay::new(trace);
+    replay.step();
+    replay.step();
+    replay.run();
+}
@@ -0,0 +1,60 @@
+//! Streams of signals via `SignalStream`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "futures-core", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use futures_executor::block_on;
+use futures_util::StreamExt;
+use futures_util::stream::FusedStream;
+use nova_autoreset_event::{AutoResetEvent, SignalStream};
+
+#[test]
+fn yields_an_item_per_signal() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                thread::sleep(Duration::from_millis(10));
+                event.signal();
+            }
+            thread::sleep(Duration::from_millis(10));
+            event.close();
+        })
+    };
+
+    let mut signals = SignalStream::new(event);
+    let items = block_on(async {
+        let mut items = 0;
+        while signals.next().await.is_some() {
+            items += 1;
+        }
+        items
+    });
+    signaller.join().unwrap();
+    assert_eq!(items, 3);
+    assert!(signals.is_terminated());
+}
+
+#[test]
+fn signals_are_coalesced() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let signals = SignalStream::new(event.clone());
+    event.signal();
+    event.signal();
+    event.close();
+    // A pending signal is dropped once the event is closed
+    assert_eq!(block_on(signals.collect::<Vec<_>>()), []);
+
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let mut signals = SignalStream::new(event.clone());
+    event.signal();
+    event.signal();
+    assert_eq!(block_on(signals.next()), Some(()));
+    assert!(!event.try_wait());
+}
@@ -0,0 +1,61 @@
+//! Rate limiting via `Throttle`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::Throttle;
+
+#[test]
+fn permits_are_spaced_evenly() {
+    let throttle = Throttle::new(200).unwrap();
+    assert_eq!(throttle.interval(), Duration::from_millis(5));
+
+    let first = throttle.acquire();
+    let mut previous = first;
+    for _ in 0..10 {
+        let permit = throttle.acquire();
+        // Later if the thread was descheduled for longer than an interval
+        assert!(permit - previous >= Duration::from_millis(5));
+        assert!(Instant::now() >= permit);
+        previous = permit;
+    }
+    assert!(first.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn try_acquire() {
+    let throttle = Throttle::with_interval(Duration::from_millis(50)).unwrap();
+    assert!(throttle.try_acquire());
+    assert!(!throttle.try_acquire());
+    thread::sleep(Duration::from_millis(60));
+    assert!(throttle.try_acquire());
+
+    let throttle = Throttle::with_interval(Duration::MAX).unwrap();
+    assert!(throttle.try_acquire());
+    assert!(!throttle.try_acquire());
+}
+
+#[test]
+fn concurrent_permits_are_distinct() {
+    let throttle = Arc::new(Throttle::new(1000).unwrap());
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let throttle = throttle.clone();
+            thread::spawn(move || (0..10).map(|_| throttle.acquire()).collect::<Vec<_>>())
+        })
+        .collect();
+
+    let mut permits: Vec<_> = threads
+        .into_iter()
+        .flat_map(|thread| thread.join().unwrap())
+        .collect();
+    permits.sort();
+    for pair in permits.windows(2) {
+        assert!(pair[1] - pair[0] >= Duration::from_millis(1));
+    }
+}
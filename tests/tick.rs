@@ -0,0 +1,144 @@
+//! Selecting on events together with crossbeam channels via `AutoResetEvent::as_tick_receiver`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(feature = "crossbeam-channel", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{RecvTimeoutError, Select, select};
+use nova_autoreset_event::AutoResetEvent;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[test]
+fn select_with_channels() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let ticks = event.as_tick_receiver().unwrap();
+    let (sender, messages) = crossbeam_channel::unbounded();
+
+    let signaller = {
+        let event = event.clone();
+        // The sender of the test keeps the channel connected after the thread has exited
+        let sender = sender.clone();
+        thread::spawn(move || {
+            sender.send(1).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        select! {
+            recv(ticks) -> tick => received.push(tick.map(|()| 0).unwrap()),
+            recv(messages) -> message => received.push(message.unwrap()),
+            default(TIMEOUT) => panic!("neither the channel nor the event has been signalled"),
+        }
+    }
+    received.sort();
+    assert_eq!(received, [0, 1]);
+    signaller.join().unwrap();
+}
+
+#[test]
+fn dynamic_select() {
+    let events: Vec<_> = (0..4)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let receivers: Vec<_> = events
+        .iter()
+        .map(|event| event.as_tick_receiver().unwrap())
+        .collect();
+    let (_sender, messages) = crossbeam_channel::unbounded::<()>();
+
+    let mut select = Select::new();
+    for ticks in &receivers {
+        select.recv(ticks);
+    }
+    let messages_index = select.recv(&messages);
+
+    let signaller = {
+        let event = events[2].clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+
+    let operation = select.select_timeout(TIMEOUT).unwrap();
+    let index = operation.index();
+    assert_ne!(index, messages_index);
+    operation.recv(&receivers[index]).unwrap();
+    assert_eq!(index, 2);
+    signaller.join().unwrap();
+}
+
+#[test]
+fn signals_are_coalesced() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let ticks = event.as_tick_receiver().unwrap();
+
+    event.signal();
+    ticks.recv_timeout(TIMEOUT).unwrap();
+
+    // While a tick is pending, further signals are merged into it
+    for _ in 0..10 {
+        event.signal();
+    }
+    ticks.recv_timeout(TIMEOUT).unwrap();
+    // Signals that arrived after the tick had been sent may still be pending on the event
+    let _ = ticks.recv_timeout(Duration::from_millis(50));
+    assert_eq!(
+        ticks.recv_timeout(Duration::from_millis(50)),
+        Err(RecvTimeoutError::Timeout)
+    );
+}
+
+#[test]
+fn close_disconnects() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    let ticks = event.as_tick_receiver().unwrap();
+
+    event.close();
+    assert_eq!(
+        ticks.recv_timeout(TIMEOUT),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
+
+#[test]
+fn dropped_receivers_release_the_event() {
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    drop(event.as_tick_receiver().unwrap());
+
+    event.signal();
+    for _ in 0..1000 {
+        if Arc::strong_count(&event) == 1 {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("the forwarder still holds the event");
+}
+
+#[test]
+fn many_events() {
+    // More events than a single forwarder thread handles
+    let events: Vec<_> = (0..200)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let receivers: Vec<_> = events
+        .iter()
+        .map(|event| event.as_tick_receiver().unwrap())
+        .collect();
+
+    for event in &events {
+        event.signal();
+    }
+    for ticks in &receivers {
+        ticks.recv_timeout(TIMEOUT).unwrap();
+    }
+}
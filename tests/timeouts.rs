@@ -0,0 +1,171 @@
+//! Properties of `try_wait_for` for arbitrary timeouts on every backend.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, Backend};
+use proptest::prelude::*;
+
+// Waits take real time, so the number of cases is kept small
+const CASES: u32 = 32;
+
+// How much later than its timeout a wait may return on a loaded machine
+const SLACK: Duration = Duration::from_secs(1);
+
+fn backends() -> impl Strategy<Value = Backend> {
+    prop::sample::select(Backend::available())
+}
+
+// Timeouts that are whole milliseconds
+fn millisecond_timeouts() -> impl Strategy<Value = Duration> {
+    prop_oneof![
+        Just(Duration::ZERO),
+        (1u64..=30).prop_map(Duration::from_millis)
+    ]
+}
+
+// Timeouts that are not a multiple of a millisecond
+fn fractional_timeouts() -> impl Strategy<Value = Duration> {
+    (0u64..=5, 1u32..1_000_000).prop_map(|(millis, nanos)| {
+        Duration::from_millis(millis) + Duration::from_nanos(nanos as u64)
+    })
+}
+
+// Timeouts beyond the limits of the kernel APIs, up to ones whose deadline overflows `Instant`
+fn huge_timeouts() -> impl Strategy<Value = Duration> {
+    prop_oneof![
+        Just(Duration::MAX),
+        Just(Duration::from_secs(u64::MAX)),
+        Just(Duration::from_millis(i32::MAX as u64 + 1)),
+        Just(Duration::from_millis(u32::MAX as u64 + 1)),
+        (1u64 << 32..u64::MAX).prop_map(Duration::from_millis),
+        (1u64 << 32..u64::MAX).prop_map(Duration::from_nanos),
+    ]
+}
+
+fn event(backend: Backend) -> Arc<AutoResetEvent> {
+    Arc::new(AutoResetEvent::builder().backend(backend).build().unwrap())
+}
+
+// Checks that an unsignalled wait times out, but not before its timeout
+fn check_timeout(backend: Backend, timeout: Duration) -> Result<(), TestCaseError> {
+    let event = event(backend);
+    let start = Instant::now();
+    prop_assert!(!event.try_wait_for(timeout));
+    let elapsed = start.elapsed();
+
+    prop_assert!(
+        elapsed >= timeout,
+        "{}: {:?} timed out after {:?}",
+        backend,
+        timeout,
+        elapsed
+    );
+    prop_assert!(
+        elapsed < timeout + SLACK,
+        "{}: {:?} timed out after {:?}",
+        backend,
+        timeout,
+        elapsed
+    );
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn does_not_time_out_early(backend in backends(), timeout in millisecond_timeouts()) {
+        check_timeout(backend, timeout)?;
+    }
+
+    #[test]
+    fn does_not_time_out_early_with_fractional_timeouts(
+        backend in backends(),
+        timeout in fractional_timeouts(),
+    ) {
+        check_timeout(backend, timeout)?;
+    }
+
+    // A huge timeout must neither overflow nor be truncated into a short one, so only the signal
+    // ends the wait
+    #[test]
+    fn huge_timeouts_wait_for_the_signal(
+        backend in backends(),
+        timeout in huge_timeouts(),
+        delay in 1u64..=20,
+    ) {
+        let delay = Duration::from_millis(delay);
+        let event = event(backend);
+        let signaller = {
+            let event = event.clone();
+            thread::spawn(move || {
+                thread::sleep(delay);
+                event.signal();
+            })
+        };
+
+        let start = Instant::now();
+        prop_assert!(event.try_wait_for(timeout), "{}: {:?}", backend, timeout);
+        let elapsed = start.elapsed();
+        signaller.join().unwrap();
+        prop_assert!(elapsed < delay + SLACK, "{}: {:?} returned after {:?}", backend, timeout, elapsed);
+    }
+
+    // A signal that arrives before the wait is consumed without blocking, whatever the timeout
+    #[test]
+    fn signalled_event_does_not_block(
+        backend in backends(),
+        timeout in prop_oneof![millisecond_timeouts(), fractional_timeouts(), huge_timeouts()],
+    ) {
+        let event = event(backend);
+        event.signal();
+        prop_assert!(event.try_wait_for(timeout));
+        prop_assert!(!event.try_wait());
+    }
+}
+
+// Kernel timeouts that end before the deadline, as the chunks of an oversized timeout do, neither
+// end the wait nor make it time out early
+#[cfg(feature = "fault-injection")]
+#[test]
+fn early_kernel_timeouts_are_retried() {
+    use nova_autoreset_event::{Fault, FaultInjector};
+
+    for &backend in Backend::available() {
+        let faults = FaultInjector::new();
+        let event = Arc::new(
+            AutoResetEvent::builder()
+                .backend(backend)
+                .faults(faults.clone())
+                .build()
+                .unwrap(),
+        );
+
+        let timeout = Duration::from_millis(30);
+        faults.inject(Fault::Timeout, 3);
+        let start = Instant::now();
+        assert!(!event.try_wait_for(timeout), "{}", backend);
+        assert!(start.elapsed() >= timeout, "{}", backend);
+        assert_eq!(faults.injected(Fault::Timeout), 3, "{}", backend);
+
+        for timeout in [Duration::MAX, Duration::from_millis(i32::MAX as u64 + 1)] {
+            faults.inject(Fault::Timeout, 3);
+            let signaller = {
+                let event = event.clone();
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    event.signal();
+                })
+            };
+            assert!(event.try_wait_for(timeout), "{}: {:?}", backend, timeout);
+            signaller.join().unwrap();
+            assert_eq!(faults.pending(Fault::Timeout), 0, "{}", backend);
+        }
+    }
+}
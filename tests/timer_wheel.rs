@@ -0,0 +1,99 @@
+//! Signalling events at their deadlines via `TimerWheel`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(not(any(loom, madsim)))]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nova_autoreset_event::{AutoResetEvent, TimerWheel};
+
+#[test]
+fn timers_expire_at_their_deadlines() {
+    let wheel = TimerWheel::new().unwrap();
+    let early = Arc::new(AutoResetEvent::new().unwrap());
+    let late = Arc::new(AutoResetEvent::new().unwrap());
+
+    let start = Instant::now();
+    // Scheduled out of order, the earlier deadline has to wake the service thread
+    let late_timer = wheel.signal_after(&late, Duration::from_millis(100));
+    let early_timer = wheel.signal_after(&early, Duration::from_millis(20));
+    assert_eq!(wheel.len(), 2);
+
+    assert_eq!(AutoResetEvent::wait_any(&[&early, &late]), 0);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(Instant::now() >= early_timer.deadline());
+
+    late.wait();
+    assert!(Instant::now() >= late_timer.deadline());
+    assert!(wheel.is_empty());
+    assert!(!early_timer.cancel());
+}
+
+#[test]
+fn cancelled_timers_do_not_expire() {
+    let wheel = TimerWheel::new().unwrap();
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+
+    let timer = wheel.signal_after(&event, Duration::from_millis(20));
+    assert!(timer.cancel());
+    assert!(!timer.cancel());
+    assert!(wheel.is_empty());
+    assert!(!event.try_wait_for(Duration::from_millis(100)));
+
+    // Also after the wheel has been dropped
+    let timer = wheel.signal_after(&event, Duration::from_secs(60));
+    drop(wheel);
+    assert!(!timer.cancel());
+}
+
+#[test]
+fn many_timers() {
+    let wheel = TimerWheel::new().unwrap();
+    let events: Vec<_> = (0..2000)
+        .map(|_| Arc::new(AutoResetEvent::new().unwrap()))
+        .collect();
+    let start = Instant::now();
+    for (i, event) in events.iter().enumerate() {
+        wheel.signal_at(event, start + Duration::from_micros(50 * (i as u64 % 1000)));
+    }
+
+    for event in &events {
+        assert!(event.try_wait_for(Duration::from_secs(10)));
+    }
+    assert!(wheel.is_empty());
+}
+
+#[test]
+fn past_and_far_deadlines() {
+    let wheel = TimerWheel::new().unwrap();
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+
+    wheel.signal_at(&event, Instant::now() - Duration::from_millis(1));
+    assert!(event.try_wait_for(Duration::from_secs(10)));
+
+    let timer = wheel.signal_after(&event, Duration::MAX);
+    assert!(!event.try_wait_for(Duration::from_millis(20)));
+    assert!(timer.cancel());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", windows))]
+#[test]
+fn timers_are_not_rounded_to_milliseconds() {
+    let wheel = TimerWheel::new().unwrap();
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+
+    let delays: Vec<_> = (0..20)
+        .map(|_| {
+            let timer = wheel.signal_after(&event, Duration::from_micros(300));
+            event.wait();
+            timer.deadline().elapsed()
+        })
+        .collect();
+
+    // Timed waits would round every sleep of the service thread up to 1 ms. Only the fastest timer
+    // is checked, as the threads may be descheduled on a loaded machine.
+    let fastest = delays.iter().min().unwrap();
+    assert!(*fastest < Duration::from_micros(500), "{:?}", delays);
+}
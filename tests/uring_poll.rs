@@ -0,0 +1,120 @@
+//! Waiting for events on an io_uring via `AutoResetEvent::uring_poll_entry`.
+
+// Real events cannot be used outside a loom model, see `tests/loom.rs`, and blocking waits panic
+// under madsim, see `tests/madsim.rs`
+#![cfg(all(target_os = "linux", feature = "io-uring", not(any(loom, madsim))))]
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use io_uring::{IoUring, cqueue, opcode};
+use nova_autoreset_event::AutoResetEvent;
+
+const POLL: u64 = 1;
+const CANCEL: u64 = 2;
+
+// io_uring may be disabled, e.g. by seccomp filters, in which case the tests pass vacuously
+fn ring() -> Option<IoUring> {
+    IoUring::new(8).ok()
+}
+
+fn submit_poll(ring: &mut IoUring, event: &AutoResetEvent) {
+    let entry = event.uring_poll_entry().user_data(POLL);
+    unsafe { ring.submission().push(&entry).unwrap() };
+    ring.submit().unwrap();
+}
+
+fn next_completion(ring: &mut IoUring) -> cqueue::Entry {
+    loop {
+        if let Some(completion) = ring.completion().next() {
+            return completion;
+        }
+        ring.submit_and_wait(1).unwrap();
+    }
+}
+
+#[test]
+fn test_completion_per_signal() {
+    let Some(mut ring) = ring() else { return };
+    let event = AutoResetEvent::new().unwrap();
+    submit_poll(&mut ring, &event);
+
+    for _ in 0..3 {
+        event.signal();
+        let completion = next_completion(&mut ring);
+        assert_eq!(completion.user_data(), POLL);
+        assert!(cqueue::more(completion.flags()));
+        assert!(event.complete_uring_poll(completion.result()).unwrap());
+    }
+    assert!(ring.completion().next().is_none());
+}
+
+#[test]
+fn test_signal_of_other_thread() {
+    let Some(mut ring) = ring() else { return };
+    let event = Arc::new(AutoResetEvent::new().unwrap());
+    submit_poll(&mut ring, &event);
+
+    let signaller = {
+        let event = event.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            event.signal();
+        })
+    };
+
+    let completion = next_completion(&mut ring);
+    assert!(event.complete_uring_poll(completion.result()).unwrap());
+    signaller.join().unwrap();
+}
+
+#[test]
+fn test_signal_consumed_by_other_waiter() {
+    let Some(mut ring) = ring() else { return };
+    let event = AutoResetEvent::new().unwrap();
+    submit_poll(&mut ring, &event);
+
+    event.signal();
+    let completion = next_completion(&mut ring);
+    assert!(event.try_wait());
+    assert!(!event.complete_uring_poll(completion.result()).unwrap());
+    assert!(!event.is_closed());
+}
+
+#[test]
+fn test_close() {
+    let Some(mut ring) = ring() else { return };
+    let event = AutoResetEvent::new().unwrap();
+    submit_poll(&mut ring, &event);
+
+    event.close();
+    let completion = next_completion(&mut ring);
+    assert!(!event.complete_uring_poll(completion.result()).unwrap());
+    assert!(event.is_closed());
+}
+
+#[test]
+fn test_cancel() {
+    let Some(mut ring) = ring() else { return };
+    let event = AutoResetEvent::new().unwrap();
+    submit_poll(&mut ring, &event);
+
+    let cancel = opcode::AsyncCancel::new(POLL).build().user_data(CANCEL);
+    unsafe { ring.submission().push(&cancel).unwrap() };
+    ring.submit_and_wait(2).unwrap();
+
+    let mut completions: Vec<_> = ring.completion().collect();
+    completions.sort_by_key(|completion| completion.user_data());
+    assert_eq!(completions.len(), 2);
+    let err = event
+        .complete_uring_poll(completions[0].result())
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ECANCELED));
+    assert!(!cqueue::more(completions[0].flags()));
+    assert_eq!(completions[1].result(), 0);
+
+    // The signal is left to other waiters
+    event.signal();
+    assert!(event.try_wait());
+}